@@ -311,6 +311,12 @@ fn run_algos_vecmat<R: Runtime, MP: MatmulPrecision>() {
         Strategy::DoubleVecMat(BlueprintStrategy::Inferred(().into())),
     );
 
+    println!("Decode");
+    run::<R, MP>(
+        Default::default(),
+        Strategy::Decode(BlueprintStrategy::Inferred(().into())),
+    );
+
     println!("Simple Unit Min");
     run::<R, MP>(
         Default::default(),
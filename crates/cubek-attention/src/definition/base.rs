@@ -1,5 +1,7 @@
 use cubecl::ir::{ElemType, FloatKind, StorageType};
 
+pub use cubek_matmul::definition::Determinism;
+
 #[derive(Clone, Debug)]
 /// Description of an attention problem to solve, regardless of actual data
 pub struct AttentionProblem {
@@ -27,6 +29,9 @@ pub enum AttentionIdent {
 pub struct AttentionOptions {
     pub causal: bool,
     pub accumulator_precision: AccumulatorPrecision,
+    /// With [Determinism::Strict], non [deterministic](crate::launch::Strategy::is_deterministic)
+    /// strategies are rejected at launch.
+    pub determinism: Determinism,
 }
 
 impl AttentionProblem {
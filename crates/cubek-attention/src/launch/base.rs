@@ -3,7 +3,9 @@ use cubecl::{Runtime, client::ComputeClient, prelude::TensorHandleRef};
 use cubecl::std::tensor::TensorHandle;
 
 use crate::definition::AttentionSetupError;
-use crate::definition::{AttentionDims, AttentionGlobalTypes, AttentionOptions, AttentionProblem};
+use crate::definition::{
+    AttentionDims, AttentionGlobalTypes, AttentionOptions, AttentionProblem, Determinism,
+};
 use crate::launch::args::{TensorArgs, TensorInputsLaunch};
 use crate::routines::DeviceSettings;
 use crate::routines::{
@@ -26,6 +28,19 @@ pub enum Strategy {
     Unit(BlueprintStrategy<UnitRoutine>),
 }
 
+impl Strategy {
+    /// Whether the strategy produces bitwise identical results from one run to another.
+    ///
+    /// Only those strategies can be launched with [Determinism::Strict].
+    pub fn is_deterministic(&self) -> bool {
+        match self {
+            // Each output row is owned by a single cube which visits the key/value sequence
+            // in order.
+            Strategy::BlackboxAccelerated(_) | Strategy::Unit(_) => true,
+        }
+    }
+}
+
 #[allow(clippy::result_large_err, clippy::too_many_arguments)]
 pub fn launch<R: Runtime>(
     strategy: Strategy,
@@ -63,6 +78,12 @@ pub fn launch_ref<R: Runtime>(
     attention_global_types: &AttentionGlobalTypes,
     attention_options: AttentionOptions,
 ) -> Result<(), AttentionSetupError> {
+    if attention_options.determinism == Determinism::Strict && !strategy.is_deterministic() {
+        return Err(AttentionSetupError::InvalidConfig(Box::new(
+            "The attention strategy isn't deterministic",
+        )));
+    }
+
     match strategy {
         Strategy::BlackboxAccelerated(strategy) => {
             launch_attention::<R, BlackboxAcceleratedRoutine>(
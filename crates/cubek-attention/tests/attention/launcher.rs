@@ -6,7 +6,9 @@ use cubek_attention::{
 };
 
 use cubecl::client::ComputeClient;
-use cubek_test_utils::{Distribution, StrideSpec, TestInput, current_test_mode};
+use cubek_test_utils::{
+    Distribution, HostData, HostDataType, StrideSpec, TestInput, current_test_mode,
+};
 
 pub fn test_launch(
     client: ComputeClient<TestRuntime>,
@@ -85,6 +87,7 @@ pub fn test_launch(
         AttentionOptions {
             causal: problem.options.causal,
             accumulator_precision: problem.options.accumulator_precision,
+            determinism: problem.options.determinism,
         },
     ) {
         Ok(_) => assert_result(
@@ -108,3 +111,84 @@ pub fn test_launch(
         }
     }
 }
+
+/// Launches the same problem twice on the same inputs and checks that both outputs are
+/// bitwise identical.
+pub fn test_launch_reproducible(
+    client: ComputeClient<TestRuntime>,
+    problem: AttentionProblem,
+    strategy: Strategy,
+) {
+    let input = |ident: AttentionIdent, seed: u64| {
+        let dtype = match ident {
+            AttentionIdent::Query => problem.global_dtypes.query,
+            AttentionIdent::Key => problem.global_dtypes.key,
+            _ => problem.global_dtypes.value,
+        };
+        TestInput::random(
+            client.clone(),
+            problem.shape(ident).to_vec(),
+            dtype,
+            seed,
+            Distribution::Uniform(-1., 1.),
+            StrideSpec::RowMajor,
+        )
+        .generate_without_host_data()
+    };
+    let query = input(AttentionIdent::Query, 12);
+    let key = input(AttentionIdent::Key, 34);
+    let value = input(AttentionIdent::Value, 56);
+    let mask = problem.masked.then(|| {
+        TestInput::random(
+            client.clone(),
+            problem.shape(AttentionIdent::Mask).to_vec(),
+            problem.global_dtypes.mask,
+            78,
+            Distribution::Bernoulli(0.1),
+            StrideSpec::RowMajor,
+        )
+        .generate_without_host_data()
+    });
+
+    let run = || {
+        let out = TestInput::zeros(
+            client.clone(),
+            problem.shape(AttentionIdent::Out).to_vec(),
+            problem.global_dtypes.out,
+            StrideSpec::RowMajor,
+        )
+        .generate_without_host_data();
+
+        launch(
+            strategy.clone(),
+            &client,
+            query.clone(),
+            key.clone(),
+            value.clone(),
+            mask.clone(),
+            out.clone(),
+            &problem.global_dtypes,
+            problem.options.clone(),
+        )
+        .map(|_| HostData::from_tensor_handle(&client, &out, HostDataType::F32))
+    };
+
+    let (first, second) = match (run(), run()) {
+        (Ok(first), Ok(second)) => (first, second),
+        (Err(err), _) | (_, Err(err)) => {
+            if current_test_mode().should_fail_on_test_compilation_fail() {
+                panic!("Test did not run: {}", err)
+            }
+            return;
+        }
+    };
+
+    let len = first.shape.iter().product::<usize>();
+    for i in 0..len {
+        assert_eq!(
+            first.data.get_f32(i).to_bits(),
+            second.data.get_f32(i).to_bits(),
+            "Runs differ at index {i}"
+        );
+    }
+}
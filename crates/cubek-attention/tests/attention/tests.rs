@@ -1,9 +1,9 @@
-use crate::attention::launcher::test_launch;
+use crate::attention::launcher::{test_launch, test_launch_reproducible};
 use crate::attention::tiling_scheme_ops::*;
 use cubecl::{Runtime, TestRuntime};
 use cubek_attention::definition::{
     AccumulatorPrecision, AttentionDims, AttentionOptions, AttentionPartitionSize,
-    AttentionProblem, AttentionStageSize, AttentionTilingScheme, HypercubeBlueprint,
+    AttentionProblem, AttentionStageSize, AttentionTilingScheme, Determinism, HypercubeBlueprint,
 };
 use cubek_attention::routines::DeviceSettings;
 
@@ -38,6 +38,7 @@ fn one_tile_simple() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
 
@@ -91,6 +92,7 @@ fn one_partition_several_planes() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
 
@@ -146,6 +148,7 @@ fn problem_smaller_than_one_tile_seq_q_seq_kv_val_dim() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -194,6 +197,7 @@ fn head_dim_oob() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -241,6 +245,7 @@ fn two_rows_in_array_tile() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -289,6 +294,7 @@ fn one_tile_seqq16() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -337,6 +343,7 @@ fn one_tile_seqq4() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -384,6 +391,7 @@ fn seqq2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -431,6 +439,7 @@ fn hd2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -478,6 +487,7 @@ fn kv2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -525,6 +535,7 @@ fn vd2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -572,6 +583,7 @@ fn hd2_vd2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -619,6 +631,7 @@ fn all2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -667,6 +680,7 @@ fn global_iterations_2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -715,6 +729,7 @@ fn global_iterations_2_kv2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -763,6 +778,7 @@ fn partition_kv1_global1_with_oob() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -811,6 +827,7 @@ fn partition_seqq2_global2_kv2_global2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -858,6 +875,7 @@ fn partition_many_planes() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -906,6 +924,7 @@ fn partition_kv1_global3_with_oob() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -954,6 +973,7 @@ fn partition_oob_in_q() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1001,6 +1021,7 @@ fn partition_kv2_with_oob() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1048,6 +1069,7 @@ fn partition_kv2_causal() {
         options: AttentionOptions {
             causal: true,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1095,6 +1117,7 @@ fn partition_kv2_masked() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1142,6 +1165,7 @@ fn stage2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1189,6 +1213,7 @@ fn stage4() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1238,6 +1263,7 @@ fn stage2_problem4() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1285,6 +1311,7 @@ fn reuse_key_value() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1332,6 +1359,7 @@ fn double_row_wise() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1379,6 +1407,7 @@ fn one_tile_masked() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1426,6 +1455,7 @@ fn one_tile_causal() {
         options: AttentionOptions {
             causal: true,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1473,6 +1503,7 @@ fn one_tile_masked_causal() {
         options: AttentionOptions {
             causal: true,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1521,6 +1552,7 @@ fn masked_oob() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1569,6 +1601,7 @@ fn masked_larger() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1616,6 +1649,7 @@ fn num_heads_2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1663,6 +1697,7 @@ fn batch_2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1710,6 +1745,7 @@ fn batch_2_seqq2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1757,6 +1793,7 @@ fn num_heads_2_batch_2() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1804,6 +1841,7 @@ fn num_heads_2_masked() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1856,6 +1894,7 @@ fn huge_problem() {
         options: AttentionOptions {
             causal: false,
             accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         },
     };
     let launch_settings = DeviceSettings::new(&client, &problem);
@@ -1873,3 +1912,57 @@ fn huge_problem() {
     let strategy = strategy(blueprint);
     test_launch(client, problem, strategy)
 }
+
+#[test]
+fn one_tile_strict_is_reproducible() {
+    let client = <TestRuntime as Runtime>::client(&Default::default());
+
+    let tiling_scheme = AttentionTilingScheme {
+        tile_size: tile_size(),
+        partition_size: AttentionPartitionSize {
+            seq_q: 1,
+            seq_kv: 1,
+            head_dim: 1,
+            val_dim: 1,
+        },
+        stage_size: AttentionStageSize {
+            seq_q: minimal_seq_q_stage(),
+        },
+    };
+
+    let problem = AttentionProblem {
+        dims: AttentionDims {
+            batch: 1,
+            num_heads: 1,
+            seq_q: elements_in_stage_seq_q(&tiling_scheme),
+            seq_kv: elements_in_partition_seq_kv(&tiling_scheme),
+            head_dim: elements_in_partition_head_dim(&tiling_scheme),
+            val_dim: elements_in_partition_val_dim(&tiling_scheme),
+        },
+        masked: false,
+        global_dtypes: global_dtypes(),
+        options: AttentionOptions {
+            causal: false,
+            accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::Strict,
+        },
+    };
+
+    let launch_settings = DeviceSettings::new(&client, &problem);
+
+    let blueprint = AttentionBlueprint {
+        hypercube_blueprint: HypercubeBlueprint {},
+        tiling_scheme,
+        plane_dim: launch_settings.plane_dim,
+        reuse_key_value: false,
+        two_rows_in_array_tile: false,
+        line_sizes: launch_settings.line_sizes,
+        masked: problem.masked,
+        causal: problem.options.causal,
+        check_bounds: tiling_scheme.check_bounds(&problem.dims),
+    };
+
+    let strategy = strategy(blueprint);
+
+    test_launch_reproducible(client, problem, strategy)
+}
@@ -6,7 +6,7 @@ use cubecl::std::{
 use cubek_matmul::components::{
     global::{
         GlobalConfig, GlobalWriter, PartitionedStage, PlaneWriter, SharedGlobalMatmulConfig,
-        read::SyncStrategy,
+        promote_accumulators_periodically, read::SyncStrategy,
     },
     stage::{StageConfig, StageMatmul, StridedStageMemory},
 };
@@ -94,7 +94,7 @@ where
 
         let mut barrier = LL::SyncStrategy::create_barrier();
 
-        for _ in 0..k_iterations {
+        for i in 0..k_iterations {
             lhs_reader.load_stage(&mut barrier, config.lhs_reader_config());
             rhs_reader.load_stage(&mut barrier, config.rhs_reader_config());
 
@@ -110,6 +110,8 @@ where
                 &partition_scheduler,
             );
 
+            promote_accumulators_periodically::<MP, SMM>(acc, i, 1, config.stage_config());
+
             lhs_reader.advance_view();
             rhs_reader.advance_view();
 
@@ -1,4 +1,6 @@
-use cubek_matmul::definition::{MatmulGlobalElems, MatmulProblem, MatrixLayout};
+use cubek_matmul::definition::{
+    AccumulatorPrecision, MatmulGlobalElems, MatmulProblem, MatrixLayout,
+};
 
 #[derive(Clone, Debug, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ConvolutionOperation {
@@ -84,6 +86,8 @@ impl ConvolutionProblem {
             out_strides: MatrixLayout::RowMajor.to_strides(&[self.m, self.n]),
             out_layout: MatrixLayout::RowMajor,
             global_dtypes: self.global_dtypes.clone(),
+            accumulator_precision: AccumulatorPrecision::default(),
+            out_split: None,
         }
     }
 
@@ -9,6 +9,8 @@ use cubecl::{
     server::{self},
 };
 use cubek_convolution::components::ConvolutionProblem;
+use cubek_test_utils::reference::{Conv2dOptions, conv2d_cpu_reference};
+use cubek_test_utils::{HostData, HostDataVec, StrideSpec};
 use std::fmt::Display;
 
 pub trait TestPrecision {
@@ -79,10 +81,7 @@ where
             false => 10e-6,
         };
 
-        let expected = conv_cpu_reference::<Self>(lhs, rhs, problem)
-            .into_iter()
-            .map(|x| x.cast_into())
-            .collect::<Vec<EG>>();
+        let expected = conv_reference::<EG, ES>(lhs, rhs, problem);
 
         if let Err(e) =
             assert_equals_approx::<R, EG>(client, out, shape, strides, &expected, epsilon)
@@ -92,6 +91,61 @@ where
     }
 }
 
+/// Runs the shared CPU reference on the inputs, rounded to the stage precision first.
+fn conv_reference<EG, ES>(lhs: &[EG], rhs: &[EG], problem: &ConvolutionProblem) -> Vec<EG>
+where
+    EG: CastInto<ES> + Copy,
+    ES: CastInto<f32>,
+    f32: CastInto<EG>,
+{
+    let host_data = |data: &[EG], shape: Vec<usize>| {
+        let data = data
+            .iter()
+            .map(|x| {
+                let x: ES = (*x).cast_into();
+                x.cast_into()
+            })
+            .collect();
+        let strides = StrideSpec::RowMajor.compute_strides(&shape);
+
+        HostData {
+            data: HostDataVec::F32(data),
+            shape,
+            strides,
+        }
+    };
+
+    let kernel_size = &problem.kernel_size;
+    let input = host_data(
+        lhs,
+        vec![
+            problem.batches,
+            problem.in_shape[0],
+            problem.in_shape[1],
+            problem.channels,
+        ],
+    );
+    let weight = host_data(
+        rhs,
+        vec![
+            problem.n,
+            kernel_size[0] as usize,
+            kernel_size[1] as usize,
+            problem.channels,
+        ],
+    );
+    let options = Conv2dOptions {
+        stride: [problem.stride[0] as usize, problem.stride[1] as usize],
+        padding: [problem.padding[0], problem.padding[1]],
+        dilation: [problem.dilation[0] as usize, problem.dilation[1] as usize],
+    };
+
+    match conv2d_cpu_reference(&input, &weight, &options).data {
+        HostDataVec::F32(out) => out.into_iter().map(|x| x.cast_into()).collect(),
+        HostDataVec::Bool(_) => unreachable!("The reference outputs f32"),
+    }
+}
+
 /// Compares the content of a handle to a given slice of f32.
 pub(crate) fn assert_equals_approx<R: Runtime, F: Float + CubeElement + Display>(
     client: &ComputeClient<R>,
@@ -128,103 +182,6 @@ pub(crate) fn assert_equals_approx<R: Runtime, F: Float + CubeElement + Display>
     Ok(())
 }
 
-/// Solves a matmul problem with EG inputs, multiplied as ES and accumulated as EA.
-///
-/// This is a naive CPU implementation, very slow on large payloads,
-/// not designed to be used for other purposes than testing.
-pub(crate) fn conv_cpu_reference<P: TestPrecision>(
-    lhs: &[P::EG],
-    rhs: &[P::EG],
-    problem: &ConvolutionProblem,
-) -> Vec<P::EA>
-where
-{
-    let n = problem.batches;
-    let h = problem.in_shape[0];
-    let w = problem.in_shape[1];
-    let c = problem.channels;
-
-    let out_h = problem.out_shape[0];
-    let out_w = problem.out_shape[1];
-    let out_channels = problem.n;
-
-    let kh = problem.kernel_size[0] as usize;
-    let kw = problem.kernel_size[1] as usize;
-
-    let padding = &problem.padding;
-    let stride = &problem.stride;
-    let dilation = &problem.dilation;
-
-    let lhs_stride_n = h * w * c;
-    let lhs_stride_h = w * c;
-    let lhs_stride_w = c;
-    let lhs_stride_c = 1;
-
-    let rhs_stride_out_c = kh * kw * c;
-    let rhs_stride_kh = kw * c;
-    let rhs_stride_kw = c;
-    let rhs_stride_in_c = 1;
-
-    let out_stride_n = out_h * out_w * out_channels;
-    let out_stride_h = out_w * out_channels;
-    let out_stride_w = out_channels;
-    let out_stride_c = 1;
-
-    let mut out = vec![P::EA::from_int(0); n * out_h * out_w * out_channels];
-
-    for nth_batch in 0..n {
-        let batch_in = nth_batch * lhs_stride_n;
-        let batch_out = nth_batch * out_stride_n;
-
-        for out_y in 0..out_h {
-            let out_offset = batch_out + out_y * out_stride_h;
-
-            for out_x in 0..out_w {
-                let out_offset = out_offset + out_x * out_stride_w;
-
-                for out_c in 0..out_channels {
-                    let out_pos = out_offset + out_c * out_stride_c;
-                    let weight_offset = out_c * rhs_stride_out_c;
-
-                    let mut acc = P::EA::from_int(0);
-                    for in_c in 0..c {
-                        let in_offset = batch_in + in_c * lhs_stride_c;
-                        let weight_offset = weight_offset + in_c * rhs_stride_in_c;
-
-                        for ky in 0..kh {
-                            let weight_offset = weight_offset + ky * rhs_stride_kh;
-
-                            for kx in 0..kw {
-                                let weight_pos = weight_offset + kx * rhs_stride_kw;
-
-                                let in_y = out_y as i32 * stride[0] as i32
-                                    + ky as i32 * dilation[0] as i32
-                                    - padding[0];
-                                let in_x = out_x as i32 * stride[1] as i32
-                                    + kx as i32 * dilation[1] as i32
-                                    - padding[1];
-
-                                if in_y >= 0 && in_y < h as i32 && in_x >= 0 && in_x < w as i32 {
-                                    let in_pos = in_offset
-                                        + in_y as usize * lhs_stride_h
-                                        + in_x as usize * lhs_stride_w;
-
-                                    let value: P::ES = lhs[in_pos].cast_into();
-                                    let weight: P::ES = rhs[weight_pos].cast_into();
-                                    acc += (value * weight).cast_into();
-                                }
-                            }
-                        }
-                    }
-                    out[out_pos] = acc;
-                }
-            }
-        }
-    }
-
-    out
-}
-
 pub trait CastInto<E> {
     fn cast_into(self) -> E;
 }
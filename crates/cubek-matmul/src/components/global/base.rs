@@ -7,7 +7,7 @@ use crate::components::global::read::ReaderMode;
 use crate::components::global::{
     GlobalWriterConfig, InputLoadFlow, LoadFlows, PlaneFlowConfig, SpecializedLoadingSides,
 };
-use crate::components::stage::{StageConfig, StageMemoryConfig};
+use crate::components::stage::{StageConfig, StageMatmul, StageMemoryConfig};
 use crate::definition::StageIdent;
 use crate::definition::TilingBlueprint;
 use crate::definition::{AccG, MatmulSetupError};
@@ -241,3 +241,27 @@ impl GlobalReaderConfig {
         self.plane_dim * self.loading_planes_count()
     }
 }
+
+/// Promotes the accumulators once every
+/// [promotion period](StageConfig::accumulator_promotion_period), when they are promoted.
+///
+/// Must be called after each iteration of the k-loop, where `iteration` is the index of the
+/// iteration that just ended and each iteration executes `stages_per_iteration` stages.
+#[cube]
+pub fn promote_accumulators_periodically<MP: MatmulPrecision, SMM: StageMatmul<MP>>(
+    acc: &mut SMM::Accumulators,
+    iteration: u32,
+    #[comptime] stages_per_iteration: u32,
+    #[comptime] config: SMM::Config,
+) {
+    if comptime![config.accumulator_promotion_period().is_some()] {
+        let period = comptime![u32::max(
+            config.accumulator_promotion_period().unwrap() / stages_per_iteration,
+            1
+        )];
+
+        if (iteration + 1) % period == 0 {
+            SMM::promote_accumulators(acc, config);
+        }
+    }
+}
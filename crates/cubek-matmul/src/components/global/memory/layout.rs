@@ -8,7 +8,7 @@ use cubecl::std::{
 use cubecl_common::quant::scheme::{QuantLevel, QuantScheme};
 
 use crate::components::global::memory::GlobalMemoryConfig;
-use crate::definition::{MatmulProblem, MatrixLayout};
+use crate::definition::{MatmulProblem, MatrixLayout, OutputSplit, SplitDim};
 
 /// Global layout that uses the last two dimensions and ignores all others.
 #[derive(CubeType, CubeLaunch, Clone, Copy)]
@@ -259,6 +259,111 @@ impl<'a, R: Runtime> GlobalLayoutLaunch<'a, R> {
     }
 }
 
+/// Global layout where rows and columns are each split into several strided sub-dimensions,
+/// allowing the matrix to be scattered into a permuted tensor.
+#[derive(CubeType, CubeLaunch)]
+pub struct SplitGlobalLayout {
+    row_shape: Sequence<FastDivmod>,
+    row_strides: Sequence<u32>,
+    col_shape: Sequence<FastDivmod>,
+    col_strides: Sequence<u32>,
+    batch_stride: u32,
+    rows: u32,
+    cols: u32,
+
+    #[cube(comptime)]
+    line_size: u32,
+    #[cube(comptime)]
+    config: GlobalLayoutConfig,
+}
+
+#[cube]
+impl Layout for SplitGlobalLayout {
+    type Coordinates = Coords3d;
+    type SourceCoordinates = Coords1d;
+
+    fn to_source_pos(&self, coords: Self::Coordinates) -> u32 {
+        let (batch, row, col) = coords;
+
+        let row_offs = split_offset(row, &self.row_shape, &self.row_strides);
+        let col_offs = split_offset(col, &self.col_shape, &self.col_strides);
+
+        (batch * self.batch_stride + row_offs + col_offs) / comptime![self.line_size]
+    }
+
+    fn to_source_pos_checked(&self, coords: Self::Coordinates) -> (u32, bool) {
+        (self.to_source_pos(coords), self.is_in_bounds(coords))
+    }
+
+    fn shape(&self) -> Self::Coordinates {
+        (u32::MAX.runtime(), self.rows, self.cols)
+    }
+
+    fn is_in_bounds(&self, pos: Self::Coordinates) -> bool {
+        let (_, row, col) = pos;
+
+        match comptime!((self.config.check_row_bounds, self.config.check_col_bounds)) {
+            (true, true) => row < self.rows && col < self.cols,
+            (true, false) => row < self.rows,
+            (false, true) => col < self.cols,
+            (false, false) => true,
+        }
+    }
+}
+
+/// Offset of `pos` once decomposed along the given sub-dimensions, innermost last.
+#[cube]
+fn split_offset(pos: u32, shape: &Sequence<FastDivmod>, strides: &Sequence<u32>) -> u32 {
+    let mut pos = pos;
+    let mut offset = 0;
+    let shape = shape.rev();
+    let strides = strides.rev();
+
+    #[unroll]
+    for i in 0..shape.len() {
+        let (rem, local_pos) = shape.index(i).div_mod(pos);
+        pos = rem;
+        offset += local_pos * *strides.index(i);
+    }
+
+    offset
+}
+
+impl<'a, R: Runtime> SplitGlobalLayoutLaunch<'a, R> {
+    pub fn from_split(
+        client: &ComputeClient<R>,
+        split: &OutputSplit,
+        line_size: u8,
+        config: GlobalLayoutConfig,
+    ) -> Self {
+        let shape = |dims: &[SplitDim]| {
+            dims.iter()
+                .map(|dim| FastDivmodArgs::new(client, dim.size as u32))
+                .collect()
+        };
+        let strides = |dims: &[SplitDim]| {
+            dims.iter()
+                .map(|dim| ScalarArg::new(dim.stride as u32))
+                .collect()
+        };
+
+        // Outputs of consecutive batches are laid out back to back
+        let batch_stride = split.max_offset() + 1;
+
+        SplitGlobalLayoutLaunch::new(
+            shape(&split.rows),
+            strides(&split.rows),
+            shape(&split.cols),
+            strides(&split.cols),
+            ScalarArg::new(batch_stride as u32),
+            ScalarArg::new(split.num_rows() as u32),
+            ScalarArg::new(split.num_cols() as u32),
+            line_size as u32,
+            config,
+        )
+    }
+}
+
 #[derive(CubeType, CubeLaunch)]
 pub struct BatchLayout {
     batch_shape: Sequence<FastDivmod>,
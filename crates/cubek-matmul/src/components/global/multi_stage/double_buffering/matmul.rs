@@ -4,7 +4,9 @@ use crate::components::global::multi_stage::double_buffer_execution::{
 use crate::components::global::read::{
     PartialLoadingStrategy, PartialStageGlobalReader, StageBuffer, ZeroGlobalReader,
 };
-use crate::components::global::{GlobalMatmul, GlobalWriter, SharedGlobalMatmulConfig};
+use crate::components::global::{
+    GlobalMatmul, GlobalWriter, SharedGlobalMatmulConfig, promote_accumulators_periodically,
+};
 use crate::components::global::{Specializer, read::SyncStrategy};
 use crate::components::stage;
 use crate::components::stage::{FilledStage, StridedStageMemory};
@@ -124,7 +126,7 @@ where
 
         LL::SyncStrategy::sync::<MP, _>(&mut barrier_a, config);
 
-        for _ in 0..num_loops {
+        for i in 0..num_loops {
             execute_current_and_read_next::<
                 MP,
                 SMM,
@@ -174,6 +176,8 @@ where
                 config,
             );
 
+            promote_accumulators_periodically::<MP, SMM>(&mut acc, i, 2, config.stage_config);
+
             LL::SyncStrategy::sync::<MP, _>(&mut barrier_a, config);
         }
 
@@ -3,7 +3,9 @@ use crate::components::global::read::{
     FullLoadingStrategy, FullStageGlobalReader, LoadingValidation as _, PartialLoadingStrategy,
     PartialStageGlobalReader, StageBuffer, ZeroGlobalReader,
 };
-use crate::components::global::{self, GlobalWriter, SharedGlobalMatmulConfig};
+use crate::components::global::{
+    self, GlobalWriter, SharedGlobalMatmulConfig, promote_accumulators_periodically,
+};
 use crate::components::global::{Specializer, read::sync::Synchronous};
 use crate::components::stage::StageConfig as _;
 use crate::components::stage::StridedStageFamily;
@@ -136,7 +138,7 @@ where
 
         sync_cube();
 
-        for _ in 0..num_loops {
+        for i in 0..num_loops {
             execute_current_and_read_next::<
                 MP,
                 SMM,
@@ -186,6 +188,8 @@ where
                 config,
             );
 
+            promote_accumulators_periodically::<MP, SMM>(&mut acc, i, 2, config.stage_config);
+
             lhs_reader.advance_view();
 
             sync_cube();
@@ -1,7 +1,9 @@
 use crate::components::global::read::LoaderStage;
 use crate::components::global::read::{PartialStageGlobalReader, StageBuffer, ZeroGlobalReader};
 use crate::components::global::{GlobalConfig, GlobalWriter};
-use crate::components::global::{GlobalMatmul, SharedGlobalMatmulConfig};
+use crate::components::global::{
+    GlobalMatmul, SharedGlobalMatmulConfig, promote_accumulators_periodically,
+};
 use crate::components::global::{PlaneFlowPartition, read::AsyncPartialLoadingStrategy};
 use crate::components::stage;
 use crate::components::stage::FilledStage;
@@ -164,7 +166,7 @@ where
 
             SMM::load_accumulators(&acc_reader.stage(), &mut acc, config.stage_config());
 
-            for _ in 0..num_loops {
+            for i in 0..num_loops {
                 barrier_full_a.wait_parity(phase);
                 SMM::execute(
                     &lhs_stage_a,
@@ -189,6 +191,8 @@ where
                 );
                 barrier_empty_b.arrive();
 
+                promote_accumulators_periodically::<MP, SMM>(&mut acc, i, 2, config.stage_config());
+
                 phase ^= 1;
             }
             barrier_done.arrive_and_wait();
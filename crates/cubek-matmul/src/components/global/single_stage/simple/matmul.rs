@@ -1,6 +1,6 @@
 use crate::components::{
     global::{
-        GlobalMatmul, GlobalWriter, SharedGlobalMatmulConfig,
+        GlobalMatmul, GlobalWriter, SharedGlobalMatmulConfig, promote_accumulators_periodically,
         read::{FullLoadingStrategy, FullStageGlobalReader, SyncStrategy, ZeroGlobalReader},
     },
     stage::StridedStageMemory,
@@ -117,6 +117,8 @@ where
                 &partition_scheduler,
             );
 
+            promote_accumulators_periodically::<MP, SMM>(&mut acc, i, 1, config.stage_config);
+
             lhs_reader.advance_view();
             rhs_reader.advance_view();
         }
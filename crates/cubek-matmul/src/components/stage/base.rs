@@ -141,6 +141,9 @@ pub trait StageMatmul<MP: MatmulPrecision>: 'static + Send + Sync {
         #[comptime] config: Self::Config,
     );
 
+    /// Promote all accumulators in the stage to full precision, if they are promoted
+    fn promote_accumulators(acc: &mut Self::Accumulators, #[comptime] config: Self::Config);
+
     /// Reads the result of the accumulator and hands it to the stage writer
     fn write_results<W: WriteEventListener>(
         acc: &Self::Accumulators,
@@ -168,6 +171,9 @@ pub trait StageConfig:
     fn num_main_flow_planes(&self) -> u32;
     fn plane_dim(&self) -> u32;
     fn plane_flow_config(&self) -> PlaneFlowConfig;
+    /// Number of stage executions after which the accumulators must be
+    /// [promoted](StageMatmul::promote_accumulators), if any
+    fn accumulator_promotion_period(&self) -> Option<u32>;
 
     fn lhs_smem_config(&self) -> StageMemoryConfig;
     fn rhs_smem_config(&self) -> StageMemoryConfig;
@@ -82,6 +82,14 @@ impl<TC: TileConfig> StageConfig for PartitionMatmulConfig<TC> {
         self.shared().plane_flow_config
     }
 
+    fn accumulator_promotion_period(&self) -> Option<u32> {
+        // Stages longer than the interval are rejected when validating the blueprint
+        self.shared()
+            .tile_config
+            .accumulator_promotion_interval()
+            .map(|interval| interval / self.elements_in_stage_k())
+    }
+
     fn tiles_in_partition_mn(&self) -> u32 {
         let partition_size = self.shared().partition_size;
         partition_size.m() * partition_size.n()
@@ -246,6 +254,21 @@ where
         );
     }
 
+    fn promote_accumulators(acc: &mut Self::Accumulators, #[comptime] config: Self::Config) {
+        let m_iterations = config.shared().partition_size.m();
+        let n_iterations = config.shared().partition_size.n();
+
+        #[unroll]
+        for m_iter in 0..m_iterations {
+            #[unroll]
+            for n_iter in 0..n_iterations {
+                let tile_accumulator =
+                    Accumulators::<MP, TM>::get_at_mut(acc, m_iter, n_iter, n_iterations);
+                TM::promote_acc(tile_accumulator, config.shared().tile_config);
+            }
+        }
+    }
+
     fn write_results<W: WriteEventListener>(
         acc: &Self::Accumulators,
         stage: &mut Self::OutStage,
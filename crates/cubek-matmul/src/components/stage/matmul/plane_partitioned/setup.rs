@@ -201,6 +201,17 @@ impl<
             ))));
         }
 
+        let stage_k = blueprint.tiling_scheme.elements_per_stage_along_k();
+        if let Some(promotion_interval) = dtypes
+            .accumulator_precision
+            .promotion_interval(dtypes.acc_register)
+            && stage_k > promotion_interval
+        {
+            return Err(MatmulSetupError::InvalidConfig(Box::new(format!(
+                "Error: Stage size in k ({stage_k:?}) exceeds the accumulator promotion interval ({promotion_interval:?})."
+            ))));
+        }
+
         TM::validate_blueprint(client, blueprint, dtypes, line_sizes)
     }
 }
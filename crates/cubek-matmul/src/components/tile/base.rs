@@ -44,6 +44,12 @@ pub trait TileMatmulFamily: Send + Sync + 'static {
     /// Whether this matmul family is able to cast on load/store from the stage.
     fn can_cast_stage_element() -> bool;
 
+    /// Whether this matmul can periodically promote half precision accumulators to f32,
+    /// see [promote_acc](TileMatmul::promote_acc).
+    fn supports_accumulator_promotion() -> bool {
+        false
+    }
+
     /// Returns whether this tile matmul may benefit from swizzling.
     /// Used to determine the selection, since swizzling may require different stage sizes.
     fn should_swizzle<R: Runtime>(client: &ComputeClient<R>) -> bool;
@@ -177,6 +183,12 @@ pub trait TileMatmul<L: Numeric, R: Numeric, A: Numeric>: 'static + Send + Sync
         #[comptime] config: Self::Config,
     );
 
+    /// Add the partial sums of the accumulator to its full precision counterpart and reset them.
+    ///
+    /// Only does something for tile matmuls that
+    /// [support accumulator promotion](TileMatmulFamily::supports_accumulator_promotion).
+    fn promote_acc(_acc: &mut Self::AccFragment, #[comptime] _config: Self::Config) {}
+
     /// Write the content of the output container to the given slice
     fn write_results<E: Numeric>(
         tile: &mut TileMut<Self::OutTile, E>,
@@ -18,6 +18,11 @@ pub trait TileConfig: Copy + Clone + Eq + PartialEq + Hash + Debug + Send + Sync
 
     /// Returns the [SwizzleMode] for the given ident
     fn swizzle_mode(&self, ident: StageIdent) -> SwizzleMode;
+
+    /// Number of k-steps after which the accumulators must be promoted to f32, if any
+    fn accumulator_promotion_interval(&self) -> Option<u32> {
+        None
+    }
 }
 
 /// Configuration for the Tile Matmul level
@@ -51,6 +51,9 @@ impl ProductType {
 pub struct RegisterMatmulConfig {
     pub shared: SharedTileConfig,
     pub product_type: ProductType,
+    /// Number of k-steps after which the half precision accumulator is added to an f32
+    /// accumulator, if it is promoted
+    pub promotion_interval: Option<u32>,
 }
 
 impl RegisterMatmulConfig {
@@ -58,10 +61,12 @@ impl RegisterMatmulConfig {
         lhs_layout: MatrixLayout,
         rhs_layout: MatrixLayout,
         config: SharedTileConfig,
+        promotion_interval: Option<u32>,
     ) -> Self {
         Self {
             shared: config,
             product_type: ProductType::from_layouts(lhs_layout, rhs_layout, &config),
+            promotion_interval,
         }
     }
 }
@@ -86,4 +91,8 @@ impl TileConfig for RegisterMatmulConfig {
     fn swizzle_mode(&self, ident: StageIdent) -> SwizzleMode {
         self.shared.swizzle_mode(ident)
     }
+
+    fn accumulator_promotion_interval(&self) -> Option<u32> {
+        self.promotion_interval
+    }
 }
@@ -1,4 +1,5 @@
 use cubecl::prelude::*;
+use cubecl::std::{CubeOption, CubeOptionExpand};
 use std::marker::PhantomData;
 
 use crate::components::tile::register::config::{ProductType, RegisterMatmulConfig};
@@ -25,6 +26,15 @@ pub struct UnitFragment<E: Numeric> {
     pub layout: MatrixLayout,
 }
 
+/// Accumulator of the [RegisterMatmul]
+#[derive(CubeType)]
+pub struct UnitAccumulator<A: Numeric> {
+    /// Partial sums, in the accumulator type
+    pub fragment: UnitFragment<A>,
+    /// Sum of the promoted partial sums, only present if the accumulator is promoted
+    pub promoted: CubeOption<Array<f32>>,
+}
+
 #[cube]
 impl<L: Numeric, R: Numeric, A: Numeric, AccTile: TileKind> TileMatmul<L, R, A>
     for RegisterMatmul<AccTile>
@@ -35,7 +45,7 @@ where
 
     type LhsFragment = UnitFragment<L>;
     type RhsFragment = UnitFragment<R>;
-    type AccFragment = UnitFragment<A>;
+    type AccFragment = UnitAccumulator<A>;
 
     type LhsTile = Strided;
     type RhsTile = Strided;
@@ -50,10 +60,10 @@ where
     ) {
         match config.product_type {
             ProductType::Inner => {
-                Self::inner_product(&lhs.array, &rhs.array, &mut acc.array, config)
+                Self::inner_product(&lhs.array, &rhs.array, &mut acc.fragment.array, config)
             }
             ProductType::Outer => {
-                Self::outer_product(&lhs.array, &rhs.array, &mut acc.array, config)
+                Self::outer_product(&lhs.array, &rhs.array, &mut acc.fragment.array, config)
             }
         }
     }
@@ -82,9 +92,18 @@ where
         #[comptime] layout: MatrixLayout,
         #[comptime] config: Self::Config,
     ) -> Self::AccFragment {
-        UnitFragment::<A> {
-            array: Array::new(config.shared.tile_size.mn()),
-            layout,
+        let size = config.shared.tile_size.mn();
+        let promoted = match comptime!(config.promotion_interval.is_some()) {
+            true => CubeOption::new_Some(Array::<f32>::new(size)),
+            false => CubeOption::new_None(),
+        };
+
+        UnitAccumulator::<A> {
+            fragment: UnitFragment::<A> {
+                array: Array::new(size),
+                layout,
+            },
+            promoted,
         }
     }
 
@@ -109,7 +128,37 @@ where
         acc: &mut Self::AccFragment,
         #[comptime] config: Self::Config,
     ) {
-        RegisterStageReader::<AccTile>::load_fragment(tile, acc, StageIdent::Acc, config);
+        RegisterStageReader::<AccTile>::load_fragment(
+            tile,
+            &mut acc.fragment,
+            StageIdent::Acc,
+            config,
+        );
+
+        match &mut acc.promoted {
+            CubeOption::Some(promoted) =>
+            {
+                #[unroll(UNROLL)]
+                for i in 0..config.shared.tile_size.mn() {
+                    promoted[i] = f32::from_int(0);
+                }
+            }
+            CubeOption::None => {}
+        }
+    }
+
+    fn promote_acc(acc: &mut Self::AccFragment, #[comptime] config: Self::Config) {
+        match &mut acc.promoted {
+            CubeOption::Some(promoted) =>
+            {
+                #[unroll(UNROLL)]
+                for i in 0..config.shared.tile_size.mn() {
+                    promoted[i] += f32::cast_from(acc.fragment.array[i]);
+                    acc.fragment.array[i] = A::from_int(0);
+                }
+            }
+            CubeOption::None => {}
+        }
     }
 
     fn write_results<E: Numeric>(
@@ -117,7 +166,25 @@ where
         acc: &Self::AccFragment,
         #[comptime] config: Self::Config,
     ) {
-        RegisterStageWriter::store_fragment(tile, acc, config)
+        match &acc.promoted {
+            CubeOption::Some(promoted) => {
+                // Promote the partial sums left since the last promotion without modifying `acc`
+                let size = config.shared.tile_size.mn();
+                let mut total = Array::<f32>::new(size);
+
+                #[unroll(UNROLL)]
+                for i in 0..size {
+                    total[i] = promoted[i] + f32::cast_from(acc.fragment.array[i]);
+                }
+
+                let total = UnitFragment::<f32> {
+                    array: total,
+                    layout: comptime!(acc.fragment.layout),
+                };
+                RegisterStageWriter::store_fragment(tile, &total, config)
+            }
+            CubeOption::None => RegisterStageWriter::store_fragment(tile, &acc.fragment, config),
+        }
     }
 }
 
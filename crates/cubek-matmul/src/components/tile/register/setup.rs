@@ -34,13 +34,17 @@ where
         true
     }
 
+    fn supports_accumulator_promotion() -> bool {
+        true
+    }
+
     fn cubedim_resource() -> Result<CubeDimResource, InvalidConfigError> {
         Ok(CubeDimResource::Units(1))
     }
 
     fn expand_config(
         blueprint: &TilingBlueprint,
-        _dtypes: &MatmulElems,
+        dtypes: &MatmulElems,
         _line_sizes: &MatmulLineSizes,
     ) -> Result<Self::Config, MatmulSetupError> {
         Ok(RegisterMatmulConfig::from_shared_tile_config(
@@ -51,6 +55,9 @@ where
                 blueprint.plane_dim,
                 blueprint.swizzle_modes,
             ),
+            dtypes
+                .accumulator_precision
+                .promotion_interval(dtypes.acc_register),
         ))
     }
 
@@ -1,6 +1,6 @@
 use crate::{
     components::global::memory::ViewDirection,
-    definition::{MatmulGlobalElems, MatmulProblemSize},
+    definition::{AccumulatorPrecision, MatmulGlobalElems, MatmulProblemSize},
 };
 use cubecl::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -44,6 +44,10 @@ pub struct MatmulProblem {
     pub out_layout: MatrixLayout,
 
     pub global_dtypes: MatmulGlobalElems,
+    /// Precision used to accumulate along the reduction dimension.
+    pub accumulator_precision: AccumulatorPrecision,
+    /// Permuted layout the output is written in, if any.
+    pub out_split: Option<OutputSplit>,
 }
 
 impl MatmulProblem {
@@ -78,6 +82,8 @@ impl MatmulProblem {
             rhs_layout,
             out_layout,
             global_dtypes,
+            accumulator_precision: AccumulatorPrecision::default(),
+            out_split: None,
         }
     }
 
@@ -117,15 +123,105 @@ impl MatmulProblem {
             rhs_layout,
             out_layout,
             global_dtypes,
+            accumulator_precision: AccumulatorPrecision::default(),
+            out_split: None,
         }
     }
 
+    /// Sets the precision used to accumulate along the reduction dimension.
+    pub fn with_accumulator_precision(
+        mut self,
+        accumulator_precision: AccumulatorPrecision,
+    ) -> Self {
+        self.accumulator_precision = accumulator_precision;
+        self
+    }
+
+    /// Writes the output in the given permuted layout instead of a plain matrix.
+    pub fn with_output_split(mut self, out_split: OutputSplit) -> Self {
+        self.out_split = Some(out_split);
+        self
+    }
+
     /// Returns the total number of batches of the output
     pub fn num_batches(&self) -> usize {
         self.out_batches.iter().product()
     }
 }
 
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+/// A sub-dimension of a row or column of the output.
+pub struct SplitDim {
+    pub size: usize,
+    pub stride: usize,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+/// Reinterprets the rows and the columns of the output matrix as several strided
+/// sub-dimensions, so the result can be scattered directly into a permuted tensor.
+///
+/// For instance, a QKV projection producing `[B*S, 3*H*D]` can be written into a `[3, B, H, S, D]`
+/// tensor by splitting rows into `(B, S)` and columns into `(3, H, D)`.
+pub struct OutputSplit {
+    /// Sub-dimensions of the rows, outermost first.
+    pub rows: Vec<SplitDim>,
+    /// Sub-dimensions of the columns, outermost first.
+    pub cols: Vec<SplitDim>,
+}
+
+impl OutputSplit {
+    /// Creates a split where each sub-dimension is given as `(size, stride)`.
+    pub fn new(rows: &[(usize, usize)], cols: &[(usize, usize)]) -> Self {
+        let to_dims = |dims: &[(usize, usize)]| {
+            dims.iter()
+                .map(|&(size, stride)| SplitDim { size, stride })
+                .collect()
+        };
+
+        Self {
+            rows: to_dims(rows),
+            cols: to_dims(cols),
+        }
+    }
+
+    /// Number of rows of the logical output matrix.
+    pub fn num_rows(&self) -> usize {
+        self.rows.iter().map(|dim| dim.size).product()
+    }
+
+    /// Number of columns of the logical output matrix.
+    pub fn num_cols(&self) -> usize {
+        self.cols.iter().map(|dim| dim.size).product()
+    }
+
+    /// Shape of the split output, with the innermost column sub-dimension last.
+    pub fn shape(&self) -> Vec<usize> {
+        self.rows
+            .iter()
+            .chain(self.cols.iter())
+            .map(|dim| dim.size)
+            .collect()
+    }
+
+    /// Strides of the split output, matching [shape](Self::shape).
+    pub fn strides(&self) -> Vec<usize> {
+        self.rows
+            .iter()
+            .chain(self.cols.iter())
+            .map(|dim| dim.stride)
+            .collect()
+    }
+
+    /// Largest offset written to, in elements.
+    pub fn max_offset(&self) -> usize {
+        self.rows
+            .iter()
+            .chain(self.cols.iter())
+            .map(|dim| dim.size.saturating_sub(1) * dim.stride)
+            .sum()
+    }
+}
+
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
 /// Interpretation of matrix multiplication based on input shapes.
 pub enum MatmulKind {
@@ -1,7 +1,7 @@
 use cubecl::{ir::StorageType, prelude::*};
 use half::{bf16, f16};
 
-use crate::definition::MatmulIdent;
+use crate::definition::{MatmulIdent, MatmulProblem};
 
 /// Matrix multiplication precisions.
 pub trait MatmulPrecision: Send + Sync + Copy + 'static {
@@ -149,6 +149,8 @@ pub struct MatmulElems {
     pub lhs_register: StorageType,
     pub rhs_register: StorageType,
     pub acc_register: StorageType,
+    pub accumulator_precision: AccumulatorPrecision,
+    pub determinism: Determinism,
 }
 
 #[derive(Clone, Debug)]
@@ -170,6 +172,8 @@ impl MatmulElems {
             lhs_register: <MP::Lhs as MatrixPrecision>::Register::as_type_native_unchecked(),
             rhs_register: <MP::Rhs as MatrixPrecision>::Register::as_type_native_unchecked(),
             acc_register: <MP::Acc as MatrixPrecision>::Register::as_type_native_unchecked(),
+            accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         }
     }
 
@@ -192,6 +196,41 @@ impl MatmulElems {
             lhs_register: global_elems.lhs,
             rhs_register: global_elems.rhs,
             acc_register: acc_type,
+            accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
+        }
+    }
+
+    /// Resolves the element types for a problem, taking its
+    /// [accumulator precision](AccumulatorPrecision) into account.
+    pub fn from_problem(problem: &MatmulProblem) -> Self {
+        let mut elems = Self::from_globals(&problem.global_dtypes);
+        elems.accumulator_precision = problem.accumulator_precision;
+
+        let acc_type = problem
+            .accumulator_precision
+            .accumulator_type(&problem.global_dtypes, problem.k);
+
+        if let Some(acc_type) = acc_type {
+            elems.acc_stage = acc_type;
+            elems.acc_register = acc_type;
+        }
+
+        elems
+    }
+
+    /// Accumulates half precision outputs in their own type regardless of K, for tile matmuls
+    /// that promote their partial sums to f32 periodically.
+    ///
+    /// Only applies to [half](AccumulatorPrecision::Half) accumulator precision.
+    pub fn enable_accumulator_promotion(&mut self) {
+        if matches!(
+            self.accumulator_precision,
+            AccumulatorPrecision::Half { .. }
+        ) && is_half(self.acc_global)
+        {
+            self.acc_stage = self.acc_global;
+            self.acc_register = self.acc_global;
         }
     }
 
@@ -206,6 +245,8 @@ impl MatmulElems {
             lhs_register: dtype,
             rhs_register: dtype,
             acc_register: dtype,
+            accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         }
     }
 
@@ -224,6 +265,8 @@ impl MatmulElems {
             lhs_register: register[0],
             rhs_register: register[1],
             acc_register: register[2],
+            accumulator_precision: AccumulatorPrecision::default(),
+            determinism: Determinism::default(),
         }
     }
 
@@ -268,3 +311,90 @@ impl MatmulElems {
         self.acc_stage = self.acc_global;
     }
 }
+
+/// Whether results must be bitwise reproducible from one run to another.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Determinism {
+    /// Any strategy may be used.
+    #[default]
+    Relaxed,
+    /// Only [deterministic](crate::launch::Strategy::is_deterministic) strategies may be used,
+    /// [Auto](crate::launch::Strategy::Auto) skips the others.
+    Strict,
+}
+
+/// Precision used to accumulate the partial products along the K dimension.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AccumulatorPrecision {
+    /// Always accumulate in the given type, regardless of the problem.
+    Strict(StorageType),
+    /// Let the algorithm decide.
+    /// Half precision outputs are accumulated in f32, other outputs in their own type.
+    #[default]
+    Loose,
+    /// Accumulate in the half precision output type for speed, promoting to f32
+    /// when the reduction is too long for it to stay accurate.
+    ///
+    /// Partial sums are kept in half precision for at most `promotion_interval` k-steps,
+    /// after which they are added to an f32 accumulator. Tile matmuls that can't promote
+    /// their accumulators accumulate entirely in f32 when K exceeds the interval.
+    Half { promotion_interval: u32 },
+}
+
+impl AccumulatorPrecision {
+    /// Number of k-steps after which half precision accumulation loses too much accuracy
+    /// to be used without promotion.
+    pub const DEFAULT_PROMOTION_INTERVAL: u32 = 256;
+
+    /// Half precision accumulation promoted to f32 after
+    /// [DEFAULT_PROMOTION_INTERVAL](Self::DEFAULT_PROMOTION_INTERVAL) k-steps.
+    pub fn half() -> Self {
+        Self::Half {
+            promotion_interval: Self::DEFAULT_PROMOTION_INTERVAL,
+        }
+    }
+
+    /// Returns the accumulator type to use for the given problem, or `None` if the
+    /// algorithm is free to choose.
+    pub fn accumulator_type(
+        &self,
+        global_elems: &MatmulGlobalElems,
+        k: usize,
+    ) -> Option<StorageType> {
+        match self {
+            AccumulatorPrecision::Strict(dtype) => Some(*dtype),
+            AccumulatorPrecision::Loose => None,
+            AccumulatorPrecision::Half { promotion_interval } => {
+                if !is_half(global_elems.out) {
+                    return None;
+                }
+
+                match Self::is_half_accumulation_safe(k, *promotion_interval) {
+                    true => Some(global_elems.out),
+                    false => Some(f32::as_type_native_unchecked()),
+                }
+            }
+        }
+    }
+
+    /// Whether accumulating `k` products in half precision stays within
+    /// the promotion interval.
+    pub fn is_half_accumulation_safe(k: usize, promotion_interval: u32) -> bool {
+        k <= promotion_interval as usize
+    }
+
+    /// Returns the number of k-steps after which accumulators of type `acc_dtype` must be
+    /// promoted to f32, or `None` if they are never promoted.
+    pub fn promotion_interval(&self, acc_dtype: StorageType) -> Option<u32> {
+        match self {
+            AccumulatorPrecision::Half { promotion_interval } if is_half(acc_dtype) => {
+                Some(*promotion_interval)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn is_half(dtype: StorageType) -> bool {
+    dtype == f16::as_type_native_unchecked() || dtype == bf16::as_type_native_unchecked()
+}
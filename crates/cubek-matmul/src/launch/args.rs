@@ -15,7 +15,7 @@ use crate::components::{
     global::memory::{
         BatchLayout, BatchLayoutLaunch, GlobalLayout, GlobalLayoutConfig, GlobalLayoutLaunch,
         GlobalScaleLayout, NoopLayout, NoopLayoutLaunch, SimpleTmaGlobalLayout,
-        SimpleTmaGlobalLayoutLaunch,
+        SimpleTmaGlobalLayoutLaunch, SplitGlobalLayout, SplitGlobalLayoutLaunch,
     },
     stage::SwizzleMode,
 };
@@ -65,6 +65,12 @@ pub trait ConcreteOutputFactory<A: Routine>: LaunchArg {
         line_sizes: &MatmulLineSizes,
         dtypes: &MatmulElems,
     ) -> Self::RuntimeArg<'a, R>;
+
+    /// Whether the output can be written in the [split layout](crate::definition::OutputSplit)
+    /// of the problem.
+    fn supports_output_split() -> bool {
+        false
+    }
 }
 
 #[cube]
@@ -226,6 +232,10 @@ pub struct TensorOutput<EG: Numeric> {
 }
 
 impl<EG: Numeric, A: Routine> ConcreteOutputFactory<A> for TensorOutput<EG> {
+    fn supports_output_split() -> bool {
+        true
+    }
+
     fn create<'a, R: Runtime>(
         client: &ComputeClient<R>,
         out: &'a TensorHandleRef<'a, R>,
@@ -234,6 +244,18 @@ impl<EG: Numeric, A: Routine> ConcreteOutputFactory<A> for TensorOutput<EG> {
         line_sizes: &MatmulLineSizes,
         _dtypes: &MatmulElems,
     ) -> Self::RuntimeArg<'a, R> {
+        if let Some(split) = &problem.out_split {
+            let layout = SplitGlobalLayoutLaunch::from_split(
+                client,
+                split,
+                line_sizes.out,
+                blueprint.out_global_layout_config(),
+            );
+            let view = ViewArg::new::<SplitGlobalLayout>(out.as_array_arg(line_sizes.out), layout);
+            let batch = VirtualLayoutLaunch::new::<NoopLayout>(NoopLayoutLaunch::new());
+            return TensorOutputLaunch::new(view, batch);
+        }
+
         let layout = GlobalLayoutLaunch::from_handle(
             out,
             line_sizes.out,
@@ -107,7 +107,8 @@ pub fn launch_ref<R: Runtime>(
         rhs.data().strides.to_vec(),
         out.strides.to_vec(),
         dtypes.as_global_elems(),
-    );
+    )
+    .with_accumulator_precision(dtypes.accumulator_precision);
 
     let device_settings = NaiveRoutine::device_settings(client, line_sizes);
     let launch_info = NaiveRoutine::prepare(
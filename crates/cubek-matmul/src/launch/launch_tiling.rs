@@ -1,5 +1,5 @@
 use crate::definition::MatmulProblem;
-use crate::definition::{AvailableLineSizes, MatmulElems, TilingBlueprint};
+use crate::definition::{AvailableLineSizes, MatmulElems, OutputSplit, TilingBlueprint};
 use crate::definition::{MatmulAvailabilityError, MatmulSetupError};
 use crate::launch::handle::MatmulInputHandleRef;
 use crate::launch::launch_kernel_concrete;
@@ -53,6 +53,7 @@ pub fn launch_ref<R: Runtime, A: Routine>(
             rhs.data().elem_size,
             out.elem_size,
         ),
+        None,
         dtypes,
     )
 }
@@ -112,6 +113,92 @@ pub fn launch_ref_tma<R: Runtime, A: Routine<Blueprint = TilingBlueprint>>(
         out,
         blueprint_strategy,
         AvailableLineSizes::from_type_size_tma(client, out.elem_size),
+        None,
+        dtypes,
+    )
+}
+
+/// Launch a matrix multiplication kernel writing its output in a permuted layout.
+///
+/// The rows and columns of the output matrix are scattered into `out` following `out_split`,
+/// which avoids a separate permute kernel when the consumer expects e.g. `[B, H, S, D]`.
+/// Only unbatched problems are supported.
+#[allow(clippy::result_large_err)]
+pub fn launch_ref_split_output<R: Runtime, A: Routine>(
+    client: &ComputeClient<R>,
+    lhs: &MatmulInputHandleRef<'_, R>,
+    rhs: &MatmulInputHandleRef<'_, R>,
+    out: &TensorHandleRef<'_, R>,
+    out_split: &OutputSplit,
+    blueprint_strategy: &BlueprintStrategy<A>,
+    dtypes: &mut MatmulElems,
+) -> Result<(), MatmulSetupError> {
+    let lhs_shape = lhs.shape();
+    let rhs_shape = rhs.shape();
+
+    if lhs_shape.len() != 2 || rhs_shape.len() != 2 {
+        return Err(MatmulSetupError::InvalidConfig(Box::new(
+            "Split outputs are only supported for unbatched matmuls",
+        )));
+    }
+
+    if out_split.num_rows() != lhs_shape[0] || out_split.num_cols() != rhs_shape[1] {
+        return Err(MatmulSetupError::InvalidConfig(Box::new(format!(
+            "Output split of {}x{} doesn't match the {}x{} output matrix",
+            out_split.num_rows(),
+            out_split.num_cols(),
+            lhs_shape[0],
+            rhs_shape[1],
+        ))));
+    }
+
+    // The split dictates where each element goes, so the layout of `out` itself isn't checked.
+    let out_size = out.shape.iter().product::<usize>();
+    if out_size != out_split.num_rows() * out_split.num_cols() {
+        return Err(MatmulSetupError::InvalidConfig(Box::new(format!(
+            "Output of shape {:?} doesn't have the {} elements of the output split",
+            out.shape,
+            out_split.num_rows() * out_split.num_cols(),
+        ))));
+    }
+
+    let max_offset = out_split.max_offset();
+    let out_len = out.handle.size() as usize / out.elem_size;
+    if max_offset >= out_len || max_offset > u32::MAX as usize {
+        return Err(MatmulSetupError::InvalidConfig(Box::new(format!(
+            "Output split writes up to offset {max_offset}, out of bounds of an output of {out_len} elements",
+        ))));
+    }
+
+    let lhs_owned;
+    let lhs = if matrix_batch_layout(lhs.data().strides) == MatrixBatchLayout::HighlyPermuted {
+        lhs_owned = lhs.into_contiguous(client)?;
+        &lhs_owned.as_ref()
+    } else {
+        lhs
+    };
+
+    let rhs_owned;
+    let rhs = if matrix_batch_layout(rhs.data().strides) == MatrixBatchLayout::HighlyPermuted {
+        rhs_owned = rhs.into_contiguous(client)?;
+        &rhs_owned.as_ref()
+    } else {
+        rhs
+    };
+
+    launch_inner_ref::<R, TensorArgs, A>(
+        client,
+        lhs,
+        rhs,
+        out,
+        blueprint_strategy,
+        AvailableLineSizes::from_type_sizes(
+            client,
+            lhs.data().elem_size,
+            rhs.data().elem_size,
+            out.elem_size,
+        ),
+        Some(out_split),
         dtypes,
     )
 }
@@ -124,21 +211,37 @@ fn launch_inner_ref<R: Runtime, MA: MatmulArgs, A: Routine>(
     out: &TensorHandleRef<'_, R>,
     blueprint_strategy: &BlueprintStrategy<A>,
     line_sizes: AvailableLineSizes,
+    out_split: Option<&OutputSplit>,
     dtypes: &mut MatmulElems,
 ) -> Result<(), MatmulSetupError>
 where
     InputArg<MA>: ConcreteInputsFactory<A>,
     OutputArg<MA>: ConcreteOutputFactory<A>,
 {
-    let problem = MatmulProblem::from_shapes_and_strides(
+    // A split output is seen as a plain row-major matrix by the problem, the actual
+    // strides only matter when writing.
+    let (out_shape, out_strides) = match out_split {
+        Some(split) => {
+            let (rows, cols) = (split.num_rows(), split.num_cols());
+            (vec![rows, cols], vec![cols, 1])
+        }
+        None => (out.shape.to_vec(), out.strides.to_vec()),
+    };
+
+    let mut problem = MatmulProblem::from_shapes_and_strides(
         lhs.shape().to_vec(),
         rhs.shape().to_vec(),
-        out.shape.to_vec(),
+        out_shape,
         lhs.data().strides.to_vec(),
         rhs.data().strides.to_vec(),
-        out.strides.to_vec(),
+        out_strides,
         dtypes.as_global_elems(),
-    );
+    )
+    .with_accumulator_precision(dtypes.accumulator_precision);
+
+    if let Some(split) = out_split {
+        problem = problem.with_output_split(split.clone());
+    }
 
     if !client
         .properties()
@@ -165,11 +268,14 @@ where
         ));
     }
 
-    let mut line_sizes = line_sizes
+    let line_sizes = line_sizes
         .filter_lhs_with_tensor(&problem.lhs_strides, &problem.lhs_shape, problem.lhs_layout)
-        .filter_rhs_with_tensor(&problem.rhs_strides, &problem.rhs_shape, problem.rhs_layout)
-        .filter_out_with_tensor(&problem.out_strides, &problem.out_shape)
-        .pick_max()?;
+        .filter_rhs_with_tensor(&problem.rhs_strides, &problem.rhs_shape, problem.rhs_layout);
+    let line_sizes = match &problem.out_split {
+        Some(split) => line_sizes.filter_out_with_tensor(&split.strides(), &split.shape()),
+        None => line_sizes.filter_out_with_tensor(&problem.out_strides, &problem.out_shape),
+    };
+    let mut line_sizes = line_sizes.pick_max()?;
 
     // The large line size resulting from dequantizing ends up slower due to restrictions on
     // algorithms. Use this as a quick and dirty fix.
@@ -30,6 +30,14 @@ where
     InputArg<MA>: ConcreteInputsFactory<A>,
     OutputArg<MA>: ConcreteOutputFactory<A>,
 {
+    if problem.out_split.is_some()
+        && !<OutputArg<MA> as ConcreteOutputFactory<A>>::supports_output_split()
+    {
+        return Err(MatmulSetupError::InvalidConfig(Box::new(
+            "Output split isn't supported by the output argument",
+        )));
+    }
+
     let mut view_line_sizes = line_sizes;
 
     if let MatmulInputHandleRef::Quantized { scheme, .. } = lhs {
@@ -73,6 +81,12 @@ pub fn launch_kernel_virtual<'a, MA: MatmulArgs, R: Runtime, A: Routine>(
     view_line_sizes: MatmulLineSizes,
     blueprint_strategy: &BlueprintStrategy<A>,
 ) -> Result<(), MatmulSetupError> {
+    if problem.out_split.is_some() {
+        return Err(MatmulSetupError::InvalidConfig(Box::new(
+            "Output split isn't supported for virtual outputs",
+        )));
+    }
+
     let device_settings = A::device_settings(client, view_line_sizes);
     let launch_info = A::prepare(&problem, &device_settings, blueprint_strategy)?;
 
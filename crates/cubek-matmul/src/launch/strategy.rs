@@ -12,10 +12,11 @@ use crate::{
         stage::{ColMajorTilingOrder, RowMajorTilingOrder},
         tile::{cmma::CmmaMatmul, io::Filled, mma::MmaMatmul},
     },
-    definition::{MatmulElems, MatmulSetupError},
+    definition::{Determinism, MatmulElems, MatmulSetupError},
     launch::{handle::MatmulInputHandleRef, launch_naive, launch_tiling},
     routines::{
-        BlueprintStrategy,
+        BlueprintStrategy, DECODE_MAX_M,
+        decode::DecodeAlgorithm,
         double_buffering::{
             AsyncCyclicDoubleBufferingAlgorithm, AsyncStridedDoubleBufferingAlgorithm,
             CyclicDoubleBufferingAlgorithm, HybridDoubleBufferingAlgorithm,
@@ -145,6 +146,7 @@ pub enum Strategy {
     DoubleUnit(BlueprintStrategy<DoubleUnitAlgorithm>),
     SimpleVecMat(BlueprintStrategy<SimpleVecMatAlgorithm>),
     DoubleVecMat(BlueprintStrategy<DoubleVecMatAlgorithm>),
+    Decode(BlueprintStrategy<DecodeAlgorithm>),
     Naive,
     #[default]
     Auto,
@@ -289,6 +291,9 @@ impl Display for Strategy {
             Strategy::DoubleVecMat(blueprint_strategy) => {
                 f.write_fmt(format_args!("matmul_double_vecmat{}", blueprint_strategy))
             }
+            Strategy::Decode(blueprint_strategy) => {
+                f.write_fmt(format_args!("matmul_decode{}", blueprint_strategy))
+            }
             Strategy::Naive => f.write_str("matmul_naive"),
             Strategy::Auto => f.write_str("matmul_auto"),
         }
@@ -297,6 +302,56 @@ impl Display for Strategy {
 
 #[allow(clippy::result_large_err)]
 impl Strategy {
+    /// Whether the strategy produces bitwise identical results from one run to another.
+    ///
+    /// Only those strategies can be launched with [Determinism::Strict].
+    pub fn is_deterministic(&self) -> bool {
+        match self {
+            // Every output tile is computed by a single unit or plane iterating over `k` in a
+            // fixed order, without atomics.
+            Strategy::SimpleCyclicCmma(_)
+            | Strategy::SimpleCyclicMma(_)
+            | Strategy::SimpleStridedCmma(_)
+            | Strategy::SimpleStridedMma(_)
+            | Strategy::SimpleTilewiseCmma(_)
+            | Strategy::SimpleTilewiseMma(_)
+            | Strategy::SimpleAsyncStridedCmma(_)
+            | Strategy::SimpleAsyncStridedMma(_)
+            | Strategy::SimpleAsyncCyclicCmma(_)
+            | Strategy::SimpleAsyncCyclicMma(_)
+            | Strategy::SimpleTmaCmma(_)
+            | Strategy::SimpleTmaMma(_)
+            | Strategy::DoubleCyclicCmma(_)
+            | Strategy::DoubleCyclicMma(_)
+            | Strategy::DoubleTilewiseCmma(_)
+            | Strategy::DoubleTilewiseMma(_)
+            | Strategy::DoubleHybridCmma(_)
+            | Strategy::DoubleHybridMma(_)
+            | Strategy::DoubleAsyncCyclicCmma(_)
+            | Strategy::DoubleAsyncCyclicMma(_)
+            | Strategy::DoubleAsyncStridedCmma(_)
+            | Strategy::DoubleAsyncStridedMma(_)
+            | Strategy::DoubleTmaCmma(_)
+            | Strategy::DoubleTmaMma(_)
+            | Strategy::SpecializedCyclicCmma(_)
+            | Strategy::SpecializedCyclicMma(_)
+            | Strategy::SpecializedStridedCmma(_)
+            | Strategy::SpecializedStridedMma(_)
+            | Strategy::SpecializedTmaCmma(_)
+            | Strategy::SpecializedTmaMma(_)
+            | Strategy::OrderedDoubleCmma(_)
+            | Strategy::OrderedDoubleMma(_)
+            | Strategy::SimpleUnit(_)
+            | Strategy::DoubleUnit(_)
+            | Strategy::Naive => true,
+            // `k` is split across the units of a plane, and the partial sums are combined by
+            // plane reductions whose order is up to the hardware.
+            Strategy::SimpleVecMat(_) | Strategy::DoubleVecMat(_) | Strategy::Decode(_) => false,
+            // Only selects deterministic strategies with [Determinism::Strict].
+            Strategy::Auto => true,
+        }
+    }
+
     pub(crate) fn launch_ref<R: Runtime>(
         &self,
         client: &ComputeClient<R>,
@@ -305,6 +360,12 @@ impl Strategy {
         out: &TensorHandleRef<R>,
         dtypes: &mut MatmulElems,
     ) -> Result<(), MatmulSetupError> {
+        if dtypes.determinism == Determinism::Strict && !self.is_deterministic() {
+            return Err(MatmulSetupError::InvalidConfig(Box::new(format!(
+                "{self} isn't deterministic"
+            ))));
+        }
+
         match self {
             Strategy::SimpleCyclicCmma(selection) => {
                 launch_tiling::launch_ref(client, lhs, rhs, out, selection, dtypes)
@@ -414,6 +475,9 @@ impl Strategy {
             Strategy::DoubleVecMat(selection) => {
                 launch_tiling::launch_ref(client, lhs, rhs, out, selection, dtypes)
             }
+            Strategy::Decode(selection) => {
+                launch_tiling::launch_ref(client, lhs, rhs, out, selection, dtypes)
+            }
             Strategy::Naive => launch_naive::launch_ref(client, lhs, rhs, out, dtypes),
             Strategy::Auto => auto(client, lhs, rhs, out, dtypes),
         }
@@ -427,6 +491,18 @@ fn auto<R: Runtime>(
     out: &TensorHandleRef<'_, R>,
     dtypes: &mut MatmulElems,
 ) -> Result<(), MatmulSetupError> {
+    // Few rows would be padded to a full tile by the tiled routines.
+    let shape = lhs.shape();
+    let m = shape[shape.len() - 2];
+    if m > 1 && m <= DECODE_MAX_M && dtypes.determinism == Determinism::Relaxed {
+        // Unsupported layouts or types fall back to the tiled routines, launch failures don't.
+        match Strategy::Decode(Default::default()).launch_ref(client, lhs, rhs, out, dtypes) {
+            Ok(()) => return Ok(()),
+            Err(MatmulSetupError::Launch(err)) => return Err(MatmulSetupError::Launch(err)),
+            Err(_) => {}
+        }
+    }
+
     if let Err(err) =
         Strategy::SimpleCyclicCmma(Default::default()).launch_ref(client, lhs, rhs, out, dtypes)
     {
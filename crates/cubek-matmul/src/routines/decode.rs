@@ -0,0 +1,122 @@
+use std::fmt::Display;
+
+use cubecl::Runtime;
+
+use crate::{
+    components::{
+        batch::{BatchMatmulFamily, PartitionedBatchMatmulFamily, RowMajorGlobalPartitionMatmul},
+        global::{
+            PlaneWriterFamily, read::sync_full_cyclic::SyncFullCyclicLoading,
+            single_stage::simple::SimpleMatmulFamily,
+        },
+        stage::{
+            ColMajorTilingOrder, FilledStageFamily, PlaneMatmulFamily, RowMajorTilingOrder,
+            StridedStageFamily,
+        },
+        tile::{
+            TileMatmulFamily, io::Filled, plane_vec_mat_inner_product::PlaneVecMatInnerProduct,
+        },
+    },
+    definition::{MatmulElems, MatmulProblem, MatmulSetupError, MatrixLayout, TilingBlueprint},
+    routines::{
+        BlueprintStrategy, DeviceSettings, LaunchInfo, Routine, selector::infer_blueprint_decode,
+    },
+};
+
+/// Matmul for problems with a few rows, such as autoregressive decoding.
+///
+/// Sits between [vecmat](crate::routines::vecmat) for `m == 1` and the tiled routines.
+/// Only row-major lhs and col-major rhs are supported.
+pub struct DecodeAlgorithm {}
+
+#[derive(Default, Clone)]
+pub struct DecodeStrategy {}
+
+impl Display for DecodeStrategy {
+    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl From<()> for DecodeStrategy {
+    fn from(_value: ()) -> Self {
+        Self {}
+    }
+}
+
+impl Routine for DecodeAlgorithm {
+    type Strategy = DecodeStrategy;
+    type BatchMatmul = PartitionedBatchMatmulFamily<
+        SimpleMatmulFamily<
+            PlaneMatmulFamily<
+                PlaneVecMatInnerProduct<Filled>,
+                StridedStageFamily,
+                StridedStageFamily,
+                FilledStageFamily,
+            >,
+            SyncFullCyclicLoading<RowMajorTilingOrder>,
+            SyncFullCyclicLoading<ColMajorTilingOrder>,
+            PlaneWriterFamily,
+        >,
+        RowMajorGlobalPartitionMatmul,
+    >;
+    type Blueprint = TilingBlueprint;
+    type Config = <Self::BatchMatmul as BatchMatmulFamily>::Config;
+
+    fn prepare<R: Runtime>(
+        problem: &MatmulProblem,
+        device_settings: &DeviceSettings<R>,
+        strategy: &BlueprintStrategy<Self>,
+    ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
+        // `k` is only sliced across the units of a single plane, other layouts would need
+        // a reduction across planes.
+        if problem.lhs_layout != MatrixLayout::RowMajor
+            || problem.rhs_layout != MatrixLayout::ColMajor
+        {
+            return Err(MatmulSetupError::InvalidConfig(Box::new(format!(
+                "Decode only supports a row-major lhs and a col-major rhs, got {:?} and {:?}",
+                problem.lhs_layout, problem.rhs_layout
+            ))));
+        }
+
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if PlaneVecMatInnerProduct::<Filled>::can_cast_stage_element() {
+            dtypes.adjust_stage_dtypes();
+        }
+
+        let blueprint = match strategy {
+            BlueprintStrategy::Forced(blueprint) => blueprint.clone(),
+            BlueprintStrategy::Inferred(_) => {
+                let line_sizes = device_settings.line_sizes;
+                let plane_dim = device_settings.plane_dim;
+
+                infer_blueprint_decode(
+                    &device_settings.client,
+                    problem,
+                    (1, line_sizes.out as u32, plane_dim * line_sizes.lhs as u32).into(),
+                    plane_dim,
+                )
+            }
+        };
+
+        Self::validate_blueprint(
+            &device_settings.client,
+            &blueprint,
+            problem,
+            &dtypes,
+            &device_settings.line_sizes,
+        )?;
+
+        let cubedim_resource =
+            Self::BatchMatmul::cubedim_resource(&blueprint, &dtypes, &device_settings.line_sizes)?;
+
+        LaunchInfo::new(
+            blueprint,
+            dtypes,
+            problem,
+            cubedim_resource,
+            device_settings,
+        )
+    }
+}
@@ -98,7 +98,11 @@ where
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if TMM::supports_accumulator_promotion() {
+            dtypes.enable_accumulator_promotion();
+        }
 
         if TMM::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
@@ -171,7 +175,11 @@ where
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if TMM::supports_accumulator_promotion() {
+            dtypes.enable_accumulator_promotion();
+        }
 
         if TMM::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
@@ -246,7 +254,11 @@ where
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if TMM::supports_accumulator_promotion() {
+            dtypes.enable_accumulator_promotion();
+        }
 
         if TMM::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
@@ -320,7 +332,11 @@ where
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if TMM::supports_accumulator_promotion() {
+            dtypes.enable_accumulator_promotion();
+        }
 
         if TMM::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
@@ -393,7 +409,11 @@ where
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if TMM::supports_accumulator_promotion() {
+            dtypes.enable_accumulator_promotion();
+        }
 
         if TMM::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
@@ -466,7 +486,11 @@ where
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if TMM::supports_accumulator_promotion() {
+            dtypes.enable_accumulator_promotion();
+        }
 
         if TMM::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
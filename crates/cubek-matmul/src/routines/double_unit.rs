@@ -52,7 +52,11 @@ impl Routine for DoubleUnitAlgorithm {
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if RegisterMatmul::<Filled>::supports_accumulator_promotion() {
+            dtypes.enable_accumulator_promotion();
+        }
 
         if RegisterMatmul::<Filled>::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
@@ -70,7 +74,6 @@ impl Routine for DoubleUnitAlgorithm {
                     tile: strategy.tile_size,
                     ..Default::default()
                 },
-                &problem.global_dtypes,
             ),
         };
 
@@ -1,6 +1,7 @@
 /// Naive non-cooperative matmul without tiling that can be very fast on small matrices.
 pub mod naive;
 
+pub mod decode;
 pub mod double_buffering;
 pub mod double_unit;
 pub mod ordered_double_buffering;
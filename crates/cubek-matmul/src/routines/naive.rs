@@ -39,7 +39,7 @@ impl Routine for NaiveRoutine {
         device_settings: &DeviceSettings<R>,
         _strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<Self::Blueprint>, MatmulSetupError> {
-        let dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let dtypes = MatmulElems::from_problem(problem);
         let blueprint = NaiveBlueprint {
             line_size_out: device_settings.line_sizes.out as u32,
             dtypes: dtypes.clone(),
@@ -0,0 +1,54 @@
+use cubecl::{Runtime, client::ComputeClient};
+
+use crate::components::stage::PartitionBuffering;
+use crate::definition::{
+    CubeCountStrategy, GlobalOrderStrategy, HypercubeBlueprint, MatmulProblem, PartitionSize,
+    SmAllocation, TileSize, TilingBlueprint, TilingScheme,
+};
+
+/// Largest `m` handled by the decode routine, above it tiled routines are preferred.
+pub const DECODE_MAX_M: usize = 32;
+
+/// Maximum number of output tiles accumulated in registers by a single plane.
+const DECODE_MAX_ACCUMULATORS: u32 = 32;
+
+/// Computes a [TilingBlueprint] for small `m` problems.
+///
+/// Every row of lhs is kept as its own tile in the partition of a single plane, so the `m`
+/// accumulators stay in registers. Cubes are spread along `n`, while `k` is sliced across the
+/// units of the plane and combined with plane reductions.
+pub fn infer_blueprint_decode<R: Runtime>(
+    client: &ComputeClient<R>,
+    problem: &MatmulProblem,
+    tile_size: TileSize,
+    plane_dim: u32,
+) -> TilingBlueprint {
+    let rows = problem.m as u32;
+    let num_tiles_n = (problem.n as u32).div_ceil(tile_size.n());
+    let partition_n = (DECODE_MAX_ACCUMULATORS / rows).clamp(1, num_tiles_n.max(1));
+
+    let tiling_scheme = TilingScheme::builder()
+        .with_tile_size(tile_size)
+        .with_partition_size(PartitionSize::new(rows, partition_n, 1))
+        .with_stage_size((1, 1, 1).into())
+        .build()
+        .unwrap();
+    let cube_count_strategy = match client.properties().hardware.num_streaming_multiprocessors {
+        Some(num_sms) => CubeCountStrategy::Sm {
+            num_sms,
+            sm_usage: SmAllocation::Exact,
+            cubes_first: true,
+        },
+        None => CubeCountStrategy::FromProblem,
+    };
+
+    let hypercube = HypercubeBlueprint::builder(&tiling_scheme)
+        .global_order_strategy(GlobalOrderStrategy::Default)
+        .cube_count_strategy(cube_count_strategy)
+        .build();
+
+    TilingBlueprint::builder(tiling_scheme, plane_dim, problem)
+        .partition_buffering(PartitionBuffering::Single)
+        .hypercube_blueprint(hypercube)
+        .build()
+}
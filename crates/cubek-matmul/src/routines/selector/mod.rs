@@ -1,8 +1,10 @@
 mod base;
+mod decode;
 mod plane;
 mod unit;
 
 pub use base::*;
+pub use decode::*;
 pub use plane::*;
 pub use unit::*;
 
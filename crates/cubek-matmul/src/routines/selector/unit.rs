@@ -1,11 +1,14 @@
 use std::fmt::Display;
 
 use crate::{
-    components::stage::{PartitionBuffering, SwizzleMode},
+    components::{
+        stage::{PartitionBuffering, SwizzleMode},
+        tile::{TileMatmulFamily, io::Filled, register::RegisterMatmul},
+    },
     definition::{
-        CubeCountStrategy, GlobalOrderStrategy, HypercubeBlueprint, MatmulElems, MatmulGlobalElems,
-        MatmulKind, MatmulLineSizes, MatmulProblem, MatrixLayout, SmAllocation, SwizzleModes,
-        TilingBlueprint, TilingScheme,
+        CubeCountStrategy, GlobalOrderStrategy, HypercubeBlueprint, MatmulElems, MatmulKind,
+        MatmulLineSizes, MatmulProblem, MatrixLayout, SmAllocation, SwizzleModes, TilingBlueprint,
+        TilingScheme,
     },
 };
 use cubecl::{Runtime, client::ComputeClient, ir::StorageType};
@@ -58,14 +61,17 @@ pub fn infer_blueprint_unit<R: Runtime>(
     double_buffering: bool,
     line_sizes: &MatmulLineSizes,
     options: UnitTilingBlueprintOptions,
-    global_elems: &MatmulGlobalElems,
 ) -> (TilingBlueprint, MatmulElems) {
     let kind: MatmulKind = problem.into();
     let num_sms = client.properties().hardware.num_streaming_multiprocessors;
     let min_tile_size = u8::max(line_sizes.lhs, line_sizes.rhs);
     let min_tile_size = u8::max(line_sizes.out, min_tile_size) as u32;
     let tile_size = u32::max(min_tile_size, 4);
-    let dtypes = MatmulElems::from_globals(global_elems);
+    let mut dtypes = MatmulElems::from_problem(problem);
+
+    if RegisterMatmul::<Filled>::supports_accumulator_promotion() {
+        dtypes.enable_accumulator_promotion();
+    }
 
     let blueprint = match kind {
         MatmulKind::General => general_unit_selector(
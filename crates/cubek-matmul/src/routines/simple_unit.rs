@@ -71,7 +71,11 @@ where
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if RegisterMatmul::<Filled>::supports_accumulator_promotion() {
+            dtypes.enable_accumulator_promotion();
+        }
 
         if RegisterMatmul::<Filled>::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
@@ -99,7 +103,6 @@ where
                         &device_settings.client,
                     ),
                 },
-                &problem.global_dtypes,
             ),
         };
 
@@ -82,7 +82,11 @@ where
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
+
+        if TMM::supports_accumulator_promotion() {
+            dtypes.enable_accumulator_promotion();
+        }
 
         if TMM::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
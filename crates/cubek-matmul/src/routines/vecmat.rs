@@ -70,7 +70,7 @@ impl Routine for SimpleVecMatAlgorithm {
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
 
         if PlaneVecMatInnerProduct::<Filled>::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
@@ -139,7 +139,7 @@ impl Routine for DoubleVecMatAlgorithm {
         device_settings: &DeviceSettings<R>,
         strategy: &BlueprintStrategy<Self>,
     ) -> Result<LaunchInfo<TilingBlueprint>, MatmulSetupError> {
-        let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+        let mut dtypes = MatmulElems::from_problem(problem);
 
         if PlaneVecMatInnerProduct::<Filled>::can_cast_stage_element() {
             dtypes.adjust_stage_dtypes();
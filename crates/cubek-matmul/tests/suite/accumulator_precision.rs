@@ -0,0 +1,202 @@
+use cubecl::frontend::CubePrimitive;
+use cubecl::{Runtime, TestRuntime, ir::StorageType};
+use cubek_matmul::definition::{
+    AccumulatorPrecision, MatmulElems, MatmulGlobalElems, MatmulProblem, MatrixLayout,
+};
+use cubek_matmul::launch::{MatmulInputHandleRef, launch_tiling};
+use cubek_matmul::routines::{
+    BlueprintStrategy, Routine, double_unit::DoubleUnitAlgorithm, simple_unit::SimpleUnitAlgorithm,
+};
+use cubek_test_utils::{Distribution, TestInput, current_test_mode};
+
+use crate::suite::{assert_result, layout_to_stride_spec};
+
+/// Longer than the default promotion interval, so that half accumulators are promoted.
+const LONG_K: usize = 1024;
+
+#[test]
+pub fn test_accumulator_type_strict() {
+    let precision = AccumulatorPrecision::Strict(f32_dtype());
+
+    assert_eq!(
+        precision.accumulator_type(&global_elems(f16_dtype()), 16),
+        Some(f32_dtype())
+    );
+    assert_eq!(
+        precision.accumulator_type(&global_elems(f16_dtype()), LONG_K),
+        Some(f32_dtype())
+    );
+    assert_eq!(precision.promotion_interval(f16_dtype()), None);
+}
+
+#[test]
+pub fn test_accumulator_type_loose() {
+    let precision = AccumulatorPrecision::Loose;
+
+    assert_eq!(
+        precision.accumulator_type(&global_elems(f16_dtype()), LONG_K),
+        None
+    );
+    assert_eq!(precision.promotion_interval(f16_dtype()), None);
+}
+
+#[test]
+pub fn test_accumulator_type_half() {
+    let precision = AccumulatorPrecision::half();
+
+    assert_eq!(
+        precision.accumulator_type(&global_elems(f16_dtype()), 16),
+        Some(f16_dtype())
+    );
+    assert_eq!(
+        precision.accumulator_type(&global_elems(f16_dtype()), LONG_K),
+        Some(f32_dtype())
+    );
+    assert_eq!(
+        precision.accumulator_type(&global_elems(f32_dtype()), LONG_K),
+        None
+    );
+    assert_eq!(
+        precision.promotion_interval(f16_dtype()),
+        Some(AccumulatorPrecision::DEFAULT_PROMOTION_INTERVAL)
+    );
+    assert_eq!(precision.promotion_interval(f32_dtype()), None);
+}
+
+#[test]
+pub fn test_enable_accumulator_promotion() {
+    let problem = problem_with(LONG_K, AccumulatorPrecision::half());
+    let mut dtypes = MatmulElems::from_problem(&problem);
+    assert_eq!(dtypes.acc_register, f32_dtype());
+
+    dtypes.enable_accumulator_promotion();
+    assert_eq!(dtypes.acc_register, f16_dtype());
+    assert_eq!(dtypes.acc_stage, f16_dtype());
+
+    let problem = problem_with(LONG_K, AccumulatorPrecision::Loose);
+    let mut dtypes = MatmulElems::from_problem(&problem);
+    dtypes.enable_accumulator_promotion();
+    assert_eq!(dtypes.acc_register, f32_dtype());
+}
+
+#[test]
+pub fn test_half_accumulation_simple_unit() {
+    test_accumulator_precision::<SimpleUnitAlgorithm>(LONG_K, AccumulatorPrecision::half());
+}
+
+#[test]
+pub fn test_half_accumulation_double_unit() {
+    test_accumulator_precision::<DoubleUnitAlgorithm>(LONG_K, AccumulatorPrecision::half());
+}
+
+#[test]
+pub fn test_half_accumulation_short_interval() {
+    test_accumulator_precision::<SimpleUnitAlgorithm>(
+        LONG_K,
+        AccumulatorPrecision::Half {
+            promotion_interval: 16,
+        },
+    );
+}
+
+#[test]
+pub fn test_strict_accumulation() {
+    test_accumulator_precision::<SimpleUnitAlgorithm>(
+        LONG_K,
+        AccumulatorPrecision::Strict(f32_dtype()),
+    );
+}
+
+fn test_accumulator_precision<A: Routine>(k: usize, precision: AccumulatorPrecision) {
+    let client = TestRuntime::client(&Default::default());
+    let problem = problem_with(k, precision);
+
+    let (lhs, lhs_data) = TestInput::random(
+        client.clone(),
+        problem.lhs_shape.clone(),
+        problem.global_dtypes.lhs,
+        1234,
+        Distribution::Uniform(-1., 1.),
+        layout_to_stride_spec(problem.lhs_layout),
+    )
+    .generate_with_f32_host_data();
+
+    let (rhs, rhs_data) = TestInput::random(
+        client.clone(),
+        problem.rhs_shape.clone(),
+        problem.global_dtypes.rhs,
+        5678,
+        Distribution::Uniform(-1., 1.),
+        layout_to_stride_spec(problem.rhs_layout),
+    )
+    .generate_with_f32_host_data();
+
+    let out = TestInput::zeros(
+        client.clone(),
+        problem.out_shape.clone(),
+        problem.global_dtypes.out,
+        layout_to_stride_spec(MatrixLayout::RowMajor),
+    )
+    .generate_without_host_data();
+
+    let lhs_handle = MatmulInputHandleRef::Normal(lhs.as_ref(), problem.global_dtypes.lhs);
+    let rhs_handle = MatmulInputHandleRef::Normal(rhs.as_ref(), problem.global_dtypes.rhs);
+
+    let mut dtypes = MatmulElems::from_globals(&problem.global_dtypes);
+    dtypes.accumulator_precision = precision;
+
+    if let Err(err) = launch_tiling::launch_ref::<TestRuntime, A>(
+        &client,
+        &lhs_handle,
+        &rhs_handle,
+        &out.as_ref(),
+        &BlueprintStrategy::Inferred(Default::default()),
+        &mut dtypes,
+    ) {
+        if current_test_mode().should_fail_on_test_compilation_fail() {
+            panic!("Can't launch the test: {err}");
+        }
+        println!("Skipping test, can't launch: {err}");
+        return;
+    }
+
+    // The half precision output dominates the error, whatever the accumulator.
+    assert_result(
+        &lhs_data,
+        &rhs_data,
+        &problem,
+        &client,
+        &out,
+        MatmulElems::from_globals(&problem.global_dtypes),
+    );
+}
+
+fn problem_with(k: usize, precision: AccumulatorPrecision) -> MatmulProblem {
+    MatmulProblem::from_parameters(
+        32,
+        32,
+        k,
+        vec![1],
+        MatrixLayout::RowMajor,
+        MatrixLayout::RowMajor,
+        MatrixLayout::RowMajor,
+        global_elems(f16_dtype()),
+    )
+    .with_accumulator_precision(precision)
+}
+
+fn global_elems(out: StorageType) -> MatmulGlobalElems {
+    MatmulGlobalElems {
+        lhs: f16_dtype(),
+        rhs: f16_dtype(),
+        out,
+    }
+}
+
+fn f16_dtype() -> StorageType {
+    half::f16::as_type_native_unchecked()
+}
+
+fn f32_dtype() -> StorageType {
+    f32::as_type_native_unchecked()
+}
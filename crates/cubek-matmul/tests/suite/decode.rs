@@ -0,0 +1,44 @@
+use cubecl::frontend::CubePrimitive;
+use cubecl::{Runtime, TestRuntime};
+use cubek_matmul::definition::{MatmulElems, MatmulSetupError};
+use cubek_matmul::launch::{MatmulInputHandleRef, launch_tiling};
+use cubek_matmul::routines::{BlueprintStrategy, decode::DecodeAlgorithm};
+use cubek_test_utils::{Distribution, StrideSpec, TestInput};
+
+#[test]
+pub fn test_decode_rejects_row_major_rhs() {
+    let client = TestRuntime::client(&Default::default());
+    let (m, k, n) = (4, 64, 32);
+
+    let input = |shape: Vec<usize>, seed: u64| {
+        TestInput::random(
+            client.clone(),
+            shape,
+            f32::as_type_native_unchecked(),
+            seed,
+            Distribution::Uniform(-1., 1.),
+            StrideSpec::RowMajor,
+        )
+        .generate_without_host_data()
+    };
+    let lhs = input(vec![m, k], 1234);
+    let rhs = input(vec![k, n], 5678);
+    let out = TestInput::zeros(
+        client.clone(),
+        vec![m, n],
+        f32::as_type_native_unchecked(),
+        StrideSpec::RowMajor,
+    )
+    .generate_without_host_data();
+
+    let result = launch_tiling::launch_ref::<TestRuntime, DecodeAlgorithm>(
+        &client,
+        &MatmulInputHandleRef::Normal(lhs.as_ref(), f32::as_type_native_unchecked()),
+        &MatmulInputHandleRef::Normal(rhs.as_ref(), f32::as_type_native_unchecked()),
+        &out.as_ref(),
+        &BlueprintStrategy::Inferred(Default::default()),
+        &mut MatmulElems::from_single_dtype(f32::as_type_native_unchecked()),
+    );
+
+    assert!(matches!(result, Err(MatmulSetupError::InvalidConfig(_))));
+}
@@ -0,0 +1,98 @@
+use cubecl::frontend::CubePrimitive;
+use cubecl::std::tensor::TensorHandle;
+use cubecl::{Runtime, TestRuntime, client::ComputeClient};
+use cubek_matmul::definition::{Determinism, MatmulElems, MatmulSetupError};
+use cubek_matmul::launch::{MatmulInputHandleRef, Strategy, launch_ref};
+use cubek_test_utils::{Distribution, HostData, HostDataType, StrideSpec, TestInput};
+
+#[test]
+pub fn test_strategies_are_deterministic() {
+    assert!(Strategy::Auto.is_deterministic());
+    assert!(Strategy::SimpleUnit(Default::default()).is_deterministic());
+    assert!(!Strategy::Decode(Default::default()).is_deterministic());
+}
+
+#[test]
+pub fn test_strict_rejects_decode() {
+    let result = run(
+        &Strategy::Decode(Default::default()),
+        4,
+        Determinism::Strict,
+    );
+
+    assert!(matches!(result, Err(MatmulSetupError::InvalidConfig(_))));
+}
+
+#[test]
+pub fn test_auto_strict_is_reproducible() {
+    // Few rows, which would go through the decode routine if it were allowed, and many.
+    for m in [4, 128] {
+        let first = run(&Strategy::Auto, m, Determinism::Strict).unwrap();
+        let second = run(&Strategy::Auto, m, Determinism::Strict).unwrap();
+
+        let len = first.shape.iter().product::<usize>();
+        for i in 0..len {
+            assert_eq!(
+                first.data.get_f32(i).to_bits(),
+                second.data.get_f32(i).to_bits(),
+                "Runs differ at index {i} with m={m}"
+            );
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn run(
+    strategy: &Strategy,
+    m: usize,
+    determinism: Determinism,
+) -> Result<HostData, MatmulSetupError> {
+    let client = TestRuntime::client(&Default::default());
+    let (k, n) = (512, 64);
+
+    // The decode routine only supports a col-major rhs.
+    let lhs = random(&client, vec![m, k], 1234, StrideSpec::RowMajor);
+    let rhs = random(&client, vec![k, n], 5678, StrideSpec::ColMajor);
+    let out = TestInput::zeros(
+        client.clone(),
+        vec![m, n],
+        f32::as_type_native_unchecked(),
+        StrideSpec::RowMajor,
+    )
+    .generate_without_host_data();
+
+    let mut dtypes = MatmulElems::from_single_dtype(f32::as_type_native_unchecked());
+    dtypes.determinism = determinism;
+
+    launch_ref(
+        strategy,
+        &client,
+        &MatmulInputHandleRef::Normal(lhs.as_ref(), f32::as_type_native_unchecked()),
+        &MatmulInputHandleRef::Normal(rhs.as_ref(), f32::as_type_native_unchecked()),
+        &out.as_ref(),
+        &mut dtypes,
+    )?;
+
+    Ok(HostData::from_tensor_handle(
+        &client,
+        &out,
+        HostDataType::F32,
+    ))
+}
+
+fn random(
+    client: &ComputeClient<TestRuntime>,
+    shape: Vec<usize>,
+    seed: u64,
+    strides: StrideSpec,
+) -> TensorHandle<TestRuntime> {
+    TestInput::random(
+        client.clone(),
+        shape,
+        f32::as_type_native_unchecked(),
+        seed,
+        Distribution::Uniform(-1., 1.),
+        strides,
+    )
+    .generate_without_host_data()
+}
@@ -17,6 +17,7 @@ use cubek_matmul::launch::TensorInputs;
 use cubek_matmul::launch::TensorMapArgs;
 use cubek_matmul::launch::TensorMapInputs;
 use cubek_matmul::launch::TensorOutput;
+use cubek_matmul::launch::launch_tiling;
 use cubek_matmul::routines::BlueprintStrategy;
 use cubek_matmul::routines::Routine;
 use cubek_test_utils::HostData;
@@ -91,6 +92,60 @@ pub fn test_matmul_algorithm<A: Routine<Blueprint = TilingBlueprint>>(
     }
 }
 
+#[allow(unused)]
+/// Test the correctness of the specified Matmul on the given device, letting the routine
+/// infer its own blueprint, against a naive CPU implementation over the given problem
+pub fn test_matmul_inferred<A: Routine>(
+    client: ComputeClient<TestRuntime>,
+    problem: MatmulProblem,
+) {
+    let (lhs, lhs_data) = TestInput::random(
+        client.clone(),
+        problem.lhs_shape.clone(),
+        problem.global_dtypes.lhs,
+        1234,
+        Distribution::Uniform(-1., 1.),
+        layout_to_stride_spec(problem.lhs_layout),
+    )
+    .generate_with_f32_host_data();
+
+    let (rhs, rhs_data) = TestInput::random(
+        client.clone(),
+        problem.rhs_shape.clone(),
+        problem.global_dtypes.rhs,
+        5678,
+        Distribution::Uniform(-1., 1.),
+        layout_to_stride_spec(problem.rhs_layout),
+    )
+    .generate_with_f32_host_data();
+
+    let out = TestInput::zeros(
+        client.clone(),
+        problem.out_shape.clone(),
+        problem.global_dtypes.out,
+        layout_to_stride_spec(MatrixLayout::RowMajor),
+    )
+    .generate_without_host_data();
+
+    let mut all_elems = MatmulElems::from_globals(&problem.global_dtypes.clone());
+
+    if let Err(err) = launch_tiling::launch_ref::<TestRuntime, A>(
+        &client,
+        &MatmulInputHandleRef::Normal(lhs.as_ref(), problem.global_dtypes.lhs),
+        &MatmulInputHandleRef::Normal(rhs.as_ref(), problem.global_dtypes.rhs),
+        &out.as_ref(),
+        &BlueprintStrategy::Inferred(Default::default()),
+        &mut all_elems,
+    ) {
+        if current_test_mode().should_fail_on_test_compilation_fail() {
+            panic!("Can't launch the test: {err}");
+        }
+        return;
+    }
+
+    assert_result(&lhs_data, &rhs_data, &problem, &client, &out, all_elems);
+}
+
 /// Returns whether execution succeeded
 #[allow(clippy::too_many_arguments)]
 pub fn launch_matmul_algorithm<A: Routine<Blueprint = TilingBlueprint>>(
@@ -1,4 +1,5 @@
 pub mod plane_accelerated;
+pub mod plane_decode;
 pub mod plane_vecmat;
 pub mod tma;
 pub mod unit;
@@ -0,0 +1,11 @@
+use super::*;
+use crate::suite::layered::matmul_test_launcher::test_matmul_inferred;
+use cubecl::Runtime;
+use cubecl::TestRuntime;
+
+#[test]
+pub fn test() {
+    let client = TestRuntime::client(&Default::default());
+
+    test_matmul_inferred::<Algorithm>(client, problem());
+}
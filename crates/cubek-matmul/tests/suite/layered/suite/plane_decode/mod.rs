@@ -0,0 +1,8 @@
+mod matmul_plane_decode {
+    #[cfg(all(feature = "matmul_tests_plane", feature = "matmul_tests_vecmat"))]
+    mod decode {
+        type Algorithm = cubek_matmul::routines::decode::DecodeAlgorithm;
+
+        include!("precision.rs");
+    }
+}
@@ -0,0 +1,99 @@
+mod g2x64x100 {
+    use super::*;
+    use cubek_matmul::definition::{MatmulProblem, MatrixLayout};
+
+    fn problem() -> MatmulProblem {
+        MatmulProblem::from_parameters(
+            2,
+            64,
+            100,
+            vec![1],
+            MatrixLayout::RowMajor,
+            MatrixLayout::ColMajor,
+            MatrixLayout::RowMajor,
+            elems(),
+        )
+    }
+
+    include!("launch.rs");
+}
+
+mod g3x40x200 {
+    use super::*;
+    use cubek_matmul::definition::{MatmulProblem, MatrixLayout};
+
+    fn problem() -> MatmulProblem {
+        MatmulProblem::from_parameters(
+            3,
+            40,
+            200,
+            vec![1],
+            MatrixLayout::RowMajor,
+            MatrixLayout::ColMajor,
+            MatrixLayout::RowMajor,
+            elems(),
+        )
+    }
+
+    include!("launch.rs");
+}
+
+mod g7x96x97 {
+    use super::*;
+    use cubek_matmul::definition::{MatmulProblem, MatrixLayout};
+
+    fn problem() -> MatmulProblem {
+        MatmulProblem::from_parameters(
+            7,
+            96,
+            97,
+            vec![1],
+            MatrixLayout::RowMajor,
+            MatrixLayout::ColMajor,
+            MatrixLayout::RowMajor,
+            elems(),
+        )
+    }
+
+    include!("launch.rs");
+}
+
+mod g16x64x300 {
+    use super::*;
+    use cubek_matmul::definition::{MatmulProblem, MatrixLayout};
+
+    fn problem() -> MatmulProblem {
+        MatmulProblem::from_parameters(
+            16,
+            64,
+            300,
+            vec![2],
+            MatrixLayout::RowMajor,
+            MatrixLayout::ColMajor,
+            MatrixLayout::RowMajor,
+            elems(),
+        )
+    }
+
+    include!("launch.rs");
+}
+
+mod g32x128x520 {
+    use super::*;
+    use cubek_matmul::definition::{MatmulProblem, MatrixLayout};
+
+    fn problem() -> MatmulProblem {
+        MatmulProblem::from_parameters(
+            32,
+            128,
+            520,
+            vec![1],
+            MatrixLayout::RowMajor,
+            MatrixLayout::ColMajor,
+            MatrixLayout::RowMajor,
+            elems(),
+        )
+    }
+
+    include!("launch.rs");
+}
@@ -3,6 +3,10 @@
 pub mod layered;
 pub mod naive;
 
+mod accumulator_precision;
+mod decode;
+mod determinism;
+mod output_split;
 mod reference;
 
 use cubek_matmul::definition::MatrixLayout;
@@ -0,0 +1,148 @@
+use cubecl::frontend::CubePrimitive;
+use cubecl::std::tensor::TensorHandle;
+use cubecl::{Runtime, TestRuntime, client::ComputeClient};
+use cubek_matmul::definition::{MatmulElems, MatmulSetupError, OutputSplit};
+use cubek_matmul::launch::{MatmulInputHandleRef, launch_tiling};
+use cubek_matmul::routines::{BlueprintStrategy, simple_unit::SimpleUnitAlgorithm};
+use cubek_test_utils::{
+    Distribution, HostData, HostDataType, StrideSpec, TestInput, assert_equals_approx,
+};
+
+const B: usize = 2;
+const S: usize = 12;
+const H: usize = 3;
+const D: usize = 8;
+
+#[test]
+pub fn test_output_split_matches_permute() {
+    let client = TestRuntime::client(&Default::default());
+    let (lhs, rhs) = inputs(&client);
+    let lhs = MatmulInputHandleRef::Normal(lhs.as_ref(), f32::as_type_native_unchecked());
+    let rhs = MatmulInputHandleRef::Normal(rhs.as_ref(), f32::as_type_native_unchecked());
+
+    // Plain `[B*S, H*D]` matmul, then permuted as `[B, H, S, D]`.
+    let plain = zeros(&client, vec![B * S, H * D]);
+    launch_tiling::launch_ref::<TestRuntime, SimpleUnitAlgorithm>(
+        &client,
+        &lhs,
+        &rhs,
+        &plain.as_ref(),
+        &BlueprintStrategy::Inferred(Default::default()),
+        &mut dtypes(),
+    )
+    .unwrap();
+    let plain = HostData::from_tensor_handle(&client, &plain, HostDataType::F32);
+    let expected = HostData {
+        data: plain.data,
+        shape: vec![B, H, S, D],
+        strides: vec![S * H * D, D, H * D, 1],
+    };
+
+    let split = zeros(&client, vec![B, H, S, D]);
+    launch_tiling::launch_ref_split_output::<TestRuntime, SimpleUnitAlgorithm>(
+        &client,
+        &lhs,
+        &rhs,
+        &split.as_ref(),
+        &output_split(),
+        &BlueprintStrategy::Inferred(Default::default()),
+        &mut dtypes(),
+    )
+    .unwrap();
+    let actual = HostData::from_tensor_handle(&client, &split, HostDataType::F32);
+
+    if let Err(e) = assert_equals_approx(&actual, &expected, 1e-5) {
+        panic!("{}", e);
+    }
+}
+
+#[test]
+pub fn test_output_split_shape_mismatch() {
+    let client = TestRuntime::client(&Default::default());
+    let (lhs, rhs) = inputs(&client);
+    // Large enough for every write of the split, but with twice its elements.
+    let out = zeros(&client, vec![B, H, S, 2 * D]);
+
+    let result = launch_tiling::launch_ref_split_output::<TestRuntime, SimpleUnitAlgorithm>(
+        &client,
+        &MatmulInputHandleRef::Normal(lhs.as_ref(), f32::as_type_native_unchecked()),
+        &MatmulInputHandleRef::Normal(rhs.as_ref(), f32::as_type_native_unchecked()),
+        &out.as_ref(),
+        &output_split(),
+        &BlueprintStrategy::Inferred(Default::default()),
+        &mut dtypes(),
+    );
+
+    assert!(matches!(result, Err(MatmulSetupError::InvalidConfig(_))));
+}
+
+#[test]
+pub fn test_output_split_buffer_too_small() {
+    let client = TestRuntime::client(&Default::default());
+    let (lhs, rhs) = inputs(&client);
+    let split = output_split();
+    // The right shape, backed by half the memory it needs.
+    let out = TensorHandle::new(
+        client.empty(B * S * H * D / 2 * size_of::<f32>()),
+        vec![B, H, S, D],
+        vec![H * S * D, S * D, D, 1],
+        f32::as_type_native_unchecked(),
+    );
+
+    let result = launch_tiling::launch_ref_split_output::<TestRuntime, SimpleUnitAlgorithm>(
+        &client,
+        &MatmulInputHandleRef::Normal(lhs.as_ref(), f32::as_type_native_unchecked()),
+        &MatmulInputHandleRef::Normal(rhs.as_ref(), f32::as_type_native_unchecked()),
+        &out.as_ref(),
+        &split,
+        &BlueprintStrategy::Inferred(Default::default()),
+        &mut dtypes(),
+    );
+
+    assert!(matches!(result, Err(MatmulSetupError::InvalidConfig(_))));
+}
+
+/// Rows split into `(B, S)` and columns into `(H, D)`, written as `[B, H, S, D]`.
+fn output_split() -> OutputSplit {
+    OutputSplit::new(&[(B, H * S * D), (S, D)], &[(H, S * D), (D, 1)])
+}
+
+fn inputs(
+    client: &ComputeClient<TestRuntime>,
+) -> (TensorHandle<TestRuntime>, TensorHandle<TestRuntime>) {
+    let k = 16;
+    let lhs = TestInput::random(
+        client.clone(),
+        vec![B * S, k],
+        f32::as_type_native_unchecked(),
+        1234,
+        Distribution::Uniform(-1., 1.),
+        StrideSpec::RowMajor,
+    )
+    .generate_without_host_data();
+    let rhs = TestInput::random(
+        client.clone(),
+        vec![k, H * D],
+        f32::as_type_native_unchecked(),
+        5678,
+        Distribution::Uniform(-1., 1.),
+        StrideSpec::RowMajor,
+    )
+    .generate_without_host_data();
+
+    (lhs, rhs)
+}
+
+fn zeros(client: &ComputeClient<TestRuntime>, shape: Vec<usize>) -> TensorHandle<TestRuntime> {
+    TestInput::zeros(
+        client.clone(),
+        shape,
+        f32::as_type_native_unchecked(),
+        StrideSpec::RowMajor,
+    )
+    .generate_without_host_data()
+}
+
+fn dtypes() -> MatmulElems {
+    MatmulElems::from_single_dtype(f32::as_type_native_unchecked())
+}
@@ -1,6 +1,6 @@
 use cubecl::prelude::*;
 use cubecl::std::{
-    CubeOption, CubeOptionExpand,
+    CubeOption, CubeOptionExpand, FastDivmod,
     tensor::r#virtual::{VirtualTensor, VirtualTensorOperations, VirtualTensorOperationsExpand},
 };
 use cubecl::unexpanded;
@@ -31,6 +31,18 @@ pub trait ReduceArgs: Send + Sync + 'static + Clone {
     ) -> Self::State<P>;
 
     fn read_input<P: ReduceDType>(state: &Self::State<P>, index: u32) -> Line<P::In>;
+
+    /// Read the input cast to `Acc`.
+    ///
+    /// Arguments combining multiple tensors must override it to cast each tensor before combining
+    /// them, so the combination doesn't overflow `P::In`.
+    fn read_input_as<P: ReduceDType, Acc: Numeric>(
+        state: &Self::State<P>,
+        index: u32,
+    ) -> Line<Acc> {
+        Line::cast_from(Self::read_input::<P>(state, index))
+    }
+
     fn read_output<P: ReduceDType>(state: &Self::State<P>, index: u32) -> Line<P::Out>;
 
     fn write_output<P: ReduceDType>(state: &mut Self::State<P>, index: u32, value: Line<P::Out>);
@@ -71,6 +83,24 @@ pub fn init_tensors<RA: ReduceArgs, In: Numeric, Out: Numeric>(
     (input, output)
 }
 
+/// Same as [init_tensors], but the input items are read cast to `Acc`.
+#[cube]
+pub fn init_tensors_as<RA: ReduceArgs, In: Numeric, Acc: Numeric, Out: Numeric>(
+    input: &RA::Input<In>,
+    output: &mut RA::Output<Out>,
+) -> (VirtualTensor<Acc>, VirtualTensor<Out, ReadWrite>) {
+    let mut state = RA::init_state::<(In, Out)>(input, output);
+
+    let input = TensorArg::new_input_as(&state);
+    let mut output = TensorArg::new_output(&mut state);
+
+    let input = VirtualTensor::<Acc>::new::<TensorArg<(In, Out), RA, InputAs<Acc>>>(&input);
+    let output =
+        VirtualTensor::<Out, ReadWrite>::new::<TensorArg<(In, Out), RA, Output>>(&mut output);
+
+    (input, output)
+}
+
 #[derive(Clone)]
 pub struct TensorArgs;
 
@@ -147,8 +177,220 @@ impl ReduceArgs for TensorArgs {
     }
 }
 
+/// Reduce arguments where the input is a virtual view gathering multiple reduced axes into one.
+///
+/// The metadata of the input tensor describes the virtual view, while the memory layout needed
+/// to map a virtual index back to the real buffer is provided by [MultiAxisInput].
+#[derive(Clone)]
+pub struct MultiAxisArgs;
+
+#[derive(CubeLaunch, CubeType)]
+/// Input representation for [MultiAxisArgs].
+pub struct MultiAxisInput<E: Numeric> {
+    pub tensor: Tensor<Line<E>>,
+    /// Shape of the virtual view where the reduced axis is split back into the original axes.
+    pub shape: Sequence<FastDivmod>,
+    /// Memory strides matching each entry of `shape`.
+    pub strides: Sequence<u32>,
+}
+
+#[cube]
+impl ReduceArgs for MultiAxisArgs {
+    type Input<EG: Numeric> = MultiAxisInput<EG>;
+    type Output<EG: Numeric> = Tensor<Line<EG>>;
+    type State<P: ReduceDType> = (*const MultiAxisInput<P::In>, *mut Tensor<Line<P::Out>>);
+
+    fn init_state<P: ReduceDType>(
+        input: &Self::Input<P::In>,
+        output: &mut Self::Output<P::Out>,
+    ) -> Self::State<P> {
+        (input, output)
+    }
+
+    fn read_input<P: ReduceDType>(state: &Self::State<P>, index: u32) -> Line<P::In> {
+        unsafe {
+            let offset = gather_offset(index, &(*state.0).shape, &(*state.0).strides);
+            (*state.0).tensor[offset]
+        }
+    }
+
+    fn read_output<P: ReduceDType>(state: &Self::State<P>, index: u32) -> Line<P::Out> {
+        unsafe { (*state.1)[index] }
+    }
+
+    fn write_output<P: ReduceDType>(state: &mut Self::State<P>, index: u32, value: Line<P::Out>) {
+        unsafe { (*state.1)[index] = value }
+    }
+
+    fn buffer_len_input<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.0).tensor.buffer_len() }
+    }
+
+    fn buffer_len_output<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.1).buffer_len() }
+    }
+
+    fn len_input<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.0).tensor.len() }
+    }
+
+    fn len_output<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.1).len() }
+    }
+    fn rank_input<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.0).tensor.rank() }
+    }
+
+    fn rank_output<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.1).rank() }
+    }
+
+    fn shape_input<P: ReduceDType>(state: &Self::State<P>, dim: u32) -> u32 {
+        unsafe { (*state.0).tensor.shape(dim) }
+    }
+
+    fn shape_output<P: ReduceDType>(state: &Self::State<P>, dim: u32) -> u32 {
+        unsafe { (*state.1).shape(dim) }
+    }
+
+    fn stride_input<P: ReduceDType>(state: &Self::State<P>, dim: u32) -> u32 {
+        unsafe { (*state.0).tensor.stride(dim) }
+    }
+
+    fn stride_output<P: ReduceDType>(state: &Self::State<P>, dim: u32) -> u32 {
+        unsafe { (*state.1).stride(dim) }
+    }
+
+    fn line_size_input<P: ReduceDType>(state: &Self::State<P>) -> comptime_type!(u32) {
+        unsafe { (*state.0).tensor.line_size() }
+    }
+
+    fn line_size_output<P: ReduceDType>(state: &Self::State<P>) -> comptime_type!(u32) {
+        unsafe { (*state.1).line_size() }
+    }
+}
+
+/// Reduce arguments where the input is the element-wise product of two tensors.
+///
+/// Both tensors of [ProductInput] must have the same shape and strides, so the metadata of the
+/// input is the metadata of `lhs`.
+#[derive(Clone)]
+pub struct ProductArgs;
+
+#[derive(CubeLaunch, CubeType)]
+/// Input representation for [ProductArgs].
+pub struct ProductInput<E: Numeric> {
+    pub lhs: Tensor<Line<E>>,
+    pub rhs: Tensor<Line<E>>,
+}
+
+#[cube]
+impl ReduceArgs for ProductArgs {
+    type Input<EG: Numeric> = ProductInput<EG>;
+    type Output<EG: Numeric> = Tensor<Line<EG>>;
+    type State<P: ReduceDType> = (*const ProductInput<P::In>, *mut Tensor<Line<P::Out>>);
+
+    fn init_state<P: ReduceDType>(
+        input: &Self::Input<P::In>,
+        output: &mut Self::Output<P::Out>,
+    ) -> Self::State<P> {
+        (input, output)
+    }
+
+    fn read_input<P: ReduceDType>(state: &Self::State<P>, index: u32) -> Line<P::In> {
+        unsafe { (*state.0).lhs[index] * (*state.0).rhs[index] }
+    }
+
+    fn read_input_as<P: ReduceDType, Acc: Numeric>(
+        state: &Self::State<P>,
+        index: u32,
+    ) -> Line<Acc> {
+        unsafe {
+            Line::<Acc>::cast_from((*state.0).lhs[index])
+                * Line::<Acc>::cast_from((*state.0).rhs[index])
+        }
+    }
+
+    fn read_output<P: ReduceDType>(state: &Self::State<P>, index: u32) -> Line<P::Out> {
+        unsafe { (*state.1)[index] }
+    }
+
+    fn write_output<P: ReduceDType>(state: &mut Self::State<P>, index: u32, value: Line<P::Out>) {
+        unsafe { (*state.1)[index] = value }
+    }
+
+    fn buffer_len_input<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.0).lhs.buffer_len() }
+    }
+
+    fn buffer_len_output<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.1).buffer_len() }
+    }
+
+    fn len_input<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.0).lhs.len() }
+    }
+
+    fn len_output<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.1).len() }
+    }
+    fn rank_input<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.0).lhs.rank() }
+    }
+
+    fn rank_output<P: ReduceDType>(state: &Self::State<P>) -> u32 {
+        unsafe { (*state.1).rank() }
+    }
+
+    fn shape_input<P: ReduceDType>(state: &Self::State<P>, dim: u32) -> u32 {
+        unsafe { (*state.0).lhs.shape(dim) }
+    }
+
+    fn shape_output<P: ReduceDType>(state: &Self::State<P>, dim: u32) -> u32 {
+        unsafe { (*state.1).shape(dim) }
+    }
+
+    fn stride_input<P: ReduceDType>(state: &Self::State<P>, dim: u32) -> u32 {
+        unsafe { (*state.0).lhs.stride(dim) }
+    }
+
+    fn stride_output<P: ReduceDType>(state: &Self::State<P>, dim: u32) -> u32 {
+        unsafe { (*state.1).stride(dim) }
+    }
+
+    fn line_size_input<P: ReduceDType>(state: &Self::State<P>) -> comptime_type!(u32) {
+        unsafe { (*state.0).lhs.line_size() }
+    }
+
+    fn line_size_output<P: ReduceDType>(state: &Self::State<P>) -> comptime_type!(u32) {
+        unsafe { (*state.1).line_size() }
+    }
+}
+
+/// Memory offset of the virtual `index` once decomposed along the given axes, innermost last.
+#[cube]
+fn gather_offset(index: u32, shape: &Sequence<FastDivmod>, strides: &Sequence<u32>) -> u32 {
+    let mut index = index;
+    let mut offset = 0;
+    let shape = shape.rev();
+    let strides = strides.rev();
+
+    #[unroll]
+    for i in 0..shape.len() {
+        let (rem, local_pos) = shape.index(i).div_mod(index);
+        index = rem;
+        offset += local_pos * *strides.index(i);
+    }
+
+    offset
+}
+
 pub struct Input;
 pub struct Output;
+/// Tag of an input read cast to `Acc`.
+pub struct InputAs<Acc> {
+    _acc: PhantomData<Acc>,
+}
 
 pub struct TensorArg<P: ReduceDType, RA: ReduceArgs, Tag> {
     _state: *mut RA::State<P>,
@@ -175,6 +417,21 @@ impl<P: ReduceDType, RA: ReduceArgs> TensorArg<P, RA, Input> {
     }
 }
 
+impl<P: ReduceDType, RA: ReduceArgs, Acc: Numeric> TensorArg<P, RA, InputAs<Acc>> {
+    pub fn new_input_as(_state: &RA::State<P>) -> Self {
+        unexpanded!()
+    }
+    pub fn __expand_new_input_as(
+        _scope: &mut Scope,
+        state: <RA::State<P> as CubeType>::ExpandType,
+    ) -> TensorArgExpand<P, RA, InputAs<Acc>> {
+        TensorArgExpand {
+            state,
+            tag: PhantomData,
+        }
+    }
+}
+
 impl<P: ReduceDType, RA: ReduceArgs> TensorArg<P, RA, Output> {
     pub fn new_output(_state: &mut RA::State<P>) -> Self {
         unexpanded!()
@@ -192,6 +449,10 @@ impl<P: ReduceDType, RA: ReduceArgs> TensorArg<P, RA, Output> {
 
 impl<P: ReduceDType, RA: ReduceArgs> VirtualTensorOperations<P::Out> for TensorArg<P, RA, Output> {}
 impl<P: ReduceDType, RA: ReduceArgs> VirtualTensorOperations<P::In> for TensorArg<P, RA, Input> {}
+impl<P: ReduceDType, RA: ReduceArgs, Acc: Numeric> VirtualTensorOperations<Acc>
+    for TensorArg<P, RA, InputAs<Acc>>
+{
+}
 
 impl<P: ReduceDType, RA: ReduceArgs> VirtualTensorOperationsExpand<P::In>
     for TensorArgExpand<P, RA, Input>
@@ -264,6 +525,79 @@ impl<P: ReduceDType, RA: ReduceArgs> LinedExpand for TensorArgExpand<P, RA, Inpu
     }
 }
 
+impl<P: ReduceDType, RA: ReduceArgs, Acc: Numeric> VirtualTensorOperationsExpand<Acc>
+    for TensorArgExpand<P, RA, InputAs<Acc>>
+{
+    fn __expand_read_method(
+        &self,
+        scope: &mut Scope,
+        index: ExpandElementTyped<u32>,
+    ) -> ExpandElementTyped<Line<Acc>> {
+        RA::__expand_read_input_as::<P, Acc>(scope, self.state.clone(), index)
+    }
+
+    fn __expand_write_method(
+        &self,
+        _scope: &mut Scope,
+        _index: ExpandElementTyped<u32>,
+        _value: ExpandElementTyped<Line<Acc>>,
+    ) {
+        unreachable!("Can't write to input")
+    }
+
+    fn __expand_shape_method(
+        &self,
+        scope: &mut Scope,
+        axis: ExpandElementTyped<u32>,
+    ) -> ExpandElementTyped<u32> {
+        RA::__expand_shape_input(scope, self.state.clone(), axis)
+    }
+
+    fn __expand_stride_method(
+        &self,
+        scope: &mut Scope,
+        axis: ExpandElementTyped<u32>,
+    ) -> ExpandElementTyped<u32> {
+        RA::__expand_stride_input(scope, self.state.clone(), axis)
+    }
+
+    fn __expand_rank_method(&self, scope: &mut Scope) -> ExpandElementTyped<u32> {
+        RA::__expand_rank_input(scope, self.state.clone())
+    }
+    fn __expand_len_method(&self, scope: &mut Scope) -> ExpandElementTyped<u32> {
+        RA::__expand_len_input(scope, self.state.clone())
+    }
+    fn __expand_buffer_len_method(&self, scope: &mut Scope) -> ExpandElementTyped<u32> {
+        RA::__expand_buffer_len_input(scope, self.state.clone())
+    }
+
+    fn __expand_read_window_method(
+        &self,
+        _context: &mut Scope,
+        _start: ExpandElementTyped<u32>,
+        _end: ExpandElementTyped<u32>,
+    ) -> SliceExpand<Line<Acc>, ReadOnly> {
+        panic!("Unsupported")
+    }
+
+    fn __expand_as_tensor_map_method(
+        &self,
+        scope: &mut Scope,
+    ) -> CubeOptionExpand<TensorMap<Acc, Tiled>> {
+        CubeOption::__expand_new_None(scope)
+    }
+}
+
+impl<P: ReduceDType, RA: ReduceArgs, Acc: Numeric> Lined for TensorArg<P, RA, InputAs<Acc>> {}
+impl<P: ReduceDType, RA: ReduceArgs, Acc: Numeric> LinedExpand
+    for TensorArgExpand<P, RA, InputAs<Acc>>
+{
+    fn line_size(&self) -> u32 {
+        let mut scope = Scope::root(false);
+        RA::__expand_line_size_input(&mut scope, self.state.clone())
+    }
+}
+
 impl<P: ReduceDType, RA: ReduceArgs> VirtualTensorOperationsExpand<P::Out>
     for TensorArgExpand<P, RA, Output>
 {
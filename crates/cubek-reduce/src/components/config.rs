@@ -38,3 +38,25 @@ impl IdleMode {
         !matches!(self, Self::None)
     }
 }
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+/// Whether results must be bitwise reproducible from one run to another.
+pub enum Determinism {
+    /// Order-dependent reductions such as atomics are allowed.
+    #[default]
+    Relaxed,
+    /// Only fixed-order reductions are used.
+    Strict,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+/// How the elements are added by the operations that sum them, see
+/// [ReduceOperationConfig::with_summation](crate::components::instructions::ReduceOperationConfig::with_summation).
+pub enum Summation {
+    /// The elements are added one after another to a running sum.
+    #[default]
+    Naive,
+    /// The rounding errors of the running sum are accumulated and added back at the end,
+    /// at the cost of a few more operations per element.
+    Compensated,
+}
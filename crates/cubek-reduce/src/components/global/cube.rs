@@ -2,8 +2,8 @@ use crate::{
     LineMode, ReduceInstruction, ReducePrecision,
     components::{
         global::idle_check,
-        instructions::{SharedAccumulator, fuse_accumulator_inplace, reduce_inplace},
-        readers::{Reader, cube::CubeReader},
+        instructions::{SharedAccumulator, fuse_accumulator_inplace, reduce_masked_inplace},
+        readers::{Reader, ReduceInputTransform, cube::CubeReader},
         writer::Writer,
     },
     routines::CubeBlueprint,
@@ -18,6 +18,7 @@ pub struct GlobalFullCubeReduce;
 
 #[cube]
 impl GlobalFullCubeReduce {
+    #[allow(clippy::too_many_arguments)]
     pub fn execute<P: ReducePrecision, Out: Numeric, I: ReduceInstruction<P>>(
         input: &VirtualTensor<P::EI>,
         output: &mut VirtualTensor<Out, ReadWrite>,
@@ -25,6 +26,7 @@ impl GlobalFullCubeReduce {
         inst: &I,
         #[comptime] line_mode: LineMode,
         #[comptime] blueprint: CubeBlueprint,
+        #[comptime] transform: ReduceInputTransform,
     ) {
         let write_index = CUBE_POS;
 
@@ -59,6 +61,7 @@ impl GlobalFullCubeReduce {
                 idle,
                 line_mode,
                 blueprint,
+                transform,
             );
 
             let mut accumulator_final = I::null_accumulator(inst, input_line_size);
@@ -117,6 +120,7 @@ impl GlobalFullCubeReduce {
         idle: CubeOption<bool>,
         #[comptime] line_mode: LineMode,
         #[comptime] blueprint: CubeBlueprint,
+        #[comptime] transform: ReduceInputTransform,
     ) -> I::SharedAccumulator {
         let input_line_size = input.line_size();
 
@@ -129,36 +133,27 @@ impl GlobalFullCubeReduce {
             idle,
             blueprint.bound_checks,
             line_mode,
+            transform,
         );
         let reader = CubeReader::<P>::new(reader);
         let mut accumulator = I::null_accumulator(inst, input_line_size);
 
         for i in 0..reader.length() {
-            let (item, coordinate) = reader.read(i);
-            reduce_inplace::<P, I>(inst, &mut accumulator, item, coordinate, false);
+            let (item, valid, coordinate) = reader.read(i);
+            reduce_masked_inplace::<P, I>(inst, &mut accumulator, item, valid, coordinate, false);
         }
 
         let worker_pos = Self::worker_pos(blueprint);
 
         let accumulator_plane = match comptime!(blueprint.use_planes) {
-            true => {
-                // Sync at the plane level.
-                let (item, coordinate) = I::read_accumulator(inst, &accumulator);
-                let mut accumulator_plane = I::null_accumulator(inst, input_line_size);
-                reduce_inplace::<P, I>(inst, &mut accumulator_plane, item, coordinate, true);
-                accumulator_plane
-            }
+            // Sync at the plane level.
+            true => I::fuse_plane(inst, accumulator),
             false => accumulator,
         };
 
         // Sync at the cube level.
         let accumulator_size = blueprint.num_shared_accumulators;
-        let requirements = I::requirements(inst);
-        let mut accumulator_shared = I::SharedAccumulator::allocate(
-            accumulator_size,
-            input_line_size,
-            requirements.coordinates,
-        );
+        let mut accumulator_shared = I::allocate_shared(inst, accumulator_size, input_line_size);
 
         I::SharedAccumulator::write(&mut accumulator_shared, worker_pos, accumulator_plane);
 
@@ -175,11 +170,12 @@ fn reduce_scan<P: ReducePrecision, I: ReduceInstruction<P>>(
     result: &mut I::AccumulatorItem,
     #[comptime] size: u32,
 ) {
-    for i in 0..size {
-        let item = I::SharedAccumulator::read(accumulator, i);
-        let (item, coordinate) = I::read_accumulator(inst, &item);
-        reduce_inplace::<P, I>(inst, result, item, coordinate, false);
+    for i in 1..size {
+        fuse_accumulator_inplace::<P, I>(inst, accumulator, 0, i);
     }
+
+    let fused = I::SharedAccumulator::read(accumulator, 0);
+    I::assign_accumulator(inst, result, &fused);
 }
 
 /// Use all units within a cube to fuse the first `size` elements of `accumulator` inplace like this with some padding if `size` is not a power of 2.
@@ -207,7 +203,7 @@ fn reduce_scan<P: ReducePrecision, I: ReduceInstruction<P>>(
 /// There is no out-of-bound check, so it is the responsibility of the caller to ensure that `size` is at most the length
 /// of the shared memory and that there are at least `size` units within each cube.
 #[cube]
-fn reduce_tree<P: ReducePrecision, I: ReduceInstruction<P>>(
+pub(crate) fn reduce_tree<P: ReducePrecision, I: ReduceInstruction<P>>(
     inst: &I,
     accumulator: &mut I::SharedAccumulator,
     result: &mut I::AccumulatorItem,
@@ -243,7 +239,6 @@ fn reduce_tree<P: ReducePrecision, I: ReduceInstruction<P>>(
     }
     sync_cube();
 
-    let tmp = I::SharedAccumulator::read(accumulator, 0);
-    let (item, coordinate) = I::read_accumulator(inst, &tmp);
-    reduce_inplace::<P, I>(inst, result, item, coordinate, false);
+    let fused = I::SharedAccumulator::read(accumulator, 0);
+    I::assign_accumulator(inst, result, &fused);
 }
@@ -2,8 +2,8 @@ use crate::{
     LineMode, ReduceInstruction, ReducePrecision,
     components::{
         global::idle_check,
-        instructions::reduce_inplace,
-        readers::{Reader, plane::PlaneReader},
+        instructions::reduce_masked_inplace,
+        readers::{Reader, ReduceInputTransform, plane::PlaneReader},
         writer::Writer,
     },
     routines::PlaneReduceBlueprint,
@@ -18,6 +18,7 @@ pub struct GlobalFullPlaneReduce;
 
 #[cube]
 impl GlobalFullPlaneReduce {
+    #[allow(clippy::too_many_arguments)]
     pub fn execute<P: ReducePrecision, Out: Numeric, I: ReduceInstruction<P>>(
         input: &VirtualTensor<P::EI>,
         output: &mut VirtualTensor<Out, ReadWrite>,
@@ -25,6 +26,7 @@ impl GlobalFullPlaneReduce {
         inst: &I,
         #[comptime] line_mode: LineMode,
         #[comptime] blueprint: PlaneReduceBlueprint,
+        #[comptime] transform: ReduceInputTransform,
     ) {
         let write_index = CUBE_POS * CUBE_DIM_Y + UNIT_POS_Y;
 
@@ -53,6 +55,7 @@ impl GlobalFullPlaneReduce {
                 idle,
                 line_mode,
                 blueprint,
+                transform,
             );
 
             if UNIT_POS_X == 0 {
@@ -80,6 +83,7 @@ impl GlobalFullPlaneReduce {
         idle: CubeOption<bool>,
         #[comptime] line_mode: LineMode,
         #[comptime] blueprint: PlaneReduceBlueprint,
+        #[comptime] transform: ReduceInputTransform,
     ) -> I::AccumulatorItem {
         let input_line_size = input.line_size();
 
@@ -92,29 +96,33 @@ impl GlobalFullPlaneReduce {
             idle,
             blueprint.bound_checks,
             line_mode,
+            transform,
         );
         let reader = PlaneReader::<P>::new(reader);
 
         let mut accumulator = I::null_accumulator(inst, input_line_size);
 
         for i in 0..reader.length() {
-            let (item, coordinate) = reader.read(i);
-            reduce_inplace::<P, I>(
+            let (item, valid, coordinate) = reader.read(i);
+            reduce_masked_inplace::<P, I>(
                 inst,
                 &mut accumulator,
                 item,
+                valid,
                 coordinate,
                 comptime!(!blueprint.independent),
             );
+
+            // The accumulator is the same in every unit of the plane, so the whole plane leaves.
+            if comptime!(!blueprint.independent) {
+                if I::is_decided(inst, &accumulator) {
+                    break;
+                }
+            }
         }
 
         match blueprint.independent {
-            true => {
-                let (item, coordinate) = I::read_accumulator(inst, &accumulator);
-                let mut result = I::null_accumulator(inst, input_line_size);
-                reduce_inplace::<P, I>(inst, &mut result, item, coordinate, true);
-                result
-            }
+            true => I::fuse_plane(inst, accumulator),
             false => accumulator,
         }
     }
@@ -3,7 +3,7 @@ use crate::{
     components::{
         global::idle_check,
         instructions::reduce_inplace,
-        readers::{Reader, unit::UnitReader},
+        readers::{Reader, ReduceInputTransform, unit::UnitReader},
         writer::Writer,
     },
     routines::UnitReduceBlueprint,
@@ -18,6 +18,7 @@ pub struct GlobalFullUnitReduce;
 
 #[cube]
 impl GlobalFullUnitReduce {
+    #[allow(clippy::too_many_arguments)]
     pub fn execute<P: ReducePrecision, Out: Numeric, I: ReduceInstruction<P>>(
         input: &VirtualTensor<P::EI>,
         output: &mut VirtualTensor<Out, ReadWrite>,
@@ -25,6 +26,7 @@ impl GlobalFullUnitReduce {
         inst: &I,
         #[comptime] line_mode: LineMode,
         #[comptime] blueprint: UnitReduceBlueprint,
+        #[comptime] transform: ReduceInputTransform,
     ) {
         let write_index = ABSOLUTE_POS;
         let mut writer =
@@ -51,6 +53,7 @@ impl GlobalFullUnitReduce {
                 inst,
                 idle,
                 line_mode,
+                transform,
             );
             writer.write::<P, I>(b, accumulator, inst);
         }
@@ -58,6 +61,59 @@ impl GlobalFullUnitReduce {
         writer.commit();
     }
 
+    /// Same as [`execute`](Self::execute), but each reduction is written to two outputs
+    /// using two instructions that share the same accumulator.
+    ///
+    /// Both outputs must have the same shape and strides.
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_dual<P: ReducePrecision, Out: Numeric, I: ReduceInstruction<P>>(
+        input: &VirtualTensor<P::EI>,
+        output_first: &mut VirtualTensor<Out, ReadWrite>,
+        output_second: &mut VirtualTensor<Out, ReadWrite>,
+        reduce_axis: u32,
+        inst_first: &I,
+        inst_second: &I,
+        #[comptime] line_mode: LineMode,
+        #[comptime] blueprint: UnitReduceBlueprint,
+        #[comptime] transform: ReduceInputTransform,
+    ) {
+        let write_index = ABSOLUTE_POS;
+        let mut writer_first =
+            Writer::<Out>::new::<P>(input, output_first, reduce_axis, write_index, line_mode);
+        let mut writer_second =
+            Writer::<Out>::new::<P>(input, output_second, reduce_axis, write_index, line_mode);
+
+        let write_count = writer_first.write_count();
+        let reduce_index_start = write_index * write_count;
+
+        let idle = idle_check::<P, Out>(
+            input,
+            output_first,
+            reduce_index_start,
+            line_mode,
+            blueprint.unit_idle,
+        );
+
+        for b in 0..write_count {
+            let reduce_index = reduce_index_start + b;
+            let accumulator = Self::reduce_single::<P, Out, I>(
+                input,
+                output_first,
+                reduce_axis,
+                reduce_index,
+                inst_first,
+                idle,
+                line_mode,
+                transform,
+            );
+            writer_first.write::<P, I>(b, accumulator, inst_first);
+            writer_second.write::<P, I>(b, accumulator, inst_second);
+        }
+
+        writer_first.commit();
+        writer_second.commit();
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn reduce_single<P: ReducePrecision, Out: Numeric, I: ReduceInstruction<P>>(
         input: &VirtualTensor<P::EI>,
@@ -67,6 +123,7 @@ impl GlobalFullUnitReduce {
         inst: &I,
         idle: CubeOption<bool>,
         #[comptime] line_mode: LineMode,
+        #[comptime] transform: ReduceInputTransform,
     ) -> I::AccumulatorItem {
         let input_line_size = input.line_size();
 
@@ -79,6 +136,7 @@ impl GlobalFullUnitReduce {
             idle,
             comptime!(BoundChecks::None),
             line_mode,
+            transform,
         );
         let reader = UnitReader::<P>::new(reader);
 
@@ -0,0 +1,268 @@
+use super::{
+    ArgAccumulator, ReduceCoordinate, ReduceCoordinateExpand, ReduceFamily, ReduceInstruction,
+    ReduceRequirements, highest, is_nan, lowest, plane_max_propagate_nan, plane_min_propagate_nan,
+};
+use crate::components::precision::ReducePrecision;
+use cubecl::prelude::*;
+
+/// Which extremum is selected by [`ArgExtremum`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Extremum {
+    Max,
+    Min,
+}
+
+/// Which coordinate is selected by [`ArgExtremum`] when several items are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TieBreak {
+    FirstIndex,
+    LastIndex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArgExtremumConfig {
+    pub extremum: Extremum,
+    pub tie_break: TieBreak,
+}
+
+/// Compute the maximum or minimum item together with its coordinate.
+///
+/// Unlike [`ArgMax`](super::ArgMax) and [`ArgMin`](super::ArgMin), the tie-break is explicit and
+/// NaN is handled the same way by every routine: like [`Max`](super::Max) and [`Min`](super::Min)
+/// propagate NaN, a NaN item is selected over any other item, and several NaN items are
+/// tie-broken like equal items.
+///
+/// The output of the reduction is the coordinate, see
+/// [`arg_extremum`](crate::arg_extremum) to write the item as well.
+#[derive(Debug, CubeType, Clone)]
+pub struct ArgExtremum {
+    #[cube(comptime)]
+    pub config: ArgExtremumConfig,
+}
+
+impl ReduceFamily for ArgExtremum {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ArgExtremumConfig;
+}
+
+#[cube]
+impl ArgExtremum {
+    /// Compare two pairs of items and coordinates and return a new pair
+    /// where each element in the lines is the selected item with its coordinate.
+    ///
+    /// A coordinate of `u32::MAX` marks an empty accumulator, which is never selected.
+    pub fn choose<N: Numeric>(
+        &self,
+        items0: Line<N>,
+        coordinates0: Line<u32>,
+        items1: Line<N>,
+        coordinates1: Line<u32>,
+    ) -> (Line<N>, Line<u32>) {
+        let line_size = items0.size();
+        let keep = Line::empty(line_size).fill(true);
+        let replace = Line::empty(line_size).fill(false);
+        let empty = Line::empty(line_size).fill(u32::MAX);
+
+        let nan0 = is_nan(items0);
+        let nan1 = is_nan(items1);
+        let better = match comptime!(self.config.extremum) {
+            Extremum::Max => items0.greater_than(items1),
+            Extremum::Min => items0.less_than(items1),
+        };
+        let preferred = match comptime!(self.config.tie_break) {
+            TieBreak::FirstIndex => coordinates0.less_than(coordinates1),
+            TieBreak::LastIndex => coordinates0.greater_than(coordinates1),
+        };
+
+        let tie = select_many(nan0, nan1, items0.equal(items1));
+        let wins = select_many(
+            tie,
+            preferred,
+            select_many(nan0, keep, select_many(nan1, replace, better)),
+        );
+        let to_keep = select_many(
+            coordinates1.equal(empty),
+            keep,
+            select_many(coordinates0.equal(empty), replace, wins),
+        );
+
+        let items = select_many(to_keep, items0, items1);
+        let coordinates = select_many(to_keep, coordinates0, coordinates1);
+        (items, coordinates)
+    }
+
+    /// Using plane operations, return the selected coordinate among the units whose item
+    /// matches the `target`, where NaN matches NaN.
+    fn coordinate_matching<N: Numeric>(
+        &self,
+        target: Line<N>,
+        item: Line<N>,
+        coordinate: Line<u32>,
+    ) -> Line<u32> {
+        let line_size = item.size();
+        let empty = Line::empty(line_size).fill(u32::MAX);
+        let zero = Line::empty(line_size).fill(0u32);
+        let one = Line::empty(line_size).fill(1u32);
+
+        let is_candidate = select_many(
+            coordinate.equal(empty),
+            Line::empty(line_size).fill(false),
+            select_many(is_nan(target), is_nan(item), item.equal(target)),
+        );
+
+        match comptime!(self.config.tie_break) {
+            TieBreak::FirstIndex => plane_min(select_many(is_candidate, coordinate, empty)),
+            TieBreak::LastIndex => {
+                // Shifted by one so that 0 marks the planes without any candidate.
+                let last = plane_max(select_many(is_candidate, coordinate + one, zero));
+                let missing = last.equal(zero);
+                select_many(missing, empty, select_many(missing, one, last) - one)
+            }
+        }
+    }
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for ArgExtremum {
+    type AccumulatorItem = (Line<P::EA>, Line<u32>);
+    type SharedAccumulator = ArgAccumulator<P::EA>;
+    type Config = ArgExtremumConfig;
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: true }
+    }
+
+    fn from_config(#[comptime] config: Self::Config) -> Self {
+        ArgExtremum { config }
+    }
+
+    fn null_input(this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        match comptime!(this.config.extremum) {
+            Extremum::Max => Line::empty(line_size).fill(lowest::<P::EI>()),
+            Extremum::Min => Line::empty(line_size).fill(highest::<P::EI>()),
+        }
+    }
+
+    fn null_accumulator(this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        let item = match comptime!(this.config.extremum) {
+            Extremum::Max => Line::empty(line_size).fill(lowest::<P::EA>()),
+            Extremum::Min => Line::empty(line_size).fill(highest::<P::EA>()),
+        };
+        (item, Line::empty(line_size).fill(u32::MAX))
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        destination.0 = source.0;
+        destination.1 = source.1;
+    }
+
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(accumulator.0),
+            ReduceCoordinate::new_Required(accumulator.1),
+        )
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let coordinate = match coordinate {
+            ReduceCoordinate::Required(val) => val,
+            ReduceCoordinate::NotRequired => {
+                comptime! {panic!("Coordinates are required for ArgExtremum")};
+                #[allow(unreachable_code)]
+                Line::new(0)
+            }
+        };
+
+        let (candidate_item, candidate_coordinate) = if use_planes {
+            let candidate_item = match comptime!(this.config.extremum) {
+                Extremum::Max => plane_max_propagate_nan(item),
+                Extremum::Min => plane_min_propagate_nan(item),
+            };
+            let candidate_coordinate = this.coordinate_matching(candidate_item, item, coordinate);
+            (candidate_item, candidate_coordinate)
+        } else {
+            (item, coordinate)
+        };
+
+        this.choose(
+            Line::cast_from(candidate_item),
+            candidate_coordinate,
+            accumulator.0,
+            accumulator.1,
+        )
+    }
+
+    /// The coordinate of an invalid item is marked as empty, so the item is never selected even
+    /// when it ties with the valid items.
+    fn reduce_masked(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        valid: bool,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let coordinate = match coordinate {
+            ReduceCoordinate::Required(val) => {
+                let empty = Line::empty(val.size()).fill(u32::MAX);
+                ReduceCoordinate::new_Required(select_many(
+                    Line::empty(val.size()).fill(valid),
+                    val,
+                    empty,
+                ))
+            }
+            ReduceCoordinate::NotRequired => ReduceCoordinate::new_NotRequired(),
+        };
+        Self::reduce(this, accumulator, item, coordinate, use_planes)
+    }
+
+    fn fuse_accumulators(
+        this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        this.choose(lhs.0, lhs.1, rhs.0, rhs.1)
+    }
+
+    fn merge_line<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let mut item = Line::new(accumulator.0[0]);
+        let mut coordinate = Line::new(accumulator.1[0]);
+        #[unroll]
+        for k in 1..accumulator.0.size() {
+            let (merged_item, merged_coordinate) = this.choose(
+                item,
+                coordinate,
+                Line::new(accumulator.0[k]),
+                Line::new(accumulator.1[k]),
+            );
+            item = merged_item;
+            coordinate = merged_coordinate;
+        }
+        Out::cast_from(coordinate[0])
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator.1)
+    }
+}
@@ -39,8 +39,21 @@ pub trait ReduceInstruction<P: ReducePrecision>:
     type SharedAccumulator: SharedAccumulator<Item = Self::AccumulatorItem>;
 
     fn from_config(#[comptime] config: Self::Config) -> Self;
+
+    /// Allocate the shared accumulator used to fuse the accumulators of `length` units.
+    fn allocate_shared(
+        this: &Self,
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+    ) -> Self::SharedAccumulator {
+        let requirements = Self::requirements(this);
+        Self::SharedAccumulator::allocate(length, line_size, requirements.coordinates)
+    }
+
     /// A input such that `Self::reduce(accumulator, Self::null_input(), coordinate, use_planes)`
     /// is guaranteed to return `accumulator` unchanged for any choice of `coordinate`.
+    ///
+    /// Instructions without such an input must override [`reduce_masked`](ReduceInstruction::reduce_masked).
     fn null_input(this: &Self, #[comptime] line_size: u32) -> Line<P::EI>;
 
     /// A accumulator such that `Self::fuse_accumulators(accumulator, Self::null_accumulator()` always returns
@@ -71,6 +84,21 @@ pub trait ReduceInstruction<P: ReducePrecision>:
         #[comptime] use_planes: bool,
     ) -> Self::AccumulatorItem;
 
+    /// Like [`reduce`](ReduceInstruction::reduce), for an `item` read with bound checks.
+    /// When `valid` is `false`, the item was out-of-bound and replaced by the null input.
+    ///
+    /// Defaults to [`reduce`](ReduceInstruction::reduce), which relies on the null input being neutral.
+    fn reduce_masked(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _valid: bool,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        Self::reduce(this, accumulator, item, coordinate, use_planes)
+    }
+
     /// Reduce two accumulators into a single accumulator.
     fn fuse_accumulators(
         this: &Self,
@@ -78,6 +106,24 @@ pub trait ReduceInstruction<P: ReducePrecision>:
         rhs: Self::AccumulatorItem,
     ) -> Self::AccumulatorItem;
 
+    /// Whether the `accumulator` can't change anymore, so the remaining items can be skipped.
+    ///
+    /// This is only checked by the planes reducing a vector together, after each
+    /// [`reduce`](ReduceInstruction::reduce) with `use_planes`, when every unit of the plane holds
+    /// the same accumulator. Defaults to `false`.
+    fn is_decided(_this: &Self, _accumulator: &Self::AccumulatorItem) -> bool {
+        false
+    }
+
+    /// Fuse the accumulators of all units within a plane.
+    /// Every unit of the plane receives the same fused accumulator.
+    ///
+    /// Defaults to [`fuse_plane_items`], instructions whose accumulator can't be read back
+    /// as an input item must override it.
+    fn fuse_plane(this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        fuse_plane_items::<P, Self>(this, accumulator)
+    }
+
     /// Reduce all elements of the accumulator into a single output element of type `Out`.
     fn merge_line<Out: Numeric>(
         this: &Self,
@@ -180,6 +226,19 @@ pub fn reduce_inplace<P: ReducePrecision, R: ReduceInstruction<P>>(
     R::assign_accumulator(inst, accumulator, reduction);
 }
 
+#[cube]
+pub fn reduce_masked_inplace<P: ReducePrecision, R: ReduceInstruction<P>>(
+    inst: &R,
+    accumulator: &mut R::AccumulatorItem,
+    item: Line<P::EI>,
+    valid: bool,
+    coordinate: ReduceCoordinate,
+    #[comptime] use_planes: bool,
+) {
+    let reduction = &R::reduce_masked(inst, accumulator, item, valid, coordinate, use_planes);
+    R::assign_accumulator(inst, accumulator, reduction);
+}
+
 #[cube]
 pub fn reduce_shared_inplace<P: ReducePrecision, R: ReduceInstruction<P>>(
     inst: &R,
@@ -208,3 +267,18 @@ pub fn fuse_accumulator_inplace<P: ReducePrecision, R: ReduceInstruction<P>>(
     );
     R::SharedAccumulator::write(accumulator, destination, fused);
 }
+
+/// Fuse the accumulators of all units within a plane by reading them back as items
+/// and reducing them with planes.
+///
+/// This is only valid for instructions whose accumulator can be read back as an input item.
+#[cube]
+pub fn fuse_plane_items<P: ReducePrecision, R: ReduceInstruction<P>>(
+    inst: &R,
+    accumulator: R::AccumulatorItem,
+) -> R::AccumulatorItem {
+    let (item, coordinate) = R::read_accumulator(inst, &accumulator);
+    let mut result = R::null_accumulator(inst, item.size());
+    reduce_inplace::<P, R>(inst, &mut result, item, coordinate, true);
+    result
+}
@@ -0,0 +1,602 @@
+use super::{
+    NormExponent, NormPower, ReduceCoordinate, ReduceFamily, ReduceInstruction, ReduceRequirements,
+    ScaledPowerSum, SharedAccumulator, is_nan, rescale_factor,
+};
+use crate::components::{
+    precision::ReducePrecision,
+    readers::{ReduceInputTransform, transform_input},
+};
+use cubecl::prelude::*;
+
+/// Running sum with the compensation of its rounding errors, with an independent state for
+/// each element of the lines.
+///
+/// This is the Neumaier variant of Kahan summation: every addition computes the rounding error
+/// it makes and accumulates it separately, so the error of a long sum stays close to a single
+/// rounding instead of growing with the number of elements.
+#[derive(CubeType)]
+pub struct CompensatedSumState<N: Numeric> {
+    pub sum: Line<N>,
+    /// The rounding errors of all the additions into `sum`.
+    pub compensation: Line<N>,
+}
+
+#[cube]
+impl<N: Numeric> CompensatedSumState<N> {
+    /// A state without any element.
+    pub fn null(#[comptime] line_size: u32) -> CompensatedSumState<N> {
+        let zero = Line::empty(line_size).fill(N::from_int(0));
+        CompensatedSumState::<N> {
+            sum: zero,
+            compensation: zero,
+        }
+    }
+
+    /// A state containing only the given `item`.
+    pub fn from_item(item: Line<N>) -> CompensatedSumState<N> {
+        CompensatedSumState::<N> {
+            sum: item,
+            compensation: Line::empty(item.size()).fill(N::from_int(0)),
+        }
+    }
+
+    /// Add both sums, accumulating the rounding error of the addition with both compensations.
+    pub fn merge(&self, other: &CompensatedSumState<N>) -> CompensatedSumState<N> {
+        let sum = self.sum + other.sum;
+        // The error is recovered exactly from the largest operand.
+        let error = select_many(
+            Line::abs(other.sum).greater_than(Line::abs(self.sum)),
+            (other.sum - sum) + self.sum,
+            (self.sum - sum) + other.sum,
+        );
+
+        CompensatedSumState::<N> {
+            sum,
+            compensation: self.compensation + other.compensation + error,
+        }
+    }
+
+    /// Merge the states of all units within a plane.
+    ///
+    /// The sums of the plane aren't compensated, but their error is bounded by the number of
+    /// units and doesn't grow with the length of the reduction.
+    pub fn merge_plane(&self) -> CompensatedSumState<N> {
+        CompensatedSumState::<N> {
+            sum: plane_sum(self.sum),
+            compensation: plane_sum(self.compensation),
+        }
+    }
+
+    /// Merge the elements of the lines into a state with a line size of 1.
+    pub fn merge_lanes(&self) -> CompensatedSumState<N> {
+        let mut state = CompensatedSumState::<N>::null(1u32);
+
+        #[unroll]
+        for k in 0..self.sum.size() {
+            let lane = CompensatedSumState::<N> {
+                sum: Line::new(self.sum[k]),
+                compensation: Line::new(self.compensation[k]),
+            };
+            state = state.merge(&lane);
+        }
+
+        state
+    }
+
+    /// The compensated sum of the accumulated elements.
+    ///
+    /// Once the sum overflows or is NaN, the compensation is NaN and the sum is returned as is.
+    pub fn value(&self) -> Line<N> {
+        select_many(
+            is_nan(self.compensation),
+            self.sum,
+            self.sum + self.compensation,
+        )
+    }
+}
+
+/// The shared memories used by the compensated instructions.
+#[derive(CubeType)]
+pub struct CompensatedSumAccumulator<N: Numeric> {
+    pub sum: SharedMemory<Line<N>>,
+    pub compensation: SharedMemory<Line<N>>,
+}
+
+#[cube]
+impl<N: Numeric> SharedAccumulator for CompensatedSumAccumulator<N> {
+    type Item = CompensatedSumState<N>;
+
+    fn allocate(
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+        #[comptime] _coordinate: bool,
+    ) -> Self {
+        CompensatedSumAccumulator::<N> {
+            sum: SharedMemory::new_lined(length, line_size),
+            compensation: SharedMemory::new_lined(length, line_size),
+        }
+    }
+
+    fn read(accumulator: &Self, index: u32) -> Self::Item {
+        CompensatedSumState::<N> {
+            sum: accumulator.sum[index],
+            compensation: accumulator.compensation[index],
+        }
+    }
+
+    fn write(accumulator: &mut Self, index: u32, item: Self::Item) {
+        accumulator.sum[index] = item.sum;
+        accumulator.compensation[index] = item.compensation;
+    }
+}
+
+/// Add the `item` to the `accumulator`, after summing it over the plane if `use_planes` is `true`.
+#[cube]
+fn accumulate<N: Numeric>(
+    accumulator: &CompensatedSumState<N>,
+    item: Line<N>,
+    #[comptime] use_planes: bool,
+) -> CompensatedSumState<N> {
+    let state = CompensatedSumState::<N>::from_item(item);
+
+    if comptime!(use_planes) {
+        accumulator.merge(&state.merge_plane())
+    } else {
+        accumulator.merge(&state)
+    }
+}
+
+/// Return the sum of the items with compensated summation, see [`CompensatedSumState`].
+///
+/// This is more precise than [`Sum`](super::Sum) for long reductions, at the cost of a few more
+/// operations per item and twice the shared memory. The sums of the partial results of units,
+/// planes and cubes are already combined pairwise, so the compensation matters most for the
+/// items accumulated one after another by each unit.
+///
+/// Every item is first mapped by the `transform`, computed in the accumulation precision,
+/// e.g. [`CompensatedL1Norm`] sums the absolute values.
+#[derive(Debug, CubeType, Clone)]
+pub struct CompensatedSum {
+    #[cube(comptime)]
+    pub transform: ReduceInputTransform,
+}
+
+/// Compute the L1 norm, which is the sum of the absolute values, summed like [`CompensatedSum`].
+///
+/// This is a [`CompensatedSum`] configured with [`Abs`](ReduceInputTransform::Abs).
+pub type CompensatedL1Norm = CompensatedSum;
+
+impl ReduceFamily for CompensatedSum {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ReduceInputTransform;
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for CompensatedSum {
+    type AccumulatorItem = CompensatedSumState<P::EA>;
+    type SharedAccumulator = CompensatedSumAccumulator<P::EA>;
+    type Config = ReduceInputTransform;
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(#[comptime] config: Self::Config) -> Self {
+        CompensatedSum { transform: config }
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::from_int(0))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        CompensatedSumState::<P::EA>::null(line_size)
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        destination.sum = source.sum;
+        destination.compensation = source.compensation;
+    }
+
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(accumulator.value()),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let item = transform_input::<P::EA>(Line::cast_from(item), this.transform);
+        accumulate::<P::EA>(accumulator, item, use_planes)
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs.merge(&rhs)
+    }
+
+    fn fuse_plane(_this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        accumulator.merge_plane()
+    }
+
+    fn merge_line<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        Out::cast_from(accumulator.merge_lanes().value()[0])
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator.value())
+    }
+}
+
+/// Return the mean of the items, summed like [`CompensatedSum`].
+#[derive(Debug, CubeType, Clone)]
+pub struct CompensatedMean {
+    pub(crate) sum: CompensatedSum,
+}
+
+impl ReduceFamily for CompensatedMean {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ();
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for CompensatedMean {
+    type AccumulatorItem = CompensatedSumState<P::EA>;
+    type SharedAccumulator = CompensatedSumAccumulator<P::EA>;
+    type Config = ();
+
+    fn requirements(this: &Self) -> ReduceRequirements {
+        <CompensatedSum as ReduceInstruction<P>>::requirements(&this.sum)
+    }
+
+    fn from_config(_config: Self::Config) -> Self {
+        CompensatedMean {
+            sum: CompensatedSum {
+                transform: comptime!(ReduceInputTransform::Identity),
+            },
+        }
+    }
+
+    fn null_input(this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        <CompensatedSum as ReduceInstruction<P>>::null_input(&this.sum, line_size)
+    }
+
+    fn null_accumulator(this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        <CompensatedSum as ReduceInstruction<P>>::null_accumulator(&this.sum, line_size)
+    }
+
+    fn assign_accumulator(
+        this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        <CompensatedSum as ReduceInstruction<P>>::assign_accumulator(
+            &this.sum,
+            destination,
+            source,
+        );
+    }
+
+    fn read_accumulator(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        <CompensatedSum as ReduceInstruction<P>>::read_accumulator(&this.sum, accumulator)
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        <CompensatedSum as ReduceInstruction<P>>::reduce(
+            &this.sum,
+            accumulator,
+            item,
+            coordinate,
+            use_planes,
+        )
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs.merge(&rhs)
+    }
+
+    fn fuse_plane(_this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        accumulator.merge_plane()
+    }
+
+    fn merge_line<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        shape_axis_reduce: u32,
+    ) -> Out {
+        <CompensatedSum as ReduceInstruction<P>>::merge_line::<Out>(
+            &this.sum,
+            accumulator,
+            shape_axis_reduce,
+        ) / Out::cast_from(shape_axis_reduce)
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        let line_size = accumulator.sum.size();
+        let sum = <CompensatedSum as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+            &this.sum,
+            accumulator,
+            shape_axis_reduce,
+        );
+        sum / Line::empty(line_size).fill(Out::cast_from(shape_axis_reduce))
+    }
+}
+
+/// A [`ScaledPowerSum`] whose sum of powers is a [`CompensatedSumState`], with an independent
+/// state for each element of the lines.
+///
+/// Both the sum and its compensation are relative to the same `scale`, so they are rescaled
+/// together whenever the scale grows.
+#[derive(CubeType)]
+pub struct CompensatedPowerSum<N: Numeric> {
+    /// The largest absolute value accumulated so far.
+    pub scale: Line<N>,
+    /// Compensated sum of `(|x| / scale)^p` over the accumulated elements.
+    pub sum: CompensatedSumState<N>,
+}
+
+#[cube]
+impl<N: Numeric> CompensatedPowerSum<N> {
+    /// A state without any element.
+    pub fn null(#[comptime] line_size: u32) -> CompensatedPowerSum<N> {
+        CompensatedPowerSum::<N> {
+            scale: Line::empty(line_size).fill(N::from_int(0)),
+            sum: CompensatedSumState::<N>::null(line_size),
+        }
+    }
+
+    /// A state containing only the given absolute values.
+    pub fn from_abs(item_abs: Line<N>, p: &NormPower) -> CompensatedPowerSum<N> {
+        CompensatedPowerSum::<N> {
+            scale: item_abs,
+            sum: CompensatedSumState::<N>::from_item(rescale_factor(item_abs, item_abs, p)),
+        }
+    }
+
+    /// The sum made relative to the larger `scale`.
+    fn rescaled(&self, scale: Line<N>, p: &NormPower) -> CompensatedSumState<N> {
+        let factor = rescale_factor(self.scale, scale, p);
+        CompensatedSumState::<N> {
+            sum: self.sum.sum * factor,
+            compensation: self.sum.compensation * factor,
+        }
+    }
+
+    /// Rescale both sums to the largest scale and add them.
+    pub fn merge(&self, other: &CompensatedPowerSum<N>, p: &NormPower) -> CompensatedPowerSum<N> {
+        let scale = select_many(
+            self.scale.greater_than(other.scale),
+            self.scale,
+            other.scale,
+        );
+        let sum = self.rescaled(scale, p).merge(&other.rescaled(scale, p));
+
+        CompensatedPowerSum::<N> { scale, sum }
+    }
+
+    /// Merge the states of all units within a plane.
+    pub fn merge_plane(&self, p: &NormPower) -> CompensatedPowerSum<N> {
+        let scale = plane_max(self.scale);
+        let sum = self.rescaled(scale, p).merge_plane();
+
+        CompensatedPowerSum::<N> { scale, sum }
+    }
+
+    /// Merge the elements of the lines into a state with a line size of 1.
+    pub fn merge_lanes(&self, p: &NormPower) -> CompensatedPowerSum<N> {
+        let mut state = CompensatedPowerSum::<N>::null(1u32);
+
+        #[unroll]
+        for k in 0..self.scale.size() {
+            let lane = CompensatedPowerSum::<N> {
+                scale: Line::new(self.scale[k]),
+                sum: CompensatedSumState::<N> {
+                    sum: Line::new(self.sum.sum[k]),
+                    compensation: Line::new(self.sum.compensation[k]),
+                },
+            };
+            state = state.merge(&lane, p);
+        }
+
+        state
+    }
+
+    /// The norm of the accumulated elements, see [`ScaledPowerSum::norm`].
+    pub fn norm(&self, p: &NormPower) -> Line<N> {
+        let state = ScaledPowerSum::<N> {
+            scale: self.scale,
+            sum: self.sum.value(),
+        };
+        state.norm(p)
+    }
+}
+
+/// The shared memories used by [`CompensatedL2Norm`].
+#[derive(CubeType)]
+pub struct CompensatedPowerSumAccumulator<N: Numeric> {
+    pub scale: SharedMemory<Line<N>>,
+    pub sum: SharedMemory<Line<N>>,
+    pub compensation: SharedMemory<Line<N>>,
+}
+
+#[cube]
+impl<N: Numeric> SharedAccumulator for CompensatedPowerSumAccumulator<N> {
+    type Item = CompensatedPowerSum<N>;
+
+    fn allocate(
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+        #[comptime] _coordinate: bool,
+    ) -> Self {
+        CompensatedPowerSumAccumulator::<N> {
+            scale: SharedMemory::new_lined(length, line_size),
+            sum: SharedMemory::new_lined(length, line_size),
+            compensation: SharedMemory::new_lined(length, line_size),
+        }
+    }
+
+    fn read(accumulator: &Self, index: u32) -> Self::Item {
+        CompensatedPowerSum::<N> {
+            scale: accumulator.scale[index],
+            sum: CompensatedSumState::<N> {
+                sum: accumulator.sum[index],
+                compensation: accumulator.compensation[index],
+            },
+        }
+    }
+
+    fn write(accumulator: &mut Self, index: u32, item: Self::Item) {
+        accumulator.scale[index] = item.scale;
+        accumulator.sum[index] = item.sum.sum;
+        accumulator.compensation[index] = item.sum.compensation;
+    }
+}
+
+/// Compute the L2 norm, which is the square root of the sum of the squares, summed like
+/// [`CompensatedSum`].
+///
+/// Like [`L2Norm`](super::L2Norm), the squares are scaled by the largest absolute value so the
+/// sum doesn't overflow, see [`CompensatedPowerSum`].
+#[derive(Debug, CubeType, Clone)]
+pub struct CompensatedL2Norm {
+    pub p: NormPower,
+}
+
+impl ReduceFamily for CompensatedL2Norm {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ();
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for CompensatedL2Norm {
+    type AccumulatorItem = CompensatedPowerSum<P::EA>;
+    type SharedAccumulator = CompensatedPowerSumAccumulator<P::EA>;
+    type Config = ();
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(_config: Self::Config) -> Self {
+        CompensatedL2Norm {
+            p: NormPower::from_exponent(comptime!(NormExponent::TWO)),
+        }
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::from_int(0))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        CompensatedPowerSum::<P::EA>::null(line_size)
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        destination.scale = source.scale;
+        destination.sum.sum = source.sum.sum;
+        destination.sum.compensation = source.sum.compensation;
+    }
+
+    /// Only the scale can be read back as an item, so the accumulator must never be
+    /// reduced through this function.
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(accumulator.scale),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let item_abs = Line::abs(Line::<P::EA>::cast_from(item));
+        let state = CompensatedPowerSum::<P::EA>::from_abs(item_abs, &this.p);
+
+        if comptime!(use_planes) {
+            accumulator.merge(&state.merge_plane(&this.p), &this.p)
+        } else {
+            accumulator.merge(&state, &this.p)
+        }
+    }
+
+    fn fuse_accumulators(
+        this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs.merge(&rhs, &this.p)
+    }
+
+    fn fuse_plane(this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        accumulator.merge_plane(&this.p)
+    }
+
+    fn merge_line<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let norm = accumulator.merge_lanes(&this.p).norm(&this.p);
+        Out::cast_from(norm[0])
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator.norm(&this.p))
+    }
+}
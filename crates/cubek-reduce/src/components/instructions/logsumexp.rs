@@ -0,0 +1,219 @@
+use super::{
+    ReduceCoordinate, ReduceFamily, ReduceInstruction, ReduceRequirements, SharedAccumulator,
+};
+use crate::components::precision::ReducePrecision;
+use cubecl::prelude::*;
+
+/// Running state of a log-sum-exp, with an independent state for each element of the lines.
+///
+/// The `sum` is always relative to the running `max`, so exponentials never overflow.
+#[derive(CubeType)]
+pub struct LogSumExpState<N: Numeric> {
+    pub max: Line<N>,
+    /// Sum of `exp(x - max)` over the accumulated elements.
+    pub sum: Line<N>,
+}
+
+#[cube]
+impl<N: Numeric> LogSumExpState<N> {
+    /// A state without any element.
+    pub fn null(#[comptime] line_size: u32) -> LogSumExpState<N> {
+        LogSumExpState::<N> {
+            max: Line::empty(line_size).fill(N::cast_from(f32::NEG_INFINITY)),
+            sum: Line::empty(line_size).fill(N::from_int(0)),
+        }
+    }
+
+    /// A state containing only the given `item`.
+    pub fn from_item(item: Line<N>) -> LogSumExpState<N> {
+        LogSumExpState::<N> {
+            max: item,
+            sum: rescale(item, item),
+        }
+    }
+
+    /// Rescale both sums to the common maximum and add them.
+    pub fn merge(&self, other: &LogSumExpState<N>) -> LogSumExpState<N> {
+        let max = select_many(self.max.greater_than(other.max), self.max, other.max);
+        let sum = self.sum * rescale(self.max, max) + other.sum * rescale(other.max, max);
+
+        LogSumExpState::<N> { max, sum }
+    }
+
+    /// Merge the states of all units within a plane.
+    pub fn merge_plane(&self) -> LogSumExpState<N> {
+        let max = plane_max(self.max);
+        let sum = plane_sum(self.sum * rescale(self.max, max));
+
+        LogSumExpState::<N> { max, sum }
+    }
+
+    /// Merge the elements of the lines into a state with a line size of 1.
+    pub fn merge_lanes(&self) -> LogSumExpState<N> {
+        let mut state = LogSumExpState::<N>::null(1u32);
+
+        #[unroll]
+        for k in 0..self.max.size() {
+            let lane = LogSumExpState::<N> {
+                max: Line::new(self.max[k]),
+                sum: Line::new(self.sum[k]),
+            };
+            state = state.merge(&lane);
+        }
+
+        state
+    }
+
+    /// The log-sum-exp of the accumulated elements, which is `-inf` for an empty state.
+    pub fn value(&self) -> Line<N> {
+        let log_sum = Line::<f32>::cast_from(self.sum).log();
+        Line::cast_from(Line::<f32>::cast_from(self.max) + log_sum)
+    }
+}
+
+/// Compute `exp(value - max)` in `f32`, returning 1 instead of NaN when both are the same
+/// infinity.
+///
+/// With `max == +inf`, only the `+inf` values count, and with `max == -inf` the sum is zero or
+/// counts `-inf` values, which both give a log-sum-exp of `-inf`.
+#[cube]
+fn rescale<N: Numeric>(value: Line<N>, max: Line<N>) -> Line<N> {
+    let value = Line::<f32>::cast_from(value);
+    let max = Line::<f32>::cast_from(max);
+    let zero = Line::empty(value.size()).fill(0.0f32);
+    let diff = select_many(value.equal(max), zero, value - max);
+    Line::cast_from(diff.exp())
+}
+
+/// The shared memories used by [`LogSumExp`].
+#[derive(CubeType)]
+pub struct LogSumExpAccumulator<N: Numeric> {
+    pub max: SharedMemory<Line<N>>,
+    pub sum: SharedMemory<Line<N>>,
+}
+
+#[cube]
+impl<N: Numeric> SharedAccumulator for LogSumExpAccumulator<N> {
+    type Item = LogSumExpState<N>;
+
+    fn allocate(
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+        #[comptime] _coordinate: bool,
+    ) -> Self {
+        LogSumExpAccumulator::<N> {
+            max: SharedMemory::new_lined(length, line_size),
+            sum: SharedMemory::new_lined(length, line_size),
+        }
+    }
+
+    fn read(accumulator: &Self, index: u32) -> Self::Item {
+        LogSumExpState::<N> {
+            max: accumulator.max[index],
+            sum: accumulator.sum[index],
+        }
+    }
+
+    fn write(accumulator: &mut Self, index: u32, item: Self::Item) {
+        accumulator.max[index] = item.max;
+        accumulator.sum[index] = item.sum;
+    }
+}
+
+/// Compute `log(sum(exp(x)))` with a running maximum, so large inputs don't overflow.
+///
+/// The exponentials and logarithms are computed in `f32`, while the state uses the
+/// accumulation precision.
+#[derive(Debug, CubeType, Clone)]
+pub struct LogSumExp {}
+
+impl ReduceFamily for LogSumExp {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ();
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for LogSumExp {
+    type AccumulatorItem = LogSumExpState<P::EA>;
+    type SharedAccumulator = LogSumExpAccumulator<P::EA>;
+    type Config = ();
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(_config: Self::Config) -> Self {
+        LogSumExp {}
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::cast_from(f32::NEG_INFINITY))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        LogSumExpState::<P::EA>::null(line_size)
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        destination.max = source.max;
+        destination.sum = source.sum;
+    }
+
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(accumulator.value()),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let state = LogSumExpState::<P::EA>::from_item(Line::cast_from(item));
+
+        if comptime!(use_planes) {
+            accumulator.merge(&state.merge_plane())
+        } else {
+            accumulator.merge(&state)
+        }
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs.merge(&rhs)
+    }
+
+    fn fuse_plane(_this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        accumulator.merge_plane()
+    }
+
+    fn merge_line<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        Out::cast_from(accumulator.merge_lanes().value()[0])
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator.value())
+    }
+}
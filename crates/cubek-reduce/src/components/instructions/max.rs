@@ -1,9 +1,14 @@
-use super::{ReduceCoordinate, ReduceFamily, ReduceInstruction};
+use super::{
+    ReduceCoordinate, ReduceFamily, ReduceInstruction, lowest, max_propagate_nan,
+    plane_max_propagate_nan,
+};
 use crate::{components::instructions::ReduceRequirements, components::precision::ReducePrecision};
 use cubecl::prelude::*;
 
-// TODO Add to test framework.
 /// Return the item with the maximum value.
+///
+/// NaN is propagated: the result is NaN as soon as one of the items is NaN, on every backend.
+/// Use [NanMax](super::NanMax) to ignore NaN instead.
 #[derive(Debug, CubeType, Clone)]
 pub struct Max;
 
@@ -27,11 +32,11 @@ impl<P: ReducePrecision> ReduceInstruction<P> for Max {
     }
 
     fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
-        Line::empty(line_size).fill(P::EI::min_value())
+        Line::empty(line_size).fill(lowest::<P::EI>())
     }
 
     fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
-        Line::empty(line_size).fill(P::EA::min_value())
+        Line::empty(line_size).fill(lowest::<P::EA>())
     }
 
     fn assign_accumulator(
@@ -60,15 +65,10 @@ impl<P: ReducePrecision> ReduceInstruction<P> for Max {
         #[comptime] use_planes: bool,
     ) -> Self::AccumulatorItem {
         if use_planes {
-            let candidate_item = Line::cast_from(plane_max(item));
-            select_many(
-                accumulator.greater_than(candidate_item),
-                *accumulator,
-                candidate_item,
-            )
+            let candidate_item = Line::cast_from(plane_max_propagate_nan(item));
+            max_propagate_nan(*accumulator, candidate_item)
         } else {
-            let item = Line::cast_from(item);
-            select_many(accumulator.greater_than(item), *accumulator, item)
+            max_propagate_nan(*accumulator, Line::cast_from(item))
         }
     }
 
@@ -77,7 +77,7 @@ impl<P: ReducePrecision> ReduceInstruction<P> for Max {
         lhs: Self::AccumulatorItem,
         rhs: Self::AccumulatorItem,
     ) -> Self::AccumulatorItem {
-        select_many(lhs.greater_than(rhs), lhs, rhs)
+        max_propagate_nan(lhs, rhs)
     }
 
     fn merge_line<Out: Numeric>(
@@ -85,11 +85,11 @@ impl<P: ReducePrecision> ReduceInstruction<P> for Max {
         accumulator: Self::AccumulatorItem,
         _shape_axis_reduce: u32,
     ) -> Out {
-        let mut max = P::EA::min_value();
+        let mut max = lowest::<P::EA>();
         #[unroll]
         for k in 0..accumulator.size() {
             let candidate = accumulator[k];
-            max = select(candidate > max, candidate, max);
+            max = select(candidate > max || candidate != candidate, candidate, max);
         }
         Out::cast_from(max)
     }
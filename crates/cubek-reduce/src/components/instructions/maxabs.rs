@@ -1,9 +1,12 @@
-use super::{ReduceCoordinate, ReduceFamily, ReduceInstruction};
+use super::{
+    ReduceCoordinate, ReduceFamily, ReduceInstruction, max_propagate_nan, plane_max_propagate_nan,
+};
 use crate::{components::instructions::ReduceRequirements, components::precision::ReducePrecision};
 use cubecl::prelude::*;
 
-// TODO Add to test framework.
 /// Return the item with the maximum absolute value.
+///
+/// NaN is propagated: the result is NaN as soon as one of the items is NaN, on every backend.
 #[derive(Debug, CubeType, Clone)]
 pub struct MaxAbs;
 
@@ -50,15 +53,10 @@ impl<P: ReducePrecision> ReduceInstruction<P> for MaxAbs {
         #[comptime] use_planes: bool,
     ) -> Self::AccumulatorItem {
         if use_planes {
-            let candidate_item = Line::cast_from(plane_max(Line::abs(item)));
-            select_many(
-                accumulator.greater_than(candidate_item),
-                *accumulator,
-                candidate_item,
-            )
+            let candidate_item = Line::cast_from(plane_max_propagate_nan(Line::abs(item)));
+            max_propagate_nan(*accumulator, candidate_item)
         } else {
-            let item_abs = Line::cast_from(Line::abs(item));
-            select_many(accumulator.greater_than(item_abs), *accumulator, item_abs)
+            max_propagate_nan(*accumulator, Line::cast_from(Line::abs(item)))
         }
     }
 
@@ -77,7 +75,7 @@ impl<P: ReducePrecision> ReduceInstruction<P> for MaxAbs {
         lhs: Self::AccumulatorItem,
         rhs: Self::AccumulatorItem,
     ) -> Self::AccumulatorItem {
-        select_many(lhs.greater_than(rhs), lhs, rhs)
+        max_propagate_nan(lhs, rhs)
     }
 
     fn merge_line<Out: Numeric>(
@@ -89,7 +87,7 @@ impl<P: ReducePrecision> ReduceInstruction<P> for MaxAbs {
         #[unroll]
         for k in 0..accumulator.size() {
             let candidate = accumulator[k];
-            max = select(candidate > max, candidate, max);
+            max = select(candidate > max || candidate != candidate, candidate, max);
         }
         Out::cast_from(max)
     }
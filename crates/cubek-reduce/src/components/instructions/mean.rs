@@ -2,6 +2,10 @@ use super::{ReduceCoordinate, ReduceFamily, ReduceInstruction, ReduceRequirement
 use crate::components::precision::ReducePrecision;
 use cubecl::prelude::*;
 
+/// Return the mean of the items.
+///
+/// NaN is propagated by the underlying [Sum].
+/// Use [NanMean](super::NanMean) to ignore NaN instead.
 #[derive(Debug, CubeType, Clone)]
 pub struct Mean {
     pub(crate) sum: Sum,
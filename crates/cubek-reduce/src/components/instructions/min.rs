@@ -1,9 +1,14 @@
-use super::{ReduceCoordinate, ReduceFamily, ReduceInstruction};
+use super::{
+    ReduceCoordinate, ReduceFamily, ReduceInstruction, highest, min_propagate_nan,
+    plane_min_propagate_nan,
+};
 use crate::{components::instructions::ReduceRequirements, components::precision::ReducePrecision};
 use cubecl::prelude::*;
 
-// TODO Add to test framework.
-/// Return the item with the maximum absolute value.
+/// Return the item with the minimum value.
+///
+/// NaN is propagated: the result is NaN as soon as one of the items is NaN, on every backend.
+/// Use [NanMin](super::NanMin) to ignore NaN instead.
 #[derive(Debug, CubeType, Clone)]
 pub struct Min;
 
@@ -26,11 +31,11 @@ impl<P: ReducePrecision> ReduceInstruction<P> for Min {
         Min {}
     }
     fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
-        Line::empty(line_size).fill(P::EI::max_value())
+        Line::empty(line_size).fill(highest::<P::EI>())
     }
 
     fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
-        Line::empty(line_size).fill(P::EA::max_value())
+        Line::empty(line_size).fill(highest::<P::EA>())
     }
 
     fn assign_accumulator(
@@ -59,15 +64,10 @@ impl<P: ReducePrecision> ReduceInstruction<P> for Min {
         #[comptime] use_planes: bool,
     ) -> Self::AccumulatorItem {
         if use_planes {
-            let candidate_item = Line::cast_from(plane_min(item));
-            select_many(
-                accumulator.less_than(candidate_item),
-                *accumulator,
-                candidate_item,
-            )
+            let candidate_item = Line::cast_from(plane_min_propagate_nan(item));
+            min_propagate_nan(*accumulator, candidate_item)
         } else {
-            let item = Line::cast_from(item);
-            select_many(accumulator.less_than(item), *accumulator, item)
+            min_propagate_nan(*accumulator, Line::cast_from(item))
         }
     }
 
@@ -76,7 +76,7 @@ impl<P: ReducePrecision> ReduceInstruction<P> for Min {
         lhs: Self::AccumulatorItem,
         rhs: Self::AccumulatorItem,
     ) -> Self::AccumulatorItem {
-        select_many(lhs.less_than(rhs), lhs, rhs)
+        min_propagate_nan(lhs, rhs)
     }
 
     fn merge_line<Out: Numeric>(
@@ -84,11 +84,11 @@ impl<P: ReducePrecision> ReduceInstruction<P> for Min {
         accumulator: Self::AccumulatorItem,
         _shape_axis_reduce: u32,
     ) -> Out {
-        let mut min = P::EA::max_value();
+        let mut min = highest::<P::EA>();
         #[unroll]
         for k in 0..accumulator.size() {
             let candidate = accumulator[k];
-            min = select(candidate < min, candidate, min);
+            min = select(candidate < min || candidate != candidate, candidate, min);
         }
         Out::cast_from(min)
     }
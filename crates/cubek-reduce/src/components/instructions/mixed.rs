@@ -1,8 +1,15 @@
 use super::{
-    ArgMax, ArgMin, Max, MaxAbs, Mean, Min, Prod, ReduceCoordinate, ReduceFamily,
-    ReduceInstruction, ReduceRequirements, SharedAccumulator, Sum,
+    ArgMax, ArgMin, CompensatedL1Norm, CompensatedL2Norm, CompensatedMean, CompensatedPowerSum,
+    CompensatedSum, CompensatedSumState, L1Norm, L2Norm, LogSumExp, LogSumExpState, LpNorm, Max,
+    MaxAbs, Mean, Min, NanMax, NanMean, NanMeanState, NanMin, NanSum, NonZero, NonZeroReduction,
+    NormExponent, NormPower, Prod, ReduceCoordinate, ReduceFamily, ReduceInstruction,
+    ReduceRequirements, ScaledPowerSum, SharedAccumulator, Std, Sum, Var, WelfordState,
+    fuse_plane_items,
+};
+use crate::{
+    ReduceDtypes, ReduceError, Summation,
+    components::{precision::ReducePrecision, readers::ReduceInputTransform},
 };
-use crate::{ReduceDtypes, components::precision::ReducePrecision};
 use cubecl::{
     ir::{ElemType, FloatKind, IntKind, UIntKind},
     prelude::*,
@@ -20,6 +27,21 @@ pub(crate) enum ReduceOperation {
     ArgMin(ArgMin),
     Max(Max),
     Min(Min),
+    Var(Var),
+    Std(Std),
+    LogSumExp(LogSumExp),
+    L1Norm(L1Norm),
+    L2Norm(L2Norm),
+    LpNorm(LpNorm),
+    NonZero(NonZero),
+    NanSum(NanSum),
+    NanMean(NanMean),
+    NanMax(NanMax),
+    NanMin(NanMin),
+    CompensatedSum(CompensatedSum),
+    CompensatedMean(CompensatedMean),
+    CompensatedL1Norm(CompensatedL1Norm),
+    CompensatedL2Norm(CompensatedL2Norm),
 }
 
 #[derive_cube_comptime]
@@ -32,74 +54,201 @@ pub enum ReduceOperationConfig {
     ArgMin,
     Max,
     Min,
+    /// Variance where `correction` is subtracted from the number of elements.
+    Var {
+        correction: u32,
+    },
+    /// Standard deviation where `correction` is subtracted from the number of elements.
+    Std {
+        correction: u32,
+    },
+    /// Logarithm of the sum of the exponentials, computed with a running maximum.
+    LogSumExp,
+    /// Sum of the absolute values.
+    L1Norm,
+    /// Square root of the sum of the squares, also used for the Frobenius norm.
+    L2Norm,
+    /// Root `p` of the sum of the absolute values raised to the power `p`.
+    LpNorm {
+        p: NormExponent,
+    },
+    /// Whether any element is non-zero.
+    Any,
+    /// Whether all elements are non-zero.
+    All,
+    /// The number of non-zero elements.
+    CountNonZero,
+    /// Sum of the elements that are not NaN.
+    NanSum,
+    /// Mean of the elements that are not NaN.
+    NanMean,
+    /// Maximum of the elements that are not NaN.
+    NanMax,
+    /// Minimum of the elements that are not NaN.
+    NanMin,
+    /// [Sum] with compensated summation, see [CompensatedSum].
+    CompensatedSum,
+    /// [Mean] with compensated summation, see [CompensatedMean].
+    CompensatedMean,
+    /// [L1Norm] with compensated summation, see [CompensatedL1Norm].
+    CompensatedL1Norm,
+    /// [L2Norm] with compensated summation, see [CompensatedL2Norm].
+    CompensatedL2Norm,
 }
 
 impl ReduceOperationConfig {
     /// Computes the best case precision for the given config.
-    pub fn precision(&self, input: ElemType, output: Option<ElemType>) -> ReduceDtypes {
+    ///
+    /// Returns an error if the operation doesn't support the `input` type, or if
+    /// [ArgMax](Self::ArgMax) or [ArgMin](Self::ArgMin) are missing their `output` type.
+    pub fn precision(
+        &self,
+        input: ElemType,
+        output: Option<ElemType>,
+    ) -> Result<ReduceDtypes, ReduceError> {
+        if input == ElemType::Bool {
+            return Err(ReduceError::Validation {
+                details: "Can't reduce on booleans.",
+            });
+        }
+
         match self {
             ReduceOperationConfig::Sum
             | ReduceOperationConfig::Prod
-            | ReduceOperationConfig::Mean => {}
+            | ReduceOperationConfig::Mean
+            | ReduceOperationConfig::L1Norm
+            | ReduceOperationConfig::NanSum
+            | ReduceOperationConfig::CompensatedSum
+            | ReduceOperationConfig::CompensatedMean
+            | ReduceOperationConfig::CompensatedL1Norm => {}
             // No benefit to mixed precision accumulation.
             ReduceOperationConfig::MaxAbs
             | ReduceOperationConfig::Max
             | ReduceOperationConfig::Min => {
-                return ReduceDtypes {
+                return Ok(ReduceDtypes {
                     input: input.into(),
                     output: input.into(),
                     accumulation: input.into(),
-                };
+                });
             }
-            ReduceOperationConfig::ArgMax | ReduceOperationConfig::ArgMin => {
-                return ReduceDtypes {
+            // The flags and counts are exact in u32 for any input type.
+            ReduceOperationConfig::Any | ReduceOperationConfig::All => {
+                return Ok(ReduceDtypes {
                     input: input.into(),
                     output: output
-                        .expect("ArgMax and ArgMin must specify output type")
-                        .into(),
-                    accumulation: input.into(),
-                };
+                        .map(Into::into)
+                        .unwrap_or_else(u8::as_type_native_unchecked),
+                    accumulation: u32::as_type_native_unchecked(),
+                });
             }
-        };
-
-        match input {
-            ElemType::Float(kind) => {
-                let acc = match kind {
-                    FloatKind::F64 => f64::as_type_native_unchecked(),
-                    _ => f32::as_type_native_unchecked(),
-                };
-
-                ReduceDtypes {
+            ReduceOperationConfig::CountNonZero => {
+                return Ok(ReduceDtypes {
                     input: input.into(),
-                    output: input.into(),
-                    accumulation: acc,
-                }
+                    output: output
+                        .map(Into::into)
+                        .unwrap_or_else(u32::as_type_native_unchecked),
+                    accumulation: u32::as_type_native_unchecked(),
+                });
             }
-            ElemType::Int(kind) => {
-                let acc = match kind {
-                    IntKind::I64 => i64::as_type_native_unchecked(),
-                    _ => i32::as_type_native_unchecked(),
+            ReduceOperationConfig::ArgMax | ReduceOperationConfig::ArgMin => {
+                let Some(output) = output else {
+                    return Err(ReduceError::Validation {
+                        details: "ArgMax and ArgMin must specify the output type.",
+                    });
                 };
-
-                ReduceDtypes {
+                return Ok(ReduceDtypes {
                     input: input.into(),
-                    output: input.into(),
-                    accumulation: acc,
+                    output: output.into(),
+                    accumulation: input.into(),
+                });
+            }
+            // Empty slots are marked with NaN, which only exists for floats.
+            ReduceOperationConfig::NanMax | ReduceOperationConfig::NanMin => {
+                if !matches!(input, ElemType::Float(_)) {
+                    return Err(ReduceError::Validation {
+                        details: "NanMax and NanMin require a float input.",
+                    });
                 }
+                return Ok(ReduceDtypes {
+                    input: input.into(),
+                    output: input.into(),
+                    accumulation: input.into(),
+                });
             }
-            ElemType::UInt(kind) => {
-                let acc = match kind {
-                    UIntKind::U64 => u64::as_type_native_unchecked(),
-                    _ => u32::as_type_native_unchecked(),
+            ReduceOperationConfig::Var { .. }
+            | ReduceOperationConfig::Std { .. }
+            | ReduceOperationConfig::LogSumExp
+            | ReduceOperationConfig::L2Norm
+            | ReduceOperationConfig::LpNorm { .. }
+            | ReduceOperationConfig::NanMean
+            | ReduceOperationConfig::CompensatedL2Norm => {
+                let acc = match input {
+                    ElemType::Float(FloatKind::F64) => f64::as_type_native_unchecked(),
+                    ElemType::Float(_) => f32::as_type_native_unchecked(),
+                    _ => {
+                        return Err(ReduceError::Validation {
+                            details: "Var, Std, LogSumExp, L2Norm, LpNorm, NanMean and CompensatedL2Norm require a float input.",
+                        });
+                    }
                 };
 
-                ReduceDtypes {
+                return Ok(ReduceDtypes {
                     input: input.into(),
                     output: input.into(),
                     accumulation: acc,
-                }
+                });
+            }
+        };
+
+        let accumulation = match input {
+            ElemType::Float(FloatKind::F64) => f64::as_type_native_unchecked(),
+            ElemType::Float(_) => f32::as_type_native_unchecked(),
+            ElemType::Int(IntKind::I64) => i64::as_type_native_unchecked(),
+            ElemType::Int(_) => i32::as_type_native_unchecked(),
+            ElemType::UInt(UIntKind::U64) => u64::as_type_native_unchecked(),
+            ElemType::UInt(_) => u32::as_type_native_unchecked(),
+            ElemType::Bool => unreachable!("Booleans are rejected above."),
+        };
+
+        Ok(ReduceDtypes {
+            input: input.into(),
+            output: input.into(),
+            accumulation,
+        })
+    }
+}
+
+impl ReduceOperationConfig {
+    /// The same operation with the given [Summation] of its elements.
+    ///
+    /// Only [Sum](Self::Sum), [Mean](Self::Mean), [L1Norm](Self::L1Norm) and
+    /// [L2Norm](Self::L2Norm) have a compensated counterpart, the other operations are returned
+    /// unchanged.
+    pub fn with_summation(self, summation: Summation) -> Self {
+        match (self, summation) {
+            (ReduceOperationConfig::Sum, Summation::Compensated) => {
+                ReduceOperationConfig::CompensatedSum
+            }
+            (ReduceOperationConfig::Mean, Summation::Compensated) => {
+                ReduceOperationConfig::CompensatedMean
+            }
+            (ReduceOperationConfig::L1Norm, Summation::Compensated) => {
+                ReduceOperationConfig::CompensatedL1Norm
+            }
+            (ReduceOperationConfig::L2Norm, Summation::Compensated) => {
+                ReduceOperationConfig::CompensatedL2Norm
+            }
+            (ReduceOperationConfig::CompensatedSum, Summation::Naive) => ReduceOperationConfig::Sum,
+            (ReduceOperationConfig::CompensatedMean, Summation::Naive) => {
+                ReduceOperationConfig::Mean
+            }
+            (ReduceOperationConfig::CompensatedL1Norm, Summation::Naive) => {
+                ReduceOperationConfig::L1Norm
+            }
+            (ReduceOperationConfig::CompensatedL2Norm, Summation::Naive) => {
+                ReduceOperationConfig::L2Norm
             }
-            ElemType::Bool => panic!("Can't reduce on booleans"),
+            (operation, _) => operation,
         }
     }
 }
@@ -113,22 +262,199 @@ impl ReduceFamily for ReduceOperation {
 pub struct DynamicAccumulator<N: Numeric> {
     pub elements: SharedMemory<Line<N>>,
     pub args: CubeOption<SharedMemory<Line<u32>>>,
+    pub aux: CubeOption<SharedMemory<Line<N>>>,
+    pub aux2: CubeOption<SharedMemory<Line<N>>>,
 }
 
+/// The accumulator of a [`ReduceOperation`].
+///
+/// Besides the `elements`, an operation can keep the coordinates in `args` and up to two
+/// auxiliary lines, as allocated by its `allocate_shared`.
 #[derive(CubeType)]
 pub struct DynamicAccumulatorItem<N: Numeric> {
     pub elements: Line<N>,
     pub args: CubeOption<Line<u32>>,
+    pub aux: CubeOption<Line<N>>,
+    pub aux2: CubeOption<Line<N>>,
 }
 
+/// The Welford state keeps its sum of squared deviations in the elements,
+/// the mean in the first auxiliary line and the count in the second one.
 #[cube]
-impl<In: Numeric> SharedAccumulator for DynamicAccumulator<In> {
-    type Item = DynamicAccumulatorItem<In>;
+fn welford_state<N: Numeric>(accumulator: &DynamicAccumulatorItem<N>) -> WelfordState<N> {
+    WelfordState::<N> {
+        count: accumulator.aux2.unwrap(),
+        mean: accumulator.aux.unwrap(),
+        m2: accumulator.elements,
+    }
+}
 
-    fn allocate(
+#[cube]
+fn welford_accumulator<N: Numeric>(state: WelfordState<N>) -> DynamicAccumulatorItem<N> {
+    DynamicAccumulatorItem::<N> {
+        elements: state.m2,
+        args: CubeOption::new_None(),
+        aux: CubeOption::new_Some(state.mean),
+        aux2: CubeOption::new_Some(state.count),
+    }
+}
+
+/// The log-sum-exp state keeps its rescaled sum in the elements
+/// and the running maximum in the first auxiliary line.
+#[cube]
+fn logsumexp_state<N: Numeric>(accumulator: &DynamicAccumulatorItem<N>) -> LogSumExpState<N> {
+    LogSumExpState::<N> {
+        max: accumulator.aux.unwrap(),
+        sum: accumulator.elements,
+    }
+}
+
+#[cube]
+fn logsumexp_accumulator<N: Numeric>(state: LogSumExpState<N>) -> DynamicAccumulatorItem<N> {
+    DynamicAccumulatorItem::<N> {
+        elements: state.sum,
+        args: CubeOption::new_None(),
+        aux: CubeOption::new_Some(state.max),
+        aux2: CubeOption::new_None(),
+    }
+}
+
+/// The norm state keeps its scaled sum in the elements
+/// and the scale in the first auxiliary line.
+#[cube]
+fn norm_state<N: Numeric>(accumulator: &DynamicAccumulatorItem<N>) -> ScaledPowerSum<N> {
+    ScaledPowerSum::<N> {
+        scale: accumulator.aux.unwrap(),
+        sum: accumulator.elements,
+    }
+}
+
+#[cube]
+fn norm_accumulator<N: Numeric>(state: ScaledPowerSum<N>) -> DynamicAccumulatorItem<N> {
+    DynamicAccumulatorItem::<N> {
+        elements: state.sum,
+        args: CubeOption::new_None(),
+        aux: CubeOption::new_Some(state.scale),
+        aux2: CubeOption::new_None(),
+    }
+}
+
+/// The NaN-ignoring mean state keeps its sum in the elements
+/// and the count in the first auxiliary line.
+#[cube]
+fn nan_mean_state<N: Numeric>(accumulator: &DynamicAccumulatorItem<N>) -> NanMeanState<N> {
+    NanMeanState::<N> {
+        sum: accumulator.elements,
+        count: accumulator.aux.unwrap(),
+    }
+}
+
+#[cube]
+fn nan_mean_accumulator<N: Numeric>(state: NanMeanState<N>) -> DynamicAccumulatorItem<N> {
+    DynamicAccumulatorItem::<N> {
+        elements: state.sum,
+        args: CubeOption::new_None(),
+        aux: CubeOption::new_Some(state.count),
+        aux2: CubeOption::new_None(),
+    }
+}
+
+/// The compensated states keep their sum in the elements
+/// and the compensation in the first auxiliary line.
+#[cube]
+fn compensated_state<N: Numeric>(
+    accumulator: &DynamicAccumulatorItem<N>,
+) -> CompensatedSumState<N> {
+    CompensatedSumState::<N> {
+        sum: accumulator.elements,
+        compensation: accumulator.aux.unwrap(),
+    }
+}
+
+#[cube]
+fn compensated_accumulator<N: Numeric>(state: CompensatedSumState<N>) -> DynamicAccumulatorItem<N> {
+    DynamicAccumulatorItem::<N> {
+        elements: state.sum,
+        args: CubeOption::new_None(),
+        aux: CubeOption::new_Some(state.compensation),
+        aux2: CubeOption::new_None(),
+    }
+}
+
+/// The compensated norm state keeps its scaled sum in the elements, the compensation
+/// of the sum in the first auxiliary line and the scale in the second one.
+#[cube]
+fn compensated_norm_state<N: Numeric>(
+    accumulator: &DynamicAccumulatorItem<N>,
+) -> CompensatedPowerSum<N> {
+    CompensatedPowerSum::<N> {
+        scale: accumulator.aux2.unwrap(),
+        sum: compensated_state::<N>(accumulator),
+    }
+}
+
+#[cube]
+fn compensated_norm_accumulator<N: Numeric>(
+    state: CompensatedPowerSum<N>,
+) -> DynamicAccumulatorItem<N> {
+    DynamicAccumulatorItem::<N> {
+        elements: state.sum.sum,
+        args: CubeOption::new_None(),
+        aux: CubeOption::new_Some(state.sum.compensation),
+        aux2: CubeOption::new_Some(state.scale),
+    }
+}
+
+/// Whether an operation keeps coordinates, and how many auxiliary lines its accumulator needs.
+#[derive(CubeType, Clone, Copy)]
+pub(crate) struct AccumulatorLayout {
+    #[cube(comptime)]
+    pub coordinates: bool,
+    #[cube(comptime)]
+    pub aux: u32,
+}
+
+#[cube]
+pub(crate) fn accumulator_layout(this: &ReduceOperation) -> AccumulatorLayout {
+    let (coordinates, aux) = match this {
+        ReduceOperation::Sum(..) => comptime![(false, 0u32)],
+        ReduceOperation::Prod(..) => comptime![(false, 0u32)],
+        ReduceOperation::Mean(..) => comptime![(false, 0u32)],
+        ReduceOperation::MaxAbs(..) => comptime![(false, 0u32)],
+        ReduceOperation::ArgMax(..) => comptime![(true, 0u32)],
+        ReduceOperation::ArgMin(..) => comptime![(true, 0u32)],
+        ReduceOperation::Max(..) => comptime![(false, 0u32)],
+        ReduceOperation::Min(..) => comptime![(false, 0u32)],
+        ReduceOperation::Var(..) => comptime![(false, 2u32)],
+        ReduceOperation::Std(..) => comptime![(false, 2u32)],
+        ReduceOperation::LogSumExp(..) => comptime![(false, 1u32)],
+        ReduceOperation::L1Norm(..) => comptime![(false, 0u32)],
+        ReduceOperation::L2Norm(..) => comptime![(false, 1u32)],
+        ReduceOperation::LpNorm(..) => comptime![(false, 1u32)],
+        ReduceOperation::NonZero(..) => comptime![(false, 0u32)],
+        ReduceOperation::NanSum(..) => comptime![(false, 0u32)],
+        ReduceOperation::NanMean(..) => comptime![(false, 1u32)],
+        ReduceOperation::NanMax(..) => comptime![(false, 0u32)],
+        ReduceOperation::NanMin(..) => comptime![(false, 0u32)],
+        ReduceOperation::CompensatedSum(..) => comptime![(false, 1u32)],
+        ReduceOperation::CompensatedMean(..) => comptime![(false, 1u32)],
+        ReduceOperation::CompensatedL1Norm(..) => comptime![(false, 1u32)],
+        ReduceOperation::CompensatedL2Norm(..) => comptime![(false, 2u32)],
+    };
+    AccumulatorLayout {
+        coordinates: comptime! {coordinates},
+        aux: comptime! {aux},
+    }
+}
+
+#[cube]
+impl<In: Numeric> DynamicAccumulator<In> {
+    /// Allocate the elements, the coordinates if required, and `aux` auxiliary lines.
+    pub fn allocate_with_aux(
         #[comptime] length: u32,
         #[comptime] line_size: u32,
         #[comptime] coordinate: bool,
+        #[comptime] aux: u32,
     ) -> Self {
         let elements = SharedMemory::new_lined(length, line_size);
         let args = if comptime![coordinate] {
@@ -137,8 +463,37 @@ impl<In: Numeric> SharedAccumulator for DynamicAccumulator<In> {
         } else {
             CubeOption::new_None()
         };
+        let aux2 = if comptime![aux > 1] {
+            CubeOption::new_Some(SharedMemory::new_lined(length, line_size))
+        } else {
+            CubeOption::new_None()
+        };
+        let aux = if comptime![aux > 0] {
+            CubeOption::new_Some(SharedMemory::new_lined(length, line_size))
+        } else {
+            CubeOption::new_None()
+        };
+
+        DynamicAccumulator::<In> {
+            elements,
+            args,
+            aux,
+            aux2,
+        }
+    }
+}
+
+#[cube]
+impl<In: Numeric> SharedAccumulator for DynamicAccumulator<In> {
+    type Item = DynamicAccumulatorItem<In>;
 
-        DynamicAccumulator::<In> { elements, args }
+    /// Without the operation at hand, both auxiliary lines are allocated.
+    fn allocate(
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+        #[comptime] coordinate: bool,
+    ) -> Self {
+        DynamicAccumulator::<In>::allocate_with_aux(length, line_size, coordinate, 2u32)
     }
 
     fn read(accumulator: &Self, index: u32) -> Self::Item {
@@ -147,8 +502,21 @@ impl<In: Numeric> SharedAccumulator for DynamicAccumulator<In> {
             CubeOption::Some(args) => CubeOption::new_Some(args[index]),
             CubeOption::None => CubeOption::new_None(),
         };
+        let aux = match accumulator.aux {
+            CubeOption::Some(aux) => CubeOption::new_Some(aux[index]),
+            CubeOption::None => CubeOption::new_None(),
+        };
+        let aux2 = match accumulator.aux2 {
+            CubeOption::Some(aux2) => CubeOption::new_Some(aux2[index]),
+            CubeOption::None => CubeOption::new_None(),
+        };
 
-        DynamicAccumulatorItem::<In> { elements, args }
+        DynamicAccumulatorItem::<In> {
+            elements,
+            args,
+            aux,
+            aux2,
+        }
     }
 
     fn write(accumulator: &mut Self, index: u32, item: Self::Item) {
@@ -161,6 +529,22 @@ impl<In: Numeric> SharedAccumulator for DynamicAccumulator<In> {
             }
             CubeOption::None => {}
         };
+
+        let aux = &mut accumulator.aux;
+        match aux {
+            CubeOption::Some(aux) => {
+                aux[index] = item.aux.unwrap();
+            }
+            CubeOption::None => {}
+        };
+
+        let aux2 = &mut accumulator.aux2;
+        match aux2 {
+            CubeOption::Some(aux2) => {
+                aux2[index] = item.aux2.unwrap();
+            }
+            CubeOption::None => {}
+        };
     }
 }
 
@@ -171,21 +555,26 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
     type Config = ReduceOperationConfig;
 
     fn requirements(this: &Self) -> ReduceRequirements {
-        let coordinates = match this {
-            ReduceOperation::Sum(..) => comptime![false],
-            ReduceOperation::Prod(..) => comptime![false],
-            ReduceOperation::Mean(..) => comptime![false],
-            ReduceOperation::MaxAbs(..) => comptime![false],
-            ReduceOperation::ArgMax(..) => comptime![true],
-            ReduceOperation::ArgMin(..) => comptime![true],
-            ReduceOperation::Max(..) => comptime![false],
-            ReduceOperation::Min(..) => comptime![false],
-        };
+        let layout = accumulator_layout(this);
         ReduceRequirements {
-            coordinates: comptime! {coordinates},
+            coordinates: comptime! {layout.coordinates},
         }
     }
 
+    fn allocate_shared(
+        this: &Self,
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+    ) -> Self::SharedAccumulator {
+        let layout = accumulator_layout(this);
+        DynamicAccumulator::<P::EA>::allocate_with_aux(
+            length,
+            line_size,
+            comptime! {layout.coordinates},
+            comptime! {layout.aux},
+        )
+    }
+
     fn from_config(#[comptime] config: Self::Config) -> Self {
         match config {
             ReduceOperationConfig::Sum => ReduceOperation::new_Sum(Sum {}),
@@ -196,6 +585,57 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
             ReduceOperationConfig::ArgMin => ReduceOperation::new_ArgMin(ArgMin {}),
             ReduceOperationConfig::Max => ReduceOperation::new_Max(Max {}),
             ReduceOperationConfig::Min => ReduceOperation::new_Min(Min {}),
+            ReduceOperationConfig::Var { correction } => {
+                ReduceOperation::new_Var(Var { correction })
+            }
+            ReduceOperationConfig::Std { correction } => ReduceOperation::new_Std(Std {
+                var: Var { correction },
+            }),
+            ReduceOperationConfig::LogSumExp => ReduceOperation::new_LogSumExp(LogSumExp {}),
+            ReduceOperationConfig::L1Norm => ReduceOperation::new_L1Norm(L1Norm {}),
+            ReduceOperationConfig::L2Norm => ReduceOperation::new_L2Norm(L2Norm {
+                lp: LpNorm {
+                    p: NormPower::from_exponent(comptime!(NormExponent::TWO)),
+                },
+            }),
+            ReduceOperationConfig::LpNorm { p } => ReduceOperation::new_LpNorm(LpNorm {
+                p: NormPower::from_exponent(p),
+            }),
+            ReduceOperationConfig::Any => ReduceOperation::new_NonZero(NonZero {
+                reduction: comptime!(NonZeroReduction::Any),
+            }),
+            ReduceOperationConfig::All => ReduceOperation::new_NonZero(NonZero {
+                reduction: comptime!(NonZeroReduction::All),
+            }),
+            ReduceOperationConfig::CountNonZero => ReduceOperation::new_NonZero(NonZero {
+                reduction: comptime!(NonZeroReduction::Count),
+            }),
+            ReduceOperationConfig::NanSum => ReduceOperation::new_NanSum(NanSum {}),
+            ReduceOperationConfig::NanMean => ReduceOperation::new_NanMean(NanMean {}),
+            ReduceOperationConfig::NanMax => ReduceOperation::new_NanMax(NanMax {}),
+            ReduceOperationConfig::NanMin => ReduceOperation::new_NanMin(NanMin {}),
+            ReduceOperationConfig::CompensatedSum => {
+                ReduceOperation::new_CompensatedSum(CompensatedSum {
+                    transform: comptime!(ReduceInputTransform::Identity),
+                })
+            }
+            ReduceOperationConfig::CompensatedMean => {
+                ReduceOperation::new_CompensatedMean(CompensatedMean {
+                    sum: CompensatedSum {
+                        transform: comptime!(ReduceInputTransform::Identity),
+                    },
+                })
+            }
+            ReduceOperationConfig::CompensatedL1Norm => {
+                ReduceOperation::new_CompensatedL1Norm(CompensatedSum {
+                    transform: comptime!(ReduceInputTransform::Abs),
+                })
+            }
+            ReduceOperationConfig::CompensatedL2Norm => {
+                ReduceOperation::new_CompensatedL2Norm(CompensatedL2Norm {
+                    p: NormPower::from_exponent(comptime!(NormExponent::TWO)),
+                })
+            }
         }
     }
 
@@ -219,6 +659,47 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
             }
             ReduceOperation::Max(max) => <Max as ReduceInstruction<P>>::null_input(max, line_size),
             ReduceOperation::Min(min) => <Min as ReduceInstruction<P>>::null_input(min, line_size),
+            ReduceOperation::Var(var) => <Var as ReduceInstruction<P>>::null_input(var, line_size),
+            ReduceOperation::Std(std) => <Std as ReduceInstruction<P>>::null_input(std, line_size),
+            ReduceOperation::LogSumExp(lse) => {
+                <LogSumExp as ReduceInstruction<P>>::null_input(lse, line_size)
+            }
+            ReduceOperation::L1Norm(norm) => {
+                <L1Norm as ReduceInstruction<P>>::null_input(norm, line_size)
+            }
+            ReduceOperation::L2Norm(norm) => {
+                <L2Norm as ReduceInstruction<P>>::null_input(norm, line_size)
+            }
+            ReduceOperation::LpNorm(norm) => {
+                <LpNorm as ReduceInstruction<P>>::null_input(norm, line_size)
+            }
+            ReduceOperation::NonZero(non_zero) => {
+                <NonZero as ReduceInstruction<P>>::null_input(non_zero, line_size)
+            }
+            ReduceOperation::NanSum(nan_sum) => {
+                <NanSum as ReduceInstruction<P>>::null_input(nan_sum, line_size)
+            }
+            ReduceOperation::NanMean(nan_mean) => {
+                <NanMean as ReduceInstruction<P>>::null_input(nan_mean, line_size)
+            }
+            ReduceOperation::NanMax(nan_max) => {
+                <NanMax as ReduceInstruction<P>>::null_input(nan_max, line_size)
+            }
+            ReduceOperation::NanMin(nan_min) => {
+                <NanMin as ReduceInstruction<P>>::null_input(nan_min, line_size)
+            }
+            ReduceOperation::CompensatedSum(sum) => {
+                <CompensatedSum as ReduceInstruction<P>>::null_input(sum, line_size)
+            }
+            ReduceOperation::CompensatedMean(mean) => {
+                <CompensatedMean as ReduceInstruction<P>>::null_input(mean, line_size)
+            }
+            ReduceOperation::CompensatedL1Norm(norm) => {
+                <CompensatedL1Norm as ReduceInstruction<P>>::null_input(norm, line_size)
+            }
+            ReduceOperation::CompensatedL2Norm(norm) => {
+                <CompensatedL2Norm as ReduceInstruction<P>>::null_input(norm, line_size)
+            }
         }
     }
 
@@ -230,6 +711,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Mean(sum) => {
@@ -238,6 +721,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Prod(sum) => {
@@ -246,6 +731,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::MaxAbs(maxabs) => {
@@ -255,6 +742,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::ArgMax(argmax) => {
@@ -264,6 +753,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_Some(args),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::ArgMin(argmin) => {
@@ -273,6 +764,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_Some(args),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Max(max) => {
@@ -281,6 +774,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Min(min) => {
@@ -289,8 +784,98 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::Var(var) => {
+                welford_accumulator::<P::EA>(<Var as ReduceInstruction<P>>::null_accumulator(
+                    var, line_size,
+                ))
+            }
+            ReduceOperation::Std(std) => {
+                welford_accumulator::<P::EA>(<Std as ReduceInstruction<P>>::null_accumulator(
+                    std, line_size,
+                ))
+            }
+            ReduceOperation::LogSumExp(lse) => logsumexp_accumulator::<P::EA>(
+                <LogSumExp as ReduceInstruction<P>>::null_accumulator(lse, line_size),
+            ),
+            ReduceOperation::L1Norm(norm) => {
+                let elements = <L1Norm as ReduceInstruction<P>>::null_accumulator(norm, line_size);
+
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::L2Norm(norm) => norm_accumulator::<P::EA>(
+                <L2Norm as ReduceInstruction<P>>::null_accumulator(norm, line_size),
+            ),
+            ReduceOperation::LpNorm(norm) => norm_accumulator::<P::EA>(
+                <LpNorm as ReduceInstruction<P>>::null_accumulator(norm, line_size),
+            ),
+            ReduceOperation::NonZero(non_zero) => {
+                let elements =
+                    <NonZero as ReduceInstruction<P>>::null_accumulator(non_zero, line_size);
+
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanSum(nan_sum) => {
+                let elements =
+                    <NanSum as ReduceInstruction<P>>::null_accumulator(nan_sum, line_size);
+
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanMean(nan_mean) => nan_mean_accumulator::<P::EA>(
+                <NanMean as ReduceInstruction<P>>::null_accumulator(nan_mean, line_size),
+            ),
+            ReduceOperation::NanMax(nan_max) => {
+                let elements =
+                    <NanMax as ReduceInstruction<P>>::null_accumulator(nan_max, line_size);
+
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanMin(nan_min) => {
+                let elements =
+                    <NanMin as ReduceInstruction<P>>::null_accumulator(nan_min, line_size);
+
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
+            ReduceOperation::CompensatedSum(sum) => compensated_accumulator::<P::EA>(
+                <CompensatedSum as ReduceInstruction<P>>::null_accumulator(sum, line_size),
+            ),
+            ReduceOperation::CompensatedMean(mean) => compensated_accumulator::<P::EA>(
+                <CompensatedMean as ReduceInstruction<P>>::null_accumulator(mean, line_size),
+            ),
+            ReduceOperation::CompensatedL1Norm(norm) => compensated_accumulator::<P::EA>(
+                <CompensatedL1Norm as ReduceInstruction<P>>::null_accumulator(norm, line_size),
+            ),
+            ReduceOperation::CompensatedL2Norm(norm) => compensated_norm_accumulator::<P::EA>(
+                <CompensatedL2Norm as ReduceInstruction<P>>::null_accumulator(norm, line_size),
+            ),
         }
     }
 
@@ -325,6 +910,73 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
             ReduceOperation::Min(min) => {
                 <Min as ReduceInstruction<P>>::read_accumulator(min, &accumulator.elements)
             }
+            ReduceOperation::Var(var) => <Var as ReduceInstruction<P>>::read_accumulator(
+                var,
+                &welford_state::<P::EA>(accumulator),
+            ),
+            ReduceOperation::Std(std) => <Std as ReduceInstruction<P>>::read_accumulator(
+                std,
+                &welford_state::<P::EA>(accumulator),
+            ),
+            ReduceOperation::LogSumExp(lse) => {
+                <LogSumExp as ReduceInstruction<P>>::read_accumulator(
+                    lse,
+                    &logsumexp_state::<P::EA>(accumulator),
+                )
+            }
+            ReduceOperation::L1Norm(norm) => {
+                <L1Norm as ReduceInstruction<P>>::read_accumulator(norm, &accumulator.elements)
+            }
+            ReduceOperation::L2Norm(norm) => <L2Norm as ReduceInstruction<P>>::read_accumulator(
+                norm,
+                &norm_state::<P::EA>(accumulator),
+            ),
+            ReduceOperation::LpNorm(norm) => <LpNorm as ReduceInstruction<P>>::read_accumulator(
+                norm,
+                &norm_state::<P::EA>(accumulator),
+            ),
+            ReduceOperation::NonZero(non_zero) => {
+                <NonZero as ReduceInstruction<P>>::read_accumulator(non_zero, &accumulator.elements)
+            }
+            ReduceOperation::NanSum(nan_sum) => {
+                <NanSum as ReduceInstruction<P>>::read_accumulator(nan_sum, &accumulator.elements)
+            }
+            ReduceOperation::NanMean(nan_mean) => {
+                <NanMean as ReduceInstruction<P>>::read_accumulator(
+                    nan_mean,
+                    &nan_mean_state::<P::EA>(accumulator),
+                )
+            }
+            ReduceOperation::NanMax(nan_max) => {
+                <NanMax as ReduceInstruction<P>>::read_accumulator(nan_max, &accumulator.elements)
+            }
+            ReduceOperation::NanMin(nan_min) => {
+                <NanMin as ReduceInstruction<P>>::read_accumulator(nan_min, &accumulator.elements)
+            }
+            ReduceOperation::CompensatedSum(sum) => {
+                <CompensatedSum as ReduceInstruction<P>>::read_accumulator(
+                    sum,
+                    &compensated_state::<P::EA>(accumulator),
+                )
+            }
+            ReduceOperation::CompensatedMean(mean) => {
+                <CompensatedMean as ReduceInstruction<P>>::read_accumulator(
+                    mean,
+                    &compensated_state::<P::EA>(accumulator),
+                )
+            }
+            ReduceOperation::CompensatedL1Norm(norm) => {
+                <CompensatedL1Norm as ReduceInstruction<P>>::read_accumulator(
+                    norm,
+                    &compensated_state::<P::EA>(accumulator),
+                )
+            }
+            ReduceOperation::CompensatedL2Norm(norm) => {
+                <CompensatedL2Norm as ReduceInstruction<P>>::read_accumulator(
+                    norm,
+                    &compensated_norm_state::<P::EA>(accumulator),
+                )
+            }
         }
     }
 
@@ -339,6 +991,16 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
             CubeOption::Some(val) => *val = source.args.unwrap(),
             CubeOption::None => {}
         }
+        let aux = &mut destination.aux;
+        match aux {
+            CubeOption::Some(val) => *val = source.aux.unwrap(),
+            CubeOption::None => {}
+        }
+        let aux2 = &mut destination.aux2;
+        match aux2 {
+            CubeOption::Some(val) => *val = source.aux2.unwrap(),
+            CubeOption::None => {}
+        }
     }
 
     fn reduce(
@@ -360,6 +1022,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Prod(sum) => {
@@ -373,6 +1037,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Mean(sum) => {
@@ -386,6 +1052,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::MaxAbs(maxabs) => {
@@ -399,6 +1067,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::ArgMax(argmax) => {
@@ -413,6 +1083,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_Some(args),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::ArgMin(argmin) => {
@@ -427,6 +1099,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_Some(args),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Max(max) => {
@@ -440,6 +1114,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Min(min) => {
@@ -453,8 +1129,208 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::Var(var) => {
+                welford_accumulator::<P::EA>(<Var as ReduceInstruction<P>>::reduce(
+                    var,
+                    &welford_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            ReduceOperation::Std(std) => {
+                welford_accumulator::<P::EA>(<Std as ReduceInstruction<P>>::reduce(
+                    std,
+                    &welford_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            ReduceOperation::LogSumExp(lse) => {
+                logsumexp_accumulator::<P::EA>(<LogSumExp as ReduceInstruction<P>>::reduce(
+                    lse,
+                    &logsumexp_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            ReduceOperation::L1Norm(norm) => {
+                let elements = <L1Norm as ReduceInstruction<P>>::reduce(
+                    norm,
+                    &accumulator.elements,
+                    item,
+                    coordinate,
+                    use_planes,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
+            ReduceOperation::L2Norm(norm) => {
+                norm_accumulator::<P::EA>(<L2Norm as ReduceInstruction<P>>::reduce(
+                    norm,
+                    &norm_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            ReduceOperation::LpNorm(norm) => {
+                norm_accumulator::<P::EA>(<LpNorm as ReduceInstruction<P>>::reduce(
+                    norm,
+                    &norm_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            ReduceOperation::NonZero(non_zero) => {
+                let elements = <NonZero as ReduceInstruction<P>>::reduce(
+                    non_zero,
+                    &accumulator.elements,
+                    item,
+                    coordinate,
+                    use_planes,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanSum(nan_sum) => {
+                let elements = <NanSum as ReduceInstruction<P>>::reduce(
+                    nan_sum,
+                    &accumulator.elements,
+                    item,
+                    coordinate,
+                    use_planes,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanMean(nan_mean) => {
+                nan_mean_accumulator::<P::EA>(<NanMean as ReduceInstruction<P>>::reduce(
+                    nan_mean,
+                    &nan_mean_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            ReduceOperation::NanMax(nan_max) => {
+                let elements = <NanMax as ReduceInstruction<P>>::reduce(
+                    nan_max,
+                    &accumulator.elements,
+                    item,
+                    coordinate,
+                    use_planes,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanMin(nan_min) => {
+                let elements = <NanMin as ReduceInstruction<P>>::reduce(
+                    nan_min,
+                    &accumulator.elements,
+                    item,
+                    coordinate,
+                    use_planes,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::CompensatedSum(sum) => {
+                compensated_accumulator::<P::EA>(<CompensatedSum as ReduceInstruction<P>>::reduce(
+                    sum,
+                    &compensated_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            ReduceOperation::CompensatedMean(mean) => {
+                compensated_accumulator::<P::EA>(<CompensatedMean as ReduceInstruction<P>>::reduce(
+                    mean,
+                    &compensated_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            ReduceOperation::CompensatedL1Norm(norm) => compensated_accumulator::<P::EA>(
+                <CompensatedL1Norm as ReduceInstruction<P>>::reduce(
+                    norm,
+                    &compensated_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ),
+            ),
+            ReduceOperation::CompensatedL2Norm(norm) => compensated_norm_accumulator::<P::EA>(
+                <CompensatedL2Norm as ReduceInstruction<P>>::reduce(
+                    norm,
+                    &compensated_norm_state::<P::EA>(accumulator),
+                    item,
+                    coordinate,
+                    use_planes,
+                ),
+            ),
+        }
+    }
+
+    fn reduce_masked(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        valid: bool,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        match this {
+            ReduceOperation::Var(var) => {
+                welford_accumulator::<P::EA>(<Var as ReduceInstruction<P>>::reduce_masked(
+                    var,
+                    &welford_state::<P::EA>(accumulator),
+                    item,
+                    valid,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            ReduceOperation::Std(std) => {
+                welford_accumulator::<P::EA>(<Std as ReduceInstruction<P>>::reduce_masked(
+                    std,
+                    &welford_state::<P::EA>(accumulator),
+                    item,
+                    valid,
+                    coordinate,
+                    use_planes,
+                ))
+            }
+            _ => Self::reduce(this, accumulator, item, coordinate, use_planes),
         }
     }
 
@@ -473,6 +1349,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Prod(prod) => {
@@ -484,6 +1362,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Mean(mean) => {
@@ -495,6 +1375,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::MaxAbs(maxabs) => {
@@ -506,6 +1388,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::ArgMax(argmax) => {
@@ -517,6 +1401,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_Some(args),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::ArgMin(argmin) => {
@@ -528,6 +1414,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_Some(args),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Max(max) => {
@@ -539,6 +1427,8 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
             ReduceOperation::Min(min) => {
@@ -550,8 +1440,231 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 DynamicAccumulatorItem::<P::EA> {
                     elements,
                     args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::Var(var) => {
+                welford_accumulator::<P::EA>(<Var as ReduceInstruction<P>>::fuse_accumulators(
+                    var,
+                    welford_state::<P::EA>(&lhs),
+                    welford_state::<P::EA>(&rhs),
+                ))
+            }
+            ReduceOperation::Std(std) => {
+                welford_accumulator::<P::EA>(<Std as ReduceInstruction<P>>::fuse_accumulators(
+                    std,
+                    welford_state::<P::EA>(&lhs),
+                    welford_state::<P::EA>(&rhs),
+                ))
+            }
+            ReduceOperation::LogSumExp(lse) => logsumexp_accumulator::<P::EA>(
+                <LogSumExp as ReduceInstruction<P>>::fuse_accumulators(
+                    lse,
+                    logsumexp_state::<P::EA>(&lhs),
+                    logsumexp_state::<P::EA>(&rhs),
+                ),
+            ),
+            ReduceOperation::L1Norm(norm) => {
+                let elements = <L1Norm as ReduceInstruction<P>>::fuse_accumulators(
+                    norm,
+                    lhs.elements,
+                    rhs.elements,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::L2Norm(norm) => {
+                norm_accumulator::<P::EA>(<L2Norm as ReduceInstruction<P>>::fuse_accumulators(
+                    norm,
+                    norm_state::<P::EA>(&lhs),
+                    norm_state::<P::EA>(&rhs),
+                ))
+            }
+            ReduceOperation::LpNorm(norm) => {
+                norm_accumulator::<P::EA>(<LpNorm as ReduceInstruction<P>>::fuse_accumulators(
+                    norm,
+                    norm_state::<P::EA>(&lhs),
+                    norm_state::<P::EA>(&rhs),
+                ))
+            }
+            ReduceOperation::NonZero(non_zero) => {
+                let elements = <NonZero as ReduceInstruction<P>>::fuse_accumulators(
+                    non_zero,
+                    lhs.elements,
+                    rhs.elements,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanSum(nan_sum) => {
+                let elements = <NanSum as ReduceInstruction<P>>::fuse_accumulators(
+                    nan_sum,
+                    lhs.elements,
+                    rhs.elements,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanMean(nan_mean) => {
+                nan_mean_accumulator::<P::EA>(<NanMean as ReduceInstruction<P>>::fuse_accumulators(
+                    nan_mean,
+                    nan_mean_state::<P::EA>(&lhs),
+                    nan_mean_state::<P::EA>(&rhs),
+                ))
+            }
+            ReduceOperation::NanMax(nan_max) => {
+                let elements = <NanMax as ReduceInstruction<P>>::fuse_accumulators(
+                    nan_max,
+                    lhs.elements,
+                    rhs.elements,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanMin(nan_min) => {
+                let elements = <NanMin as ReduceInstruction<P>>::fuse_accumulators(
+                    nan_min,
+                    lhs.elements,
+                    rhs.elements,
+                );
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
                 }
             }
+            ReduceOperation::CompensatedSum(sum) => compensated_accumulator::<P::EA>(
+                <CompensatedSum as ReduceInstruction<P>>::fuse_accumulators(
+                    sum,
+                    compensated_state::<P::EA>(&lhs),
+                    compensated_state::<P::EA>(&rhs),
+                ),
+            ),
+            ReduceOperation::CompensatedMean(mean) => compensated_accumulator::<P::EA>(
+                <CompensatedMean as ReduceInstruction<P>>::fuse_accumulators(
+                    mean,
+                    compensated_state::<P::EA>(&lhs),
+                    compensated_state::<P::EA>(&rhs),
+                ),
+            ),
+            ReduceOperation::CompensatedL1Norm(norm) => compensated_accumulator::<P::EA>(
+                <CompensatedL1Norm as ReduceInstruction<P>>::fuse_accumulators(
+                    norm,
+                    compensated_state::<P::EA>(&lhs),
+                    compensated_state::<P::EA>(&rhs),
+                ),
+            ),
+            ReduceOperation::CompensatedL2Norm(norm) => compensated_norm_accumulator::<P::EA>(
+                <CompensatedL2Norm as ReduceInstruction<P>>::fuse_accumulators(
+                    norm,
+                    compensated_norm_state::<P::EA>(&lhs),
+                    compensated_norm_state::<P::EA>(&rhs),
+                ),
+            ),
+        }
+    }
+
+    fn is_decided(this: &Self, accumulator: &Self::AccumulatorItem) -> bool {
+        match this {
+            ReduceOperation::NonZero(non_zero) => {
+                <NonZero as ReduceInstruction<P>>::is_decided(non_zero, &accumulator.elements)
+            }
+            _ => false,
+        }
+    }
+
+    fn fuse_plane(this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        match this {
+            ReduceOperation::Var(var) => {
+                welford_accumulator::<P::EA>(<Var as ReduceInstruction<P>>::fuse_plane(
+                    var,
+                    welford_state::<P::EA>(&accumulator),
+                ))
+            }
+            ReduceOperation::Std(std) => {
+                welford_accumulator::<P::EA>(<Std as ReduceInstruction<P>>::fuse_plane(
+                    std,
+                    welford_state::<P::EA>(&accumulator),
+                ))
+            }
+            ReduceOperation::LogSumExp(lse) => {
+                logsumexp_accumulator::<P::EA>(<LogSumExp as ReduceInstruction<P>>::fuse_plane(
+                    lse,
+                    logsumexp_state::<P::EA>(&accumulator),
+                ))
+            }
+            ReduceOperation::L2Norm(norm) => {
+                norm_accumulator::<P::EA>(<L2Norm as ReduceInstruction<P>>::fuse_plane(
+                    norm,
+                    norm_state::<P::EA>(&accumulator),
+                ))
+            }
+            ReduceOperation::LpNorm(norm) => {
+                norm_accumulator::<P::EA>(<LpNorm as ReduceInstruction<P>>::fuse_plane(
+                    norm,
+                    norm_state::<P::EA>(&accumulator),
+                ))
+            }
+            ReduceOperation::NonZero(non_zero) => {
+                let elements =
+                    <NonZero as ReduceInstruction<P>>::fuse_plane(non_zero, accumulator.elements);
+                DynamicAccumulatorItem::<P::EA> {
+                    elements,
+                    args: CubeOption::new_None(),
+                    aux: CubeOption::new_None(),
+                    aux2: CubeOption::new_None(),
+                }
+            }
+            ReduceOperation::NanMean(nan_mean) => {
+                nan_mean_accumulator::<P::EA>(<NanMean as ReduceInstruction<P>>::fuse_plane(
+                    nan_mean,
+                    nan_mean_state::<P::EA>(&accumulator),
+                ))
+            }
+            ReduceOperation::CompensatedSum(sum) => compensated_accumulator::<P::EA>(
+                <CompensatedSum as ReduceInstruction<P>>::fuse_plane(
+                    sum,
+                    compensated_state::<P::EA>(&accumulator),
+                ),
+            ),
+            ReduceOperation::CompensatedMean(mean) => compensated_accumulator::<P::EA>(
+                <CompensatedMean as ReduceInstruction<P>>::fuse_plane(
+                    mean,
+                    compensated_state::<P::EA>(&accumulator),
+                ),
+            ),
+            ReduceOperation::CompensatedL1Norm(norm) => compensated_accumulator::<P::EA>(
+                <CompensatedL1Norm as ReduceInstruction<P>>::fuse_plane(
+                    norm,
+                    compensated_state::<P::EA>(&accumulator),
+                ),
+            ),
+            ReduceOperation::CompensatedL2Norm(norm) => compensated_norm_accumulator::<P::EA>(
+                <CompensatedL2Norm as ReduceInstruction<P>>::fuse_plane(
+                    norm,
+                    compensated_norm_state::<P::EA>(&accumulator),
+                ),
+            ),
+            // The accumulators of the other operations can be read back as items.
+            _ => fuse_plane_items::<P, Self>(this, accumulator),
         }
     }
 
@@ -603,6 +1716,101 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
                 accumulator.elements,
                 shape_axis_reduce,
             ),
+            ReduceOperation::Var(var) => <Var as ReduceInstruction<P>>::merge_line::<Out>(
+                var,
+                welford_state::<P::EA>(&accumulator),
+                shape_axis_reduce,
+            ),
+            ReduceOperation::Std(std) => <Std as ReduceInstruction<P>>::merge_line::<Out>(
+                std,
+                welford_state::<P::EA>(&accumulator),
+                shape_axis_reduce,
+            ),
+            ReduceOperation::LogSumExp(lse) => {
+                <LogSumExp as ReduceInstruction<P>>::merge_line::<Out>(
+                    lse,
+                    logsumexp_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::L1Norm(norm) => <L1Norm as ReduceInstruction<P>>::merge_line::<Out>(
+                norm,
+                accumulator.elements,
+                shape_axis_reduce,
+            ),
+            ReduceOperation::L2Norm(norm) => <L2Norm as ReduceInstruction<P>>::merge_line::<Out>(
+                norm,
+                norm_state::<P::EA>(&accumulator),
+                shape_axis_reduce,
+            ),
+            ReduceOperation::LpNorm(norm) => <LpNorm as ReduceInstruction<P>>::merge_line::<Out>(
+                norm,
+                norm_state::<P::EA>(&accumulator),
+                shape_axis_reduce,
+            ),
+            ReduceOperation::NonZero(non_zero) => {
+                <NonZero as ReduceInstruction<P>>::merge_line::<Out>(
+                    non_zero,
+                    accumulator.elements,
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::NanSum(nan_sum) => {
+                <NanSum as ReduceInstruction<P>>::merge_line::<Out>(
+                    nan_sum,
+                    accumulator.elements,
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::NanMean(nan_mean) => {
+                <NanMean as ReduceInstruction<P>>::merge_line::<Out>(
+                    nan_mean,
+                    nan_mean_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::NanMax(nan_max) => {
+                <NanMax as ReduceInstruction<P>>::merge_line::<Out>(
+                    nan_max,
+                    accumulator.elements,
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::NanMin(nan_min) => {
+                <NanMin as ReduceInstruction<P>>::merge_line::<Out>(
+                    nan_min,
+                    accumulator.elements,
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::CompensatedSum(sum) => {
+                <CompensatedSum as ReduceInstruction<P>>::merge_line::<Out>(
+                    sum,
+                    compensated_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::CompensatedMean(mean) => {
+                <CompensatedMean as ReduceInstruction<P>>::merge_line::<Out>(
+                    mean,
+                    compensated_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::CompensatedL1Norm(norm) => {
+                <CompensatedL1Norm as ReduceInstruction<P>>::merge_line::<Out>(
+                    norm,
+                    compensated_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::CompensatedL2Norm(norm) => {
+                <CompensatedL2Norm as ReduceInstruction<P>>::merge_line::<Out>(
+                    norm,
+                    compensated_norm_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
         }
     }
 
@@ -656,6 +1864,111 @@ impl<P: ReducePrecision> ReduceInstruction<P> for ReduceOperation {
             ReduceOperation::Min(min) => <Min as ReduceInstruction<P>>::to_output_perpendicular::<
                 Out,
             >(min, accumulator.elements, shape_axis_reduce),
+            ReduceOperation::Var(var) => {
+                <Var as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    var,
+                    welford_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::Std(std) => {
+                <Std as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    std,
+                    welford_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::LogSumExp(lse) => {
+                <LogSumExp as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    lse,
+                    logsumexp_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::L1Norm(norm) => {
+                <L1Norm as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    norm,
+                    accumulator.elements,
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::L2Norm(norm) => {
+                <L2Norm as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    norm,
+                    norm_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::LpNorm(norm) => {
+                <LpNorm as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    norm,
+                    norm_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::NonZero(non_zero) => {
+                <NonZero as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    non_zero,
+                    accumulator.elements,
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::NanSum(nan_sum) => {
+                <NanSum as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    nan_sum,
+                    accumulator.elements,
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::NanMean(nan_mean) => {
+                <NanMean as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    nan_mean,
+                    nan_mean_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::NanMax(nan_max) => {
+                <NanMax as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    nan_max,
+                    accumulator.elements,
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::NanMin(nan_min) => {
+                <NanMin as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    nan_min,
+                    accumulator.elements,
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::CompensatedSum(sum) => {
+                <CompensatedSum as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    sum,
+                    compensated_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::CompensatedMean(mean) => {
+                <CompensatedMean as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    mean,
+                    compensated_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::CompensatedL1Norm(norm) => {
+                <CompensatedL1Norm as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    norm,
+                    compensated_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
+            ReduceOperation::CompensatedL2Norm(norm) => {
+                <CompensatedL2Norm as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                    norm,
+                    compensated_norm_state::<P::EA>(&accumulator),
+                    shape_axis_reduce,
+                )
+            }
         }
     }
 }
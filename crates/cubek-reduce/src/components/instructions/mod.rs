@@ -1,23 +1,37 @@
+mod arg_extremum;
 mod argmax;
 mod argmin;
 mod base;
+mod compensated;
+mod logsumexp;
 mod max;
 mod maxabs;
 mod mean;
 mod min;
 mod mixed;
+mod nan;
+mod non_zero;
+mod norm;
 mod prod;
 mod sum;
 mod utils;
+mod welford;
 
+pub use arg_extremum::*;
 pub use argmax::*;
 pub use argmin::*;
 pub use base::*;
+pub use compensated::*;
+pub use logsumexp::*;
 pub use max::*;
 pub use maxabs::*;
 pub use mean::*;
 pub use min::*;
 pub use mixed::*;
+pub use nan::*;
+pub use non_zero::*;
+pub use norm::*;
 pub use prod::*;
 pub use sum::*;
 pub(crate) use utils::*;
+pub use welford::*;
@@ -0,0 +1,504 @@
+use super::{
+    ReduceCoordinate, ReduceFamily, ReduceInstruction, ReduceRequirements, SharedAccumulator,
+    is_nan, max_ignore_nan, min_ignore_nan, plane_max_ignore_nan, plane_min_ignore_nan,
+    replace_nan,
+};
+use crate::components::precision::ReducePrecision;
+use cubecl::prelude::*;
+
+/// Return the sum of the items, treating NaN as zero.
+#[derive(Debug, CubeType, Clone)]
+pub struct NanSum {}
+
+impl ReduceFamily for NanSum {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ();
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for NanSum {
+    type AccumulatorItem = Line<P::EA>;
+    type SharedAccumulator = SharedMemory<Line<P::EA>>;
+    type Config = ();
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(_config: Self::Config) -> Self {
+        NanSum {}
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::from_int(0))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        Line::empty(line_size).fill(P::EA::from_int(0))
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        *destination = *source;
+    }
+
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Line<P::EA>,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(*accumulator),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let item = replace_nan(Line::<P::EA>::cast_from(item), P::EA::from_int(0));
+
+        if comptime!(use_planes) {
+            *accumulator + plane_sum(item)
+        } else {
+            *accumulator + item
+        }
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs + rhs
+    }
+
+    fn merge_line<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let mut sum = P::EA::from_int(0);
+        #[unroll]
+        for k in 0..accumulator.size() {
+            sum += accumulator[k];
+        }
+        Out::cast_from(sum)
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator)
+    }
+}
+
+/// Running sum and count of the items that are not NaN, with an independent state for each
+/// element of the lines.
+#[derive(CubeType)]
+pub struct NanMeanState<N: Numeric> {
+    pub sum: Line<N>,
+    pub count: Line<N>,
+}
+
+#[cube]
+impl<N: Numeric> NanMeanState<N> {
+    /// A state without any element.
+    pub fn null(#[comptime] line_size: u32) -> NanMeanState<N> {
+        let zero = Line::empty(line_size).fill(N::from_int(0));
+        NanMeanState::<N> {
+            sum: zero,
+            count: zero,
+        }
+    }
+
+    /// A state containing only the elements of the given `item` that are not NaN.
+    pub fn from_item(item: Line<N>) -> NanMeanState<N> {
+        let line_size = item.size();
+        NanMeanState::<N> {
+            sum: replace_nan(item, N::from_int(0)),
+            count: select_many(
+                is_nan(item),
+                Line::empty(line_size).fill(N::from_int(0)),
+                Line::empty(line_size).fill(N::from_int(1)),
+            ),
+        }
+    }
+
+    /// Add both sums and both counts.
+    pub fn merge(&self, other: &NanMeanState<N>) -> NanMeanState<N> {
+        NanMeanState::<N> {
+            sum: self.sum + other.sum,
+            count: self.count + other.count,
+        }
+    }
+
+    /// Merge the states of all units within a plane.
+    pub fn merge_plane(&self) -> NanMeanState<N> {
+        NanMeanState::<N> {
+            sum: plane_sum(self.sum),
+            count: plane_sum(self.count),
+        }
+    }
+
+    /// Merge the elements of the lines into a state with a line size of 1.
+    pub fn merge_lanes(&self) -> NanMeanState<N> {
+        let mut sum = N::from_int(0);
+        let mut count = N::from_int(0);
+
+        #[unroll]
+        for k in 0..self.sum.size() {
+            sum += self.sum[k];
+            count += self.count[k];
+        }
+
+        NanMeanState::<N> {
+            sum: Line::new(sum),
+            count: Line::new(count),
+        }
+    }
+
+    /// The mean of the accumulated elements, which is NaN for an empty state.
+    pub fn value(&self) -> Line<N> {
+        self.sum / self.count
+    }
+}
+
+/// The shared memories used by [`NanMean`].
+#[derive(CubeType)]
+pub struct NanMeanAccumulator<N: Numeric> {
+    pub sum: SharedMemory<Line<N>>,
+    pub count: SharedMemory<Line<N>>,
+}
+
+#[cube]
+impl<N: Numeric> SharedAccumulator for NanMeanAccumulator<N> {
+    type Item = NanMeanState<N>;
+
+    fn allocate(
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+        #[comptime] _coordinate: bool,
+    ) -> Self {
+        NanMeanAccumulator::<N> {
+            sum: SharedMemory::new_lined(length, line_size),
+            count: SharedMemory::new_lined(length, line_size),
+        }
+    }
+
+    fn read(accumulator: &Self, index: u32) -> Self::Item {
+        NanMeanState::<N> {
+            sum: accumulator.sum[index],
+            count: accumulator.count[index],
+        }
+    }
+
+    fn write(accumulator: &mut Self, index: u32, item: Self::Item) {
+        accumulator.sum[index] = item.sum;
+        accumulator.count[index] = item.count;
+    }
+}
+
+/// Return the mean of the items that are not NaN.
+///
+/// The result is NaN when all the items are NaN. The count is kept in the accumulation
+/// precision, so a float accumulation is expected.
+#[derive(Debug, CubeType, Clone)]
+pub struct NanMean {}
+
+impl ReduceFamily for NanMean {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ();
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for NanMean {
+    type AccumulatorItem = NanMeanState<P::EA>;
+    type SharedAccumulator = NanMeanAccumulator<P::EA>;
+    type Config = ();
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(_config: Self::Config) -> Self {
+        NanMean {}
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::cast_from(f32::NAN))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        NanMeanState::<P::EA>::null(line_size)
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        destination.sum = source.sum;
+        destination.count = source.count;
+    }
+
+    /// Only the mean can be read back as an item, so the accumulator must never be
+    /// reduced through this function.
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(accumulator.value()),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let state = NanMeanState::<P::EA>::from_item(Line::cast_from(item));
+
+        if comptime!(use_planes) {
+            accumulator.merge(&state.merge_plane())
+        } else {
+            accumulator.merge(&state)
+        }
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs.merge(&rhs)
+    }
+
+    fn fuse_plane(_this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        accumulator.merge_plane()
+    }
+
+    fn merge_line<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        Out::cast_from(accumulator.merge_lanes().value()[0])
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator.value())
+    }
+}
+
+/// Return the item with the maximum value, ignoring NaN.
+///
+/// The result is NaN when all the items are NaN. Empty slots are marked with NaN,
+/// so this requires a float input.
+#[derive(Debug, CubeType, Clone)]
+pub struct NanMax {}
+
+impl ReduceFamily for NanMax {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ();
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for NanMax {
+    type AccumulatorItem = Line<P::EA>;
+    type SharedAccumulator = SharedMemory<Line<P::EA>>;
+    type Config = ();
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(_config: Self::Config) -> Self {
+        NanMax {}
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::cast_from(f32::NAN))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        Line::empty(line_size).fill(P::EA::cast_from(f32::NAN))
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        *destination = *source;
+    }
+
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Line<P::EA>,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(*accumulator),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        if use_planes {
+            let candidate_item = Line::cast_from(plane_max_ignore_nan(item));
+            max_ignore_nan(*accumulator, candidate_item)
+        } else {
+            max_ignore_nan(*accumulator, Line::cast_from(item))
+        }
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        max_ignore_nan(lhs, rhs)
+    }
+
+    fn merge_line<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let mut max = P::EA::cast_from(f32::NAN);
+        #[unroll]
+        for k in 0..accumulator.size() {
+            let candidate = accumulator[k];
+            max = select(candidate > max || max != max, candidate, max);
+        }
+        Out::cast_from(max)
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator)
+    }
+}
+
+/// Return the item with the minimum value, ignoring NaN.
+///
+/// The result is NaN when all the items are NaN. Empty slots are marked with NaN,
+/// so this requires a float input.
+#[derive(Debug, CubeType, Clone)]
+pub struct NanMin {}
+
+impl ReduceFamily for NanMin {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ();
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for NanMin {
+    type AccumulatorItem = Line<P::EA>;
+    type SharedAccumulator = SharedMemory<Line<P::EA>>;
+    type Config = ();
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(_config: Self::Config) -> Self {
+        NanMin {}
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::cast_from(f32::NAN))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        Line::empty(line_size).fill(P::EA::cast_from(f32::NAN))
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        *destination = *source;
+    }
+
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Line<P::EA>,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(*accumulator),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        if use_planes {
+            let candidate_item = Line::cast_from(plane_min_ignore_nan(item));
+            min_ignore_nan(*accumulator, candidate_item)
+        } else {
+            min_ignore_nan(*accumulator, Line::cast_from(item))
+        }
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        min_ignore_nan(lhs, rhs)
+    }
+
+    fn merge_line<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let mut min = P::EA::cast_from(f32::NAN);
+        #[unroll]
+        for k in 0..accumulator.size() {
+            let candidate = accumulator[k];
+            min = select(candidate < min || min != min, candidate, min);
+        }
+        Out::cast_from(min)
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator)
+    }
+}
@@ -0,0 +1,183 @@
+use super::{ReduceCoordinate, ReduceFamily, ReduceInstruction, ReduceRequirements, non_zero_flag};
+use crate::components::precision::ReducePrecision;
+use cubecl::prelude::*;
+
+/// Which reduction of the non-zero flags is computed by [`NonZero`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NonZeroReduction {
+    /// Return 1 if any item is non-zero and 0 otherwise.
+    Any,
+    /// Return 1 if all items are non-zero and 0 otherwise.
+    All,
+    /// Count the items that are non-zero.
+    Count,
+}
+
+/// Reduce the flags telling whether each item is non-zero.
+///
+/// NaN is non-zero, so it counts as true. For [`Any`](NonZeroReduction::Any) and
+/// [`All`](NonZeroReduction::All), the accumulator only holds 0 or 1 and the units of a plane
+/// are combined with a single vote using `plane_max` or `plane_min`. For
+/// [`Count`](NonZeroReduction::Count), the count is kept in the accumulation precision,
+/// which should be an integer type to stay exact for long vectors.
+///
+/// When a plane reduces a vector, it stops reading once [`Any`](NonZeroReduction::Any) has seen
+/// a non-zero item or [`All`](NonZeroReduction::All) a zero item in every lane, since the vote
+/// already leaves the same flags in all its units.
+#[derive(Debug, CubeType, Clone)]
+pub struct NonZero {
+    #[cube(comptime)]
+    pub reduction: NonZeroReduction,
+}
+
+impl ReduceFamily for NonZero {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = NonZeroReduction;
+}
+
+#[cube]
+impl NonZero {
+    /// Combine two lines of flags or counts.
+    fn combine<N: Numeric>(&self, lhs: Line<N>, rhs: Line<N>) -> Line<N> {
+        match comptime!(self.reduction) {
+            NonZeroReduction::Any => select_many(lhs.greater_than(rhs), lhs, rhs),
+            NonZeroReduction::All => select_many(lhs.less_than(rhs), lhs, rhs),
+            NonZeroReduction::Count => lhs + rhs,
+        }
+    }
+
+    /// Combine the lines of flags or counts of all units within a plane.
+    fn combine_plane<N: Numeric>(&self, line: Line<N>) -> Line<N> {
+        match comptime!(self.reduction) {
+            NonZeroReduction::Any => plane_max(line),
+            NonZeroReduction::All => plane_min(line),
+            NonZeroReduction::Count => plane_sum(line),
+        }
+    }
+
+    /// The flag deciding the result of [`Any`](NonZeroReduction::Any) or
+    /// [`All`](NonZeroReduction::All) on its own.
+    fn absorbing<N: Numeric>(&self) -> N {
+        match comptime!(self.reduction) {
+            NonZeroReduction::All => N::from_int(0),
+            _ => N::from_int(1),
+        }
+    }
+
+    /// The flag or count of an empty vector.
+    fn neutral<N: Numeric>(&self) -> N {
+        match comptime!(self.reduction) {
+            NonZeroReduction::All => N::from_int(1),
+            _ => N::from_int(0),
+        }
+    }
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for NonZero {
+    type AccumulatorItem = Line<P::EA>;
+    type SharedAccumulator = SharedMemory<Line<P::EA>>;
+    type Config = NonZeroReduction;
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(#[comptime] config: Self::Config) -> Self {
+        NonZero { reduction: config }
+    }
+
+    fn null_input(this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(this.neutral::<P::EI>())
+    }
+
+    fn null_accumulator(this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        Line::empty(line_size).fill(this.neutral::<P::EA>())
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        *destination = *source;
+    }
+
+    /// A count can't always be represented by the input type, so the accumulator of
+    /// [`Count`](NonZeroReduction::Count) must never be reduced through this function.
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Line<P::EA>,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(*accumulator),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let flag = non_zero_flag::<P::EI, P::EA>(item);
+        let flag = if comptime!(use_planes) {
+            this.combine_plane(flag)
+        } else {
+            flag
+        };
+        this.combine(*accumulator, flag)
+    }
+
+    fn fuse_accumulators(
+        this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        this.combine(lhs, rhs)
+    }
+
+    fn is_decided(this: &Self, accumulator: &Self::AccumulatorItem) -> bool {
+        match comptime!(this.reduction) {
+            NonZeroReduction::Count => false,
+            _ => {
+                let absorbing = this.absorbing::<P::EA>();
+                let mut decided = true;
+                #[unroll]
+                for k in 0..accumulator.size() {
+                    if accumulator[k] != absorbing {
+                        decided = false;
+                    }
+                }
+                decided
+            }
+        }
+    }
+
+    fn fuse_plane(this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        this.combine_plane(accumulator)
+    }
+
+    fn merge_line<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let mut result = Line::empty(1u32).fill(this.neutral::<P::EA>());
+        #[unroll]
+        for k in 0..accumulator.size() {
+            result = this.combine(result, Line::new(accumulator[k]));
+        }
+        Out::cast_from(result[0])
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator)
+    }
+}
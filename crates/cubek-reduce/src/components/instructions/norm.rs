@@ -0,0 +1,542 @@
+use super::{
+    MaxAbs, ReduceCoordinate, ReduceFamily, ReduceInstruction, ReduceRequirements,
+    SharedAccumulator,
+};
+use crate::components::precision::ReducePrecision;
+use cubecl::prelude::*;
+
+/// The L∞ norm is the maximum absolute value.
+pub type LinfNorm = MaxAbs;
+
+/// Compute the L1 norm, which is the sum of the absolute values.
+#[derive(Debug, CubeType, Clone)]
+pub struct L1Norm {}
+
+impl ReduceFamily for L1Norm {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ();
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for L1Norm {
+    type AccumulatorItem = Line<P::EA>;
+    type SharedAccumulator = SharedMemory<Line<P::EA>>;
+    type Config = ();
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(_config: Self::Config) -> Self {
+        L1Norm {}
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::from_int(0))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        Line::empty(line_size).fill(P::EA::from_int(0))
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        *destination = *source;
+    }
+
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Line<P::EA>,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(*accumulator),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let item_abs = Line::<P::EA>::cast_from(Line::abs(item));
+        if comptime!(use_planes) {
+            *accumulator + plane_sum(item_abs)
+        } else {
+            *accumulator + item_abs
+        }
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs + rhs
+    }
+
+    fn merge_line<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let mut sum = P::EA::from_int(0);
+        #[unroll]
+        for k in 0..accumulator.size() {
+            sum += accumulator[k];
+        }
+        Out::cast_from(sum)
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator)
+    }
+}
+
+/// The exponent `p` of an [`LpNorm`].
+///
+/// The exponent is stored as its bit pattern so it can be part of a comptime config,
+/// which means each distinct exponent compiles its own kernel. See
+/// [`reduce_lp_norm`](crate::reduce_lp_norm) to give the exponent at runtime instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NormExponent(u32);
+
+impl NormExponent {
+    pub const TWO: NormExponent = NormExponent(0x4000_0000);
+
+    /// Create an exponent, panicking if `p` isn't a finite positive number.
+    pub fn new(p: f32) -> Self {
+        assert!(
+            p.is_finite() && p > 0.0,
+            "The exponent of a norm must be finite and positive, got {p}"
+        );
+        NormExponent(p.to_bits())
+    }
+
+    pub fn value(&self) -> f32 {
+        f32::from_bits(self.0)
+    }
+}
+
+/// The exponent `p` used by a [`ScaledPowerSum`], known at compile time or given at runtime.
+#[derive(Debug, CubeType, Clone, Copy)]
+pub struct NormPower {
+    /// The exponent, a constant when it comes from a [`NormExponent`].
+    pub p: f32,
+    /// Whether `p` is known to be 2, so the powers are plain squares.
+    #[cube(comptime)]
+    pub square: bool,
+}
+
+#[cube]
+impl NormPower {
+    /// The power of a comptime exponent.
+    pub fn from_exponent(#[comptime] p: NormExponent) -> NormPower {
+        NormPower {
+            p: f32::new(comptime!(p.value())),
+            square: comptime!(p == NormExponent::TWO),
+        }
+    }
+
+    /// The power of a runtime exponent, which must be finite and positive.
+    pub fn from_runtime(p: f32) -> NormPower {
+        NormPower {
+            p,
+            square: comptime!(false),
+        }
+    }
+}
+
+/// Running state of an Lp norm, with an independent state for each element of the lines.
+///
+/// Like the `nrm2` routine of BLAS, the elements are divided by the largest absolute value
+/// seen so far before being raised to the power `p`, so the sum can't overflow.
+#[derive(CubeType)]
+pub struct ScaledPowerSum<N: Numeric> {
+    /// The largest absolute value accumulated so far.
+    pub scale: Line<N>,
+    /// Sum of `(|x| / scale)^p` over the accumulated elements.
+    pub sum: Line<N>,
+}
+
+#[cube]
+impl<N: Numeric> ScaledPowerSum<N> {
+    /// A state without any element.
+    pub fn null(#[comptime] line_size: u32) -> ScaledPowerSum<N> {
+        let zero = Line::empty(line_size).fill(N::from_int(0));
+        ScaledPowerSum::<N> {
+            scale: zero,
+            sum: zero,
+        }
+    }
+
+    /// A state containing only the given absolute values.
+    pub fn from_abs(item_abs: Line<N>, p: &NormPower) -> ScaledPowerSum<N> {
+        ScaledPowerSum::<N> {
+            scale: item_abs,
+            sum: rescale_factor(item_abs, item_abs, p),
+        }
+    }
+
+    /// Rescale both sums to the largest scale and add them.
+    pub fn merge(&self, other: &ScaledPowerSum<N>, p: &NormPower) -> ScaledPowerSum<N> {
+        let scale = select_many(
+            self.scale.greater_than(other.scale),
+            self.scale,
+            other.scale,
+        );
+        let sum = self.sum * rescale_factor(self.scale, scale, p)
+            + other.sum * rescale_factor(other.scale, scale, p);
+
+        ScaledPowerSum::<N> { scale, sum }
+    }
+
+    /// Merge the states of all units within a plane.
+    pub fn merge_plane(&self, p: &NormPower) -> ScaledPowerSum<N> {
+        let scale = plane_max(self.scale);
+        let sum = plane_sum(self.sum * rescale_factor(self.scale, scale, p));
+
+        ScaledPowerSum::<N> { scale, sum }
+    }
+
+    /// Merge the elements of the lines into a state with a line size of 1.
+    pub fn merge_lanes(&self, p: &NormPower) -> ScaledPowerSum<N> {
+        let mut state = ScaledPowerSum::<N>::null(1u32);
+
+        #[unroll]
+        for k in 0..self.scale.size() {
+            let lane = ScaledPowerSum::<N> {
+                scale: Line::new(self.scale[k]),
+                sum: Line::new(self.sum[k]),
+            };
+            state = state.merge(&lane, p);
+        }
+
+        state
+    }
+
+    /// The norm of the accumulated elements, which is `scale * sum^(1/p)`.
+    ///
+    /// The root of the scaled sum is computed in `f32`, where it can't overflow since the sum is
+    /// at most the number of elements. A square root is then refined with a Newton step, and
+    /// multiplied by the scale, in the accumulation precision.
+    pub fn norm(&self, p: &NormPower) -> Line<N> {
+        let sum = Line::<f32>::cast_from(self.sum);
+        let root = if comptime!(p.square) {
+            refine_sqrt(self.sum, Line::cast_from(sum.sqrt()))
+        } else {
+            let inverse = Line::empty(sum.size()).fill(1.0 / p.p);
+            Line::cast_from(sum.powf(inverse))
+        };
+        self.scale * root
+    }
+}
+
+/// Refine the approximate square `root` of `value` with a Newton step.
+#[cube]
+fn refine_sqrt<N: Numeric>(value: Line<N>, root: Line<N>) -> Line<N> {
+    let zero = Line::empty(root.size()).fill(N::from_int(0));
+    let two = Line::empty(root.size()).fill(N::from_int(2));
+    let is_zero = root.equal(zero);
+    let divisor = select_many(is_zero, two, root);
+    select_many(is_zero, zero, (root + value / divisor) / two)
+}
+
+/// The factor `(scale / new_scale)^p` that makes a sum scaled by `scale` relative to `new_scale`
+/// instead, with `new_scale >= scale`.
+#[cube]
+pub(crate) fn rescale_factor<N: Numeric>(
+    scale: Line<N>,
+    new_scale: Line<N>,
+    p: &NormPower,
+) -> Line<N> {
+    Line::cast_from(power(ratio(scale, new_scale), p))
+}
+
+/// Compute `value / scale` in `f32`, where a scale of 0 can only come with a value of 0.
+///
+/// An infinite scale gives a ratio of 1 for the infinite values and 0 for the others, instead
+/// of the NaN of `inf / inf`.
+#[cube]
+fn ratio<N: Numeric>(value: Line<N>, scale: Line<N>) -> Line<f32> {
+    let value = Line::<f32>::cast_from(value);
+    let scale = Line::<f32>::cast_from(scale);
+    let zero = Line::empty(scale.size()).fill(0.0f32);
+    let one = Line::empty(scale.size()).fill(1.0f32);
+    let inf = Line::empty(scale.size()).fill(f32::INFINITY);
+
+    let divisor = select_many(scale.equal(zero), one, scale);
+    let infinite = select_many(value.equal(inf), one, zero);
+    select_many(scale.equal(inf), infinite, value / divisor)
+}
+
+#[cube]
+fn power(ratio: Line<f32>, p: &NormPower) -> Line<f32> {
+    if comptime!(p.square) {
+        ratio * ratio
+    } else {
+        let exponent = Line::empty(ratio.size()).fill(p.p);
+        ratio.powf(exponent)
+    }
+}
+
+/// The shared memories used by [`L2Norm`] and [`LpNorm`].
+#[derive(CubeType)]
+pub struct ScaledPowerSumAccumulator<N: Numeric> {
+    pub scale: SharedMemory<Line<N>>,
+    pub sum: SharedMemory<Line<N>>,
+}
+
+#[cube]
+impl<N: Numeric> SharedAccumulator for ScaledPowerSumAccumulator<N> {
+    type Item = ScaledPowerSum<N>;
+
+    fn allocate(
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+        #[comptime] _coordinate: bool,
+    ) -> Self {
+        ScaledPowerSumAccumulator::<N> {
+            scale: SharedMemory::new_lined(length, line_size),
+            sum: SharedMemory::new_lined(length, line_size),
+        }
+    }
+
+    fn read(accumulator: &Self, index: u32) -> Self::Item {
+        ScaledPowerSum::<N> {
+            scale: accumulator.scale[index],
+            sum: accumulator.sum[index],
+        }
+    }
+
+    fn write(accumulator: &mut Self, index: u32, item: Self::Item) {
+        accumulator.scale[index] = item.scale;
+        accumulator.sum[index] = item.sum;
+    }
+}
+
+/// Compute the Lp norm, which is `sum(|x|^p)^(1/p)`.
+///
+/// The sum is scaled by the largest absolute value so it doesn't overflow, see [`ScaledPowerSum`].
+/// The powers and roots are computed in `f32`, while the state uses the accumulation precision.
+///
+/// The exponent comes from the [`NormExponent`] of the config, or is given at runtime with
+/// [`reduce_lp_norm`](crate::reduce_lp_norm).
+#[derive(Debug, CubeType, Clone)]
+pub struct LpNorm {
+    pub p: NormPower,
+}
+
+impl ReduceFamily for LpNorm {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = NormExponent;
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for LpNorm {
+    type AccumulatorItem = ScaledPowerSum<P::EA>;
+    type SharedAccumulator = ScaledPowerSumAccumulator<P::EA>;
+    type Config = NormExponent;
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(#[comptime] config: Self::Config) -> Self {
+        LpNorm {
+            p: NormPower::from_exponent(config),
+        }
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::from_int(0))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        ScaledPowerSum::<P::EA>::null(line_size)
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        destination.scale = source.scale;
+        destination.sum = source.sum;
+    }
+
+    /// Only the scale can be read back as an item, so the accumulator must never be
+    /// reduced through this function.
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(accumulator.scale),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let state =
+            ScaledPowerSum::<P::EA>::from_abs(Line::<P::EA>::cast_from(Line::abs(item)), &this.p);
+
+        if comptime!(use_planes) {
+            accumulator.merge(&state.merge_plane(&this.p), &this.p)
+        } else {
+            accumulator.merge(&state, &this.p)
+        }
+    }
+
+    fn fuse_accumulators(
+        this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs.merge(&rhs, &this.p)
+    }
+
+    fn fuse_plane(this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        accumulator.merge_plane(&this.p)
+    }
+
+    fn merge_line<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let norm = accumulator.merge_lanes(&this.p).norm(&this.p);
+        Out::cast_from(norm[0])
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator.norm(&this.p))
+    }
+}
+
+/// Compute the L2 norm, which is the square root of the sum of the squares.
+///
+/// This is an [`LpNorm`] with `p = 2`, where the powers are plain squares.
+/// Reducing all the elements of a matrix gives its Frobenius norm.
+#[derive(Debug, CubeType, Clone)]
+pub struct L2Norm {
+    pub lp: LpNorm,
+}
+
+impl ReduceFamily for L2Norm {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = ();
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for L2Norm {
+    type AccumulatorItem = ScaledPowerSum<P::EA>;
+    type SharedAccumulator = ScaledPowerSumAccumulator<P::EA>;
+    type Config = ();
+
+    fn requirements(this: &Self) -> ReduceRequirements {
+        <LpNorm as ReduceInstruction<P>>::requirements(&this.lp)
+    }
+
+    fn from_config(_config: Self::Config) -> Self {
+        L2Norm {
+            lp: LpNorm {
+                p: NormPower::from_exponent(comptime!(NormExponent::TWO)),
+            },
+        }
+    }
+
+    fn null_input(this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        <LpNorm as ReduceInstruction<P>>::null_input(&this.lp, line_size)
+    }
+
+    fn null_accumulator(this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        <LpNorm as ReduceInstruction<P>>::null_accumulator(&this.lp, line_size)
+    }
+
+    fn assign_accumulator(
+        this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        <LpNorm as ReduceInstruction<P>>::assign_accumulator(&this.lp, destination, source);
+    }
+
+    fn read_accumulator(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        <LpNorm as ReduceInstruction<P>>::read_accumulator(&this.lp, accumulator)
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        <LpNorm as ReduceInstruction<P>>::reduce(
+            &this.lp,
+            accumulator,
+            item,
+            coordinate,
+            use_planes,
+        )
+    }
+
+    fn fuse_accumulators(
+        this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        <LpNorm as ReduceInstruction<P>>::fuse_accumulators(&this.lp, lhs, rhs)
+    }
+
+    fn fuse_plane(this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        <LpNorm as ReduceInstruction<P>>::fuse_plane(&this.lp, accumulator)
+    }
+
+    fn merge_line<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        shape_axis_reduce: u32,
+    ) -> Out {
+        <LpNorm as ReduceInstruction<P>>::merge_line::<Out>(
+            &this.lp,
+            accumulator,
+            shape_axis_reduce,
+        )
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        <LpNorm as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+            &this.lp,
+            accumulator,
+            shape_axis_reduce,
+        )
+    }
+}
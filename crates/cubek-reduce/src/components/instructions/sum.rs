@@ -2,6 +2,10 @@ use super::{ReduceCoordinate, ReduceFamily, ReduceInstruction, ReduceRequirement
 use crate::components::precision::ReducePrecision;
 use cubecl::prelude::*;
 
+/// Return the sum of the items.
+///
+/// NaN is propagated by the addition itself on every backend.
+/// Use [NanSum](super::NanSum) to ignore NaN instead.
 #[derive(Debug, CubeType, Clone)]
 pub struct Sum {}
 
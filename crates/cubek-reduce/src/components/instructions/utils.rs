@@ -17,3 +17,143 @@ pub(crate) fn lowest_coordinate_matching<E: CubePrimitive>(
     );
     plane_min(candidate_coordinate)
 }
+
+// Return 1 for each line element of the item that is non-zero and 0 otherwise.
+#[cube]
+pub(crate) fn non_zero_flag<In: Numeric, Acc: Numeric>(item: Line<In>) -> Line<Acc> {
+    let line_size = item.size();
+    let zero = Line::empty(line_size).fill(In::from_int(0));
+    select_many(
+        item.not_equal(zero),
+        Line::empty(line_size).fill(Acc::from_int(1)),
+        Line::empty(line_size).fill(Acc::from_int(0)),
+    )
+}
+
+// Return the lowest value of `N`, which is `-inf` for floats and the minimum for integers.
+//
+// Doubling the finite minimum of a float overflows to `-inf`, while it wraps around for integers.
+#[cube]
+pub(crate) fn lowest<N: Numeric>() -> N {
+    let min = N::min_value();
+    let doubled = min + min;
+    select(doubled < min, doubled, min)
+}
+
+// Return the highest value of `N`, which is `+inf` for floats and the maximum for integers.
+//
+// Doubling the finite maximum of a float overflows to `+inf`, while it wraps around for integers.
+#[cube]
+pub(crate) fn highest<N: Numeric>() -> N {
+    let max = N::max_value();
+    let doubled = max + max;
+    select(doubled > max, doubled, max)
+}
+
+// Return whether each line element of the item is NaN, the only value that isn't equal to itself.
+#[cube]
+pub(crate) fn is_nan<N: Numeric>(item: Line<N>) -> Line<bool> {
+    item.not_equal(item)
+}
+
+// Replace each line element of the item that is NaN by the given value.
+#[cube]
+pub(crate) fn replace_nan<N: Numeric>(item: Line<N>, value: N) -> Line<N> {
+    select_many(is_nan(item), Line::empty(item.size()).fill(value), item)
+}
+
+// Return the largest of both items for each line element, or NaN if any of them is NaN.
+#[cube]
+pub(crate) fn max_propagate_nan<N: Numeric>(lhs: Line<N>, rhs: Line<N>) -> Line<N> {
+    select_many(
+        is_nan(lhs),
+        lhs,
+        select_many(lhs.greater_than(rhs), lhs, rhs),
+    )
+}
+
+// Return the smallest of both items for each line element, or NaN if any of them is NaN.
+#[cube]
+pub(crate) fn min_propagate_nan<N: Numeric>(lhs: Line<N>, rhs: Line<N>) -> Line<N> {
+    select_many(is_nan(lhs), lhs, select_many(lhs.less_than(rhs), lhs, rhs))
+}
+
+// Return the largest of both items for each line element, ignoring NaN unless both are NaN.
+#[cube]
+pub(crate) fn max_ignore_nan<N: Numeric>(lhs: Line<N>, rhs: Line<N>) -> Line<N> {
+    select_many(
+        is_nan(rhs),
+        lhs,
+        select_many(lhs.greater_than(rhs), lhs, rhs),
+    )
+}
+
+// Return the smallest of both items for each line element, ignoring NaN unless both are NaN.
+#[cube]
+pub(crate) fn min_ignore_nan<N: Numeric>(lhs: Line<N>, rhs: Line<N>) -> Line<N> {
+    select_many(is_nan(rhs), lhs, select_many(lhs.less_than(rhs), lhs, rhs))
+}
+
+// Using plane operations, return the number of units for which each line element of the flags is true.
+#[cube]
+fn plane_count(flags: Line<bool>) -> Line<u32> {
+    plane_sum(select_many(
+        flags,
+        Line::empty(flags.size()).fill(1u32),
+        Line::empty(flags.size()).fill(0u32),
+    ))
+}
+
+// Using plane operations, return the largest item for each line element,
+// or NaN if any of them is NaN.
+//
+// The plane maximum isn't used on NaN, since its result differs between backends.
+#[cube]
+pub(crate) fn plane_max_propagate_nan<N: Numeric>(item: Line<N>) -> Line<N> {
+    let size = item.size();
+    let max = plane_max(replace_nan(item, N::min_value()));
+    select_many(
+        plane_count(is_nan(item)).equal(Line::empty(size).fill(0u32)),
+        max,
+        Line::empty(size).fill(N::cast_from(f32::NAN)),
+    )
+}
+
+// Using plane operations, return the smallest item for each line element,
+// or NaN if any of them is NaN.
+#[cube]
+pub(crate) fn plane_min_propagate_nan<N: Numeric>(item: Line<N>) -> Line<N> {
+    let size = item.size();
+    let min = plane_min(replace_nan(item, N::max_value()));
+    select_many(
+        plane_count(is_nan(item)).equal(Line::empty(size).fill(0u32)),
+        min,
+        Line::empty(size).fill(N::cast_from(f32::NAN)),
+    )
+}
+
+// Using plane operations, return the largest item for each line element ignoring NaN,
+// or NaN if all of them are NaN.
+#[cube]
+pub(crate) fn plane_max_ignore_nan<N: Numeric>(item: Line<N>) -> Line<N> {
+    let size = item.size();
+    let max = plane_max(replace_nan(item, lowest::<N>()));
+    select_many(
+        plane_count(item.equal(item)).equal(Line::empty(size).fill(0u32)),
+        Line::empty(size).fill(N::cast_from(f32::NAN)),
+        max,
+    )
+}
+
+// Using plane operations, return the smallest item for each line element ignoring NaN,
+// or NaN if all of them are NaN.
+#[cube]
+pub(crate) fn plane_min_ignore_nan<N: Numeric>(item: Line<N>) -> Line<N> {
+    let size = item.size();
+    let min = plane_min(replace_nan(item, highest::<N>()));
+    select_many(
+        plane_count(item.equal(item)).equal(Line::empty(size).fill(0u32)),
+        Line::empty(size).fill(N::cast_from(f32::NAN)),
+        min,
+    )
+}
@@ -0,0 +1,528 @@
+use super::{
+    ReduceCoordinate, ReduceFamily, ReduceInstruction, ReduceRequirements, SharedAccumulator,
+};
+use crate::components::precision::ReducePrecision;
+use cubecl::prelude::*;
+
+/// Running statistics of the Welford algorithm, with an independent state for each element
+/// of the lines.
+#[derive(CubeType)]
+pub struct WelfordState<N: Numeric> {
+    pub count: Line<N>,
+    pub mean: Line<N>,
+    /// Sum of the squared deviations from the mean.
+    pub m2: Line<N>,
+}
+
+#[cube]
+impl<N: Numeric> WelfordState<N> {
+    /// A state without any element.
+    pub fn null(#[comptime] line_size: u32) -> WelfordState<N> {
+        let zero = Line::empty(line_size).fill(N::from_int(0));
+        WelfordState::<N> {
+            count: zero,
+            mean: zero,
+            m2: zero,
+        }
+    }
+
+    /// Add the `item` to the state, skipping the elements that are not `valid`.
+    pub fn update(&self, item: Line<N>, valid: Line<bool>) -> WelfordState<N> {
+        let line_size = item.size();
+        let zero = Line::empty(line_size).fill(N::from_int(0));
+        let one = Line::empty(line_size).fill(N::from_int(1));
+
+        let count = self.count + select_many(valid, one, zero);
+        let delta = select_many(valid, item - self.mean, zero);
+        let mean = self.mean + delta / select_many(count.equal(zero), one, count);
+        let m2 = self.m2 + select_many(valid, delta * (item - mean), zero);
+
+        WelfordState::<N> { count, mean, m2 }
+    }
+
+    /// Merge two states with the parallel algorithm of Chan et al.
+    pub fn merge(&self, other: &WelfordState<N>) -> WelfordState<N> {
+        let line_size = self.count.size();
+        let zero = Line::empty(line_size).fill(N::from_int(0));
+        let one = Line::empty(line_size).fill(N::from_int(1));
+
+        let count = self.count + other.count;
+        let divisor = select_many(count.equal(zero), one, count);
+        let delta = other.mean - self.mean;
+        let mean = self.mean + delta * other.count / divisor;
+        let m2 = self.m2 + other.m2 + delta * delta * self.count * other.count / divisor;
+
+        WelfordState::<N> { count, mean, m2 }
+    }
+
+    /// Merge the states of all units within a plane.
+    pub fn merge_plane(&self) -> WelfordState<N> {
+        let line_size = self.count.size();
+        let zero = Line::empty(line_size).fill(N::from_int(0));
+        let one = Line::empty(line_size).fill(N::from_int(1));
+
+        let count = plane_sum(self.count);
+        let divisor = select_many(count.equal(zero), one, count);
+        let mean = plane_sum(self.count * self.mean) / divisor;
+        let delta = self.mean - mean;
+        let m2 = plane_sum(self.m2 + self.count * delta * delta);
+
+        WelfordState::<N> { count, mean, m2 }
+    }
+
+    /// Merge the elements of the lines into a state with a line size of 1.
+    pub fn merge_lanes(&self) -> WelfordState<N> {
+        let mut state = WelfordState::<N>::null(1u32);
+
+        #[unroll]
+        for k in 0..self.count.size() {
+            let lane = WelfordState::<N> {
+                count: Line::new(self.count[k]),
+                mean: Line::new(self.mean[k]),
+                m2: Line::new(self.m2[k]),
+            };
+            state = state.merge(&lane);
+        }
+
+        state
+    }
+
+    /// The variance of the elements, where `correction` is subtracted from the count.
+    pub fn variance(&self, #[comptime] correction: u32) -> Line<N> {
+        let correction =
+            Line::empty(self.count.size()).fill(N::from_int(comptime!(correction as i64)));
+        self.m2 / (self.count - correction)
+    }
+}
+
+/// The shared memories used by [`Var`], [`Std`] and [`MeanVar`].
+#[derive(CubeType)]
+pub struct WelfordAccumulator<N: Numeric> {
+    pub count: SharedMemory<Line<N>>,
+    pub mean: SharedMemory<Line<N>>,
+    pub m2: SharedMemory<Line<N>>,
+}
+
+#[cube]
+impl<N: Numeric> SharedAccumulator for WelfordAccumulator<N> {
+    type Item = WelfordState<N>;
+
+    fn allocate(
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+        #[comptime] _coordinate: bool,
+    ) -> Self {
+        WelfordAccumulator::<N> {
+            count: SharedMemory::new_lined(length, line_size),
+            mean: SharedMemory::new_lined(length, line_size),
+            m2: SharedMemory::new_lined(length, line_size),
+        }
+    }
+
+    fn read(accumulator: &Self, index: u32) -> Self::Item {
+        WelfordState::<N> {
+            count: accumulator.count[index],
+            mean: accumulator.mean[index],
+            m2: accumulator.m2[index],
+        }
+    }
+
+    fn write(accumulator: &mut Self, index: u32, item: Self::Item) {
+        accumulator.count[index] = item.count;
+        accumulator.mean[index] = item.mean;
+        accumulator.m2[index] = item.m2;
+    }
+}
+
+/// Compute the variance using the Welford algorithm.
+///
+/// The sum of the squared deviations is divided by the number of elements minus `correction`.
+/// Use a correction of 0 for the population variance and 1 for the unbiased sample variance.
+#[derive(Debug, CubeType, Clone)]
+pub struct Var {
+    #[cube(comptime)]
+    pub correction: u32,
+}
+
+impl ReduceFamily for Var {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = u32;
+}
+
+#[cube]
+impl Var {
+    /// Add an input item to the state if it is `valid`, possibly reducing all items within the plane.
+    pub(crate) fn reduce_state<EI: Numeric, EA: Numeric>(
+        accumulator: &WelfordState<EA>,
+        item: Line<EI>,
+        valid: bool,
+        #[comptime] use_planes: bool,
+    ) -> WelfordState<EA> {
+        let valid = Line::empty(item.size()).fill(valid);
+        let item = Line::<EA>::cast_from(item);
+
+        if comptime!(use_planes) {
+            let single = WelfordState::<EA>::null(item.size()).update(item, valid);
+            accumulator.merge(&single.merge_plane())
+        } else {
+            accumulator.update(item, valid)
+        }
+    }
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for Var {
+    type AccumulatorItem = WelfordState<P::EA>;
+    type SharedAccumulator = WelfordAccumulator<P::EA>;
+    type Config = u32;
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(#[comptime] config: Self::Config) -> Self {
+        Var { correction: config }
+    }
+
+    /// Every input is counted, masked reads are skipped through their validity instead.
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::from_int(0))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        WelfordState::<P::EA>::null(line_size)
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        destination.count = source.count;
+        destination.mean = source.mean;
+        destination.m2 = source.m2;
+    }
+
+    /// Only the mean can be read back as an item, so the accumulator must never be
+    /// reduced through this function.
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(accumulator.mean),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        Var::reduce_state::<P::EI, P::EA>(accumulator, item, true, use_planes)
+    }
+
+    fn reduce_masked(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        valid: bool,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        Var::reduce_state::<P::EI, P::EA>(accumulator, item, valid, use_planes)
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs.merge(&rhs)
+    }
+
+    fn fuse_plane(_this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        accumulator.merge_plane()
+    }
+
+    fn merge_line<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let variance = accumulator.merge_lanes().variance(this.correction);
+        Out::cast_from(variance[0])
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator.variance(this.correction))
+    }
+}
+
+/// Compute the standard deviation using the Welford algorithm.
+///
+/// This is the square root of [`Var`] with the same `correction`, the square root is computed in `f32`.
+#[derive(Debug, CubeType, Clone)]
+pub struct Std {
+    pub var: Var,
+}
+
+impl ReduceFamily for Std {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = u32;
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for Std {
+    type AccumulatorItem = WelfordState<P::EA>;
+    type SharedAccumulator = WelfordAccumulator<P::EA>;
+    type Config = u32;
+
+    fn requirements(this: &Self) -> ReduceRequirements {
+        <Var as ReduceInstruction<P>>::requirements(&this.var)
+    }
+
+    fn from_config(#[comptime] config: Self::Config) -> Self {
+        Std {
+            var: Var { correction: config },
+        }
+    }
+
+    fn null_input(this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        <Var as ReduceInstruction<P>>::null_input(&this.var, line_size)
+    }
+
+    fn null_accumulator(this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::null_accumulator(&this.var, line_size)
+    }
+
+    fn assign_accumulator(
+        this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        <Var as ReduceInstruction<P>>::assign_accumulator(&this.var, destination, source);
+    }
+
+    fn read_accumulator(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        <Var as ReduceInstruction<P>>::read_accumulator(&this.var, accumulator)
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::reduce(&this.var, accumulator, item, coordinate, use_planes)
+    }
+
+    fn reduce_masked(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        valid: bool,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::reduce_masked(
+            &this.var,
+            accumulator,
+            item,
+            valid,
+            coordinate,
+            use_planes,
+        )
+    }
+
+    fn fuse_accumulators(
+        this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::fuse_accumulators(&this.var, lhs, rhs)
+    }
+
+    fn fuse_plane(this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::fuse_plane(&this.var, accumulator)
+    }
+
+    fn merge_line<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        shape_axis_reduce: u32,
+    ) -> Out {
+        let variance = <Var as ReduceInstruction<P>>::merge_line::<f32>(
+            &this.var,
+            accumulator,
+            shape_axis_reduce,
+        );
+        Out::cast_from(variance.sqrt())
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        let variance = <Var as ReduceInstruction<P>>::to_output_perpendicular::<f32>(
+            &this.var,
+            accumulator,
+            shape_axis_reduce,
+        );
+        Line::cast_from(variance.sqrt())
+    }
+}
+
+/// Which statistic of [`MeanVar`] is written to the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MeanVarOutput {
+    Mean,
+    Var,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MeanVarConfig {
+    pub correction: u32,
+    pub output: MeanVarOutput,
+}
+
+/// Compute the mean and the variance together using the Welford algorithm.
+///
+/// Both statistics come from the same accumulator, the `output` only selects which one is written.
+/// See [`mean_var`](crate::mean_var) to write both statistics with a single launch.
+#[derive(Debug, CubeType, Clone)]
+pub struct MeanVar {
+    pub var: Var,
+    #[cube(comptime)]
+    pub output: MeanVarOutput,
+}
+
+impl ReduceFamily for MeanVar {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = MeanVarConfig;
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for MeanVar {
+    type AccumulatorItem = WelfordState<P::EA>;
+    type SharedAccumulator = WelfordAccumulator<P::EA>;
+    type Config = MeanVarConfig;
+
+    fn requirements(this: &Self) -> ReduceRequirements {
+        <Var as ReduceInstruction<P>>::requirements(&this.var)
+    }
+
+    fn from_config(#[comptime] config: Self::Config) -> Self {
+        MeanVar {
+            var: Var {
+                correction: config.correction,
+            },
+            output: config.output,
+        }
+    }
+
+    fn null_input(this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        <Var as ReduceInstruction<P>>::null_input(&this.var, line_size)
+    }
+
+    fn null_accumulator(this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::null_accumulator(&this.var, line_size)
+    }
+
+    fn assign_accumulator(
+        this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        <Var as ReduceInstruction<P>>::assign_accumulator(&this.var, destination, source);
+    }
+
+    fn read_accumulator(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        <Var as ReduceInstruction<P>>::read_accumulator(&this.var, accumulator)
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::reduce(&this.var, accumulator, item, coordinate, use_planes)
+    }
+
+    fn reduce_masked(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        valid: bool,
+        coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::reduce_masked(
+            &this.var,
+            accumulator,
+            item,
+            valid,
+            coordinate,
+            use_planes,
+        )
+    }
+
+    fn fuse_accumulators(
+        this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::fuse_accumulators(&this.var, lhs, rhs)
+    }
+
+    fn fuse_plane(this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        <Var as ReduceInstruction<P>>::fuse_plane(&this.var, accumulator)
+    }
+
+    fn merge_line<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        shape_axis_reduce: u32,
+    ) -> Out {
+        match comptime!(this.output) {
+            MeanVarOutput::Mean => Out::cast_from(accumulator.merge_lanes().mean[0]),
+            MeanVarOutput::Var => <Var as ReduceInstruction<P>>::merge_line::<Out>(
+                &this.var,
+                accumulator,
+                shape_axis_reduce,
+            ),
+        }
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        this: &Self,
+        accumulator: Self::AccumulatorItem,
+        shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        match comptime!(this.output) {
+            MeanVarOutput::Mean => Line::cast_from(accumulator.mean),
+            MeanVarOutput::Var => <Var as ReduceInstruction<P>>::to_output_perpendicular::<Out>(
+                &this.var,
+                accumulator,
+                shape_axis_reduce,
+            ),
+        }
+    }
+}
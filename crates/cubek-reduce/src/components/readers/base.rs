@@ -2,7 +2,9 @@ use crate::{
     BoundChecks, LineMode, ReduceInstruction, ReducePrecision,
     components::{
         instructions::{ReduceCoordinate, ReduceRequirements},
-        readers::{parallel::ParallelReader, perpendicular::PerpendicularReader},
+        readers::{
+            ReduceInputTransform, parallel::ParallelReader, perpendicular::PerpendicularReader,
+        },
     },
 };
 use cubecl::{
@@ -28,6 +30,7 @@ impl<P: ReducePrecision> Reader<P> {
         idle: CubeOption<bool>,
         #[comptime] bound_checks: BoundChecks,
         #[comptime] line_mode: LineMode,
+        #[comptime] transform: ReduceInputTransform,
     ) -> Reader<P> {
         match line_mode {
             LineMode::Parallel => Reader::<P>::new_Parallel(ParallelReader::<P>::new::<I, Out>(
@@ -38,6 +41,7 @@ impl<P: ReducePrecision> Reader<P> {
                 reduce_index,
                 idle,
                 bound_checks,
+                transform,
             )),
             LineMode::Perpendicular => {
                 Reader::<P>::new_Perpendicular(PerpendicularReader::<P>::new::<I, Out>(
@@ -48,6 +52,7 @@ impl<P: ReducePrecision> Reader<P> {
                     reduce_index,
                     idle,
                     bound_checks,
+                    transform,
                 ))
             }
         }
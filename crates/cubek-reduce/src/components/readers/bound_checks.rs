@@ -1,4 +1,7 @@
-use crate::{BoundChecks, ReduceInstruction, ReducePrecision};
+use crate::{
+    BoundChecks, ReduceInstruction, ReducePrecision,
+    components::readers::{ReduceInputTransform, transform_input},
+};
 use cubecl::{
     prelude::*,
     std::{
@@ -53,22 +56,37 @@ impl<P: ReducePrecision> ReaderBoundChecks<P> {
             }
         }
     }
-    pub fn read(&self, pos: u32, offset: u32, view: &View<Line<P::EI>, Coords1d>) -> Line<P::EI> {
+    /// Read the item at `offset` with the `transform` applied, along with whether it is in bound.
+    /// Out-of-bound items are replaced by the null input.
+    pub fn read(
+        &self,
+        pos: u32,
+        offset: u32,
+        view: &View<Line<P::EI>, Coords1d>,
+        #[comptime] transform: ReduceInputTransform,
+    ) -> (Line<P::EI>, bool) {
         match self {
-            ReaderBoundChecks::NotRequired => view[offset],
+            ReaderBoundChecks::NotRequired => (transform_input(view[offset], transform), true),
             ReaderBoundChecks::Required(checks) => match comptime!(checks.bound_checks) {
-                BoundChecks::None => view[offset],
+                BoundChecks::None => (transform_input(view[offset], transform), true),
                 BoundChecks::Mask => {
                     let mask = pos < checks.pos_max;
                     let index = offset * u32::cast_from(mask);
-                    select(mask, view[index], checks.null_input)
+                    let item = select(
+                        mask,
+                        transform_input(view[index], transform),
+                        checks.null_input,
+                    );
+                    (item, mask)
                 }
                 BoundChecks::Branch => {
-                    if pos < checks.pos_max {
-                        view[offset]
+                    let valid = pos < checks.pos_max;
+                    let item = if valid {
+                        transform_input(view[offset], transform)
                     } else {
                         checks.null_input
-                    }
+                    };
+                    (item, valid)
                 }
             },
         }
@@ -19,7 +19,8 @@ impl<P: ReducePrecision> CubeReader<P> {
         CubeReader::<P> { reader }
     }
 
-    pub fn read(&self, line_index: u32) -> (Line<P::EI>, ReduceCoordinate) {
+    /// Read the item at `line_index`, along with whether it is in bound.
+    pub fn read(&self, line_index: u32) -> (Line<P::EI>, bool, ReduceCoordinate) {
         match &self.reader {
             Reader::Parallel(reader) => reader.read_cube(line_index),
             Reader::Perpendicular(reader) => reader.read_cube(line_index),
@@ -3,7 +3,9 @@ pub mod plane;
 pub mod unit;
 
 mod base;
+mod transform;
 pub use base::*;
+pub use transform::*;
 
 pub(crate) mod bound_checks;
 pub(crate) mod parallel;
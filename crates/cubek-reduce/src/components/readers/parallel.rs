@@ -2,7 +2,7 @@ use crate::{
     BoundChecks, LineMode, ReduceInstruction, ReducePrecision,
     components::{
         instructions::{ReduceCoordinate, ReduceRequirements},
-        readers::bound_checks::ReaderBoundChecks,
+        readers::{ReduceInputTransform, bound_checks::ReaderBoundChecks, transform_input},
     },
 };
 use cubecl::{
@@ -25,12 +25,15 @@ pub struct ParallelReader<P: ReducePrecision> {
     requirements: ReduceRequirements,
     #[cube(comptime)]
     line_size: u32,
+    #[cube(comptime)]
+    transform: ReduceInputTransform,
     bound_checks: ReaderBoundChecks<P>,
     num_chunks: u32,
 }
 
 #[cube]
 impl<P: ReducePrecision> ParallelReader<P> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new<I: ReduceInstruction<P>, Out: Numeric>(
         input: &VirtualTensor<P::EI>,
         output: &mut VirtualTensor<Out, ReadWrite>,
@@ -39,6 +42,7 @@ impl<P: ReducePrecision> ParallelReader<P> {
         reduce_index: u32,
         idle: CubeOption<bool>,
         #[comptime] bound_checks: BoundChecks,
+        #[comptime] transform: ReduceInputTransform,
     ) -> ParallelReader<P> {
         let line_size = input.line_size();
 
@@ -62,6 +66,7 @@ impl<P: ReducePrecision> ParallelReader<P> {
             batch_offset,
             requirements,
             line_size,
+            transform,
             bound_checks,
             num_chunks,
         }
@@ -79,13 +84,15 @@ impl<P: ReducePrecision> ParallelReader<P> {
         self.num_chunks.div_ceil(CUBE_DIM)
     }
 
-    pub fn read_cube(&self, line_index: u32) -> (Line<P::EI>, ReduceCoordinate) {
+    pub fn read_cube(&self, line_index: u32) -> (Line<P::EI>, bool, ReduceCoordinate) {
         let plane_pos = line_index * CUBE_DIM;
         let unit_pos = UNIT_POS;
         let pos = plane_pos + unit_pos;
         let offset = pos + self.batch_offset;
 
-        let item = self.bound_checks.read(pos, offset, &self.view);
+        let (item, valid) = self
+            .bound_checks
+            .read(pos, offset, &self.view, self.transform);
 
         let coordinate = ReduceCoordinate::new(
             (line_index * self.line_size * CUBE_DIM) + UNIT_POS * self.line_size,
@@ -94,16 +101,18 @@ impl<P: ReducePrecision> ParallelReader<P> {
             LineMode::Parallel,
         );
 
-        (item, coordinate)
+        (item, valid, coordinate)
     }
 
-    pub fn read_plane(&self, line_index: u32) -> (Line<P::EI>, ReduceCoordinate) {
+    pub fn read_plane(&self, line_index: u32) -> (Line<P::EI>, bool, ReduceCoordinate) {
         let plane_pos = line_index * CUBE_DIM_X;
         let unit_pos = UNIT_POS_X;
         let pos = plane_pos + unit_pos;
         let offset = pos + self.batch_offset;
 
-        let item = self.bound_checks.read(pos, offset, &self.view);
+        let (item, valid) = self
+            .bound_checks
+            .read(pos, offset, &self.view, self.transform);
 
         let coordinate = ReduceCoordinate::new(
             (line_index * self.line_size * CUBE_DIM_X) + UNIT_POS_X * self.line_size,
@@ -112,12 +121,12 @@ impl<P: ReducePrecision> ParallelReader<P> {
             LineMode::Parallel,
         );
 
-        (item, coordinate)
+        (item, valid, coordinate)
     }
 
     pub fn read_unit(&self, line_index: u32) -> (Line<P::EI>, ReduceCoordinate) {
         let offset = line_index + self.batch_offset;
-        let item = self.view[offset];
+        let item = transform_input(self.view[offset], self.transform);
 
         let coordinate = ReduceCoordinate::new(
             line_index * self.line_size,
@@ -2,7 +2,7 @@ use crate::{
     BoundChecks, LineMode, ReduceInstruction, ReducePrecision,
     components::{
         instructions::{ReduceCoordinate, ReduceRequirements},
-        readers::bound_checks::ReaderBoundChecks,
+        readers::{ReduceInputTransform, bound_checks::ReaderBoundChecks, transform_input},
     },
 };
 use cubecl::{
@@ -26,12 +26,15 @@ pub struct PerpendicularReader<P: ReducePrecision> {
     requirements: ReduceRequirements,
     #[cube(comptime)]
     line_size: u32,
+    #[cube(comptime)]
+    transform: ReduceInputTransform,
     bound_checks: ReaderBoundChecks<P>,
     shape: u32,
 }
 
 #[cube]
 impl<P: ReducePrecision> PerpendicularReader<P> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new<I: ReduceInstruction<P>, Out: Numeric>(
         input: &VirtualTensor<P::EI>,
         output: &mut VirtualTensor<Out, ReadWrite>,
@@ -40,6 +43,7 @@ impl<P: ReducePrecision> PerpendicularReader<P> {
         reduce_index: u32,
         idle: CubeOption<bool>,
         #[comptime] bound_checks: BoundChecks,
+        #[comptime] transform: ReduceInputTransform,
     ) -> PerpendicularReader<P> {
         let line_size = input.line_size();
         let output_index = reduce_index * line_size;
@@ -63,6 +67,7 @@ impl<P: ReducePrecision> PerpendicularReader<P> {
             vector_offset_stride,
             requirements,
             line_size,
+            transform,
             bound_checks,
             shape,
         }
@@ -80,7 +85,7 @@ impl<P: ReducePrecision> PerpendicularReader<P> {
         self.shape.div_ceil(CUBE_DIM)
     }
 
-    pub fn read_cube(&self, line_index: u32) -> (Line<P::EI>, ReduceCoordinate) {
+    pub fn read_cube(&self, line_index: u32) -> (Line<P::EI>, bool, ReduceCoordinate) {
         let plane_pos = line_index * CUBE_DIM;
         let unit_pos = UNIT_POS;
         let pos = plane_pos + unit_pos;
@@ -88,7 +93,9 @@ impl<P: ReducePrecision> PerpendicularReader<P> {
             + unit_pos * self.vector_offset_stride
             + self.batch_offset;
 
-        let item = self.bound_checks.read(pos, offset, &self.view);
+        let (item, valid) = self
+            .bound_checks
+            .read(pos, offset, &self.view, self.transform);
 
         let coordinate = ReduceCoordinate::new(
             line_index * CUBE_DIM + UNIT_POS,
@@ -97,10 +104,10 @@ impl<P: ReducePrecision> PerpendicularReader<P> {
             LineMode::Perpendicular,
         );
 
-        (item, coordinate)
+        (item, valid, coordinate)
     }
 
-    pub fn read_plane(&self, line_index: u32) -> (Line<P::EI>, ReduceCoordinate) {
+    pub fn read_plane(&self, line_index: u32) -> (Line<P::EI>, bool, ReduceCoordinate) {
         let plane_pos = line_index * CUBE_DIM_X;
         let unit_pos = UNIT_POS_X;
         let pos = plane_pos + unit_pos;
@@ -108,7 +115,9 @@ impl<P: ReducePrecision> PerpendicularReader<P> {
             + unit_pos * self.vector_offset_stride
             + self.batch_offset;
 
-        let item = self.bound_checks.read(pos, offset, &self.view);
+        let (item, valid) = self
+            .bound_checks
+            .read(pos, offset, &self.view, self.transform);
 
         let coordinate = ReduceCoordinate::new(
             line_index * CUBE_DIM_X + UNIT_POS_X,
@@ -117,12 +126,12 @@ impl<P: ReducePrecision> PerpendicularReader<P> {
             LineMode::Perpendicular,
         );
 
-        (item, coordinate)
+        (item, valid, coordinate)
     }
 
     pub fn read_unit(&self, line_index: u32) -> (Line<P::EI>, ReduceCoordinate) {
         let offset = self.batch_offset + line_index * self.vector_offset_stride;
-        let item = self.view[offset];
+        let item = transform_input(self.view[offset], self.transform);
 
         let coordinate = ReduceCoordinate::new(
             line_index,
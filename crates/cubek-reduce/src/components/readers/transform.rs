@@ -0,0 +1,80 @@
+use cubecl::prelude::*;
+
+/// An element-wise map applied by the readers to every input item before it is reduced.
+///
+/// The items are cast to the accumulation type before being transformed, so a map like
+/// [Square](ReduceInputTransform::Square) doesn't overflow a narrow input type.
+/// Items masked by the bound checks are replaced by the null input of the instruction
+/// and are never transformed, so a map doesn't have to preserve the null input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ReduceInputTransform {
+    /// The items are reduced unchanged.
+    #[default]
+    Identity,
+    /// `x * x`, e.g. to compute a sum of squares.
+    Square,
+    /// `|x|`
+    Abs,
+    /// `exp(x - shift)`, computed in `f32`.
+    Exp { shift: TransformScalar },
+    /// `x * factor`, computed in `f32`.
+    Scale { factor: TransformScalar },
+}
+
+/// A float constant of a [ReduceInputTransform].
+///
+/// It is stored as bits so that the transform can be hashed as part of the kernel definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransformScalar(u32);
+
+impl TransformScalar {
+    /// Create a constant, panicking if `value` isn't finite.
+    pub fn new(value: f32) -> Self {
+        assert!(
+            value.is_finite(),
+            "The constant of an input transform must be finite, got {value}"
+        );
+        TransformScalar(value.to_bits())
+    }
+
+    pub fn value(&self) -> f32 {
+        f32::from_bits(self.0)
+    }
+}
+
+impl ReduceInputTransform {
+    /// Shorthand for [Exp](ReduceInputTransform::Exp).
+    pub fn exp(shift: f32) -> Self {
+        ReduceInputTransform::Exp {
+            shift: TransformScalar::new(shift),
+        }
+    }
+
+    /// Shorthand for [Scale](ReduceInputTransform::Scale).
+    pub fn scale(factor: f32) -> Self {
+        ReduceInputTransform::Scale {
+            factor: TransformScalar::new(factor),
+        }
+    }
+}
+
+/// Apply the `transform` to every element of the `item`.
+#[cube]
+pub fn transform_input<N: Numeric>(
+    item: Line<N>,
+    #[comptime] transform: ReduceInputTransform,
+) -> Line<N> {
+    match comptime!(transform) {
+        ReduceInputTransform::Identity => item,
+        ReduceInputTransform::Square => item * item,
+        ReduceInputTransform::Abs => Line::abs(item),
+        ReduceInputTransform::Exp { shift } => {
+            let shift = Line::empty(item.size()).fill(f32::new(comptime!(shift.value())));
+            Line::cast_from(Line::exp(Line::<f32>::cast_from(item) - shift))
+        }
+        ReduceInputTransform::Scale { factor } => {
+            let factor = Line::empty(item.size()).fill(f32::new(comptime!(factor.value())));
+            Line::cast_from(Line::<f32>::cast_from(item) * factor)
+        }
+    }
+}
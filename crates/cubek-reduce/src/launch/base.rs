@@ -1,16 +1,19 @@
 use crate::{
     LineMode, ReduceError, ReducePrecision,
     components::{
-        args::{ReduceArgs, TensorArgs, init_tensors},
+        args::{
+            ProductArgs, ProductInputLaunch, ReduceArgs, TensorArgs, init_tensors, init_tensors_as,
+        },
         global::{
             cube::GlobalFullCubeReduce, plane::GlobalFullPlaneReduce, unit::GlobalFullUnitReduce,
         },
         instructions::*,
+        readers::ReduceInputTransform,
     },
     launch::{ReduceStrategy, RoutineStrategy, generate_line_size},
     routines::{
-        GlobalReduceBlueprint, ReduceBlueprint, ReduceLineSettings, ReduceProblem, Routine,
-        cube::CubeRoutine, plane::PlaneRoutine, unit::UnitRoutine,
+        GlobalReduceBlueprint, ReduceBlueprint, ReduceLaunchSettings, ReduceLineSettings,
+        ReduceProblem, Routine, cube::CubeRoutine, plane::PlaneRoutine, unit::UnitRoutine,
     },
 };
 use cubecl::{prelude::*, std::tensor::r#virtual::VirtualTensor};
@@ -35,6 +38,130 @@ pub(crate) fn launch_reduce<Run: Runtime>(
     dtypes: ReduceDtypes,
     inst: ReduceOperationConfig,
 ) -> Result<(), ReduceError> {
+    let (blueprint, settings) = prepare_reduce(client, &input, &output, axis, strategy, dtypes)?;
+
+    unsafe {
+        reduce_kernel::launch_unchecked::<TensorArgs, Run>(
+            client,
+            settings.cube_count,
+            settings.cube_dim,
+            input.as_tensor_arg(settings.line.line_size_input),
+            output.as_tensor_arg(settings.line.line_size_output),
+            ScalarArg::new(axis),
+            blueprint,
+            inst,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+        .map_err(ReduceError::Launch)
+    }
+}
+
+/// Launch a reduce kernel for the instruction family `I` configured with `config`.
+///
+/// The `transform` is applied to the input items, or to their element-wise product with `rhs`
+/// when provided. Both are computed once the items are cast to the accumulation type, so they
+/// don't overflow the input type. This function assumes that all parameters are already validated, see the
+/// entrypoints `reduce_with` and `reduce_transformed` in `lib.rs`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn launch_reduce_with<Run: Runtime, I: ReduceFamily>(
+    client: &ComputeClient<Run>,
+    input: TensorHandleRef<Run>,
+    rhs: Option<TensorHandleRef<Run>>,
+    output: TensorHandleRef<Run>,
+    axis: u32,
+    strategy: ReduceStrategy,
+    dtypes: ReduceDtypes,
+    config: I::Config,
+    transform: ReduceInputTransform,
+) -> Result<(), ReduceError> {
+    let (blueprint, settings) = prepare_reduce(client, &input, &output, axis, strategy, dtypes)?;
+    let line_size_input = settings.line.line_size_input;
+    let accumulate_input = rhs.is_some() || transform != ReduceInputTransform::Identity;
+
+    unsafe {
+        match rhs {
+            None => reduce_with_kernel::launch_unchecked::<TensorArgs, I, Run>(
+                client,
+                settings.cube_count,
+                settings.cube_dim,
+                input.as_tensor_arg(line_size_input),
+                output.as_tensor_arg(settings.line.line_size_output),
+                ScalarArg::new(axis),
+                blueprint,
+                config,
+                transform,
+                accumulate_input,
+                dtypes.input,
+                dtypes.output,
+                dtypes.accumulation,
+            ),
+            Some(rhs) => reduce_with_kernel::launch_unchecked::<ProductArgs, I, Run>(
+                client,
+                settings.cube_count,
+                settings.cube_dim,
+                ProductInputLaunch::new(
+                    input.as_tensor_arg(line_size_input),
+                    rhs.as_tensor_arg(line_size_input),
+                ),
+                output.as_tensor_arg(settings.line.line_size_output),
+                ScalarArg::new(axis),
+                blueprint,
+                config,
+                transform,
+                accumulate_input,
+                dtypes.input,
+                dtypes.output,
+                dtypes.accumulation,
+            ),
+        }
+        .map_err(ReduceError::Launch)
+    }
+}
+
+/// Launch a reduce kernel computing the [LpNorm] with the exponent `p` given at runtime,
+/// so that every exponent shares the same kernel. This function assumes that all parameters
+/// are already validated, see the entrypoint `reduce_lp_norm` in `lib.rs`.
+pub(crate) fn launch_lp_norm<Run: Runtime>(
+    client: &ComputeClient<Run>,
+    input: TensorHandleRef<Run>,
+    output: TensorHandleRef<Run>,
+    axis: u32,
+    p: f32,
+    strategy: ReduceStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    let (blueprint, settings) = prepare_reduce(client, &input, &output, axis, strategy, dtypes)?;
+
+    unsafe {
+        lp_norm_kernel::launch_unchecked::<Run>(
+            client,
+            settings.cube_count,
+            settings.cube_dim,
+            input.as_tensor_arg(settings.line.line_size_input),
+            output.as_tensor_arg(settings.line.line_size_output),
+            ScalarArg::new(axis),
+            ScalarArg::new(p),
+            blueprint,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+        .map_err(ReduceError::Launch)
+    }
+}
+
+/// Select the line sizes, the blueprint and the launch settings to reduce the given `axis`
+/// of `input` into `output`.
+pub(crate) fn prepare_reduce<Run: Runtime>(
+    client: &ComputeClient<Run>,
+    input: &TensorHandleRef<Run>,
+    output: &TensorHandleRef<Run>,
+    axis: u32,
+    strategy: ReduceStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(ReduceBlueprint, ReduceLaunchSettings), ReduceError> {
     let problem = ReduceProblem {
         vector_size: input.shape[axis as usize] as u32,
         vector_count: output.shape.iter().map(|i| *i as u32).product(),
@@ -47,8 +174,8 @@ pub(crate) fn launch_reduce<Run: Runtime>(
     };
     let (line_size_input, line_size_output) = generate_line_size::<Run>(
         client,
-        &input,
-        &output,
+        input,
+        output,
         axis as usize,
         problem.dtypes.input,
         line_mode,
@@ -60,36 +187,29 @@ pub(crate) fn launch_reduce<Run: Runtime>(
         line_size_output,
     };
 
-    let (blueprint, settings) = match strategy.routine {
+    prepare_routine(client, problem, settings, strategy.routine)
+}
+
+/// Select the blueprint and launch settings of the given routine for a problem.
+pub(crate) fn prepare_routine<Run: Runtime>(
+    client: &ComputeClient<Run>,
+    problem: ReduceProblem,
+    settings: ReduceLineSettings,
+    routine: RoutineStrategy,
+) -> Result<(ReduceBlueprint, ReduceLaunchSettings), ReduceError> {
+    match routine {
         RoutineStrategy::Unit(strategy) => {
             let routine = UnitRoutine;
-            routine.prepare(client, problem, settings, strategy)?
+            routine.prepare(client, problem, settings, strategy)
         }
         RoutineStrategy::Plane(strategy) => {
             let routine = PlaneRoutine;
-            routine.prepare(client, problem, settings, strategy)?
+            routine.prepare(client, problem, settings, strategy)
         }
         RoutineStrategy::Cube(strategy) => {
             let routine = CubeRoutine;
-            routine.prepare(client, problem, settings, strategy)?
+            routine.prepare(client, problem, settings, strategy)
         }
-    };
-
-    unsafe {
-        reduce_kernel::launch_unchecked::<TensorArgs, Run>(
-            client,
-            settings.cube_count,
-            settings.cube_dim,
-            input.as_tensor_arg(settings.line.line_size_input),
-            output.as_tensor_arg(settings.line.line_size_output),
-            ScalarArg::new(axis),
-            blueprint,
-            inst,
-            dtypes.input,
-            dtypes.output,
-            dtypes.accumulation,
-        )
-        .map_err(ReduceError::Launch)
     }
 }
 
@@ -108,6 +228,82 @@ pub fn reduce_kernel<In: Numeric, Out: Numeric, Acc: Numeric, RA: ReduceArgs>(
     reduce_kernel_virtual::<In, Out, Acc>(&input, &mut output, axis_reduce, blueprint, config);
 }
 
+/// Same as [reduce_kernel], but for any instruction family `I` instead of the
+/// [ReduceOperationConfig] of the provided instructions, and with the `transform`
+/// applied by the readers to every input item.
+///
+/// When `accumulate_input` is set, the input is read cast to `Acc` and the instruction reduces
+/// items of type `Acc`, so a product of tensors or a transform is computed in `Acc`.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+pub fn reduce_with_kernel<
+    In: Numeric,
+    Out: Numeric,
+    Acc: Numeric,
+    RA: ReduceArgs,
+    I: ReduceFamily,
+>(
+    input: &RA::Input<In>,
+    output: &mut RA::Output<Out>,
+    axis_reduce: u32,
+    #[comptime] blueprint: ReduceBlueprint,
+    #[comptime] config: I::Config,
+    #[comptime] transform: ReduceInputTransform,
+    #[comptime] accumulate_input: bool,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    if comptime!(accumulate_input) {
+        let (input, mut output) = init_tensors_as::<RA, In, Acc, Out>(input, output);
+        reduce_kernel_inner::<(Acc, Acc), Out, I>(
+            &input,
+            &mut output,
+            axis_reduce,
+            blueprint,
+            config,
+            transform,
+        );
+    } else {
+        let (input, mut output) = init_tensors::<RA, In, Out>(input, output);
+        reduce_kernel_inner::<(In, Acc), Out, I>(
+            &input,
+            &mut output,
+            axis_reduce,
+            blueprint,
+            config,
+            transform,
+        );
+    }
+}
+
+/// Same as [reduce_kernel] for the [LpNorm] with a runtime exponent `p`.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+pub fn lp_norm_kernel<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    output: &mut Tensor<Line<Out>>,
+    axis_reduce: u32,
+    p: f32,
+    #[comptime] blueprint: ReduceBlueprint,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let (input, mut output) = init_tensors::<TensorArgs, In, Out>(input, output);
+    let inst = &LpNorm {
+        p: NormPower::from_runtime(p),
+    };
+    reduce_instruction::<(In, Acc), Out, LpNorm>(
+        &input,
+        &mut output,
+        axis_reduce,
+        inst,
+        blueprint,
+        comptime!(ReduceInputTransform::Identity),
+    );
+}
+
 #[cube]
 pub fn reduce_kernel_virtual<In: Numeric, Out: Numeric, Acc: Numeric>(
     input: &VirtualTensor<In>,
@@ -122,6 +318,7 @@ pub fn reduce_kernel_virtual<In: Numeric, Out: Numeric, Acc: Numeric>(
         axis_reduce,
         blueprint,
         config,
+        comptime!(ReduceInputTransform::Identity),
     )
 }
 
@@ -132,39 +329,56 @@ fn reduce_kernel_inner<P: ReducePrecision, Out: Numeric, R: ReduceFamily>(
     axis_reduce: u32,
     #[comptime] blueprint: ReduceBlueprint,
     #[comptime] config: R::Config,
+    #[comptime] transform: ReduceInputTransform,
 ) {
     let inst = &R::Instruction::<P>::from_config(config);
+    reduce_instruction::<P, Out, R::Instruction<P>>(
+        input,
+        output,
+        axis_reduce,
+        inst,
+        blueprint,
+        transform,
+    );
+}
 
+/// Reduce with an instruction already built, e.g. from runtime arguments.
+#[cube]
+fn reduce_instruction<P: ReducePrecision, Out: Numeric, I: ReduceInstruction<P>>(
+    input: &VirtualTensor<P::EI>,
+    output: &mut VirtualTensor<Out, ReadWrite>,
+    axis_reduce: u32,
+    inst: &I,
+    #[comptime] blueprint: ReduceBlueprint,
+    #[comptime] transform: ReduceInputTransform,
+) {
     match comptime!(blueprint.global) {
-        GlobalReduceBlueprint::Cube(cube) => {
-            GlobalFullCubeReduce::execute::<P, Out, R::Instruction<P>>(
-                input,
-                output,
-                axis_reduce,
-                inst,
-                blueprint.line_mode,
-                cube,
-            )
-        }
-        GlobalReduceBlueprint::Plane(plane) => {
-            GlobalFullPlaneReduce::execute::<P, Out, R::Instruction<P>>(
-                input,
-                output,
-                axis_reduce,
-                inst,
-                blueprint.line_mode,
-                plane,
-            )
-        }
-        GlobalReduceBlueprint::Unit(unit) => {
-            GlobalFullUnitReduce::execute::<P, Out, R::Instruction<P>>(
-                input,
-                output,
-                axis_reduce,
-                inst,
-                blueprint.line_mode,
-                unit,
-            )
-        }
+        GlobalReduceBlueprint::Cube(cube) => GlobalFullCubeReduce::execute::<P, Out, I>(
+            input,
+            output,
+            axis_reduce,
+            inst,
+            blueprint.line_mode,
+            cube,
+            transform,
+        ),
+        GlobalReduceBlueprint::Plane(plane) => GlobalFullPlaneReduce::execute::<P, Out, I>(
+            input,
+            output,
+            axis_reduce,
+            inst,
+            blueprint.line_mode,
+            plane,
+            transform,
+        ),
+        GlobalReduceBlueprint::Unit(unit) => GlobalFullUnitReduce::execute::<P, Out, I>(
+            input,
+            output,
+            axis_reduce,
+            inst,
+            blueprint.line_mode,
+            unit,
+            transform,
+        ),
     };
 }
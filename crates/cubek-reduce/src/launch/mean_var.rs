@@ -0,0 +1,130 @@
+use crate::{
+    LineMode, ReduceError,
+    components::{
+        args::{TensorArgs, init_tensors},
+        global::unit::GlobalFullUnitReduce,
+        instructions::{MeanVar, MeanVarConfig, MeanVarOutput, ReduceInstruction},
+        readers::ReduceInputTransform,
+    },
+    launch::{
+        LineSizeStrategy, ReduceDtypes, RoutineStrategy, generate_line_size, prepare_routine,
+    },
+    routines::{
+        BlueprintStrategy, GlobalReduceBlueprint, ReduceLineSettings, ReduceProblem,
+        UnitReduceBlueprint, unit::UnitStrategy,
+    },
+};
+use cubecl::prelude::*;
+
+/// Launch a kernel writing both the mean and the variance of the given `axis`.
+/// This function assumes that all parameters are already validated and that
+/// `mean` and `var` have the same strides.
+///
+/// Each unit reduces full vectors, so both statistics come from the same accumulator.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn launch_mean_var<Run: Runtime>(
+    client: &ComputeClient<Run>,
+    input: TensorHandleRef<Run>,
+    mean: TensorHandleRef<Run>,
+    var: TensorHandleRef<Run>,
+    axis: u32,
+    correction: u32,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    let problem = ReduceProblem {
+        vector_size: input.shape[axis as usize] as u32,
+        vector_count: mean.shape.iter().map(|i| *i as u32).product(),
+        axis,
+        dtypes,
+    };
+    let line_mode = match input.strides[axis as usize] {
+        1 => LineMode::Parallel,
+        _ => LineMode::Perpendicular,
+    };
+    let (line_size_input, line_size_output) = generate_line_size::<Run>(
+        client,
+        &input,
+        &mean,
+        axis as usize,
+        problem.dtypes.input,
+        line_mode,
+        &LineSizeStrategy {
+            parallel_output_vectorization: false,
+        },
+    );
+    let settings = ReduceLineSettings {
+        line_mode,
+        line_size_input,
+        line_size_output,
+    };
+
+    let (blueprint, settings) = prepare_routine(
+        client,
+        problem,
+        settings,
+        RoutineStrategy::Unit(BlueprintStrategy::Inferred(UnitStrategy)),
+    )?;
+    let GlobalReduceBlueprint::Unit(unit) = blueprint.global else {
+        unreachable!("The unit routine always selects a unit blueprint");
+    };
+
+    unsafe {
+        mean_var_kernel::launch_unchecked::<Run>(
+            client,
+            settings.cube_count,
+            settings.cube_dim,
+            input.as_tensor_arg(settings.line.line_size_input),
+            mean.as_tensor_arg(settings.line.line_size_output),
+            var.as_tensor_arg(settings.line.line_size_output),
+            ScalarArg::new(axis),
+            blueprint.line_mode,
+            unit,
+            correction,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+        .map_err(ReduceError::Launch)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn mean_var_kernel<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    mean: &mut Tensor<Line<Out>>,
+    var: &mut Tensor<Line<Out>>,
+    axis_reduce: u32,
+    #[comptime] line_mode: LineMode,
+    #[comptime] blueprint: UnitReduceBlueprint,
+    #[comptime] correction: u32,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let (input_virtual, mut mean) = init_tensors::<TensorArgs, In, Out>(input, mean);
+    let (_, mut var) = init_tensors::<TensorArgs, In, Out>(input, var);
+
+    let inst_mean =
+        &<MeanVar as ReduceInstruction<(In, Acc)>>::from_config(comptime!(MeanVarConfig {
+            correction,
+            output: MeanVarOutput::Mean,
+        }));
+    let inst_var =
+        &<MeanVar as ReduceInstruction<(In, Acc)>>::from_config(comptime!(MeanVarConfig {
+            correction,
+            output: MeanVarOutput::Var,
+        }));
+
+    GlobalFullUnitReduce::execute_dual::<(In, Acc), Out, MeanVar>(
+        &input_virtual,
+        &mut mean,
+        &mut var,
+        axis_reduce,
+        inst_mean,
+        inst_var,
+        line_mode,
+        blueprint,
+        comptime!(ReduceInputTransform::Identity),
+    );
+}
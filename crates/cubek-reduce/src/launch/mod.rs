@@ -1,9 +1,15 @@
 pub mod tune_key;
 
 mod base;
+mod mean_var;
+mod multi_axis;
 mod strategy;
+mod tune;
 mod utils;
 
 pub use base::*;
+pub use mean_var::*;
+pub use multi_axis::*;
 pub use strategy::*;
+pub use tune::*;
 pub use utils::*;
@@ -0,0 +1,118 @@
+use crate::{
+    LineMode, ReduceError,
+    components::{
+        args::{MultiAxisArgs, MultiAxisInputLaunch},
+        instructions::ReduceOperationConfig,
+    },
+    launch::{ReduceDtypes, ReduceStrategy, launch_reduce, prepare_routine, reduce_kernel},
+    routines::{ReduceLineSettings, ReduceProblem},
+};
+use cubecl::{prelude::*, std::FastDivmodArgs};
+
+/// Launch a reduce kernel over multiple axes. This function assumes that all parameters are
+/// already validated and that `axes` is sorted.
+///
+/// When the reduced axes are contiguous in memory, they are coalesced into a single axis and the
+/// regular reduce path is used. Otherwise, the reduced axes are gathered into a virtual axis
+/// and each read is mapped back to memory by [MultiAxisArgs].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn launch_reduce_axes<Run: Runtime>(
+    client: &ComputeClient<Run>,
+    input: TensorHandleRef<Run>,
+    output: TensorHandleRef<Run>,
+    axes: &[usize],
+    strategy: ReduceStrategy,
+    dtypes: ReduceDtypes,
+    inst: ReduceOperationConfig,
+) -> Result<(), ReduceError> {
+    // The reduced axes are merged into the innermost one, all others have a virtual size of 1.
+    let axis = axes[axes.len() - 1];
+    let mut shape = input.shape.to_vec();
+    for a in axes {
+        shape[*a] = 1;
+    }
+    shape[axis] = axes.iter().map(|a| input.shape[*a]).product();
+
+    if is_coalescable(input.shape, input.strides, axes) {
+        let input = unsafe {
+            TensorHandleRef::from_raw_parts(input.handle, input.strides, &shape, input.elem_size)
+        };
+
+        return launch_reduce::<Run>(client, input, output, axis as u32, strategy, dtypes, inst);
+    }
+
+    // The virtual view is contiguous, so a virtual index can be decomposed back along the
+    // original axes, where the reduced axes take the place of the virtual one.
+    let strides = contiguous_strides(&shape);
+    let mut gather = Vec::with_capacity(input.shape.len());
+    for a in 0..input.shape.len() {
+        if a == axis {
+            gather.extend(axes.iter().map(|r| (input.shape[*r], input.strides[*r])));
+        } else if !axes.contains(&a) {
+            gather.push((input.shape[a], input.strides[a]));
+        }
+    }
+
+    let problem = ReduceProblem {
+        vector_size: shape[axis] as u32,
+        vector_count: output.shape.iter().map(|i| *i as u32).product(),
+        axis: axis as u32,
+        dtypes,
+    };
+    // Reads are scattered in memory, so vectorization isn't possible.
+    let settings = ReduceLineSettings {
+        line_mode: match strides[axis] {
+            1 => LineMode::Parallel,
+            _ => LineMode::Perpendicular,
+        },
+        line_size_input: 1,
+        line_size_output: 1,
+    };
+
+    let (blueprint, settings) = prepare_routine(client, problem, settings, strategy.routine)?;
+
+    let view =
+        unsafe { TensorHandleRef::from_raw_parts(input.handle, &strides, &shape, input.elem_size) };
+    let input = MultiAxisInputLaunch::new(
+        view.as_tensor_arg(settings.line.line_size_input),
+        gather
+            .iter()
+            .map(|(size, _)| FastDivmodArgs::new(client, *size as u32))
+            .collect(),
+        gather
+            .iter()
+            .map(|(_, stride)| ScalarArg::new(*stride as u32))
+            .collect(),
+    );
+
+    unsafe {
+        reduce_kernel::launch_unchecked::<MultiAxisArgs, Run>(
+            client,
+            settings.cube_count,
+            settings.cube_dim,
+            input,
+            output.as_tensor_arg(settings.line.line_size_output),
+            ScalarArg::new(axis as u32),
+            blueprint,
+            inst,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+        .map_err(ReduceError::Launch)
+    }
+}
+
+/// Whether the sorted `axes` can be addressed as a single axis with the stride of the last one.
+fn is_coalescable(shape: &[usize], strides: &[usize], axes: &[usize]) -> bool {
+    axes.windows(2)
+        .all(|pair| strides[pair[0]] == strides[pair[1]] * shape[pair[1]])
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
@@ -0,0 +1,283 @@
+use crate::{
+    ReduceDtypes, ReduceError,
+    components::instructions::ReduceOperationConfig,
+    launch::{
+        LineSizeStrategy, ReduceStrategy, RoutineStrategy, launch_reduce, prepare_reduce,
+        tune_key::ReduceAutotuneKey,
+    },
+    routines::{BlueprintStrategy, cube::CubeStrategy, plane::PlaneStrategy, unit::UnitStrategy},
+};
+use core::fmt::Display;
+use std::{
+    collections::HashSet,
+    sync::{LazyLock, Mutex},
+};
+
+use cubecl::{
+    prelude::*,
+    std::tensor::TensorHandle,
+    tune::{LocalTuner, Tunable, TunableSet, TuneGroup, local_tuner},
+};
+
+const PRIORITY_MAX: i8 = 2;
+/// A negative priority skips the tunable.
+const PRIORITY_SKIP: i8 = -1;
+
+/// The inputs of every tunable, owned so that they can be benchmarked.
+type ReduceTuneInputs<R> = (
+    ComputeClient<R>,
+    TensorHandle<R>,
+    TensorHandle<R>,
+    usize,
+    ReduceOperationConfig,
+    ReduceDtypes,
+);
+
+/// Identify the device on which the strategies were benchmarked.
+#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+pub(crate) struct ReduceTuneId {
+    runtime: &'static str,
+    device: String,
+}
+
+impl ReduceTuneId {
+    fn new<R: Runtime>(client: &ComputeClient<R>, device: &R::Device) -> Self {
+        Self {
+            runtime: R::name(client),
+            device: format!("{device:?}"),
+        }
+    }
+}
+
+impl Display for ReduceTuneId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}-{}", self.runtime, self.device)
+    }
+}
+
+static TUNER: LocalTuner<ReduceAutotuneKey, ReduceTuneId> = local_tuner!("reduce");
+
+/// The keys for which [`check_supported`] already found a tunable, on each device.
+static SUPPORTED_KEYS: LazyLock<Mutex<HashSet<(ReduceTuneId, ReduceAutotuneKey)>>> =
+    LazyLock::new(Default::default);
+
+/// Launch a reduce kernel with the fastest strategy for the [key](ReduceAutotuneKey) of the
+/// problem. This function assumes that all parameters are already validated.
+///
+/// Returns the error of the last strategy when none of them supports the problem.
+pub(crate) fn launch_reduce_autotune<R: Runtime>(
+    client: &ComputeClient<R>,
+    device: &R::Device,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    axis: usize,
+    operation: ReduceOperationConfig,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    let id = ReduceTuneId::new::<R>(client, device);
+    let key = tune_key(input.shape, input.strides, axis, operation, dtypes);
+    let checked = (id.clone(), key);
+    if !SUPPORTED_KEYS.lock().unwrap().contains(&checked) {
+        check_supported(client, &input, &output, axis, dtypes)?;
+        SUPPORTED_KEYS.lock().unwrap().insert(checked);
+    }
+
+    let tunables = TUNER.init(|| {
+        let routine = TuneGroup::<ReduceAutotuneKey>::new("routine", |_| PRIORITY_MAX);
+        // The output is only vectorized when the reduced axis is contiguous, otherwise both
+        // line size strategies launch the same kernel.
+        let output_vectorization =
+            TuneGroup::<ReduceAutotuneKey>::new("output_vectorization", |key| {
+                match key.axis_is_contiguous {
+                    true => PRIORITY_MAX,
+                    false => PRIORITY_SKIP,
+                }
+            });
+
+        let mut set = TunableSet::new(create_key::<R>, input_gen::<R>);
+        for (name, routine_strategy) in routine_strategies() {
+            set = set.with(
+                tunable::<R>(name, routine_strategy.clone(), false)
+                    .group(&routine, |_| PRIORITY_MAX),
+            );
+            set = set.with(
+                tunable::<R>(name, routine_strategy, true)
+                    .group(&output_vectorization, |_| PRIORITY_MAX),
+            );
+        }
+        set
+    });
+
+    // The tunables are benchmarked later on, so they own their tensors.
+    let input = TensorHandle::new(
+        input.handle.clone(),
+        input.shape.to_vec(),
+        input.strides.to_vec(),
+        dtypes.input,
+    );
+    let output = TensorHandle::new(
+        output.handle.clone(),
+        output.shape.to_vec(),
+        output.strides.to_vec(),
+        dtypes.output,
+    );
+
+    TUNER.execute(
+        &id,
+        client,
+        tunables,
+        (client.clone(), input, output, axis, operation, dtypes),
+    );
+    Ok(())
+}
+
+/// Check that at least one tunable can be launched, since the tuner doesn't report the
+/// tunables that failed.
+///
+/// Only called the first time a key is seen, the problems sharing a key are assumed to be
+/// supported by the same tunables.
+fn check_supported<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: &TensorHandleRef<R>,
+    output: &TensorHandleRef<R>,
+    axis: usize,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    let mut result = Ok(());
+    for (_, routine) in routine_strategies() {
+        for parallel_output_vectorization in [false, true] {
+            let strategy = ReduceStrategy {
+                routine: routine.clone(),
+                line_size: LineSizeStrategy {
+                    parallel_output_vectorization,
+                },
+            };
+            match prepare_reduce(client, input, output, axis as u32, strategy, dtypes) {
+                Ok(_) => return Ok(()),
+                Err(err) => result = Err(err),
+            }
+        }
+    }
+    result
+}
+
+/// The routines benchmarked for every key.
+fn routine_strategies() -> [(&'static str, RoutineStrategy); 5] {
+    [
+        (
+            "unit",
+            RoutineStrategy::Unit(BlueprintStrategy::Inferred(UnitStrategy)),
+        ),
+        (
+            "plane_independent",
+            RoutineStrategy::Plane(BlueprintStrategy::Inferred(PlaneStrategy {
+                independent: true,
+            })),
+        ),
+        (
+            "plane",
+            RoutineStrategy::Plane(BlueprintStrategy::Inferred(PlaneStrategy {
+                independent: false,
+            })),
+        ),
+        (
+            "cube_planes",
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: true,
+            })),
+        ),
+        (
+            "cube",
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: false,
+            })),
+        ),
+    ]
+}
+
+/// A tunable launching the given routine, failing when it isn't supported by the client.
+fn tunable<R: Runtime>(
+    name: &str,
+    routine: RoutineStrategy,
+    parallel_output_vectorization: bool,
+) -> Tunable<ReduceAutotuneKey, ReduceTuneInputs<R>, ()> {
+    let name = match parallel_output_vectorization {
+        true => format!("reduce_{name}_output_vectorization"),
+        false => format!("reduce_{name}"),
+    };
+    let strategy = ReduceStrategy {
+        routine,
+        line_size: LineSizeStrategy {
+            parallel_output_vectorization,
+        },
+    };
+
+    Tunable::new(
+        &name,
+        move |client: ComputeClient<R>,
+              input: TensorHandle<R>,
+              output: TensorHandle<R>,
+              axis: usize,
+              operation: ReduceOperationConfig,
+              dtypes: ReduceDtypes| {
+            launch_reduce::<R>(
+                &client,
+                input.as_ref(),
+                output.as_ref(),
+                axis as u32,
+                strategy.clone(),
+                dtypes,
+                operation,
+            )
+            .map_err(|err| format!("{err}"))
+        },
+    )
+}
+
+fn create_key<R: Runtime>(
+    _client: &ComputeClient<R>,
+    input: &TensorHandle<R>,
+    _output: &TensorHandle<R>,
+    axis: &usize,
+    operation: &ReduceOperationConfig,
+    dtypes: &ReduceDtypes,
+) -> ReduceAutotuneKey {
+    tune_key(&input.shape, &input.strides, *axis, *operation, *dtypes)
+}
+
+fn tune_key(
+    shape: &[usize],
+    strides: &[usize],
+    axis: usize,
+    operation: ReduceOperationConfig,
+    dtypes: ReduceDtypes,
+) -> ReduceAutotuneKey {
+    ReduceAutotuneKey::generate(
+        dtypes.input.elem_type(),
+        dtypes.output.elem_type(),
+        dtypes.accumulation.elem_type(),
+        operation,
+        shape,
+        strides[axis] == 1,
+        axis,
+    )
+}
+
+fn input_gen<R: Runtime>(
+    _key: &ReduceAutotuneKey,
+    client: &ComputeClient<R>,
+    input: &TensorHandle<R>,
+    output: &TensorHandle<R>,
+    axis: &usize,
+    operation: &ReduceOperationConfig,
+    dtypes: &ReduceDtypes,
+) -> ReduceTuneInputs<R> {
+    (
+        client.clone(),
+        input.clone(),
+        output.clone(),
+        *axis,
+        *operation,
+        *dtypes,
+    )
+}
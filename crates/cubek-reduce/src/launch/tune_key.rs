@@ -1,12 +1,16 @@
 use cubecl::{AutotuneKey, ir::ElemType};
 use serde::{Deserialize, Serialize};
 
+use crate::components::instructions::ReduceOperationConfig;
+
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, AutotuneKey)]
 /// Autotune key representative of reduce versions
 pub struct ReduceAutotuneKey {
     elem_input: ElemType,
     elem_output: ElemType,
     elem_acc: ElemType,
+    /// The accumulator of the operation, operations sharing it share the tuned strategy.
+    pub accumulator: ReduceAccumulatorClass,
     /// Whether the axis is contiguous.
     pub axis_is_contiguous: bool,
     /// The length of the vector to reduce.
@@ -30,13 +34,14 @@ impl ReduceAutotuneKey {
         elem_input: ElemType,
         elem_output: ElemType,
         elem_acc: ElemType,
+        operation: ReduceOperationConfig,
         input_shape: &[usize],
         axis_is_contiguous: bool,
         axis: usize,
     ) -> Self {
         let rank = input_shape.len();
 
-        if axis > rank {
+        if axis >= rank {
             panic!("axis {axis} is out-of-bound for a rank of {rank}");
         }
 
@@ -52,9 +57,57 @@ impl ReduceAutotuneKey {
             elem_input,
             elem_output,
             elem_acc,
+            ReduceAccumulatorClass::from(operation),
             axis_is_contiguous,
             reduce_axis_shape,
             reduce_count,
         )
     }
 }
+
+/// The layout of the accumulator of an operation, which drives the cost of the routines.
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ReduceAccumulatorClass {
+    /// A single value per element, e.g. a sum or a maximum.
+    Value,
+    /// A value and its coordinate per element, e.g. an argmax.
+    Indexed,
+    /// A value and `aux` auxiliary values per element, e.g. the moments of a variance.
+    Auxiliary { aux: u32 },
+}
+
+impl From<ReduceOperationConfig> for ReduceAccumulatorClass {
+    fn from(operation: ReduceOperationConfig) -> Self {
+        match operation {
+            ReduceOperationConfig::Sum
+            | ReduceOperationConfig::Prod
+            | ReduceOperationConfig::Mean
+            | ReduceOperationConfig::MaxAbs
+            | ReduceOperationConfig::Max
+            | ReduceOperationConfig::Min
+            | ReduceOperationConfig::L1Norm
+            | ReduceOperationConfig::Any
+            | ReduceOperationConfig::All
+            | ReduceOperationConfig::CountNonZero
+            | ReduceOperationConfig::NanSum
+            | ReduceOperationConfig::NanMax
+            | ReduceOperationConfig::NanMin => ReduceAccumulatorClass::Value,
+            ReduceOperationConfig::ArgMax | ReduceOperationConfig::ArgMin => {
+                ReduceAccumulatorClass::Indexed
+            }
+            ReduceOperationConfig::Var { .. } | ReduceOperationConfig::Std { .. } => {
+                ReduceAccumulatorClass::Auxiliary { aux: 2 }
+            }
+            ReduceOperationConfig::LogSumExp
+            | ReduceOperationConfig::L2Norm
+            | ReduceOperationConfig::LpNorm { .. }
+            | ReduceOperationConfig::NanMean
+            | ReduceOperationConfig::CompensatedSum
+            | ReduceOperationConfig::CompensatedMean
+            | ReduceOperationConfig::CompensatedL1Norm
+            | ReduceOperationConfig::CompensatedL2Norm => {
+                ReduceAccumulatorClass::Auxiliary { aux: 1 }
+            }
+        }
+    }
+}
@@ -8,6 +8,8 @@
 //! This crate provides a main entrypoint as the [`reduce`] function which allows to automatically
 //! perform a reduction for a given instruction implementing the [`ReduceInstruction`] trait and a given [`ReduceStrategy`].
 //! It also provides implementation of the [`ReduceInstruction`] trait for common operations in the [`instructions`] module.
+//! Custom instructions can be launched with the same strategies through [`reduce_with`].
+//! Alternatively, [`reduce_autotune`] benchmarks the strategies and caches the fastest one.
 //! Finally, it provides many reusable primitives to perform different general reduction algorithms in the [`primitives`] module.
 
 pub mod components;
@@ -17,17 +19,38 @@ pub mod routines;
 mod error;
 
 pub use crate::launch::ReduceStrategy;
-use crate::{components::instructions::ReduceOperationConfig, launch::launch_reduce};
+use crate::{
+    components::{
+        instructions::{ReduceOperation, ReduceOperationConfig},
+        readers::ReduceInputTransform,
+    },
+    launch::{
+        launch_lp_norm, launch_mean_var, launch_reduce, launch_reduce_autotune, launch_reduce_axes,
+        launch_reduce_with,
+    },
+};
 pub use components::{
     args::init_tensors,
     config::*,
     instructions::{ReduceFamily, ReduceInstruction},
     precision::ReducePrecision,
 };
-use cubecl::prelude::*;
+use cubecl::{ir::ElemType, prelude::*};
 pub use error::*;
-pub use launch::{ReduceDtypes, reduce_kernel};
-pub use routines::shared_sum::shared_sum;
+pub use launch::{ReduceDtypes, reduce_kernel, reduce_with_kernel};
+pub use routines::{
+    arg_extremum::arg_extremum,
+    channel_norm::{batch_norm, group_norm},
+    histogram::{HistogramBins, bincount, histogram},
+    layer_norm::{layer_norm, rms_norm},
+    layer_norm_backward::{layer_norm_backward, rms_norm_backward},
+    reduce_all::reduce_all,
+    scan::scan,
+    segmented::segmented_reduce,
+    shared_sum::{shared_sum, shared_sum_with},
+    softmax::{log_softmax, softmax},
+    top_k::top_k,
+};
 
 /// Reduce the given `axis` of the `input` tensor using the instruction `Inst` and write the result into `output`.
 ///
@@ -92,7 +115,7 @@ pub fn reduce<R: Runtime>(
     dtypes: ReduceDtypes,
 ) -> Result<(), ReduceError> {
     validate_axis(input.shape.len(), axis)?;
-    valid_output_shape(input.shape, output.shape, axis)?;
+    valid_output_shape(input.shape, output.shape, &[axis])?;
 
     launch_reduce::<R>(
         client,
@@ -105,22 +128,236 @@ pub fn reduce<R: Runtime>(
     )
 }
 
+/// Reduce the given `axis` of the `input` tensor like [`reduce`], selecting the [`ReduceStrategy`]
+/// by autotuning on `device`.
+///
+/// The first reduction of a [`ReduceAutotuneKey`](launch::tune_key::ReduceAutotuneKey) benchmarks
+/// the unit, plane and cube routines with both [`LineSizeStrategy`](launch::LineSizeStrategy)
+/// options, skipping the ones that aren't supported by the `client`. The fastest strategy is cached
+/// and reused for every problem with the same key. The key holds the accumulator class of the
+/// `operation`, so the operations with the same accumulator share their strategies.
+///
+/// Returns the same validation errors as [`reduce`], and the error of the last strategy when none
+/// of them supports the problem.
+pub fn reduce_autotune<R: Runtime>(
+    client: &ComputeClient<R>,
+    device: &R::Device,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    axis: usize,
+    operation: ReduceOperationConfig,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    validate_axis(input.shape.len(), axis)?;
+    valid_output_shape(input.shape, output.shape, &[axis])?;
+
+    launch_reduce_autotune::<R>(client, device, input, output, axis, operation, dtypes)
+}
+
+/// Reduce the given `axis` of the `input` tensor using any instruction family `I` configured
+/// with `config` and write the result into `output`.
+///
+/// This behaves like [`reduce`] and selects the routine the same way from `strategy`, but
+/// it isn't restricted to the provided instructions of [`ReduceOperationConfig`]. A custom
+/// instruction only has to implement [`ReduceFamily`] and [`ReduceInstruction`], see
+/// [`Sum`](components::instructions::Sum) for a simple example.
+///
+/// Returns the same errors as [`reduce`].
+pub fn reduce_with<R: Runtime, I: ReduceFamily>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    axis: usize,
+    strategy: ReduceStrategy,
+    config: I::Config,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    validate_axis(input.shape.len(), axis)?;
+    valid_output_shape(input.shape, output.shape, &[axis])?;
+
+    launch_reduce_with::<R, I>(
+        client,
+        input,
+        None,
+        output,
+        axis as u32,
+        strategy,
+        dtypes,
+        config,
+        ReduceInputTransform::Identity,
+    )
+}
+
+/// Compute the Lp norm along the given `axis` of the `input` tensor, with the exponent `p` given
+/// at runtime.
+///
+/// This behaves like [`reduce`] with [`LpNorm`](ReduceOperationConfig::LpNorm), whose comptime
+/// exponent compiles a kernel for each `p`. Here, every exponent shares the same kernel, at the
+/// cost of computing the powers with `powf` even when `p` is 2.
+///
+/// Returns the same errors as [`reduce`], and an error if `p` isn't a finite positive number or
+/// if the accumulation type isn't a float.
+pub fn reduce_lp_norm<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    axis: usize,
+    p: f32,
+    strategy: ReduceStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    validate_axis(input.shape.len(), axis)?;
+    valid_output_shape(input.shape, output.shape, &[axis])?;
+    if !(p.is_finite() && p > 0.0) {
+        return Err(ReduceError::Validation {
+            details: "The exponent of a norm must be finite and positive.",
+        });
+    }
+    if !matches!(dtypes.accumulation.elem_type(), ElemType::Float(_)) {
+        return Err(ReduceError::Validation {
+            details: "The accumulation type of a norm must be a float.",
+        });
+    }
+
+    launch_lp_norm::<R>(client, input, output, axis as u32, p, strategy, dtypes)
+}
+
+/// Reduce the given `axis` of the `input` tensor like [`reduce`], after applying the element-wise
+/// `transform` to every item, e.g. a sum of squares with [`Square`](ReduceInputTransform::Square).
+///
+/// When `rhs` is provided, the items of `input` are first multiplied element-wise with the items
+/// of `rhs`, so that a [`Sum`](ReduceOperationConfig::Sum) computes the dot products along the axis.
+/// The transform is applied by the readers, so the mapped input is never written to global memory.
+/// Both the product and the transform are computed in the accumulation type of `dtypes`.
+///
+/// Returns the same errors as [`reduce`], and an error if `rhs` doesn't have the same shape and
+/// strides as `input`.
+#[allow(clippy::too_many_arguments)]
+pub fn reduce_transformed<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    rhs: Option<TensorHandleRef<R>>,
+    output: TensorHandleRef<R>,
+    axis: usize,
+    strategy: ReduceStrategy,
+    operation: ReduceOperationConfig,
+    transform: ReduceInputTransform,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    validate_axis(input.shape.len(), axis)?;
+    valid_output_shape(input.shape, output.shape, &[axis])?;
+    if let Some(rhs) = &rhs
+        && (rhs.shape != input.shape || rhs.strides != input.strides)
+    {
+        return Err(ReduceError::Validation {
+            details: "The rhs must have the same shape and strides as the input.",
+        });
+    }
+
+    launch_reduce_with::<R, ReduceOperation>(
+        client,
+        input,
+        rhs,
+        output,
+        axis as u32,
+        strategy,
+        dtypes,
+        operation,
+        transform,
+    )
+}
+
+/// Reduce all the given `axes` of the `input` tensor in a single launch and write the result into `output`.
+///
+/// This behaves like [`reduce`], except that the shape of `output` must be the same as input
+/// with a value of 1 for every reduced axis. The axes can be provided in any order.
+///
+/// When the reduced axes are contiguous in memory, they are coalesced into a single axis and the
+/// reduction is as efficient as reducing one axis. Otherwise, the reduced axes are read through
+/// a virtual axis, without vectorization.
+///
+/// For [`ArgMax`](ReduceOperationConfig::ArgMax) and [`ArgMin`](ReduceOperationConfig::ArgMin),
+/// the returned index is flattened over the reduced axes in row-major order.
+///
+/// Returns an error if `axes` is empty, contains duplicates or an axis larger than the `input` rank.
+pub fn reduce_axes<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    axes: &[usize],
+    strategy: ReduceStrategy,
+    operation: ReduceOperationConfig,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    let mut axes = axes.to_vec();
+    axes.sort_unstable();
+
+    if axes.is_empty() {
+        return Err(ReduceError::Validation {
+            details: "At least one axis must be reduced.",
+        });
+    }
+    if axes.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err(ReduceError::Validation {
+            details: "The reduced axes must be unique.",
+        });
+    }
+    for axis in axes.iter() {
+        validate_axis(input.shape.len(), *axis)?;
+    }
+    valid_output_shape(input.shape, output.shape, &axes)?;
+
+    launch_reduce_axes::<R>(client, input, output, &axes, strategy, dtypes, operation)
+}
+
+/// Compute both the mean and the variance of the given `axis` of the `input` tensor in a single pass.
+///
+/// The variance divides the sum of the squared deviations by the number of elements minus `correction`,
+/// so use 0 for the population variance and 1 for the unbiased sample variance.
+/// Both statistics are accumulated with the Welford algorithm, see
+/// [`MeanVar`](components::instructions::MeanVar).
+///
+/// The shapes of `mean` and `var` must be the same as input except with a value of 1 for the given `axis`,
+/// and both outputs must have the same strides.
+pub fn mean_var<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    mean: TensorHandleRef<R>,
+    var: TensorHandleRef<R>,
+    axis: usize,
+    correction: u32,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    validate_axis(input.shape.len(), axis)?;
+    valid_output_shape(input.shape, mean.shape, &[axis])?;
+    valid_output_shape(input.shape, var.shape, &[axis])?;
+    if mean.strides != var.strides {
+        return Err(ReduceError::Validation {
+            details: "The mean and variance outputs must have the same strides.",
+        });
+    }
+
+    launch_mean_var::<R>(client, input, mean, var, axis as u32, correction, dtypes)
+}
+
 // Check that the given axis is less than the rank of the input.
 fn validate_axis(rank: usize, axis: usize) -> Result<(), ReduceError> {
-    if axis > rank {
+    if axis >= rank {
         return Err(ReduceError::InvalidAxis { axis, rank });
     }
     Ok(())
 }
 
-// Check that the output shape match the input shape with the given axis set to 1.
+// Check that the output shape match the input shape with the given axes set to 1.
 fn valid_output_shape(
     input_shape: &[usize],
     output_shape: &[usize],
-    axis: usize,
+    axes: &[usize],
 ) -> Result<(), ReduceError> {
     let mut expected_shape = input_shape.to_vec();
-    expected_shape[axis] = 1;
+    for axis in axes {
+        expected_shape[*axis] = 1;
+    }
     if output_shape != expected_shape {
         return Err(ReduceError::MismatchShape {
             expected_shape,
@@ -0,0 +1,122 @@
+use cubecl::prelude::*;
+
+use crate::{
+    LineMode, ReduceDtypes, ReduceError,
+    components::instructions::{
+        ArgExtremum, ArgExtremumConfig, ReduceCoordinate, ReduceInstruction, reduce_inplace,
+    },
+    launch::RoutineStrategy,
+    routines::{
+        GlobalReduceBlueprint,
+        layer_norm::{RowWorker, prepare_rows},
+        reduce_all::fuse_cube,
+        scan::vector_offset,
+    },
+    valid_output_shape, validate_axis,
+};
+
+/// Select the maximum or minimum element along the given `axis` of the `input` tensor and
+/// write it into `values`, with its position along the axis written into `indices`.
+///
+/// This replaces a `Max` and an `ArgMax` reduction, or their minimum counterparts, with a single
+/// pass. The [TieBreak](crate::components::instructions::TieBreak) of the `config` selects the
+/// first or the last position among equal elements. NaN is selected over any other element and
+/// several NaN are tie-broken like equal elements, see [ArgExtremum].
+/// The selection is the same for every [RoutineStrategy].
+///
+/// The `values` tensor has the element type `dtype` of the input and `indices` holds `u32`.
+/// Both must have the same shape as `input` except with a value of 1 for the given `axis`.
+///
+/// Returns an error if the plane routine is requested but planes are unavailable or of variable size.
+#[allow(clippy::too_many_arguments)]
+pub fn arg_extremum<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    values: TensorHandleRef<R>,
+    indices: TensorHandleRef<R>,
+    axis: usize,
+    config: ArgExtremumConfig,
+    strategy: RoutineStrategy,
+    dtype: StorageType,
+) -> Result<(), ReduceError> {
+    validate_axis(input.shape.len(), axis)?;
+    valid_output_shape(input.shape, values.shape, &[axis])?;
+    valid_output_shape(input.shape, indices.shape, &[axis])?;
+
+    // The elements are only compared, so they are accumulated without any conversion.
+    let dtypes = ReduceDtypes {
+        input: dtype,
+        output: dtype,
+        accumulation: dtype,
+    };
+    let (blueprint, launch, num_rows) = prepare_rows(client, &input, axis, strategy, dtypes)?;
+
+    unsafe {
+        arg_extremum_kernel::launch_unchecked::<R>(
+            client,
+            launch.cube_count,
+            launch.cube_dim,
+            input.as_tensor_arg(1),
+            values.as_tensor_arg(1),
+            indices.as_tensor_arg(1),
+            ScalarArg::new(axis as u32),
+            ScalarArg::new(num_rows),
+            blueprint.global,
+            config,
+            launch.cube_dim.num_elems(),
+            dtype,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn arg_extremum_kernel<N: Numeric>(
+    input: &Tensor<Line<N>>,
+    values: &mut Tensor<Line<N>>,
+    indices: &mut Tensor<Line<u32>>,
+    axis: u32,
+    num_rows: u32,
+    #[comptime] blueprint: GlobalReduceBlueprint,
+    #[comptime] config: ArgExtremumConfig,
+    #[comptime] shared_memory_size: u32,
+    #[define(N)] _dtype: StorageType,
+) {
+    let worker = RowWorker::new(blueprint);
+    let row = worker.row;
+    let length = worker.length(num_rows, input.shape(axis));
+
+    let inst = &<ArgExtremum as ReduceInstruction<(N, N)>>::from_config(config);
+    let requirements = <ArgExtremum as ReduceInstruction<(N, N)>>::requirements(inst);
+    let input_offset = vector_offset::<N>(input, row, axis);
+
+    let mut accumulator = <ArgExtremum as ReduceInstruction<(N, N)>>::null_accumulator(inst, 1u32);
+    let mut k = worker.first;
+    while k < length {
+        let coordinate = ReduceCoordinate::new(k, requirements, 1u32, LineMode::Parallel);
+        reduce_inplace::<(N, N), ArgExtremum>(
+            inst,
+            &mut accumulator,
+            input[input_offset + k * input.stride(axis)],
+            coordinate,
+            false,
+        );
+        k += worker.step;
+    }
+
+    let result = match comptime!(blueprint) {
+        GlobalReduceBlueprint::Unit(_) => accumulator,
+        GlobalReduceBlueprint::Plane(_) => {
+            <ArgExtremum as ReduceInstruction<(N, N)>>::fuse_plane(inst, accumulator)
+        }
+        GlobalReduceBlueprint::Cube(_) => {
+            fuse_cube::<(N, N), ArgExtremum>(inst, accumulator, shared_memory_size, 1u32)
+        }
+    };
+
+    if row < num_rows && worker.leader {
+        values[vector_offset::<N>(values, row, axis)] = result.0;
+        indices[vector_offset::<u32>(indices, row, axis)] = result.1;
+    }
+}
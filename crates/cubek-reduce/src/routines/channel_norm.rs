@@ -0,0 +1,723 @@
+use cubecl::prelude::*;
+
+use crate::{
+    ReduceDtypes, ReduceError,
+    components::{
+        global::cube::reduce_tree,
+        instructions::{
+            ReduceInstruction, SharedAccumulator, Var, WelfordAccumulator, WelfordState,
+        },
+    },
+    routines::{cube_count_safe, layer_norm::optional_arg, scan::vector_offset},
+};
+
+/// The number of units of the cubes of every channel norm kernel.
+const CUBE_SIZE: u32 = 256;
+/// The maximum number of elements reduced by a cube into a partial Welford state.
+const SPLIT_SIZE: u32 = 8192;
+/// The maximum number of rows reduced by a unit into a partial Welford state when every group
+/// is a single channel.
+const SPLIT_ROWS: u32 = 256;
+
+/// The optional tensors of [group_norm].
+pub struct GroupNormTensors<'a, R: Runtime> {
+    /// The scale of the normalized values, with the shape of the channel axis.
+    pub gamma: Option<TensorHandleRef<'a, R>>,
+    /// The offset of the normalized values, with the shape of the channel axis.
+    pub beta: Option<TensorHandleRef<'a, R>>,
+    /// Where the mean of each group is written, with a shape of `[batch, num_groups]`.
+    pub mean: Option<TensorHandleRef<'a, R>>,
+    /// Where the reciprocal of the standard deviation of each group is written, with the same
+    /// shape as `mean`.
+    pub rstd: Option<TensorHandleRef<'a, R>>,
+}
+
+impl<R: Runtime> Default for GroupNormTensors<'_, R> {
+    fn default() -> Self {
+        Self {
+            gamma: None,
+            beta: None,
+            mean: None,
+            rstd: None,
+        }
+    }
+}
+
+/// The optional tensors of [batch_norm].
+pub struct BatchNormTensors<'a, R: Runtime> {
+    /// The scale of the normalized values, with the shape of the channel axis.
+    pub gamma: Option<TensorHandleRef<'a, R>>,
+    /// The offset of the normalized values, with the shape of the channel axis.
+    pub beta: Option<TensorHandleRef<'a, R>>,
+    /// The running mean of each channel, updated in training mode and used in inference mode.
+    pub running_mean: Option<TensorHandleRef<'a, R>>,
+    /// The running unbiased variance of each channel, updated in training mode and used in
+    /// inference mode.
+    pub running_var: Option<TensorHandleRef<'a, R>>,
+    /// Where the mean of each channel is written, with the shape of the channel axis.
+    /// Only valid in training mode.
+    pub mean: Option<TensorHandleRef<'a, R>>,
+    /// Where the reciprocal of the standard deviation of each channel is written, with the shape
+    /// of the channel axis. Only valid in training mode.
+    pub rstd: Option<TensorHandleRef<'a, R>>,
+}
+
+impl<R: Runtime> Default for BatchNormTensors<'_, R> {
+    fn default() -> Self {
+        Self {
+            gamma: None,
+            beta: None,
+            running_mean: None,
+            running_var: None,
+            mean: None,
+            rstd: None,
+        }
+    }
+}
+
+/// Whether [batch_norm] normalizes with the statistics of the batch or the running statistics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchNormMode {
+    /// Normalize with the statistics of the batch, which are merged into the running statistics
+    /// when they are provided, that is `running = (1 - momentum) * running + momentum * batch`.
+    Training { momentum: f32 },
+    /// Normalize with the running statistics, which must be provided.
+    Inference,
+}
+
+/// Normalize the channels of the `input` tensor by groups and write the result into `output`,
+/// that is `(x - mean) * rstd * gamma + beta` with `rstd = 1 / sqrt(var + epsilon)`.
+///
+/// The input is channels-last, like the NHWC tensors of convolutions, with the batch as the
+/// first axis. The channels are split into `num_groups` consecutive groups, and the statistics
+/// of each group are computed for each batch element over all other axes. The `output` must
+/// have the same shape as `input`, and `gamma` and `beta` are applied per channel.
+///
+/// The elements of every group are split in chunks reduced by different cubes into Welford
+/// states, which are then merged into the mean and variance of the group. A last kernel applies
+/// the normalization to every element. The statistics are computed with the `accumulation`
+/// type of the `dtypes`, which is also the type of `mean` and `rstd`.
+///
+/// Returns an error if a tensor has an invalid shape or if the number of channels isn't a
+/// multiple of `num_groups`.
+pub fn group_norm<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    num_groups: usize,
+    tensors: GroupNormTensors<R>,
+    epsilon: f32,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    validate_channel_norm(&input, &output, [&tensors.gamma, &tensors.beta])?;
+    let num_channels = input.shape[input.shape.len() - 1];
+    if num_groups == 0 || !num_channels.is_multiple_of(num_groups) {
+        return Err(ReduceError::Validation {
+            details: "The number of channels must be a multiple of the number of groups.",
+        });
+    }
+    for tensor in [&tensors.mean, &tensors.rstd].into_iter().flatten() {
+        validate_shape(tensor, &[input.shape[0], num_groups])?;
+    }
+
+    launch_channel_norm(
+        client,
+        input,
+        output,
+        tensors.gamma,
+        tensors.beta,
+        num_groups,
+        true,
+        ChannelStatistics::Batch {
+            mean: tensors.mean,
+            rstd: tensors.rstd,
+            running: None,
+        },
+        epsilon,
+        dtypes,
+    )
+}
+
+/// Normalize every channel of the `input` tensor and write the result into `output`,
+/// that is `(x - mean) * rstd * gamma + beta` with `rstd = 1 / sqrt(var + epsilon)`.
+///
+/// The input is channels-last, like the NHWC tensors of convolutions. In
+/// [training](BatchNormMode::Training) mode, the statistics of each channel are computed over
+/// all other axes, like in [group_norm] with one group per channel and a single batch element.
+/// The running statistics are then updated with the unbiased variance, and the `mean` and
+/// `rstd` of the batch can be written for the backward pass. In
+/// [inference](BatchNormMode::Inference) mode, the running statistics are applied directly.
+///
+/// The running and batch statistics have the `accumulation` type of the `dtypes`.
+///
+/// Returns an error if a tensor has an invalid shape, if `running_mean` and `running_var`
+/// aren't both provided or missing, or if the running statistics are missing or the batch
+/// statistics are requested in inference mode.
+pub fn batch_norm<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    tensors: BatchNormTensors<R>,
+    mode: BatchNormMode,
+    epsilon: f32,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    validate_channel_norm(&input, &output, [&tensors.gamma, &tensors.beta])?;
+    let num_channels = input.shape[input.shape.len() - 1];
+    for tensor in [
+        &tensors.running_mean,
+        &tensors.running_var,
+        &tensors.mean,
+        &tensors.rstd,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        validate_shape(tensor, &[num_channels])?;
+    }
+
+    let running = match (tensors.running_mean, tensors.running_var) {
+        (Some(mean), Some(var)) => Some((mean, var)),
+        (None, None) => None,
+        _ => {
+            return Err(ReduceError::Validation {
+                details: "The running mean and variance must be provided together.",
+            });
+        }
+    };
+    let statistics = match mode {
+        BatchNormMode::Training { momentum } => ChannelStatistics::Batch {
+            mean: tensors.mean,
+            rstd: tensors.rstd,
+            running: running.map(|(mean, var)| (mean, var, momentum)),
+        },
+        BatchNormMode::Inference => {
+            if tensors.mean.is_some() || tensors.rstd.is_some() {
+                return Err(ReduceError::Validation {
+                    details: "The batch statistics are only computed in training mode.",
+                });
+            }
+            let Some((mean, var)) = running else {
+                return Err(ReduceError::Validation {
+                    details: "The running statistics are required in inference mode.",
+                });
+            };
+            ChannelStatistics::Running { mean, var }
+        }
+    };
+
+    launch_channel_norm(
+        client,
+        input,
+        output,
+        tensors.gamma,
+        tensors.beta,
+        num_channels,
+        false,
+        statistics,
+        epsilon,
+        dtypes,
+    )
+}
+
+/// The statistics used to normalize the groups.
+enum ChannelStatistics<'a, R: Runtime> {
+    /// Compute the statistics of the input, optionally writing them and merging them into the
+    /// running statistics with a momentum.
+    Batch {
+        mean: Option<TensorHandleRef<'a, R>>,
+        rstd: Option<TensorHandleRef<'a, R>>,
+        running: Option<(TensorHandleRef<'a, R>, TensorHandleRef<'a, R>, f32)>,
+    },
+    /// Use the given running statistics.
+    Running {
+        mean: TensorHandleRef<'a, R>,
+        var: TensorHandleRef<'a, R>,
+    },
+}
+
+/// The comptime settings of the channel norm kernels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ChannelNormSettings {
+    gamma: bool,
+    beta: bool,
+    /// Whether the applied statistics are a variance instead of its reciprocal square root.
+    variance: bool,
+    running: bool,
+}
+
+fn validate_channel_norm<R: Runtime>(
+    input: &TensorHandleRef<R>,
+    output: &TensorHandleRef<R>,
+    affine: [&Option<TensorHandleRef<R>>; 2],
+) -> Result<(), ReduceError> {
+    if input.shape.len() < 2 {
+        return Err(ReduceError::Validation {
+            details: "Channel normalizations require an input with a batch and a channel axis.",
+        });
+    }
+    validate_shape(output, input.shape)?;
+    for tensor in affine.into_iter().flatten() {
+        validate_shape(tensor, &input.shape[input.shape.len() - 1..])?;
+    }
+    Ok(())
+}
+
+fn validate_shape<R: Runtime>(
+    tensor: &TensorHandleRef<R>,
+    expected_shape: &[usize],
+) -> Result<(), ReduceError> {
+    if tensor.shape != expected_shape {
+        return Err(ReduceError::MismatchShape {
+            expected_shape: expected_shape.to_vec(),
+            output_shape: tensor.shape.to_vec(),
+        });
+    }
+    Ok(())
+}
+
+/// Normalize the groups of `num_groups` channels of every batch element, or of the whole input
+/// when `per_batch` is false.
+#[allow(clippy::too_many_arguments)]
+fn launch_channel_norm<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    gamma: Option<TensorHandleRef<R>>,
+    beta: Option<TensorHandleRef<R>>,
+    num_groups: usize,
+    per_batch: bool,
+    statistics: ChannelStatistics<R>,
+    epsilon: f32,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    if input.shape.contains(&0) {
+        return Ok(());
+    }
+
+    let num_channels = input.shape[input.shape.len() - 1];
+    let num_rows = input.shape.iter().product::<usize>() / num_channels;
+    let num_batches = if per_batch { input.shape[0] } else { 1 };
+    let rows_per_batch = num_rows / num_batches;
+    let num_statistics = num_batches * num_groups;
+
+    // The missing tensors are bound to a placeholder that is never read nor written.
+    let elem_sizes = [dtypes.input, dtypes.accumulation].map(|dtype| dtype.size());
+    let placeholder = client.empty(elem_sizes.into_iter().max().unwrap());
+    let [input_placeholder, acc_placeholder] = elem_sizes.map(|elem_size| unsafe {
+        TensorHandleRef::<R>::from_raw_parts(&placeholder, &[1], &[1], elem_size)
+    });
+
+    // The batch statistics that aren't requested are still needed to apply the normalization.
+    let acc_size = dtypes.accumulation.size();
+    let scratch_len = match &statistics {
+        ChannelStatistics::Batch { .. } => num_statistics,
+        ChannelStatistics::Running { .. } => 1,
+    };
+    let scratch = [(); 2].map(|_| client.empty(scratch_len * acc_size));
+    let scratch_shape = [num_statistics];
+    let [mean_scratch, rstd_scratch] = [0, 1].map(|i| unsafe {
+        TensorHandleRef::<R>::from_raw_parts(&scratch[i], &[1], &scratch_shape, acc_size)
+    });
+
+    let (mean, deviation, variance) = match statistics {
+        ChannelStatistics::Batch {
+            mean,
+            rstd,
+            running,
+        } => {
+            let mean = mean.unwrap_or(mean_scratch);
+            let rstd = rstd.unwrap_or(rstd_scratch);
+            launch_batch_statistics(
+                client,
+                &input,
+                &mean,
+                &rstd,
+                running,
+                &acc_placeholder,
+                num_groups,
+                num_statistics,
+                rows_per_batch,
+                epsilon,
+                dtypes,
+            )?;
+            (mean, rstd, false)
+        }
+        ChannelStatistics::Running { mean, var } => (mean, var, true),
+    };
+
+    let settings = ChannelNormSettings {
+        gamma: gamma.is_some(),
+        beta: beta.is_some(),
+        variance,
+        running: false,
+    };
+    let (cube_count, _) = cube_count_safe(
+        client,
+        (num_rows * num_channels).div_ceil(CUBE_SIZE as usize) as u32,
+    );
+    unsafe {
+        channel_norm_apply_kernel::launch_unchecked::<R>(
+            client,
+            cube_count,
+            CubeDim::new_1d(CUBE_SIZE),
+            input.as_tensor_arg(1),
+            optional_arg(&gamma, &input_placeholder),
+            optional_arg(&beta, &input_placeholder),
+            mean.as_tensor_arg(1),
+            deviation.as_tensor_arg(1),
+            output.as_tensor_arg(1),
+            ScalarArg::new(num_rows as u32),
+            ScalarArg::new(rows_per_batch as u32),
+            ScalarArg::new(num_groups as u32),
+            ScalarArg::new(epsilon),
+            settings,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+/// Write the `mean` and `rstd` of every group, and update the running statistics.
+#[allow(clippy::too_many_arguments)]
+fn launch_batch_statistics<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: &TensorHandleRef<R>,
+    mean: &TensorHandleRef<R>,
+    rstd: &TensorHandleRef<R>,
+    running: Option<(TensorHandleRef<R>, TensorHandleRef<R>, f32)>,
+    acc_placeholder: &TensorHandleRef<R>,
+    num_groups: usize,
+    num_statistics: usize,
+    rows_per_batch: usize,
+    epsilon: f32,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    let num_channels = input.shape[input.shape.len() - 1];
+    // With a single channel per group, like in batch norm, a cube would read a channel at the
+    // stride of the rows, so each unit reduces a channel instead and consecutive units read
+    // consecutive channels.
+    let per_channel = num_groups == num_channels;
+    let num_splits = match per_channel {
+        true => (rows_per_batch as u32).div_ceil(SPLIT_ROWS),
+        false => ((rows_per_batch * num_channels / num_groups) as u32).div_ceil(SPLIT_SIZE),
+    };
+    let num_partials = num_statistics as u32 * num_splits;
+
+    // Each cube writes the Welford state of a chunk of a group.
+    let acc_size = dtypes.accumulation.size();
+    let partials = [(); 3].map(|_| client.empty(num_partials as usize * acc_size));
+    let [counts, means, m2s] = [0, 1, 2].map(|i| unsafe {
+        ArrayArg::from_raw_parts_and_size(&partials[i], num_partials as usize, 1, acc_size)
+    });
+
+    if per_channel {
+        let (cube_count, _) = cube_count_safe(client, num_partials.div_ceil(CUBE_SIZE));
+        unsafe {
+            channel_norm_channel_partials_kernel::launch_unchecked::<R>(
+                client,
+                cube_count,
+                CubeDim::new_1d(CUBE_SIZE),
+                input.as_tensor_arg(1),
+                counts,
+                means,
+                m2s,
+                ScalarArg::new(rows_per_batch as u32),
+                ScalarArg::new(num_splits),
+                ScalarArg::new(num_partials),
+                dtypes.input,
+                dtypes.accumulation,
+            )
+        }
+        .map_err(ReduceError::Launch)?;
+    } else {
+        let (cube_count, _) = cube_count_safe(client, num_partials);
+        unsafe {
+            channel_norm_partials_kernel::launch_unchecked::<R>(
+                client,
+                cube_count,
+                CubeDim::new_1d(CUBE_SIZE),
+                input.as_tensor_arg(1),
+                counts,
+                means,
+                m2s,
+                ScalarArg::new(rows_per_batch as u32),
+                ScalarArg::new(num_groups as u32),
+                ScalarArg::new(num_splits),
+                ScalarArg::new(num_partials),
+                CUBE_SIZE,
+                dtypes.input,
+                dtypes.accumulation,
+            )
+        }
+        .map_err(ReduceError::Launch)?;
+    }
+
+    // The states of the chunks are merged into the statistics of every group.
+    let settings = ChannelNormSettings {
+        gamma: false,
+        beta: false,
+        variance: false,
+        running: running.is_some(),
+    };
+    let momentum = running.as_ref().map_or(0.0, |(_, _, momentum)| *momentum);
+    let [counts, means, m2s] = [0, 1, 2].map(|i| unsafe {
+        ArrayArg::from_raw_parts_and_size(&partials[i], num_partials as usize, 1, acc_size)
+    });
+    let (running_mean, running_var) = match &running {
+        Some((mean, var, _)) => (mean.as_tensor_arg(1), var.as_tensor_arg(1)),
+        None => (
+            acc_placeholder.as_tensor_arg(1),
+            acc_placeholder.as_tensor_arg(1),
+        ),
+    };
+
+    let (cube_count, _) = cube_count_safe(client, (num_statistics as u32).div_ceil(CUBE_SIZE));
+    unsafe {
+        channel_norm_statistics_kernel::launch_unchecked::<R>(
+            client,
+            cube_count,
+            CubeDim::new_1d(CUBE_SIZE),
+            counts,
+            means,
+            m2s,
+            mean.as_tensor_arg(1),
+            rstd.as_tensor_arg(1),
+            running_mean,
+            running_var,
+            ScalarArg::new(num_statistics as u32),
+            ScalarArg::new(num_splits),
+            ScalarArg::new(epsilon),
+            ScalarArg::new(momentum),
+            settings,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+/// Each cube reduces a chunk of the elements of a group into a Welford state.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn channel_norm_partials_kernel<In: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    counts: &mut Array<Acc>,
+    means: &mut Array<Acc>,
+    m2s: &mut Array<Acc>,
+    rows_per_batch: u32,
+    num_groups: u32,
+    num_splits: u32,
+    num_partials: u32,
+    #[comptime] shared_memory_size: u32,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    // The whole cube takes this branch.
+    if CUBE_POS >= num_partials {
+        terminate!();
+    }
+
+    let axis = input.rank() - 1;
+    let group_size = input.shape(axis) / num_groups;
+    let statistic = CUBE_POS / num_splits;
+    let batch = statistic / num_groups;
+    let group = statistic % num_groups;
+
+    let group_len = rows_per_batch * group_size;
+    let start = (CUBE_POS % num_splits) * SPLIT_SIZE;
+    let end = select(
+        start + SPLIT_SIZE < group_len,
+        start + SPLIT_SIZE,
+        group_len,
+    );
+
+    // Consecutive units read consecutive channels of the group.
+    let mut state = WelfordState::<Acc>::null(1u32);
+    let mut k = start + UNIT_POS;
+    while k < end {
+        let row = batch * rows_per_batch + k / group_size;
+        let channel = group * group_size + k % group_size;
+        let value = input[vector_offset::<In>(input, row, axis) + channel * input.stride(axis)];
+        state = Var::reduce_state::<In, Acc>(&state, value, true, false);
+        k += CUBE_DIM;
+    }
+
+    let mut shared = WelfordAccumulator::<Acc>::allocate(shared_memory_size, 1u32, false);
+    WelfordAccumulator::<Acc>::write(&mut shared, UNIT_POS, state);
+    sync_cube();
+
+    let inst = &<Var as ReduceInstruction<(Acc, Acc)>>::from_config(0u32);
+    reduce_tree::<(Acc, Acc), Var>(inst, &mut shared, &mut state, UNIT_POS, shared_memory_size);
+
+    if UNIT_POS == 0 {
+        counts[CUBE_POS] = state.count[0];
+        means[CUBE_POS] = state.mean[0];
+        m2s[CUBE_POS] = state.m2[0];
+    }
+}
+
+/// Each unit reduces a chunk of the rows of a channel into a Welford state, for groups of a
+/// single channel.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn channel_norm_channel_partials_kernel<In: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    counts: &mut Array<Acc>,
+    means: &mut Array<Acc>,
+    m2s: &mut Array<Acc>,
+    rows_per_batch: u32,
+    num_splits: u32,
+    num_partials: u32,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    if ABSOLUTE_POS >= num_partials {
+        terminate!();
+    }
+
+    // Consecutive units read consecutive channels of the same rows.
+    let axis = input.rank() - 1;
+    let num_channels = input.shape(axis);
+    let channel = ABSOLUTE_POS % num_channels;
+    let split = (ABSOLUTE_POS / num_channels) % num_splits;
+    let batch = ABSOLUTE_POS / (num_channels * num_splits);
+
+    let start = split * SPLIT_ROWS;
+    let end = select(
+        start + SPLIT_ROWS < rows_per_batch,
+        start + SPLIT_ROWS,
+        rows_per_batch,
+    );
+
+    let mut state = WelfordState::<Acc>::null(1u32);
+    for k in start..end {
+        let row = batch * rows_per_batch + k;
+        let value = input[vector_offset::<In>(input, row, axis) + channel * input.stride(axis)];
+        state = Var::reduce_state::<In, Acc>(&state, value, true, false);
+    }
+
+    // The partials of a statistic are contiguous, as expected by the statistics kernel.
+    let index = (batch * num_channels + channel) * num_splits + split;
+    counts[index] = state.count[0];
+    means[index] = state.mean[0];
+    m2s[index] = state.m2[0];
+}
+
+/// Each unit merges the Welford states of the chunks of a group.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn channel_norm_statistics_kernel<Acc: Numeric>(
+    counts: &Array<Acc>,
+    means: &Array<Acc>,
+    m2s: &Array<Acc>,
+    mean: &mut Tensor<Line<Acc>>,
+    rstd: &mut Tensor<Line<Acc>>,
+    running_mean: &mut Tensor<Line<Acc>>,
+    running_var: &mut Tensor<Line<Acc>>,
+    num_statistics: u32,
+    num_splits: u32,
+    epsilon: f32,
+    momentum: f32,
+    #[comptime] settings: ChannelNormSettings,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let statistic = ABSOLUTE_POS;
+    if statistic >= num_statistics {
+        terminate!();
+    }
+
+    let mut state = WelfordState::<Acc>::null(1u32);
+    for split in 0..num_splits {
+        let index = statistic * num_splits + split;
+        let partial = WelfordState::<Acc> {
+            count: Line::new(counts[index]),
+            mean: Line::new(means[index]),
+            m2: Line::new(m2s[index]),
+        };
+        state = state.merge(&partial);
+    }
+
+    let mean_value = Line::<f32>::cast_from(state.mean);
+    let count = Line::<f32>::cast_from(state.count);
+    let m2 = Line::<f32>::cast_from(state.m2);
+    let rstd_value = Line::new(1.0f32) / (m2 / count + Line::new(epsilon)).sqrt();
+    mean[element_offset::<Acc>(mean, statistic)] = Line::cast_from(mean_value);
+    rstd[element_offset::<Acc>(rstd, statistic)] = Line::cast_from(rstd_value);
+
+    if comptime!(settings.running) {
+        let momentum = Line::new(momentum);
+        let decay = Line::new(1.0f32) - momentum;
+        let one = Line::new(1.0f32);
+        // Like PyTorch, the running variance is only updated when the unbiased variance is
+        // defined.
+        let has_variance = count.greater_than(one);
+        let unbiased_var = m2 / select_many(has_variance, count - one, one);
+
+        let offset = element_offset::<Acc>(running_mean, statistic);
+        let previous = Line::<f32>::cast_from(running_mean[offset]);
+        running_mean[offset] = Line::cast_from(previous * decay + mean_value * momentum);
+
+        let offset = element_offset::<Acc>(running_var, statistic);
+        let previous = Line::<f32>::cast_from(running_var[offset]);
+        let updated = previous * decay + unbiased_var * momentum;
+        running_var[offset] = Line::cast_from(select_many(has_variance, updated, previous));
+    }
+}
+
+/// Each unit normalizes one element with the statistics of its group.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn channel_norm_apply_kernel<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    gamma: &Tensor<Line<In>>,
+    beta: &Tensor<Line<In>>,
+    mean: &Tensor<Line<Acc>>,
+    deviation: &Tensor<Line<Acc>>,
+    output: &mut Tensor<Line<Out>>,
+    num_rows: u32,
+    rows_per_batch: u32,
+    num_groups: u32,
+    epsilon: f32,
+    #[comptime] settings: ChannelNormSettings,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let axis = input.rank() - 1;
+    let num_channels = input.shape(axis);
+    let row = ABSOLUTE_POS / num_channels;
+    let channel = ABSOLUTE_POS % num_channels;
+    if row >= num_rows {
+        terminate!();
+    }
+
+    let statistic = (row / rows_per_batch) * num_groups + channel / (num_channels / num_groups);
+    let mean_value = Line::<f32>::cast_from(mean[element_offset::<Acc>(mean, statistic)]);
+    let deviation_value =
+        Line::<f32>::cast_from(deviation[element_offset::<Acc>(deviation, statistic)]);
+    let rstd_value = if comptime!(settings.variance) {
+        Line::new(1.0f32) / (deviation_value + Line::new(epsilon)).sqrt()
+    } else {
+        deviation_value
+    };
+
+    let value = input[vector_offset::<In>(input, row, axis) + channel * input.stride(axis)];
+    let mut result = (Line::<f32>::cast_from(value) - mean_value) * rstd_value;
+    if comptime!(settings.gamma) {
+        result *= Line::<f32>::cast_from(gamma[channel * gamma.stride(0)]);
+    }
+    if comptime!(settings.beta) {
+        result += Line::<f32>::cast_from(beta[channel * beta.stride(0)]);
+    }
+    output[vector_offset::<Out>(output, row, axis) + channel * output.stride(axis)] =
+        Line::cast_from(result);
+}
+
+/// The offset of the element at the given row-major `index` of the tensor.
+#[cube]
+fn element_offset<N: Numeric>(tensor: &Tensor<Line<N>>, index: u32) -> u32 {
+    // No axis is skipped, since the axis is out of the tensor.
+    vector_offset::<N>(tensor, index, tensor.rank())
+}
@@ -0,0 +1,528 @@
+use cubecl::features::TypeUsage;
+use cubecl::ir::ElemType;
+use cubecl::prelude::*;
+
+use crate::{
+    Determinism, ReduceError,
+    routines::{cube_count_safe, layer_norm::optional_arg, scan::vector_offset},
+};
+
+/// The number of units of the cubes of every histogram kernel.
+const CUBE_SIZE: u32 = 256;
+/// The number of elements binned by each cube, before the limits on the number of cubes.
+const ELEMENTS_PER_CUBE: u32 = 16 * CUBE_SIZE;
+/// The maximum number of cubes binning the input.
+const MAX_CUBES: u32 = 1024;
+/// The maximum number of bins privatized in the shared memory of each cube.
+/// Above that, the bins are updated directly in global memory.
+const MAX_SHARED_BINS: u32 = 4096;
+/// The maximum number of partial bins written by all cubes of the deterministic kernel.
+const MAX_PARTIAL_BINS: u32 = 1 << 22;
+
+/// How the bins of a [histogram] are defined. The number of bins is the size of the output.
+pub enum HistogramBins<'a, R: Runtime> {
+    /// Bins of equal width covering `[min, max]`.
+    Range { min: f32, max: f32 },
+    /// Bin `i` covers `[edges[i], edges[i + 1])`, except that the last bin also includes its right
+    /// edge. The edges are sorted in increasing order, have the element type of the input and
+    /// one more element than the output.
+    Edges(TensorHandleRef<'a, R>),
+}
+
+/// How an element is mapped to its bin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum BinMapping {
+    Integer,
+    Range,
+    Edges,
+}
+
+/// Count the occurrences of each integer of the `input` tensor into the bins of `output`.
+///
+/// The `output` has a single axis, and bin `i` counts the elements equal to `i`. The elements
+/// outside of `[0, num_bins)` are ignored. When `weights` are provided, with the same shape as
+/// `input` and the element type of `output`, each element adds its weight to its bin instead of 1.
+///
+/// See [histogram] for the details of how the bins are computed.
+///
+/// # Important
+///
+/// Like [shared_sum](crate::shared_sum), this doesn't set the value of output to 0 before counting,
+/// the counts are added to the current values of `output`.
+pub fn bincount<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    weights: Option<TensorHandleRef<R>>,
+    output: TensorHandleRef<R>,
+    determinism: Determinism,
+    input_elem: ElemType,
+    output_elem: ElemType,
+) -> Result<(), ReduceError> {
+    launch_histogram(
+        client,
+        input,
+        weights,
+        None,
+        output,
+        BinMapping::Integer,
+        (0.0, 0.0),
+        determinism,
+        input_elem,
+        output_elem,
+    )
+}
+
+/// Count the elements of the `input` tensor falling into each of the given `bins`.
+///
+/// The `output` has a single axis with one element per bin. The elements outside of the bins,
+/// including NaN, are ignored. When `weights` are provided, with the same shape as `input` and the
+/// element type of `output`, each element adds its weight to its bin instead of 1.
+///
+/// Each cube counts a contiguous chunk of the input into bins privatized in shared memory, which
+/// are then added to `output` with atomics. With [Determinism::Strict], or when atomic addition
+/// isn't supported for the output type, each cube writes its bins to a temporary buffer instead,
+/// and the buffers are summed in a fixed order. The counts are then bitwise reproducible, even
+/// with float weights. Float bins privatized in shared memory are always counted that way, since
+/// float atomics aren't guaranteed in shared memory.
+///
+/// # Important
+///
+/// Like [shared_sum](crate::shared_sum), this doesn't set the value of output to 0 before counting,
+/// the counts are added to the current values of `output`.
+#[allow(clippy::too_many_arguments)]
+pub fn histogram<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    weights: Option<TensorHandleRef<R>>,
+    output: TensorHandleRef<R>,
+    bins: HistogramBins<R>,
+    determinism: Determinism,
+    input_elem: ElemType,
+    output_elem: ElemType,
+) -> Result<(), ReduceError> {
+    match bins {
+        HistogramBins::Range { min, max } => {
+            if !(min.is_finite() && max.is_finite() && min < max) {
+                return Err(ReduceError::Validation {
+                    details: "The range of a histogram must be finite and non-empty.",
+                });
+            }
+            launch_histogram(
+                client,
+                input,
+                weights,
+                None,
+                output,
+                BinMapping::Range,
+                (min, max),
+                determinism,
+                input_elem,
+                output_elem,
+            )
+        }
+        HistogramBins::Edges(edges) => {
+            if edges.shape.len() != 1
+                || output.shape.len() != 1
+                || edges.shape[0] != output.shape[0] + 1
+            {
+                return Err(ReduceError::Validation {
+                    details: "The edges of a histogram must have one more element than the output.",
+                });
+            }
+            launch_histogram(
+                client,
+                input,
+                weights,
+                Some(edges),
+                output,
+                BinMapping::Edges,
+                (0.0, 0.0),
+                determinism,
+                input_elem,
+                output_elem,
+            )
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn launch_histogram<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    weights: Option<TensorHandleRef<R>>,
+    edges: Option<TensorHandleRef<R>>,
+    output: TensorHandleRef<R>,
+    mapping: BinMapping,
+    (min, max): (f32, f32),
+    determinism: Determinism,
+    input_elem: ElemType,
+    output_elem: ElemType,
+) -> Result<(), ReduceError> {
+    if output.shape.len() != 1 || output.shape[0] == 0 {
+        return Err(ReduceError::Validation {
+            details: "The output of a histogram must have a single non-empty axis.",
+        });
+    }
+    if let Some(weights) = &weights
+        && weights.shape != input.shape
+    {
+        return Err(ReduceError::Validation {
+            details: "The weights must have the same shape as the input.",
+        });
+    }
+
+    let num_elements = input.shape.iter().product::<usize>() as u32;
+    let num_bins = output.shape[0] as u32;
+    if num_elements == 0 {
+        return Ok(());
+    }
+
+    // The missing tensors are bound to a placeholder that is never read.
+    let placeholder = client.empty(input.elem_size.max(output.elem_size));
+    let input_placeholder =
+        unsafe { TensorHandleRef::<R>::from_raw_parts(&placeholder, &[1], &[1], input.elem_size) };
+    let output_placeholder =
+        unsafe { TensorHandleRef::<R>::from_raw_parts(&placeholder, &[1], &[1], output.elem_size) };
+
+    let shared_bins = num_bins <= MAX_SHARED_BINS;
+    let use_atomics = determinism == Determinism::Relaxed
+        && client
+            .properties()
+            .type_usage(StorageType::Atomic(output_elem))
+            .contains(TypeUsage::AtomicAdd)
+        && !(shared_bins && matches!(output_elem, ElemType::Float(_)));
+    let weighted = weights.is_some();
+    let scale = match mapping {
+        BinMapping::Range => num_bins as f32 / (max - min),
+        _ => 0.0,
+    };
+
+    let mut num_cubes = num_elements.div_ceil(ELEMENTS_PER_CUBE).min(MAX_CUBES);
+    if !use_atomics {
+        num_cubes = num_cubes.min((MAX_PARTIAL_BINS / num_bins).max(1));
+    }
+    let chunk_size = num_elements.div_ceil(num_cubes);
+    let cube_dim = CubeDim::new_1d(CUBE_SIZE);
+    let num_shared_bins = match shared_bins {
+        true => num_bins,
+        false => 0,
+    };
+
+    if use_atomics {
+        return unsafe {
+            histogram_atomic_kernel::launch_unchecked::<R>(
+                client,
+                CubeCount::new_1d(num_cubes),
+                cube_dim,
+                input.as_tensor_arg(1),
+                optional_arg(&weights, &output_placeholder),
+                optional_arg(&edges, &input_placeholder),
+                output.as_tensor_arg(1),
+                ScalarArg::new(min),
+                ScalarArg::new(max),
+                ScalarArg::new(scale),
+                ScalarArg::new(num_elements),
+                ScalarArg::new(chunk_size),
+                mapping,
+                weighted,
+                num_shared_bins,
+                input_elem,
+                output_elem,
+            )
+        }
+        .map_err(ReduceError::Launch);
+    }
+
+    let num_partials = (num_cubes * num_bins) as usize;
+    let partials = client.empty(num_partials * output.elem_size);
+
+    unsafe {
+        histogram_partials_kernel::launch_unchecked::<R>(
+            client,
+            CubeCount::new_1d(num_cubes),
+            cube_dim,
+            input.as_tensor_arg(1),
+            optional_arg(&weights, &output_placeholder),
+            optional_arg(&edges, &input_placeholder),
+            ArrayArg::from_raw_parts_and_size(&partials, num_partials, 1, output.elem_size),
+            ScalarArg::new(num_bins),
+            ScalarArg::new(min),
+            ScalarArg::new(max),
+            ScalarArg::new(scale),
+            ScalarArg::new(num_elements),
+            ScalarArg::new(chunk_size),
+            mapping,
+            weighted,
+            CUBE_SIZE,
+            num_shared_bins,
+            input_elem,
+            output_elem,
+        )
+    }
+    .map_err(ReduceError::Launch)?;
+
+    let (cube_count, _) = cube_count_safe(client, num_bins.div_ceil(CUBE_SIZE));
+    unsafe {
+        histogram_merge_kernel::launch_unchecked::<R>(
+            client,
+            cube_count,
+            cube_dim,
+            ArrayArg::from_raw_parts_and_size(&partials, num_partials, 1, output.elem_size),
+            output.as_tensor_arg(1),
+            ScalarArg::new(num_cubes),
+            output_elem,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+/// Count the chunk of each cube into bins in shared memory, then add them to the output.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn histogram_atomic_kernel<N: Numeric, W: Numeric>(
+    input: &Tensor<Line<N>>,
+    weights: &Tensor<Line<W>>,
+    edges: &Tensor<Line<N>>,
+    output: &mut Tensor<Atomic<W>>,
+    min: f32,
+    max: f32,
+    scale: f32,
+    num_elements: u32,
+    chunk_size: u32,
+    #[comptime] mapping: BinMapping,
+    #[comptime] weighted: bool,
+    #[comptime] num_shared_bins: u32,
+    #[define(N)] _input_dtype: ElemType,
+    #[define(W)] _output_dtype: ElemType,
+) {
+    let num_bins = output.shape(0);
+    let start = CUBE_POS * chunk_size;
+    let end = select(
+        start + chunk_size < num_elements,
+        start + chunk_size,
+        num_elements,
+    );
+
+    if comptime!(num_shared_bins > 0) {
+        let bins = SharedMemory::<Atomic<W>>::new(num_shared_bins);
+        let mut k = UNIT_POS;
+        while k < num_shared_bins {
+            Atomic::store(&bins[k], W::from_int(0));
+            k += CUBE_DIM;
+        }
+        sync_cube();
+
+        let mut index = start + UNIT_POS;
+        while index < end {
+            let bin = bin_index(input, edges, index, min, max, scale, num_bins, mapping);
+            if bin != u32::MAX {
+                Atomic::add(&bins[bin], element_weight(weights, index, weighted));
+            }
+            index += CUBE_DIM;
+        }
+        sync_cube();
+
+        // Only the bins reached by the chunk are added to the output.
+        let mut k = UNIT_POS;
+        while k < num_shared_bins {
+            let count = Atomic::load(&bins[k]);
+            if count != W::from_int(0) {
+                Atomic::add(&output[k * output.stride(0)], count);
+            }
+            k += CUBE_DIM;
+        }
+    } else {
+        let mut index = start + UNIT_POS;
+        while index < end {
+            let bin = bin_index(input, edges, index, min, max, scale, num_bins, mapping);
+            if bin != u32::MAX {
+                Atomic::add(
+                    &output[bin * output.stride(0)],
+                    element_weight(weights, index, weighted),
+                );
+            }
+            index += CUBE_DIM;
+        }
+    }
+}
+
+/// Count the chunk of each cube into its own row of partial bins, without atomics.
+///
+/// The elements are binned a tile at a time, then every unit goes through the whole tile and
+/// only updates the bins it owns, so each bin is updated in the order of the elements. When
+/// there are `num_shared_bins`, the bins are privatized in shared memory and the row is written
+/// once at the end.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn histogram_partials_kernel<N: Numeric, W: Numeric>(
+    input: &Tensor<Line<N>>,
+    weights: &Tensor<Line<W>>,
+    edges: &Tensor<Line<N>>,
+    partials: &mut Array<W>,
+    num_bins: u32,
+    min: f32,
+    max: f32,
+    scale: f32,
+    num_elements: u32,
+    chunk_size: u32,
+    #[comptime] mapping: BinMapping,
+    #[comptime] weighted: bool,
+    #[comptime] tile_size: u32,
+    #[comptime] num_shared_bins: u32,
+    #[define(N)] _input_dtype: ElemType,
+    #[define(W)] _output_dtype: ElemType,
+) {
+    let row = CUBE_POS * num_bins;
+    let shared = comptime!(num_shared_bins > 0);
+    let start = CUBE_POS * chunk_size;
+    let end = select(
+        start + chunk_size < num_elements,
+        start + chunk_size,
+        num_elements,
+    );
+
+    // A unit owns the bins equal to its position modulo the cube size.
+    let mut bins = SharedMemory::<W>::new(comptime!(num_shared_bins.max(1)));
+    let mut k = UNIT_POS;
+    while k < num_bins {
+        if comptime!(shared) {
+            bins[k] = W::from_int(0);
+        } else {
+            partials[row + k] = W::from_int(0);
+        }
+        k += CUBE_DIM;
+    }
+
+    let mut tile_bins = SharedMemory::<u32>::new(tile_size);
+    let mut tile_weights = SharedMemory::<W>::new(tile_size);
+
+    let mut tile = start;
+    while tile < end {
+        let index = tile + UNIT_POS;
+        let mut bin = u32::MAX;
+        let mut weight = W::from_int(0);
+        if index < end {
+            bin = bin_index(input, edges, index, min, max, scale, num_bins, mapping);
+            weight = element_weight(weights, index, weighted);
+        }
+        tile_bins[UNIT_POS] = bin;
+        tile_weights[UNIT_POS] = weight;
+        sync_cube();
+
+        for j in 0..tile_size {
+            let bin = tile_bins[j];
+            if bin != u32::MAX && bin % CUBE_DIM == UNIT_POS {
+                if comptime!(shared) {
+                    bins[bin] += tile_weights[j];
+                } else {
+                    partials[row + bin] += tile_weights[j];
+                }
+            }
+        }
+        sync_cube();
+
+        tile += tile_size;
+    }
+
+    if comptime!(shared) {
+        let mut k = UNIT_POS;
+        while k < num_bins {
+            partials[row + k] = bins[k];
+            k += CUBE_DIM;
+        }
+    }
+}
+
+/// Add the partial bins of all cubes to the output, in the order of the cubes.
+#[cube(launch_unchecked)]
+fn histogram_merge_kernel<W: Numeric>(
+    partials: &Array<W>,
+    output: &mut Tensor<W>,
+    num_cubes: u32,
+    #[define(W)] _dtype: ElemType,
+) {
+    let num_bins = output.shape(0);
+    let bin = ABSOLUTE_POS;
+    if bin >= num_bins {
+        terminate!();
+    }
+
+    let mut count = W::from_int(0);
+    for cube in 0..num_cubes {
+        count += partials[cube * num_bins + bin];
+    }
+    output[bin * output.stride(0)] += count;
+}
+
+/// The bin of the element at `index`, or `u32::MAX` if the element is outside of the bins.
+#[allow(clippy::too_many_arguments)]
+#[cube]
+fn bin_index<N: Numeric>(
+    input: &Tensor<Line<N>>,
+    edges: &Tensor<Line<N>>,
+    index: u32,
+    min: f32,
+    max: f32,
+    scale: f32,
+    num_bins: u32,
+    #[comptime] mapping: BinMapping,
+) -> u32 {
+    // Every dimension is decomposed, so this is the offset of the element itself.
+    let value = input[vector_offset::<N>(input, index, input.rank())][0];
+    let mut bin = u32::MAX;
+
+    match comptime!(mapping) {
+        BinMapping::Integer => {
+            if value >= N::from_int(0) {
+                let candidate = u32::cast_from(value);
+                if candidate < num_bins {
+                    bin = candidate;
+                }
+            }
+        }
+        BinMapping::Range => {
+            // NaN fails both comparisons.
+            let value = f32::cast_from(value);
+            if value >= min && value <= max {
+                let candidate = u32::cast_from((value - min) * scale);
+                bin = select(candidate < num_bins, candidate, num_bins - 1);
+            }
+        }
+        BinMapping::Edges => {
+            let value = f32::cast_from(value);
+            let stride = edges.stride(0);
+            let first = f32::cast_from(edges[0][0]);
+            let last = f32::cast_from(edges[num_bins * stride][0]);
+            if value >= first && value <= last {
+                // The last edge below or equal to the value, the right edge of the last bin
+                // being included in that bin.
+                let mut low = 0u32;
+                let mut high = num_bins;
+                while high - low > 1 {
+                    let mid = (low + high) / 2;
+                    if f32::cast_from(edges[mid * stride][0]) <= value {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                }
+                bin = low;
+            }
+        }
+    }
+
+    bin
+}
+
+#[cube]
+fn element_weight<W: Numeric>(
+    weights: &Tensor<Line<W>>,
+    index: u32,
+    #[comptime] weighted: bool,
+) -> W {
+    if comptime!(weighted) {
+        weights[vector_offset::<W>(weights, index, weights.rank())][0]
+    } else {
+        W::from_int(1)
+    }
+}
@@ -0,0 +1,473 @@
+use cubecl::prelude::*;
+
+use crate::{
+    LineMode, ReduceDtypes, ReduceError,
+    components::{
+        global::cube::reduce_tree,
+        instructions::{
+            ReduceInstruction, SharedAccumulator, Var, WelfordAccumulator, WelfordState,
+        },
+    },
+    launch::{RoutineStrategy, prepare_routine},
+    routines::{
+        GlobalReduceBlueprint, ReduceBlueprint, ReduceLaunchSettings, ReduceLineSettings,
+        ReduceProblem, scan::vector_offset,
+    },
+    valid_output_shape,
+};
+
+/// The optional tensors of [layer_norm] and [rms_norm].
+pub struct NormTensors<'a, R: Runtime> {
+    /// Added to the input before normalizing, with the same shape as the input.
+    pub residual: Option<TensorHandleRef<'a, R>>,
+    /// Where the sum of the input and the `residual` is written, with the same shape as the input.
+    pub residual_output: Option<TensorHandleRef<'a, R>>,
+    /// The scale of the normalized values, with the shape of the normalized axis.
+    pub gamma: Option<TensorHandleRef<'a, R>>,
+    /// The offset of the normalized values, with the shape of the normalized axis.
+    pub beta: Option<TensorHandleRef<'a, R>>,
+    /// Where the mean of each row is written, with the shape of the input except for a
+    /// normalized axis of 1. Only valid for [layer_norm].
+    pub mean: Option<TensorHandleRef<'a, R>>,
+    /// Where the reciprocal of the standard deviation of each row is written, with the same
+    /// shape as `mean`.
+    pub rstd: Option<TensorHandleRef<'a, R>>,
+}
+
+impl<R: Runtime> Default for NormTensors<'_, R> {
+    fn default() -> Self {
+        Self {
+            residual: None,
+            residual_output: None,
+            gamma: None,
+            beta: None,
+            mean: None,
+            rstd: None,
+        }
+    }
+}
+
+/// Normalize the last axis of the `input` tensor to a zero mean and a unit variance and write
+/// the result into `output`, that is `(x - mean) * rstd * gamma + beta` with
+/// `rstd = 1 / sqrt(var + epsilon)`.
+///
+/// The `output` tensor must have the same shape as `input`. When a residual is provided, `x` is
+/// the sum of the input and the residual, which can also be written back. The `mean` and `rstd`
+/// of each row can be written for the backward pass.
+///
+/// Each row is normalized by a unit, a plane or a cube depending on the [RoutineStrategy],
+/// in a single kernel. A first pass accumulates the statistics with the Welford algorithm,
+/// as in [Var], and a second pass writes the normalized values. The statistics are computed
+/// with the `accumulation` type of the `dtypes`, which is also the type of `mean` and `rstd`.
+///
+/// Returns an error if a tensor has an invalid shape, if `residual_output` is provided without
+/// `residual`, or if the plane routine is requested but planes are unavailable or of variable size.
+#[allow(clippy::too_many_arguments)]
+pub fn layer_norm<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    tensors: NormTensors<R>,
+    epsilon: f32,
+    strategy: RoutineStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    launch_norm(
+        client, input, output, tensors, epsilon, strategy, false, dtypes,
+    )
+}
+
+/// Normalize the last axis of the `input` tensor by its root mean square and write the result
+/// into `output`, that is `x * rstd * gamma + beta` with `rstd = 1 / sqrt(mean(x^2) + epsilon)`.
+///
+/// This behaves like [layer_norm], except that the mean isn't subtracted, so
+/// [NormTensors::mean] must be `None`.
+#[allow(clippy::too_many_arguments)]
+pub fn rms_norm<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    tensors: NormTensors<R>,
+    epsilon: f32,
+    strategy: RoutineStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    if tensors.mean.is_some() {
+        return Err(ReduceError::Validation {
+            details: "RMSNorm doesn't compute a mean.",
+        });
+    }
+
+    launch_norm(
+        client, input, output, tensors, epsilon, strategy, true, dtypes,
+    )
+}
+
+/// The comptime settings of the norm kernel, with a flag for every optional tensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct NormSettings {
+    rms: bool,
+    residual: bool,
+    residual_output: bool,
+    gamma: bool,
+    beta: bool,
+    mean: bool,
+    rstd: bool,
+    /// The number of elements of the shared memory of a cube.
+    shared_memory_size: u32,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn launch_norm<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    tensors: NormTensors<R>,
+    epsilon: f32,
+    strategy: RoutineStrategy,
+    rms: bool,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    if input.shape.is_empty() {
+        return Err(ReduceError::Validation {
+            details: "Normalizing requires an input with at least one axis.",
+        });
+    }
+    let axis = input.shape.len() - 1;
+    let row_len = input.shape[axis];
+
+    for tensor in [
+        Some(&output),
+        tensors.residual.as_ref(),
+        tensors.residual_output.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if tensor.shape != input.shape {
+            return Err(ReduceError::MismatchShape {
+                expected_shape: input.shape.to_vec(),
+                output_shape: tensor.shape.to_vec(),
+            });
+        }
+    }
+    if tensors.residual_output.is_some() && tensors.residual.is_none() {
+        return Err(ReduceError::Validation {
+            details: "The residual output requires a residual input.",
+        });
+    }
+    for tensor in [tensors.gamma.as_ref(), tensors.beta.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        if tensor.shape != [row_len] {
+            return Err(ReduceError::MismatchShape {
+                expected_shape: vec![row_len],
+                output_shape: tensor.shape.to_vec(),
+            });
+        }
+    }
+    for tensor in [tensors.mean.as_ref(), tensors.rstd.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        valid_output_shape(input.shape, tensor.shape, &[axis])?;
+    }
+
+    let (blueprint, launch, num_rows) = prepare_rows(client, &input, axis, strategy, dtypes)?;
+
+    let settings = NormSettings {
+        rms,
+        residual: tensors.residual.is_some(),
+        residual_output: tensors.residual_output.is_some(),
+        gamma: tensors.gamma.is_some(),
+        beta: tensors.beta.is_some(),
+        mean: tensors.mean.is_some(),
+        rstd: tensors.rstd.is_some(),
+        shared_memory_size: launch.cube_dim.num_elems(),
+    };
+
+    // The missing tensors are bound to a placeholder that is never read nor written.
+    let elem_sizes = [dtypes.input, dtypes.output, dtypes.accumulation].map(|dtype| dtype.size());
+    let placeholder = client.empty(elem_sizes.into_iter().max().unwrap());
+    let [input_placeholder, output_placeholder, acc_placeholder] =
+        elem_sizes.map(|elem_size| unsafe {
+            TensorHandleRef::<R>::from_raw_parts(&placeholder, &[1], &[1], elem_size)
+        });
+
+    unsafe {
+        norm_kernel::launch_unchecked::<R>(
+            client,
+            launch.cube_count,
+            launch.cube_dim,
+            input.as_tensor_arg(1),
+            optional_arg(&tensors.residual, &input_placeholder),
+            optional_arg(&tensors.gamma, &input_placeholder),
+            optional_arg(&tensors.beta, &input_placeholder),
+            output.as_tensor_arg(1),
+            optional_arg(&tensors.residual_output, &output_placeholder),
+            optional_arg(&tensors.mean, &acc_placeholder),
+            optional_arg(&tensors.rstd, &acc_placeholder),
+            ScalarArg::new(num_rows),
+            ScalarArg::new(epsilon),
+            blueprint.global,
+            settings,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+/// Select how the rows along the given `axis` of `input` are shared by units, planes or cubes,
+/// returning the number of rows with the blueprint and launch settings of the routine.
+pub(crate) fn prepare_rows<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: &TensorHandleRef<R>,
+    axis: usize,
+    strategy: RoutineStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(ReduceBlueprint, ReduceLaunchSettings, u32), ReduceError> {
+    let row_len = input.shape[axis];
+
+    let hardware = &client.properties().hardware;
+    if matches!(strategy, RoutineStrategy::Plane(_))
+        && hardware.plane_size_min != hardware.plane_size_max
+    {
+        return Err(ReduceError::ImprecisePlaneDim);
+    }
+
+    let num_rows = (input.shape.iter().product::<usize>() / row_len) as u32;
+    let problem = ReduceProblem {
+        vector_size: row_len as u32,
+        vector_count: num_rows,
+        axis: axis as u32,
+        dtypes,
+    };
+    let line_mode = match input.strides[axis] {
+        1 => LineMode::Parallel,
+        _ => LineMode::Perpendicular,
+    };
+    let line_settings = ReduceLineSettings {
+        line_mode,
+        line_size_input: 1,
+        line_size_output: 1,
+    };
+    let (blueprint, launch) = prepare_routine(client, problem, line_settings, strategy)?;
+
+    Ok((blueprint, launch, num_rows))
+}
+
+/// Bind a missing optional tensor to the placeholder.
+pub(crate) fn optional_arg<'a, R: Runtime>(
+    tensor: &'a Option<TensorHandleRef<'a, R>>,
+    placeholder: &'a TensorHandleRef<'a, R>,
+) -> TensorArg<'a, R> {
+    tensor.as_ref().unwrap_or(placeholder).as_tensor_arg(1)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn norm_kernel<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    residual: &Tensor<Line<In>>,
+    gamma: &Tensor<Line<In>>,
+    beta: &Tensor<Line<In>>,
+    output: &mut Tensor<Line<Out>>,
+    residual_output: &mut Tensor<Line<Out>>,
+    mean: &mut Tensor<Line<Acc>>,
+    rstd: &mut Tensor<Line<Acc>>,
+    num_rows: u32,
+    epsilon: f32,
+    #[comptime] blueprint: GlobalReduceBlueprint,
+    #[comptime] settings: NormSettings,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let axis = input.rank() - 1;
+    let worker = RowWorker::new(blueprint);
+    let row = worker.row;
+    let length = worker.length(num_rows, input.shape(axis));
+
+    let input_offset = vector_offset::<In>(input, row, axis);
+    let output_offset = vector_offset::<Out>(output, row, axis);
+    let mut residual_offset = 0u32;
+    if comptime!(settings.residual) {
+        residual_offset = vector_offset::<In>(residual, row, axis);
+    }
+    let mut residual_output_offset = 0u32;
+    if comptime!(settings.residual_output) {
+        residual_output_offset = vector_offset::<Out>(residual_output, row, axis);
+    }
+
+    let mut state = WelfordState::<Acc>::null(1u32);
+    let mut k = worker.first;
+    while k < length {
+        let value = read_row::<In, Acc>(
+            input,
+            residual,
+            input_offset,
+            residual_offset,
+            axis,
+            k,
+            settings.residual,
+        );
+        let value = round_residual::<Out, Acc>(value, settings.residual_output);
+        if comptime!(settings.residual_output) {
+            residual_output[residual_output_offset + k * residual_output.stride(axis)] =
+                Line::cast_from(value);
+        }
+        state = Var::reduce_state::<Acc, Acc>(&state, value, true, false);
+        k += worker.step;
+    }
+
+    match comptime!(blueprint) {
+        GlobalReduceBlueprint::Unit(_) => {}
+        GlobalReduceBlueprint::Plane(_) => {
+            state = state.merge_plane();
+        }
+        GlobalReduceBlueprint::Cube(_) => {
+            let mut shared =
+                WelfordAccumulator::<Acc>::allocate(settings.shared_memory_size, 1u32, false);
+            WelfordAccumulator::<Acc>::write(&mut shared, UNIT_POS, state);
+            sync_cube();
+
+            // The tree leaves the fused state in every unit.
+            let inst = &<Var as ReduceInstruction<(Acc, Acc)>>::from_config(0u32);
+            reduce_tree::<(Acc, Acc), Var>(
+                inst,
+                &mut shared,
+                &mut state,
+                UNIT_POS,
+                settings.shared_memory_size,
+            );
+        }
+    };
+
+    let mean_value = Line::<f32>::cast_from(state.mean);
+    let variance = Line::<f32>::cast_from(state.m2) / Line::<f32>::cast_from(state.count);
+    let second_moment = if comptime!(settings.rms) {
+        variance + mean_value * mean_value
+    } else {
+        variance
+    };
+    let rstd_value = Line::new(1.0f32) / (second_moment + Line::new(epsilon)).sqrt();
+    let center = if comptime!(settings.rms) {
+        Line::new(0.0f32)
+    } else {
+        mean_value
+    };
+
+    if row < num_rows && worker.leader {
+        if comptime!(settings.mean) {
+            mean[vector_offset::<Acc>(mean, row, axis)] = Line::cast_from(mean_value);
+        }
+        if comptime!(settings.rstd) {
+            rstd[vector_offset::<Acc>(rstd, row, axis)] = Line::cast_from(rstd_value);
+        }
+    }
+
+    let mut k = worker.first;
+    while k < length {
+        let value = read_row::<In, Acc>(
+            input,
+            residual,
+            input_offset,
+            residual_offset,
+            axis,
+            k,
+            settings.residual,
+        );
+        let value = round_residual::<Out, Acc>(value, settings.residual_output);
+        let mut result = (Line::<f32>::cast_from(value) - center) * rstd_value;
+        if comptime!(settings.gamma) {
+            result *= Line::<f32>::cast_from(gamma[k * gamma.stride(0)]);
+        }
+        if comptime!(settings.beta) {
+            result += Line::<f32>::cast_from(beta[k * beta.stride(0)]);
+        }
+        output[output_offset + k * output.stride(axis)] = Line::cast_from(result);
+        k += worker.step;
+    }
+}
+
+/// The row of the current unit, shared with the other units of its plane or cube like a vector
+/// is shared by the reduce routines.
+#[derive(CubeType)]
+pub(crate) struct RowWorker {
+    pub row: u32,
+    /// The first element of the row read by the current unit.
+    pub first: u32,
+    /// The distance between the elements read by the current unit.
+    pub step: u32,
+    /// Whether the current unit writes the statistics of the row.
+    pub leader: bool,
+}
+
+#[cube]
+impl RowWorker {
+    pub fn new(#[comptime] blueprint: GlobalReduceBlueprint) -> RowWorker {
+        match comptime!(blueprint) {
+            GlobalReduceBlueprint::Unit(_) => RowWorker {
+                row: ABSOLUTE_POS,
+                first: 0u32,
+                step: 1u32,
+                leader: true,
+            },
+            GlobalReduceBlueprint::Plane(_) => RowWorker {
+                row: CUBE_POS * CUBE_DIM_Y + UNIT_POS_Y,
+                first: UNIT_POS_X,
+                step: CUBE_DIM_X,
+                leader: UNIT_POS_X == 0,
+            },
+            GlobalReduceBlueprint::Cube(_) => RowWorker {
+                row: CUBE_POS,
+                first: UNIT_POS,
+                step: CUBE_DIM,
+                leader: UNIT_POS == 0,
+            },
+        }
+    }
+
+    /// The number of elements to read in the row.
+    ///
+    /// Idle units still take part in the plane instructions and synchronizations, but without
+    /// any element.
+    pub fn length(&self, num_rows: u32, row_len: u32) -> u32 {
+        select(self.row < num_rows, row_len, 0u32)
+    }
+}
+
+/// Round the sum of the input and the residual to the output type when it is stored, so the row
+/// is normalized like the residual output the next layer will read.
+#[cube]
+fn round_residual<Out: Numeric, Acc: Numeric>(
+    value: Line<Acc>,
+    #[comptime] residual_output: bool,
+) -> Line<Acc> {
+    if comptime!(residual_output) {
+        Line::cast_from(Line::<Out>::cast_from(value))
+    } else {
+        value
+    }
+}
+
+/// Read the element `k` of the row, adding the residual if there is one.
+#[cube]
+pub(crate) fn read_row<In: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    residual: &Tensor<Line<In>>,
+    input_offset: u32,
+    residual_offset: u32,
+    axis: u32,
+    k: u32,
+    #[comptime] has_residual: bool,
+) -> Line<Acc> {
+    let mut value = Line::<Acc>::cast_from(input[input_offset + k * input.stride(axis)]);
+    if comptime!(has_residual) {
+        value += Line::cast_from(residual[residual_offset + k * residual.stride(axis)]);
+    }
+    value
+}
@@ -0,0 +1,488 @@
+use cubecl::prelude::*;
+
+use crate::{
+    ReduceDtypes, ReduceError,
+    components::{
+        global::cube::reduce_tree,
+        instructions::{ReduceOperationConfig, Sum},
+    },
+    launch::{LineSizeStrategy, ReduceStrategy, RoutineStrategy, launch_reduce},
+    routines::{
+        BlueprintStrategy, GlobalReduceBlueprint, cube_count_safe,
+        layer_norm::{RowWorker, optional_arg, prepare_rows},
+        scan::vector_offset,
+        unit::UnitStrategy,
+    },
+    valid_output_shape,
+};
+
+/// The number of rows reduced by a cube into partial gradients of gamma and beta.
+const ROWS_PER_CHUNK: u32 = 32;
+/// The number of columns handled by a cube computing partial gradients.
+const COLUMNS_PER_CUBE: u32 = 64;
+
+/// The optional tensors of [layer_norm_backward] and [rms_norm_backward].
+pub struct NormBackwardTensors<'a, R: Runtime> {
+    /// The scale of the forward pass, with the shape of the normalized axis.
+    pub gamma: Option<TensorHandleRef<'a, R>>,
+    /// Where the gradient of gamma is written, with the shape of the normalized axis.
+    pub grad_gamma: Option<TensorHandleRef<'a, R>>,
+    /// Where the gradient of beta is written, with the shape of the normalized axis.
+    pub grad_beta: Option<TensorHandleRef<'a, R>>,
+}
+
+impl<R: Runtime> Default for NormBackwardTensors<'_, R> {
+    fn default() -> Self {
+        Self {
+            gamma: None,
+            grad_gamma: None,
+            grad_beta: None,
+        }
+    }
+}
+
+/// Compute the gradients of [layer_norm](super::layer_norm::layer_norm) from the gradient of
+/// its output `grad_output`, the normalized `input` and the `mean` and `rstd` saved by the
+/// forward pass. When the forward pass added a residual, `input` is the sum written back.
+///
+/// The gradient of the input is written into `grad_input`, with the same shape as `input`.
+/// With `xhat = (x - mean) * rstd` and `g = grad_output * gamma`, it is
+/// `rstd * (g - mean(g) - xhat * mean(g * xhat))` where the means are taken over each row.
+/// Those per-row terms are reduced by a unit, a plane or a cube depending on the [RoutineStrategy],
+/// in a single kernel.
+///
+/// The gradients of gamma and beta are the sums over all rows of `grad_output * xhat` and
+/// `grad_output`. Each cube first reduces a chunk of rows for consecutive columns into partial
+/// sums, which are then reduced along the rows in perpendicular mode with the reduce routines.
+///
+/// The types of `mean`, `rstd` and the partial sums are the `accumulation` type of the `dtypes`,
+/// while the gradients have the `output` type.
+#[allow(clippy::too_many_arguments)]
+pub fn layer_norm_backward<R: Runtime>(
+    client: &ComputeClient<R>,
+    grad_output: TensorHandleRef<R>,
+    input: TensorHandleRef<R>,
+    mean: TensorHandleRef<R>,
+    rstd: TensorHandleRef<R>,
+    grad_input: TensorHandleRef<R>,
+    tensors: NormBackwardTensors<R>,
+    strategy: RoutineStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    launch_norm_backward(
+        client,
+        grad_output,
+        input,
+        Some(mean),
+        rstd,
+        grad_input,
+        tensors,
+        strategy,
+        dtypes,
+    )
+}
+
+/// Compute the gradients of [rms_norm](super::layer_norm::rms_norm) from the gradient of its
+/// output `grad_output`, the normalized `input` and the `rstd` saved by the forward pass.
+///
+/// This behaves like [layer_norm_backward] without a mean, so the gradient of the input is
+/// `rstd * (g - xhat * mean(g * xhat))` with `xhat = x * rstd`.
+#[allow(clippy::too_many_arguments)]
+pub fn rms_norm_backward<R: Runtime>(
+    client: &ComputeClient<R>,
+    grad_output: TensorHandleRef<R>,
+    input: TensorHandleRef<R>,
+    rstd: TensorHandleRef<R>,
+    grad_input: TensorHandleRef<R>,
+    tensors: NormBackwardTensors<R>,
+    strategy: RoutineStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    launch_norm_backward(
+        client,
+        grad_output,
+        input,
+        None,
+        rstd,
+        grad_input,
+        tensors,
+        strategy,
+        dtypes,
+    )
+}
+
+/// The comptime settings of the backward kernels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct NormBackwardSettings {
+    rms: bool,
+    gamma: bool,
+    grad_gamma: bool,
+    grad_beta: bool,
+    /// The number of elements of the shared memory of a cube.
+    shared_memory_size: u32,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn launch_norm_backward<R: Runtime>(
+    client: &ComputeClient<R>,
+    grad_output: TensorHandleRef<R>,
+    input: TensorHandleRef<R>,
+    mean: Option<TensorHandleRef<R>>,
+    rstd: TensorHandleRef<R>,
+    grad_input: TensorHandleRef<R>,
+    tensors: NormBackwardTensors<R>,
+    strategy: RoutineStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    if input.shape.is_empty() {
+        return Err(ReduceError::Validation {
+            details: "Normalizing requires an input with at least one axis.",
+        });
+    }
+    let axis = input.shape.len() - 1;
+    let row_len = input.shape[axis];
+
+    for tensor in [&grad_output, &grad_input] {
+        if tensor.shape != input.shape {
+            return Err(ReduceError::MismatchShape {
+                expected_shape: input.shape.to_vec(),
+                output_shape: tensor.shape.to_vec(),
+            });
+        }
+    }
+    for tensor in [mean.as_ref(), Some(&rstd)].into_iter().flatten() {
+        valid_output_shape(input.shape, tensor.shape, &[axis])?;
+    }
+    for tensor in [
+        tensors.gamma.as_ref(),
+        tensors.grad_gamma.as_ref(),
+        tensors.grad_beta.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if tensor.shape != [row_len] {
+            return Err(ReduceError::MismatchShape {
+                expected_shape: vec![row_len],
+                output_shape: tensor.shape.to_vec(),
+            });
+        }
+    }
+
+    let (blueprint, launch, num_rows) = prepare_rows(client, &input, axis, strategy, dtypes)?;
+
+    let settings = NormBackwardSettings {
+        rms: mean.is_none(),
+        gamma: tensors.gamma.is_some(),
+        grad_gamma: tensors.grad_gamma.is_some(),
+        grad_beta: tensors.grad_beta.is_some(),
+        shared_memory_size: launch.cube_dim.num_elems(),
+    };
+
+    // The missing tensors are bound to a placeholder that is never read nor written.
+    let placeholder = client.empty(dtypes.input.size().max(dtypes.accumulation.size()));
+    let [input_placeholder, acc_placeholder] =
+        [dtypes.input, dtypes.accumulation].map(|dtype| unsafe {
+            TensorHandleRef::<R>::from_raw_parts(&placeholder, &[1], &[1], dtype.size())
+        });
+
+    unsafe {
+        norm_backward_kernel::launch_unchecked::<R>(
+            client,
+            launch.cube_count,
+            launch.cube_dim,
+            grad_output.as_tensor_arg(1),
+            input.as_tensor_arg(1),
+            optional_arg(&tensors.gamma, &input_placeholder),
+            optional_arg(&mean, &acc_placeholder),
+            rstd.as_tensor_arg(1),
+            grad_input.as_tensor_arg(1),
+            ScalarArg::new(num_rows),
+            blueprint.global,
+            settings,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)?;
+
+    if !settings.grad_gamma && !settings.grad_beta {
+        return Ok(());
+    }
+
+    // Each cube writes the partial gradients of a chunk of rows for consecutive columns.
+    let num_chunks = num_rows.div_ceil(ROWS_PER_CHUNK);
+    let num_column_blocks = (row_len as u32).div_ceil(COLUMNS_PER_CUBE);
+    let num_partials = num_chunks as usize * row_len;
+    let elem_size = dtypes.accumulation.size();
+    let partial_lens = [settings.grad_gamma, settings.grad_beta]
+        .map(|enabled| if enabled { num_partials } else { 1 });
+    let partials = partial_lens.map(|len| client.empty(len * elem_size));
+    let [grad_gamma_partials, grad_beta_partials] = [0, 1]
+        .map(|i| ArrayArg::from_raw_parts_and_size(&partials[i], partial_lens[i], 1, elem_size));
+
+    let (cube_count, _) = cube_count_safe(client, num_chunks * num_column_blocks);
+    unsafe {
+        norm_backward_partials_kernel::launch_unchecked::<R>(
+            client,
+            cube_count,
+            CubeDim::new_1d(COLUMNS_PER_CUBE),
+            grad_output.as_tensor_arg(1),
+            input.as_tensor_arg(1),
+            optional_arg(&mean, &acc_placeholder),
+            rstd.as_tensor_arg(1),
+            grad_gamma_partials,
+            grad_beta_partials,
+            ScalarArg::new(num_rows),
+            ScalarArg::new(num_chunks),
+            ScalarArg::new(num_column_blocks),
+            settings,
+            dtypes.input,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)?;
+
+    // The final pass sums the partial gradients of every column along the chunks.
+    let partials_shape = [num_chunks as usize, row_len];
+    let partials_strides = [row_len, 1];
+    let output_shape = [1, row_len];
+    let strategy = ReduceStrategy {
+        routine: RoutineStrategy::Unit(BlueprintStrategy::Inferred(UnitStrategy)),
+        line_size: LineSizeStrategy {
+            parallel_output_vectorization: false,
+        },
+    };
+    for (handle, output) in partials.iter().zip([tensors.grad_gamma, tensors.grad_beta]) {
+        let Some(output) = output else {
+            continue;
+        };
+        let output_strides = [row_len * output.strides[0], output.strides[0]];
+        let (partials, output) = unsafe {
+            (
+                TensorHandleRef::<R>::from_raw_parts(
+                    handle,
+                    &partials_strides,
+                    &partials_shape,
+                    elem_size,
+                ),
+                TensorHandleRef::<R>::from_raw_parts(
+                    output.handle,
+                    &output_strides,
+                    &output_shape,
+                    output.elem_size,
+                ),
+            )
+        };
+        launch_reduce::<R>(
+            client,
+            partials,
+            output,
+            0,
+            strategy.clone(),
+            ReduceDtypes {
+                input: dtypes.accumulation,
+                output: dtypes.output,
+                accumulation: dtypes.accumulation,
+            },
+            ReduceOperationConfig::Sum,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn norm_backward_kernel<In: Numeric, Out: Numeric, Acc: Numeric>(
+    grad_output: &Tensor<Line<In>>,
+    input: &Tensor<Line<In>>,
+    gamma: &Tensor<Line<In>>,
+    mean: &Tensor<Line<Acc>>,
+    rstd: &Tensor<Line<Acc>>,
+    grad_input: &mut Tensor<Line<Out>>,
+    num_rows: u32,
+    #[comptime] blueprint: GlobalReduceBlueprint,
+    #[comptime] settings: NormBackwardSettings,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let axis = input.rank() - 1;
+    let worker = RowWorker::new(blueprint);
+    let length = worker.length(num_rows, input.shape(axis));
+
+    // Idle units read the statistics of the first row, which are never used.
+    let row = select(worker.row < num_rows, worker.row, 0u32);
+    let statistics = RowStatistics::new::<Acc>(mean, rstd, row, axis, settings.rms);
+
+    let grad_output_offset = vector_offset::<In>(grad_output, row, axis);
+    let input_offset = vector_offset::<In>(input, row, axis);
+    let grad_input_offset = vector_offset::<Out>(grad_input, row, axis);
+
+    // The sums of `g` and `g * xhat` over the row.
+    let mut sum_g = Line::<Acc>::empty(1u32).fill(Acc::from_int(0));
+    let mut sum_gx = Line::<Acc>::empty(1u32).fill(Acc::from_int(0));
+    let mut k = worker.first;
+    while k < length {
+        let g = scaled_gradient::<In>(grad_output, gamma, grad_output_offset, axis, k, settings);
+        let xhat = statistics.normalize::<In>(input, input_offset, axis, k);
+        sum_g += Line::cast_from(g);
+        sum_gx += Line::cast_from(g * xhat);
+        k += worker.step;
+    }
+
+    match comptime!(blueprint) {
+        GlobalReduceBlueprint::Unit(_) => {}
+        GlobalReduceBlueprint::Plane(_) => {
+            sum_g = plane_sum(sum_g);
+            sum_gx = plane_sum(sum_gx);
+        }
+        GlobalReduceBlueprint::Cube(_) => {
+            sum_g = fuse_sum_cube::<Acc>(sum_g, settings.shared_memory_size);
+            sum_gx = fuse_sum_cube::<Acc>(sum_gx, settings.shared_memory_size);
+        }
+    };
+
+    let row_len = Line::new(f32::cast_from(input.shape(axis)));
+    let mean_g = Line::<f32>::cast_from(sum_g) / row_len;
+    let mean_gx = Line::<f32>::cast_from(sum_gx) / row_len;
+
+    let mut k = worker.first;
+    while k < length {
+        let g = scaled_gradient::<In>(grad_output, gamma, grad_output_offset, axis, k, settings);
+        let xhat = statistics.normalize::<In>(input, input_offset, axis, k);
+        let mut result = g - xhat * mean_gx;
+        if comptime!(!settings.rms) {
+            result -= mean_g;
+        }
+        grad_input[grad_input_offset + k * grad_input.stride(axis)] =
+            Line::cast_from(result * statistics.rstd);
+        k += worker.step;
+    }
+}
+
+/// Each unit sums a chunk of rows of one column into partial gradients of gamma and beta.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn norm_backward_partials_kernel<In: Numeric, Acc: Numeric>(
+    grad_output: &Tensor<Line<In>>,
+    input: &Tensor<Line<In>>,
+    mean: &Tensor<Line<Acc>>,
+    rstd: &Tensor<Line<Acc>>,
+    grad_gamma_partials: &mut Array<Acc>,
+    grad_beta_partials: &mut Array<Acc>,
+    num_rows: u32,
+    num_chunks: u32,
+    num_column_blocks: u32,
+    #[comptime] settings: NormBackwardSettings,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let axis = input.rank() - 1;
+    let row_len = input.shape(axis);
+    let chunk = CUBE_POS / num_column_blocks;
+    let column = (CUBE_POS % num_column_blocks) * CUBE_DIM + UNIT_POS;
+    if chunk >= num_chunks || column >= row_len {
+        terminate!();
+    }
+
+    let start = chunk * ROWS_PER_CHUNK;
+    let end = start + ROWS_PER_CHUNK;
+    let end = select(end < num_rows, end, num_rows);
+
+    let mut grad_gamma = Line::<Acc>::empty(1u32).fill(Acc::from_int(0));
+    let mut grad_beta = Line::<Acc>::empty(1u32).fill(Acc::from_int(0));
+    for row in start..end {
+        let offset = vector_offset::<In>(grad_output, row, axis);
+        let dy = Line::<f32>::cast_from(grad_output[offset + column * grad_output.stride(axis)]);
+
+        if comptime!(settings.grad_gamma) {
+            let statistics = RowStatistics::new::<Acc>(mean, rstd, row, axis, settings.rms);
+            let input_offset = vector_offset::<In>(input, row, axis);
+            let xhat = statistics.normalize::<In>(input, input_offset, axis, column);
+            grad_gamma += Line::cast_from(dy * xhat);
+        }
+        if comptime!(settings.grad_beta) {
+            grad_beta += Line::cast_from(dy);
+        }
+    }
+
+    let index = chunk * row_len + column;
+    if comptime!(settings.grad_gamma) {
+        grad_gamma_partials[index] = grad_gamma[0];
+    }
+    if comptime!(settings.grad_beta) {
+        grad_beta_partials[index] = grad_beta[0];
+    }
+}
+
+/// The statistics saved by the forward pass for one row, in `f32`.
+#[derive(CubeType)]
+struct RowStatistics {
+    mean: Line<f32>,
+    rstd: Line<f32>,
+}
+
+#[cube]
+impl RowStatistics {
+    fn new<Acc: Numeric>(
+        mean: &Tensor<Line<Acc>>,
+        rstd: &Tensor<Line<Acc>>,
+        row: u32,
+        axis: u32,
+        #[comptime] rms: bool,
+    ) -> RowStatistics {
+        let mut mean_value = Line::new(0.0f32);
+        if comptime!(!rms) {
+            mean_value = Line::cast_from(mean[vector_offset::<Acc>(mean, row, axis)]);
+        }
+
+        RowStatistics {
+            mean: mean_value,
+            rstd: Line::cast_from(rstd[vector_offset::<Acc>(rstd, row, axis)]),
+        }
+    }
+
+    /// The normalized element `k` of the row, `xhat = (x - mean) * rstd`.
+    fn normalize<In: Numeric>(
+        &self,
+        input: &Tensor<Line<In>>,
+        offset: u32,
+        axis: u32,
+        k: u32,
+    ) -> Line<f32> {
+        let value = Line::<f32>::cast_from(input[offset + k * input.stride(axis)]);
+        (value - self.mean) * self.rstd
+    }
+}
+
+/// The element `k` of the gradient of the normalized row, `g = grad_output * gamma`.
+#[cube]
+fn scaled_gradient<In: Numeric>(
+    grad_output: &Tensor<Line<In>>,
+    gamma: &Tensor<Line<In>>,
+    offset: u32,
+    axis: u32,
+    k: u32,
+    #[comptime] settings: NormBackwardSettings,
+) -> Line<f32> {
+    let mut g = Line::<f32>::cast_from(grad_output[offset + k * grad_output.stride(axis)]);
+    if comptime!(settings.gamma) {
+        g *= Line::<f32>::cast_from(gamma[k * gamma.stride(0)]);
+    }
+    g
+}
+
+/// Sum the values of all units within the cube, the result is valid for every unit.
+#[cube]
+fn fuse_sum_cube<Acc: Numeric>(value: Line<Acc>, #[comptime] size: u32) -> Line<Acc> {
+    let mut shared = SharedMemory::<Acc>::new_lined(size, 1u32);
+    shared[UNIT_POS] = value;
+    sync_cube();
+
+    let mut result = value;
+    reduce_tree::<(Acc, Acc), Sum>(&Sum {}, &mut shared, &mut result, UNIT_POS, size);
+    result
+}
@@ -1,7 +1,17 @@
+pub mod arg_extremum;
+pub mod channel_norm;
 pub mod cube;
+pub mod histogram;
+pub mod layer_norm;
+pub mod layer_norm_backward;
 pub mod plane;
+pub mod reduce_all;
 pub mod reduce_dim;
+pub mod scan;
+pub mod segmented;
 pub mod shared_sum;
+pub mod softmax;
+pub mod top_k;
 pub mod unit;
 
 mod base;
@@ -0,0 +1,320 @@
+use cubecl::prelude::*;
+use cubecl::std::{CubeOption, CubeOptionExpand, tensor::is_contiguous};
+
+use crate::{
+    LineMode, ReduceDtypes, ReduceError, ReducePrecision,
+    components::{
+        global::cube::reduce_tree,
+        instructions::{
+            DynamicAccumulatorItem, ReduceCoordinate, ReduceInstruction, ReduceOperation,
+            ReduceOperationConfig, SharedAccumulator, accumulator_layout, reduce_inplace,
+        },
+    },
+};
+
+/// Reduce all the elements of the input tensor into the single element of `output`,
+/// distributing the work over `cube_count` cubes.
+///
+/// This works for every [ReduceOperationConfig] in two phases. First, each cube reduces a
+/// contiguous chunk of the input into partial results written to a temporary buffer. Then,
+/// a single cube reduces the partial results in a fixed order and writes the final value.
+/// No atomics are used, so the result is reproducible and `output` doesn't need to be initialized.
+///
+/// For [ArgMax](ReduceOperationConfig::ArgMax) and [ArgMin](ReduceOperationConfig::ArgMin),
+/// the returned index is the position of the element in the flattened input tensor.
+///
+/// Returns an error if the input isn't contiguous, if `cube_count` is zero or if `output` isn't
+/// a tensor with a single element of the same rank as `input`.
+pub fn reduce_all<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    cube_count: u32,
+    operation: ReduceOperationConfig,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    let expected_shape = vec![1; input.shape.len()];
+    if output.shape != expected_shape {
+        return Err(ReduceError::MismatchShape {
+            expected_shape,
+            output_shape: output.shape.to_vec(),
+        });
+    }
+    if !is_contiguous(input.shape, input.strides) {
+        return Err(ReduceError::Validation {
+            details: "Reducing a full tensor requires a contiguous input.",
+        });
+    }
+    if cube_count == 0 {
+        return Err(ReduceError::Validation {
+            details: "Reducing a full tensor requires at least one cube.",
+        });
+    }
+
+    let input_len = input.shape.iter().map(|s| *s as u32).product::<u32>();
+
+    // Compute the optimal line size.
+    let line_size = client
+        .io_optimized_line_sizes_unchecked(dtypes.input.size())
+        .filter(|line_size| input_len % *line_size as u32 == 0)
+        .max()
+        .unwrap_or(1) as u32;
+
+    // Compute extra parameters.
+    let cube_dim = CubeDim::new_2d(32, 8); // NOTE: If you change that, keep the unit count a power of 2.
+    let num_units = cube_count * cube_dim.num_elems();
+    let num_lines_per_unit = input_len.div_ceil(num_units * line_size);
+
+    // Each cube writes one partial accumulator per element of its line.
+    let num_partials = (cube_count * line_size) as usize;
+    let partials = [
+        dtypes.accumulation.size(),
+        size_of::<u32>(),
+        dtypes.accumulation.size(),
+        dtypes.accumulation.size(),
+    ]
+    .map(|elem_size| (client.empty(num_partials * elem_size), elem_size));
+    let partial_args = || {
+        partials.each_ref().map(|(handle, elem_size)| {
+            ArrayArg::from_raw_parts_and_size(handle, num_partials, 1, *elem_size)
+        })
+    };
+
+    let [elements, coordinates, aux, aux2] = partial_args();
+    unsafe {
+        reduce_all_partials_kernel::launch_unchecked(
+            client,
+            CubeCount::new_1d(cube_count),
+            cube_dim,
+            input.as_tensor_arg(line_size as u8),
+            elements,
+            coordinates,
+            aux,
+            aux2,
+            cube_dim.num_elems(),
+            num_lines_per_unit,
+            operation,
+            dtypes.input,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)?;
+
+    let [elements, coordinates, aux, aux2] = partial_args();
+    unsafe {
+        reduce_all_final_kernel::launch_unchecked(
+            client,
+            CubeCount::new_single(),
+            cube_dim,
+            elements,
+            coordinates,
+            aux,
+            aux2,
+            output.as_tensor_arg(1),
+            ScalarArg::new(input_len),
+            cube_dim.num_elems(),
+            operation,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn reduce_all_partials_kernel<In: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    elements: &mut Array<Acc>,
+    coordinates: &mut Array<u32>,
+    aux: &mut Array<Acc>,
+    aux2: &mut Array<Acc>,
+    #[comptime] shared_memory_size: u32,
+    #[comptime] num_lines_per_unit: u32,
+    #[comptime] config: ReduceOperationConfig,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    reduce_all_partials::<(In, Acc)>(
+        input,
+        elements,
+        coordinates,
+        aux,
+        aux2,
+        shared_memory_size,
+        num_lines_per_unit,
+        config,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn reduce_all_final_kernel<In: Numeric, Out: Numeric, Acc: Numeric>(
+    elements: &Array<Acc>,
+    coordinates: &Array<u32>,
+    aux: &Array<Acc>,
+    aux2: &Array<Acc>,
+    output: &mut Tensor<Line<Out>>,
+    input_len: u32,
+    #[comptime] shared_memory_size: u32,
+    #[comptime] config: ReduceOperationConfig,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    reduce_all_final::<(In, Acc), Out>(
+        elements,
+        coordinates,
+        aux,
+        aux2,
+        output,
+        input_len,
+        shared_memory_size,
+        config,
+    );
+}
+
+/// Reduce the lines assigned to the current cube and write its partial accumulator.
+#[allow(clippy::too_many_arguments)]
+#[cube]
+fn reduce_all_partials<P: ReducePrecision>(
+    input: &Tensor<Line<P::EI>>,
+    elements: &mut Array<P::EA>,
+    coordinates: &mut Array<u32>,
+    aux: &mut Array<P::EA>,
+    aux2: &mut Array<P::EA>,
+    #[comptime] shared_memory_size: u32,
+    #[comptime] num_lines_per_unit: u32,
+    #[comptime] config: ReduceOperationConfig,
+) {
+    let inst = &<ReduceOperation as ReduceInstruction<P>>::from_config(config);
+    let line_size = input.line_size();
+    let requirements = <ReduceOperation as ReduceInstruction<P>>::requirements(inst);
+
+    // Each unit reduce `num_lines_per_unit` lines.
+    let start = ABSOLUTE_POS * num_lines_per_unit;
+    let end = start + num_lines_per_unit;
+
+    // Prevent out-of-bound access
+    let start = select(start < input.len(), start, input.len());
+    let end = select(end < input.len(), end, input.len());
+
+    let mut accumulator =
+        <ReduceOperation as ReduceInstruction<P>>::null_accumulator(inst, line_size);
+    for k in start..end {
+        let coordinate =
+            ReduceCoordinate::new(k * line_size, requirements, line_size, LineMode::Parallel);
+        reduce_inplace::<P, ReduceOperation>(inst, &mut accumulator, input[k], coordinate, false);
+    }
+
+    let result = fuse_cube::<P, ReduceOperation>(inst, accumulator, shared_memory_size, line_size);
+
+    if UNIT_POS == 0 {
+        let offset = CUBE_POS * line_size;
+
+        #[unroll]
+        for k in 0..line_size {
+            elements[offset + k] = result.elements[k];
+        }
+        write_partial_line::<u32>(coordinates, &result.args, offset);
+        write_partial_line::<P::EA>(aux, &result.aux, offset);
+        write_partial_line::<P::EA>(aux2, &result.aux2, offset);
+    }
+}
+
+/// Reduce all partial accumulators in a fixed order and write the final value.
+#[allow(clippy::too_many_arguments)]
+#[cube]
+fn reduce_all_final<P: ReducePrecision, Out: Numeric>(
+    elements: &Array<P::EA>,
+    coordinates: &Array<u32>,
+    aux: &Array<P::EA>,
+    aux2: &Array<P::EA>,
+    output: &mut Tensor<Line<Out>>,
+    input_len: u32,
+    #[comptime] shared_memory_size: u32,
+    #[comptime] config: ReduceOperationConfig,
+) {
+    let inst = &<ReduceOperation as ReduceInstruction<P>>::from_config(config);
+    let layout = accumulator_layout(inst);
+
+    // Each unit fuses the partials at a fixed stride, so the order never changes.
+    let mut accumulator = <ReduceOperation as ReduceInstruction<P>>::null_accumulator(inst, 1u32);
+    let mut k = UNIT_POS;
+    while k < elements.len() {
+        let partial = DynamicAccumulatorItem::<P::EA> {
+            elements: Line::new(elements[k]),
+            args: read_partial_line::<u32>(coordinates, k, layout.coordinates),
+            aux: read_partial_line::<P::EA>(aux, k, comptime!(layout.aux > 0)),
+            aux2: read_partial_line::<P::EA>(aux2, k, comptime!(layout.aux > 1)),
+        };
+        let fused = <ReduceOperation as ReduceInstruction<P>>::fuse_accumulators(
+            inst,
+            accumulator,
+            partial,
+        );
+        <ReduceOperation as ReduceInstruction<P>>::assign_accumulator(
+            inst,
+            &mut accumulator,
+            &fused,
+        );
+        k += CUBE_DIM;
+    }
+
+    let result = fuse_cube::<P, ReduceOperation>(inst, accumulator, shared_memory_size, 1u32);
+
+    if UNIT_POS == 0 {
+        output[0] = Line::new(
+            <ReduceOperation as ReduceInstruction<P>>::merge_line::<Out>(inst, result, input_len),
+        );
+    }
+}
+
+#[cube]
+fn write_partial_line<N: Numeric>(
+    partials: &mut Array<N>,
+    line: &CubeOption<Line<N>>,
+    offset: u32,
+) {
+    match line {
+        CubeOption::Some(line) =>
+        {
+            #[unroll]
+            for k in 0..line.size() {
+                partials[offset + k] = line[k];
+            }
+        }
+        CubeOption::None => {}
+    }
+}
+
+#[cube]
+fn read_partial_line<N: Numeric>(
+    partials: &Array<N>,
+    index: u32,
+    #[comptime] enabled: bool,
+) -> CubeOption<Line<N>> {
+    if comptime!(enabled) {
+        CubeOption::new_Some(Line::new(partials[index]))
+    } else {
+        CubeOption::new_None()
+    }
+}
+
+/// Fuse the accumulators of all units within the cube, the result is only valid for the first unit.
+#[cube]
+pub(crate) fn fuse_cube<P: ReducePrecision, I: ReduceInstruction<P>>(
+    inst: &I,
+    accumulator: I::AccumulatorItem,
+    #[comptime] shared_memory_size: u32,
+    #[comptime] line_size: u32,
+) -> I::AccumulatorItem {
+    let mut shared = I::allocate_shared(inst, shared_memory_size, line_size);
+    I::SharedAccumulator::write(&mut shared, UNIT_POS, accumulator);
+    sync_cube();
+
+    let mut result = I::null_accumulator(inst, line_size);
+    reduce_tree::<P, I>(inst, &mut shared, &mut result, UNIT_POS, shared_memory_size);
+    result
+}
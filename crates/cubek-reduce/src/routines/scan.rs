@@ -0,0 +1,845 @@
+use cubecl::ir::ElemType;
+use cubecl::prelude::*;
+use cubecl::server::Handle;
+use cubecl::std::tensor::r#virtual::VirtualTensor;
+
+use crate::{
+    LineMode, ReduceDtypes, ReduceError,
+    components::{
+        args::{ReduceArgs, TensorArgs, init_tensors},
+        instructions::{max_propagate_nan, min_propagate_nan},
+    },
+    launch::support_plane,
+    routines::cube_count_safe,
+    validate_axis,
+};
+
+/// The associative operator combining the elements of a [scan].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanOperation {
+    /// Cumulative sum.
+    Sum,
+    /// Cumulative product.
+    Prod,
+    /// Cumulative maximum, propagating NaN.
+    Max,
+    /// Cumulative minimum, propagating NaN.
+    Min,
+    /// Logarithm of the cumulative sum of the exponentials, computed in `f32`.
+    LogSumExp,
+}
+
+/// Which prefixes are written by [scan].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScanConfig {
+    pub operation: ScanOperation,
+    /// Exclude the element at each position from its own prefix if `true`,
+    /// so the first prefix is the identity of the operation.
+    pub exclusive: bool,
+}
+
+/// How the work of [scan] is distributed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanStrategy {
+    /// Each unit scans a full vector sequentially.
+    ///
+    /// This is best when there are many short vectors.
+    Unit,
+    /// A cube scans a full vector, walking the axis tile by tile with a running carry.
+    ///
+    /// This is best when there are many vectors.
+    Cube { use_planes: bool },
+    /// Each vector is split into `num_blocks` blocks scanned by different cubes.
+    ///
+    /// A first pass computes the total of every block, a second pass scans the totals of each
+    /// vector and a third pass scans every block again starting from the total of the
+    /// previous blocks. This is best when there are few long vectors.
+    MultiPass { use_planes: bool, num_blocks: u32 },
+}
+
+/// The number of units of a cube when planes are not used.
+const CUBE_SIZE: u32 = 64;
+
+/// Compute the prefixes of the given `axis` of the `input` tensor and write them into `output`.
+///
+/// The `output` tensor must have the same shape as `input`. The prefixes are accumulated with
+/// the `accumulation` type of the `dtypes`, then cast to the `output` type.
+///
+/// Like the reductions, the tensors are read in lines. When the axis is contiguous, the lanes of
+/// a line are consecutive elements of the axis, scanned before the lines. Otherwise, the lanes
+/// belong to different vectors along the contiguous last axis, which are scanned independently.
+///
+/// With `use_planes`, the cubes are made of a single plane and the plane scan instructions are
+/// used for [ScanOperation::Sum] and [ScanOperation::Prod]. The other operations always scan
+/// through shared memory.
+///
+/// Returns an error if the planes are requested but unavailable or of variable size,
+/// or if `num_blocks` is 0.
+pub fn scan<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    axis: usize,
+    config: ScanConfig,
+    strategy: ScanStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    validate_axis(input.shape.len(), axis)?;
+    if output.shape != input.shape {
+        return Err(ReduceError::MismatchShape {
+            expected_shape: input.shape.to_vec(),
+            output_shape: output.shape.to_vec(),
+        });
+    }
+
+    let (use_planes, num_blocks) = match strategy {
+        ScanStrategy::Unit => (false, 1),
+        ScanStrategy::Cube { use_planes } => (use_planes, 1),
+        ScanStrategy::MultiPass {
+            use_planes,
+            num_blocks,
+        } => (use_planes, num_blocks),
+    };
+    if num_blocks == 0 {
+        return Err(ReduceError::Validation {
+            details: "The number of blocks must be at least 1.",
+        });
+    }
+
+    let cube_size = if use_planes {
+        let hardware = &client.properties().hardware;
+        if !support_plane(client) {
+            return Err(ReduceError::PlanesUnavailable);
+        }
+        if hardware.plane_size_min != hardware.plane_size_max {
+            return Err(ReduceError::ImprecisePlaneDim);
+        }
+        hardware.plane_size_max
+    } else {
+        CUBE_SIZE
+    };
+    let (line_mode, line_size) = line_settings(client, &input, &output, axis, dtypes.input);
+    let settings = ScanSettings {
+        axis: axis as u32,
+        num_blocks,
+        cube_size,
+        use_planes,
+        unit: strategy == ScanStrategy::Unit,
+        line_mode,
+        line_size,
+        float: matches!(dtypes.accumulation.elem_type(), ElemType::Float(_)),
+    };
+
+    if num_blocks == 1 {
+        return launch_scan(client, input, output, None, settings, config, dtypes);
+    }
+
+    let (num_vectors, _) = settings.layout(input.shape);
+    let num_totals = num_vectors as usize * num_blocks as usize;
+    let carry_size = settings.carry_line_size() as usize;
+    let elem_size = dtypes.accumulation.size();
+    let totals = client.empty(num_totals * carry_size * elem_size);
+    let carries = client.empty(num_totals * carry_size * elem_size);
+
+    // The totals of the blocks of each vector, with the lanes of the lines as the last axis.
+    let shape = [num_vectors as usize, num_blocks as usize, carry_size];
+    let strides = [num_blocks as usize * carry_size, carry_size, 1];
+    let (totals, carries_tensor) = unsafe {
+        (
+            TensorHandleRef::from_raw_parts(&totals, &strides, &shape, elem_size),
+            TensorHandleRef::from_raw_parts(&carries, &strides, &shape, elem_size),
+        )
+    };
+    launch_totals(client, input, &totals, settings, config, dtypes)?;
+
+    // The carry of each block is the exclusive scan of the totals of its vector.
+    let (line_mode, line_size) =
+        line_settings(client, &totals, &carries_tensor, 1, dtypes.accumulation);
+    launch_scan(
+        client,
+        totals,
+        carries_tensor,
+        None,
+        ScanSettings {
+            axis: 1,
+            num_blocks: 1,
+            unit: false,
+            line_mode,
+            line_size,
+            ..settings
+        },
+        ScanConfig {
+            exclusive: true,
+            ..config
+        },
+        ReduceDtypes {
+            input: dtypes.accumulation,
+            output: dtypes.accumulation,
+            accumulation: dtypes.accumulation,
+        },
+    )?;
+
+    launch_scan(
+        client,
+        input,
+        output,
+        Some((&carries, num_totals * carry_size)),
+        settings,
+        config,
+        dtypes,
+    )
+}
+
+/// The line mode and the largest line size covering the same elements of `input` and `output`.
+///
+/// The lines run along the axis when it's contiguous in the input, or along the last axis
+/// otherwise, which must then be contiguous in both tensors.
+fn line_settings<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: &TensorHandleRef<R>,
+    output: &TensorHandleRef<R>,
+    axis: usize,
+    dtype: StorageType,
+) -> (LineMode, u8) {
+    let (line_mode, lined_axis) = match input.strides[axis] {
+        1 => (LineMode::Parallel, axis),
+        _ => (LineMode::Perpendicular, input.shape.len() - 1),
+    };
+    if line_mode == LineMode::Perpendicular && lined_axis == axis {
+        return (line_mode, 1);
+    }
+
+    let line_size = client
+        .io_optimized_line_sizes_unchecked(dtype.size())
+        .filter(|line_size| {
+            [input, output]
+                .into_iter()
+                .all(|tensor| is_lined(tensor, lined_axis, *line_size as usize))
+        })
+        .max()
+        .unwrap_or(1);
+    (line_mode, line_size)
+}
+
+/// Whether the tensor can be read in lines along the given `axis`, which requires every line to
+/// start at a multiple of the line size.
+fn is_lined<R: Runtime>(tensor: &TensorHandleRef<R>, axis: usize, line_size: usize) -> bool {
+    tensor.strides[axis] == 1
+        && tensor.shape[axis].is_multiple_of(line_size)
+        && tensor
+            .strides
+            .iter()
+            .zip(tensor.shape)
+            .enumerate()
+            .all(|(dim, (stride, shape))| {
+                dim == axis || *shape == 1 || stride.is_multiple_of(line_size)
+            })
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ScanSettings {
+    axis: u32,
+    num_blocks: u32,
+    cube_size: u32,
+    use_planes: bool,
+    /// Whether each unit scans a full vector.
+    unit: bool,
+    line_mode: LineMode,
+    line_size: u8,
+    /// Whether the accumulation type is a float, which has infinities.
+    float: bool,
+}
+
+impl ScanSettings {
+    /// The number of vectors of lines and the number of lines in each block.
+    fn layout(&self, shape: &[usize]) -> (u32, u32) {
+        let line_size = self.line_size as u32;
+        let axis_size = shape[self.axis as usize] as u32;
+        let num_vectors = (shape.iter().product::<usize>() / axis_size as usize) as u32;
+        let (num_vectors, axis_len) = match self.line_mode {
+            LineMode::Parallel => (num_vectors, axis_size / line_size),
+            LineMode::Perpendicular => (num_vectors / line_size, axis_size),
+        };
+        (num_vectors, axis_len.div_ceil(self.num_blocks))
+    }
+
+    /// The line size of the totals and the carries of the blocks, which have a single lane when
+    /// the lanes are along the axis.
+    fn carry_line_size(&self) -> u8 {
+        carry_line_size(self.line_size as u32, self.line_mode) as u8
+    }
+}
+
+fn launch_scan<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    carries: Option<(&Handle, usize)>,
+    settings: ScanSettings,
+    config: ScanConfig,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    let (num_vectors, block_len) = settings.layout(input.shape);
+    let line_size = settings.line_size;
+
+    if settings.unit {
+        let (cube_count, _) = cube_count_safe(client, num_vectors.div_ceil(settings.cube_size));
+        return unsafe {
+            scan_unit_kernel::launch_unchecked::<TensorArgs, R>(
+                client,
+                cube_count,
+                CubeDim::new_1d(settings.cube_size),
+                input.as_tensor_arg(line_size),
+                output.as_tensor_arg(line_size),
+                ScalarArg::new(settings.axis),
+                ScalarArg::new(num_vectors),
+                config,
+                settings.line_mode,
+                settings.float,
+                dtypes.input,
+                dtypes.output,
+                dtypes.accumulation,
+            )
+        }
+        .map_err(ReduceError::Launch);
+    }
+
+    // Without carries, a placeholder is bound but never read.
+    let placeholder;
+    let (carries, num_carries) = match carries {
+        Some(carries) => carries,
+        None => {
+            placeholder = client.empty(dtypes.accumulation.size() * line_size as usize);
+            (&placeholder, line_size as usize)
+        }
+    };
+
+    let (cube_count, _) = cube_count_safe(client, num_vectors * settings.num_blocks);
+    unsafe {
+        scan_kernel::launch_unchecked::<TensorArgs, R>(
+            client,
+            cube_count,
+            CubeDim::new_1d(settings.cube_size),
+            input.as_tensor_arg(line_size),
+            output.as_tensor_arg(line_size),
+            ArrayArg::from_raw_parts_and_size(
+                carries,
+                num_carries,
+                settings.carry_line_size(),
+                dtypes.accumulation.size(),
+            ),
+            ScalarArg::new(settings.axis),
+            ScalarArg::new(num_vectors),
+            ScalarArg::new(settings.num_blocks),
+            ScalarArg::new(block_len),
+            config,
+            settings.line_mode,
+            settings.cube_size,
+            settings.use_planes,
+            settings.num_blocks > 1,
+            settings.float,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+fn launch_totals<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    totals: &TensorHandleRef<R>,
+    settings: ScanSettings,
+    config: ScanConfig,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    let (num_vectors, block_len) = settings.layout(input.shape);
+    let (cube_count, _) = cube_count_safe(client, num_vectors * settings.num_blocks);
+
+    unsafe {
+        scan_totals_kernel::launch_unchecked::<TensorArgs, R>(
+            client,
+            cube_count,
+            CubeDim::new_1d(settings.cube_size),
+            input.as_tensor_arg(settings.line_size),
+            totals.as_tensor_arg(settings.carry_line_size()),
+            ScalarArg::new(settings.axis),
+            ScalarArg::new(num_vectors),
+            ScalarArg::new(settings.num_blocks),
+            ScalarArg::new(block_len),
+            config,
+            settings.line_mode,
+            settings.cube_size,
+            settings.use_planes,
+            settings.float,
+            dtypes.input,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+/// Each unit scans one full vector.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn scan_unit_kernel<In: Numeric, Out: Numeric, Acc: Numeric, RA: ReduceArgs>(
+    input: &RA::Input<In>,
+    output: &mut RA::Output<Out>,
+    axis: u32,
+    num_vectors: u32,
+    #[comptime] config: ScanConfig,
+    #[comptime] line_mode: LineMode,
+    #[comptime] float: bool,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let vector = ABSOLUTE_POS;
+    if vector >= num_vectors {
+        terminate!();
+    }
+
+    let (input, mut output) = init_tensors::<RA, In, Out>(input, output);
+    let input_vector = LineVector::new(&input, vector, axis, line_mode);
+    let output_vector = LineVector::new(&output, vector, axis, line_mode);
+    let line_size = input.line_size();
+    let carry_size = comptime!(carry_line_size(line_size, line_mode));
+
+    let mut carry = identity::<Acc>(config.operation, float, carry_size);
+    for position in 0..axis_length::<In>(&input, axis, line_mode) {
+        let value = Line::cast_from(input.read(input_vector.index(position)));
+        let lanes = scan_line::<Acc>(value, config.operation, line_mode, float);
+        let local = if comptime!(config.exclusive) {
+            lanes.exclusive
+        } else {
+            lanes.inclusive
+        };
+
+        let previous = spread::<Acc>(carry, line_size, line_mode);
+        output.write(
+            output_vector.index(position),
+            Line::cast_from(combine::<Acc>(previous, local, config.operation)),
+        );
+        carry = combine::<Acc>(carry, lanes.total, config.operation);
+    }
+}
+
+/// Each cube scans one block of one vector, starting from the carry of the previous blocks.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn scan_kernel<In: Numeric, Out: Numeric, Acc: Numeric, RA: ReduceArgs>(
+    input: &RA::Input<In>,
+    output: &mut RA::Output<Out>,
+    carries: &Array<Line<Acc>>,
+    axis: u32,
+    num_vectors: u32,
+    num_blocks: u32,
+    block_len: u32,
+    #[comptime] config: ScanConfig,
+    #[comptime] line_mode: LineMode,
+    #[comptime] cube_size: u32,
+    #[comptime] use_planes: bool,
+    #[comptime] has_carries: bool,
+    #[comptime] float: bool,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let vector = CUBE_POS / num_blocks;
+    if vector >= num_vectors {
+        terminate!();
+    }
+
+    let (input, mut output) = init_tensors::<RA, In, Out>(input, output);
+    let input_vector = LineVector::new(&input, vector, axis, line_mode);
+    let output_vector = LineVector::new(&output, vector, axis, line_mode);
+    let line_size = input.line_size();
+    let carry_size = comptime!(carry_line_size(line_size, line_mode));
+
+    let axis_len = axis_length::<In>(&input, axis, line_mode);
+    let start = (CUBE_POS % num_blocks) * block_len;
+    let end = start + block_len;
+    let end = select(end < axis_len, end, axis_len);
+
+    let mut carry = identity::<Acc>(config.operation, float, carry_size);
+    if comptime!(has_carries) {
+        carry = carries[CUBE_POS];
+    }
+
+    let mut shared = SharedMemory::<Acc>::new_lined(cube_size, carry_size);
+    let mut tile = start;
+    while tile < end {
+        let position = tile + UNIT_POS;
+        let mut value = identity::<Acc>(config.operation, float, line_size);
+        if position < end {
+            value = Line::cast_from(input.read(input_vector.index(position)));
+        }
+
+        // The cube scans the totals of the lines, which are the lines themselves when their
+        // lanes are independent.
+        let lanes = scan_line::<Acc>(value, config.operation, line_mode, float);
+        let prefix = scan_cube::<Acc>(
+            lanes.total,
+            &mut shared,
+            config.operation,
+            use_planes,
+            float,
+        );
+        let local = if comptime!(config.exclusive) {
+            lanes.exclusive
+        } else {
+            lanes.inclusive
+        };
+
+        if position < end {
+            let previous = combine::<Acc>(carry, prefix.exclusive, config.operation);
+            let previous = spread::<Acc>(previous, line_size, line_mode);
+            output.write(
+                output_vector.index(position),
+                Line::cast_from(combine::<Acc>(previous, local, config.operation)),
+            );
+        }
+
+        carry = combine::<Acc>(carry, prefix.total, config.operation);
+        tile += CUBE_DIM;
+    }
+}
+
+/// Each cube computes the total of one block of one vector.
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn scan_totals_kernel<In: Numeric, Acc: Numeric, RA: ReduceArgs>(
+    input: &RA::Input<In>,
+    totals: &mut RA::Output<Acc>,
+    axis: u32,
+    num_vectors: u32,
+    num_blocks: u32,
+    block_len: u32,
+    #[comptime] config: ScanConfig,
+    #[comptime] line_mode: LineMode,
+    #[comptime] cube_size: u32,
+    #[comptime] use_planes: bool,
+    #[comptime] float: bool,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    let vector = CUBE_POS / num_blocks;
+    if vector >= num_vectors {
+        terminate!();
+    }
+
+    let (input, mut totals) = init_tensors::<RA, In, Acc>(input, totals);
+    let input_vector = LineVector::new(&input, vector, axis, line_mode);
+    let line_size = input.line_size();
+    let carry_size = comptime!(carry_line_size(line_size, line_mode));
+
+    let axis_len = axis_length::<In>(&input, axis, line_mode);
+    let start = (CUBE_POS % num_blocks) * block_len;
+    let end = start + block_len;
+    let end = select(end < axis_len, end, axis_len);
+
+    // Each unit combines the lines at a stride, the order doesn't matter for a total.
+    let mut total = identity::<Acc>(config.operation, float, line_size);
+    let mut position = start + UNIT_POS;
+    while position < end {
+        let value = Line::cast_from(input.read(input_vector.index(position)));
+        total = combine::<Acc>(total, value, config.operation);
+        position += CUBE_DIM;
+    }
+    let total = scan_line::<Acc>(total, config.operation, line_mode, float).total;
+
+    let mut shared = SharedMemory::<Acc>::new_lined(cube_size, carry_size);
+    let prefix = scan_cube::<Acc>(total, &mut shared, config.operation, use_planes, float);
+
+    if UNIT_POS == 0 {
+        totals.write(CUBE_POS, prefix.total);
+    }
+}
+
+/// The number of lanes of the carries, see [ScanSettings::carry_line_size].
+fn carry_line_size(line_size: u32, line_mode: LineMode) -> u32 {
+    match line_mode {
+        LineMode::Parallel => 1,
+        LineMode::Perpendicular => line_size,
+    }
+}
+
+/// The offset of the first element of the vector, from its coordinates along the other axes.
+#[cube]
+pub(crate) fn vector_offset<N: Numeric>(tensor: &Tensor<Line<N>>, vector: u32, axis: u32) -> u32 {
+    let mut remainder = vector;
+    let mut offset = 0u32;
+    for i in 0..tensor.rank() {
+        let dim = tensor.rank() - 1 - i;
+        if dim != axis {
+            let shape = tensor.shape(dim);
+            offset += (remainder % shape) * tensor.stride(dim);
+            remainder /= shape;
+        }
+    }
+    offset
+}
+
+/// Same as [vector_offset], for a virtual tensor.
+#[cube]
+fn virtual_vector_offset<N: Numeric, IO: Clone>(
+    tensor: &VirtualTensor<N, IO>,
+    vector: u32,
+    axis: u32,
+) -> u32 {
+    let mut remainder = vector;
+    let mut offset = 0u32;
+    for i in 0..tensor.rank() {
+        let dim = tensor.rank() - 1 - i;
+        if dim != axis {
+            let shape = tensor.shape(dim);
+            offset += (remainder % shape) * tensor.stride(dim);
+            remainder /= shape;
+        }
+    }
+    offset
+}
+
+/// The number of lines along the axis.
+#[cube]
+fn axis_length<N: Numeric>(
+    tensor: &VirtualTensor<N>,
+    axis: u32,
+    #[comptime] line_mode: LineMode,
+) -> u32 {
+    match comptime!(line_mode) {
+        LineMode::Parallel => tensor.shape(axis) / tensor.line_size(),
+        LineMode::Perpendicular => tensor.shape(axis),
+    }
+}
+
+/// The lines of a vector of lines, that is of consecutive vectors in perpendicular mode.
+#[derive(CubeType)]
+struct LineVector {
+    /// The index of the first line.
+    offset: u32,
+    /// The distance between consecutive lines.
+    stride: u32,
+}
+
+#[cube]
+impl LineVector {
+    fn new<N: Numeric, IO: Clone>(
+        tensor: &VirtualTensor<N, IO>,
+        vector: u32,
+        axis: u32,
+        #[comptime] line_mode: LineMode,
+    ) -> LineVector {
+        let line_size = tensor.line_size();
+        match comptime!(line_mode) {
+            LineMode::Parallel => LineVector {
+                offset: virtual_vector_offset::<N, IO>(tensor, vector, axis) / line_size,
+                stride: tensor.stride(axis),
+            },
+            LineMode::Perpendicular => LineVector {
+                offset: virtual_vector_offset::<N, IO>(tensor, vector * line_size, axis)
+                    / line_size,
+                stride: tensor.stride(axis) / line_size,
+            },
+        }
+    }
+
+    fn index(&self, position: u32) -> u32 {
+        self.offset + position * self.stride
+    }
+}
+
+/// The prefixes of the current unit within its cube.
+#[derive(CubeType)]
+pub struct ScanPrefix<N: Numeric> {
+    pub inclusive: Line<N>,
+    pub exclusive: Line<N>,
+    /// The combination of the values of all units.
+    pub total: Line<N>,
+}
+
+/// Scan the values of all units within the cube, ordered by unit position.
+///
+/// With `use_planes`, the cube must be a single plane.
+#[cube]
+fn scan_cube<N: Numeric>(
+    value: Line<N>,
+    shared: &mut SharedMemory<Line<N>>,
+    #[comptime] operation: ScanOperation,
+    #[comptime] use_planes: bool,
+    #[comptime] float: bool,
+) -> ScanPrefix<N> {
+    if comptime!(use_planes && operation == ScanOperation::Sum) {
+        ScanPrefix::<N> {
+            inclusive: plane_inclusive_sum(value),
+            exclusive: plane_exclusive_sum(value),
+            total: plane_sum(value),
+        }
+    } else if comptime!(use_planes && operation == ScanOperation::Prod) {
+        ScanPrefix::<N> {
+            inclusive: plane_inclusive_prod(value),
+            exclusive: plane_exclusive_prod(value),
+            total: plane_prod(value),
+        }
+    } else {
+        scan_shared::<N>(value, shared, operation, float)
+    }
+}
+
+/// Hillis-Steele scan through shared memory, which works for any operation.
+#[cube]
+fn scan_shared<N: Numeric>(
+    value: Line<N>,
+    shared: &mut SharedMemory<Line<N>>,
+    #[comptime] operation: ScanOperation,
+    #[comptime] float: bool,
+) -> ScanPrefix<N> {
+    shared[UNIT_POS] = value;
+    sync_cube();
+
+    let line_size = value.size();
+    let mut offset = 1u32;
+    while offset < CUBE_DIM {
+        let current = shared[UNIT_POS];
+        let mut previous = identity::<N>(operation, float, line_size);
+        if UNIT_POS >= offset {
+            previous = shared[UNIT_POS - offset];
+        }
+        sync_cube();
+
+        shared[UNIT_POS] = combine::<N>(previous, current, operation);
+        sync_cube();
+        offset *= 2;
+    }
+
+    let mut exclusive = identity::<N>(operation, float, line_size);
+    if UNIT_POS > 0 {
+        exclusive = shared[UNIT_POS - 1];
+    }
+    let prefix = ScanPrefix::<N> {
+        inclusive: shared[UNIT_POS],
+        exclusive,
+        total: shared[CUBE_DIM - 1],
+    };
+
+    // The shared memory is reused by the next tile.
+    sync_cube();
+    prefix
+}
+
+/// The prefixes of the lanes of a line and their total, with a single lane when the lanes are
+/// along the axis. In perpendicular mode, the lanes are independent and the total is the line.
+#[cube]
+fn scan_line<N: Numeric>(
+    value: Line<N>,
+    #[comptime] operation: ScanOperation,
+    #[comptime] line_mode: LineMode,
+    #[comptime] float: bool,
+) -> ScanPrefix<N> {
+    let line_size = value.size();
+    match comptime!(line_mode) {
+        LineMode::Parallel => {
+            let mut inclusive = Line::empty(line_size);
+            let mut exclusive = Line::empty(line_size);
+            let mut total = identity::<N>(operation, float, 1u32);
+
+            #[unroll]
+            for k in 0..line_size {
+                exclusive[k] = total[0];
+                total = combine::<N>(total, Line::new(value[k]), operation);
+                inclusive[k] = total[0];
+            }
+
+            ScanPrefix::<N> {
+                inclusive,
+                exclusive,
+                total,
+            }
+        }
+        LineMode::Perpendicular => ScanPrefix::<N> {
+            inclusive: value,
+            exclusive: identity::<N>(operation, float, line_size),
+            total: value,
+        },
+    }
+}
+
+/// Spread the prefix of the previous lines over the lanes of a line.
+#[cube]
+fn spread<N: Numeric>(
+    prefix: Line<N>,
+    #[comptime] line_size: u32,
+    #[comptime] line_mode: LineMode,
+) -> Line<N> {
+    match comptime!(line_mode) {
+        LineMode::Parallel => Line::empty(line_size).fill(prefix[0]),
+        LineMode::Perpendicular => prefix,
+    }
+}
+
+/// The value leaving any other value unchanged when combined with it.
+///
+/// For floats, the maximum and minimum start from the infinities so they are kept as well.
+#[cube]
+fn identity<N: Numeric>(
+    #[comptime] operation: ScanOperation,
+    #[comptime] float: bool,
+    #[comptime] line_size: u32,
+) -> Line<N> {
+    let identity = match comptime!(operation) {
+        ScanOperation::Sum => N::from_int(0),
+        ScanOperation::Prod => N::from_int(1),
+        ScanOperation::Max => {
+            if comptime!(float) {
+                N::cast_from(f32::NEG_INFINITY)
+            } else {
+                N::min_value()
+            }
+        }
+        ScanOperation::Min => {
+            if comptime!(float) {
+                N::cast_from(f32::INFINITY)
+            } else {
+                N::max_value()
+            }
+        }
+        ScanOperation::LogSumExp => N::cast_from(f32::NEG_INFINITY),
+    };
+    Line::empty(line_size).fill(identity)
+}
+
+/// Combine the prefix `lhs` with the following elements `rhs`.
+#[cube]
+fn combine<N: Numeric>(
+    lhs: Line<N>,
+    rhs: Line<N>,
+    #[comptime] operation: ScanOperation,
+) -> Line<N> {
+    match comptime!(operation) {
+        ScanOperation::Sum => lhs + rhs,
+        ScanOperation::Prod => lhs * rhs,
+        ScanOperation::Max => max_propagate_nan(lhs, rhs),
+        ScanOperation::Min => min_propagate_nan(lhs, rhs),
+        ScanOperation::LogSumExp => log_add_exp(lhs, rhs),
+    }
+}
+
+/// Compute `log(exp(lhs) + exp(rhs))` in `f32` relative to the largest value,
+/// so the exponentials never overflow.
+#[cube]
+fn log_add_exp<N: Numeric>(lhs: Line<N>, rhs: Line<N>) -> Line<N> {
+    let size = lhs.size();
+    let lhs = Line::<f32>::cast_from(lhs);
+    let rhs = Line::<f32>::cast_from(rhs);
+    let one = Line::empty(size).fill(1.0f32);
+    let ln_2 = Line::empty(size).fill(core::f32::consts::LN_2);
+
+    let is_lhs_max = lhs.greater_than(rhs);
+    let max = select_many(is_lhs_max, lhs, rhs);
+    let min = select_many(is_lhs_max, rhs, lhs);
+    let sum = max + (one + (min - max).exp()).log();
+
+    // Equal operands skip the difference, which is NaN for equal infinities.
+    Line::cast_from(select_many(lhs.equal(rhs), max + ln_2, sum))
+}
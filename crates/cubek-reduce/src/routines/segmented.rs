@@ -0,0 +1,296 @@
+use cubecl::prelude::*;
+
+use crate::{
+    LineMode, ReduceDtypes, ReduceError, ReducePrecision,
+    components::instructions::{
+        DynamicAccumulatorItem, ReduceCoordinate, ReduceInstruction, ReduceOperation,
+        ReduceOperationConfig, reduce_inplace,
+    },
+    launch::support_plane,
+    routines::{cube_count_safe, reduce_all::fuse_cube},
+};
+
+/// Segments with at most this many elements are reduced by a single unit.
+const UNIT_SEGMENT_MAX: u32 = 32;
+/// Segments with at most this many elements are reduced by a single plane when planes are available.
+const PLANE_SEGMENT_MAX: u32 = 1024;
+/// The number of units of a cube reducing short segments.
+const UNIT_CUBE_SIZE: u32 = 64;
+/// The number of planes of a cube reducing long segments.
+const NUM_PLANES: u32 = 8;
+
+/// Reduce each segment `[offsets[i], offsets[i + 1])` of the `input` vector into `output[i]`.
+///
+/// This is meant for variable-length sequences packed into a single vector, where `offsets`
+/// holds the cumulative lengths of the sequences starting with 0. This works for every
+/// [ReduceOperationConfig] without padding the segments to a common length.
+///
+/// The routine is picked per segment from its length, without reading the offsets back to the host.
+/// A first launch reduces every short segment with a single unit and lists the other segments.
+/// A second launch assigns a cube to every listed segment, which is reduced by a single plane if
+/// it's not too long and planes are available, or by the full cube otherwise. That launch is sized
+/// for the most long segments the input can hold, so short segments don't cost a cube.
+///
+/// For [ArgMax](ReduceOperationConfig::ArgMax) and [ArgMin](ReduceOperationConfig::ArgMin),
+/// the returned index is relative to the start of the segment.
+/// An empty segment is written as the merged null accumulator of the operation.
+///
+/// The `offsets` must be increasing and not larger than the length of `input`, this isn't checked.
+/// Returns an error if `input` or `output` isn't a vector, or if `offsets` doesn't have one more
+/// element than `output`.
+pub fn segmented_reduce<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    offsets: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    operation: ReduceOperationConfig,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    if input.shape.len() != 1 || output.shape.len() != 1 {
+        return Err(ReduceError::Validation {
+            details: "Segmented reductions require a vector input and output.",
+        });
+    }
+    let num_segments = output.shape[0];
+    if offsets.shape != [num_segments + 1] {
+        return Err(ReduceError::Validation {
+            details: "The offsets must have one more element than the output.",
+        });
+    }
+    if num_segments == 0 {
+        return Ok(());
+    }
+
+    let hardware = &client.properties().hardware;
+    let use_planes = support_plane(client) && hardware.plane_size_min == hardware.plane_size_max;
+    let plane_size = if use_planes {
+        hardware.plane_size_max
+    } else {
+        32
+    };
+
+    // The segments too long for a unit are listed in any order, after their count.
+    let max_long_segments = num_segments.min(input.shape[0] / (UNIT_SEGMENT_MAX as usize + 1));
+    let num_long_handle = client.create_from_slice(u32::as_bytes(&[0]));
+    let long_segments_handle = client.empty(max_long_segments.max(1) * size_of::<u32>());
+    let long_segments_arg = || unsafe {
+        ArrayArg::from_raw_parts_and_size(
+            &long_segments_handle,
+            max_long_segments.max(1),
+            1,
+            size_of::<u32>(),
+        )
+    };
+    let num_long_arg =
+        || unsafe { ArrayArg::from_raw_parts_and_size(&num_long_handle, 1, 1, size_of::<u32>()) };
+
+    let (cube_count, _) = cube_count_safe(client, (num_segments as u32).div_ceil(UNIT_CUBE_SIZE));
+    unsafe {
+        segmented_reduce_unit_kernel::launch_unchecked::<R>(
+            client,
+            cube_count,
+            CubeDim::new_1d(UNIT_CUBE_SIZE),
+            input.as_tensor_arg(1),
+            offsets.as_tensor_arg(1),
+            output.as_tensor_arg(1),
+            long_segments_arg(),
+            num_long_arg(),
+            operation,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)?;
+
+    if max_long_segments == 0 {
+        return Ok(());
+    }
+
+    // NOTE: The plane size is a power of 2, so the unit count of the cube is also a power of 2.
+    let cube_dim = CubeDim::new_2d(plane_size, NUM_PLANES);
+    let (cube_count, _) = cube_count_safe(client, max_long_segments as u32);
+    unsafe {
+        segmented_reduce_cube_kernel::launch_unchecked::<R>(
+            client,
+            cube_count,
+            cube_dim,
+            input.as_tensor_arg(1),
+            offsets.as_tensor_arg(1),
+            output.as_tensor_arg(1),
+            long_segments_arg(),
+            num_long_arg(),
+            cube_dim.num_elems(),
+            use_planes,
+            operation,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn segmented_reduce_unit_kernel<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    offsets: &Tensor<Line<u32>>,
+    output: &mut Tensor<Line<Out>>,
+    long_segments: &mut Array<u32>,
+    num_long_segments: &mut Array<Atomic<u32>>,
+    #[comptime] config: ReduceOperationConfig,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    reduce_segment_unit::<(In, Acc), Out>(
+        input,
+        offsets,
+        output,
+        long_segments,
+        num_long_segments,
+        config,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn segmented_reduce_cube_kernel<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    offsets: &Tensor<Line<u32>>,
+    output: &mut Tensor<Line<Out>>,
+    long_segments: &Array<u32>,
+    num_long_segments: &Array<u32>,
+    #[comptime] shared_memory_size: u32,
+    #[comptime] use_planes: bool,
+    #[comptime] config: ReduceOperationConfig,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    reduce_segment_cube::<(In, Acc), Out>(
+        input,
+        offsets,
+        output,
+        long_segments,
+        num_long_segments,
+        shared_memory_size,
+        use_planes,
+        config,
+    );
+}
+
+/// Each unit reduces one segment, listing the segments too long for a single unit.
+#[cube]
+fn reduce_segment_unit<P: ReducePrecision, Out: Numeric>(
+    input: &Tensor<Line<P::EI>>,
+    offsets: &Tensor<Line<u32>>,
+    output: &mut Tensor<Line<Out>>,
+    long_segments: &mut Array<u32>,
+    num_long_segments: &mut Array<Atomic<u32>>,
+    #[comptime] config: ReduceOperationConfig,
+) {
+    let segment = ABSOLUTE_POS;
+    if segment >= output.shape(0) {
+        terminate!();
+    }
+
+    let start = offsets[segment * offsets.stride(0)][0];
+    let length = offsets[(segment + 1) * offsets.stride(0)][0] - start;
+    if length > UNIT_SEGMENT_MAX {
+        let index = Atomic::add(&num_long_segments[0], 1u32);
+        long_segments[index] = segment;
+        terminate!();
+    }
+
+    let inst = &<ReduceOperation as ReduceInstruction<P>>::from_config(config);
+    let accumulator = reduce_segment_part::<P>(inst, input, start, length, 0u32, 1u32);
+
+    output[segment * output.stride(0)] = Line::new(
+        <ReduceOperation as ReduceInstruction<P>>::merge_line::<Out>(inst, accumulator, length),
+    );
+}
+
+/// Each cube reduces one of the segments listed as too long for a single unit.
+#[cube]
+fn reduce_segment_cube<P: ReducePrecision, Out: Numeric>(
+    input: &Tensor<Line<P::EI>>,
+    offsets: &Tensor<Line<u32>>,
+    output: &mut Tensor<Line<Out>>,
+    long_segments: &Array<u32>,
+    num_long_segments: &Array<u32>,
+    #[comptime] shared_memory_size: u32,
+    #[comptime] use_planes: bool,
+    #[comptime] config: ReduceOperationConfig,
+) {
+    // The whole cube takes this branch.
+    if CUBE_POS >= num_long_segments[0] {
+        terminate!();
+    }
+
+    let segment = long_segments[CUBE_POS];
+    let start = offsets[segment * offsets.stride(0)][0];
+    let length = offsets[(segment + 1) * offsets.stride(0)][0] - start;
+
+    let inst = &<ReduceOperation as ReduceInstruction<P>>::from_config(config);
+
+    if comptime!(use_planes) {
+        // The whole cube takes this branch, so the other planes can safely leave.
+        if length <= PLANE_SEGMENT_MAX {
+            if UNIT_POS_Y == 0 {
+                let accumulator =
+                    reduce_segment_part::<P>(inst, input, start, length, UNIT_POS_X, CUBE_DIM_X);
+                let result =
+                    <ReduceOperation as ReduceInstruction<P>>::fuse_plane(inst, accumulator);
+
+                if UNIT_POS_X == 0 {
+                    output[segment * output.stride(0)] = Line::new(
+                        <ReduceOperation as ReduceInstruction<P>>::merge_line::<Out>(
+                            inst, result, length,
+                        ),
+                    );
+                }
+            }
+            terminate!();
+        }
+    }
+
+    let accumulator = reduce_segment_part::<P>(inst, input, start, length, UNIT_POS, CUBE_DIM);
+    let result = fuse_cube::<P, ReduceOperation>(inst, accumulator, shared_memory_size, 1u32);
+
+    if UNIT_POS == 0 {
+        output[segment * output.stride(0)] = Line::new(
+            <ReduceOperation as ReduceInstruction<P>>::merge_line::<Out>(inst, result, length),
+        );
+    }
+}
+
+/// Reduce the elements of the segment starting at `first` with the given `step`.
+#[cube]
+fn reduce_segment_part<P: ReducePrecision>(
+    inst: &ReduceOperation,
+    input: &Tensor<Line<P::EI>>,
+    start: u32,
+    length: u32,
+    first: u32,
+    step: u32,
+) -> DynamicAccumulatorItem<P::EA> {
+    let requirements = <ReduceOperation as ReduceInstruction<P>>::requirements(inst);
+    let stride = input.stride(0);
+
+    let mut accumulator = <ReduceOperation as ReduceInstruction<P>>::null_accumulator(inst, 1u32);
+    let mut k = first;
+    while k < length {
+        let coordinate = ReduceCoordinate::new(k, requirements, 1u32, LineMode::Parallel);
+        reduce_inplace::<P, ReduceOperation>(
+            inst,
+            &mut accumulator,
+            input[(start + k) * stride],
+            coordinate,
+            false,
+        );
+        k += step;
+    }
+    accumulator
+}
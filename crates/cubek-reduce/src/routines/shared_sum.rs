@@ -2,14 +2,15 @@ use cubecl::features::TypeUsage;
 use cubecl::ir::ElemType;
 use cubecl::prelude::*;
 
-use crate::ReduceError;
+use crate::{Determinism, ReduceError};
 
 /// Sum all the elements of the input tensor distributed over `cube_count` cubes.
 ///
 /// This is an optimized version for summing large tensors using multiple cubes.
 /// For summing a single axis, the regular reduce entry point is preferred.
 ///
-/// Return an error if atomic addition is not supported for the type `N`.
+/// The sums of all cubes are combined using atomic additions, see [shared_sum_with] for a
+/// reproducible alternative. Return an error if atomic addition is not supported for the type `N`.
 ///
 /// # Important
 ///
@@ -58,12 +59,39 @@ pub fn shared_sum<R: Runtime>(
     output: TensorHandleRef<R>,
     cube_count: u32,
     input_elem: ElemType,
+) -> Result<(), ReduceError> {
+    shared_sum_with(
+        client,
+        input,
+        output,
+        cube_count,
+        input_elem,
+        Determinism::Relaxed,
+    )
+}
+
+/// Same as [shared_sum], with control over the reproducibility of the result.
+///
+/// With [Determinism::Relaxed], the sums of all cubes are combined using atomic additions,
+/// so the rounding may vary from one run to another.
+///
+/// With [Determinism::Strict], each cube writes its sum to a temporary buffer which is then
+/// summed by a single cube in a fixed order, making the result bitwise reproducible.
+/// Atomic addition isn't required in that case.
+pub fn shared_sum_with<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    cube_count: u32,
+    input_elem: ElemType,
+    determinism: Determinism,
 ) -> Result<(), ReduceError> {
     // Check that the client supports atomic addition.
-    if !client
-        .properties()
-        .type_usage(StorageType::Atomic(input_elem))
-        .contains(TypeUsage::AtomicAdd)
+    if determinism == Determinism::Relaxed
+        && !client
+            .properties()
+            .type_usage(StorageType::Atomic(input_elem))
+            .contains(TypeUsage::AtomicAdd)
     {
         return Err(ReduceError::MissingAtomicAdd(input_elem.into()));
     }
@@ -81,13 +109,25 @@ pub fn shared_sum<R: Runtime>(
     let cube_dim = CubeDim::new_2d(32, 8); // NOTE: If you change that, keep the unit count a power of 2.
     let num_units = cube_count * cube_dim.num_elems();
     let num_lines_per_unit = input_len.div_ceil(num_units * line_size);
-    let cube_count = CubeCount::new_1d(cube_count);
+
+    if determinism == Determinism::Strict {
+        return shared_sum_deterministic(
+            client,
+            input,
+            output,
+            cube_count,
+            cube_dim,
+            line_size,
+            num_lines_per_unit,
+            input_elem,
+        );
+    }
 
     // Launch kernel
     let result = unsafe {
         shared_sum_kernel::launch_unchecked(
             client,
-            cube_count,
+            CubeCount::new_1d(cube_count),
             cube_dim,
             input.as_tensor_arg(line_size as u8),
             output.as_tensor_arg(1),
@@ -104,6 +144,50 @@ pub fn shared_sum<R: Runtime>(
     }
 }
 
+/// Same as [shared_sum], but the sums of the cubes are combined in a second kernel
+/// instead of with atomics.
+#[allow(clippy::too_many_arguments)]
+fn shared_sum_deterministic<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    cube_count: u32,
+    cube_dim: CubeDim,
+    line_size: u32,
+    num_lines_per_unit: u32,
+    input_elem: ElemType,
+) -> Result<(), ReduceError> {
+    let partials = client.empty(cube_count as usize * input.elem_size);
+
+    unsafe {
+        shared_sum_partials_kernel::launch_unchecked(
+            client,
+            CubeCount::new_1d(cube_count),
+            cube_dim,
+            input.as_tensor_arg(line_size as u8),
+            ArrayArg::from_raw_parts_and_size(&partials, cube_count as usize, 1, input.elem_size),
+            cube_dim.num_elems(),
+            line_size,
+            num_lines_per_unit,
+            input_elem,
+        )
+    }
+    .map_err(ReduceError::Launch)?;
+
+    unsafe {
+        sum_partials_kernel::launch_unchecked(
+            client,
+            CubeCount::new_single(),
+            cube_dim,
+            ArrayArg::from_raw_parts_and_size(&partials, cube_count as usize, 1, input.elem_size),
+            output.as_tensor_arg(1),
+            cube_dim.num_elems(),
+            input_elem,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
 #[cube(launch_unchecked)]
 fn shared_sum_kernel<N: Numeric>(
     input: &Tensor<Line<N>>,
@@ -113,6 +197,63 @@ fn shared_sum_kernel<N: Numeric>(
     #[comptime] num_lines_per_unit: u32,
     #[define(N)] _dtype: ElemType,
 ) {
+    let sum = cube_sum(input, shared_memory_size, line_size, num_lines_per_unit);
+
+    // Add the sum for the current cube to the output.
+    if UNIT_POS == 0 {
+        Atomic::add(&output[0], sum);
+    }
+}
+
+#[cube(launch_unchecked)]
+fn shared_sum_partials_kernel<N: Numeric>(
+    input: &Tensor<Line<N>>,
+    partials: &mut Array<N>,
+    #[comptime] shared_memory_size: u32,
+    #[comptime] line_size: u32,
+    #[comptime] num_lines_per_unit: u32,
+    #[define(N)] _dtype: ElemType,
+) {
+    let sum = cube_sum(input, shared_memory_size, line_size, num_lines_per_unit);
+
+    // Write the sum for the current cube, it's combined with the others later.
+    if UNIT_POS == 0 {
+        partials[CUBE_POS] = sum;
+    }
+}
+
+#[cube(launch_unchecked)]
+fn sum_partials_kernel<N: Numeric>(
+    partials: &Array<N>,
+    output: &mut Tensor<N>,
+    #[comptime] shared_memory_size: u32,
+    #[define(N)] _dtype: ElemType,
+) {
+    let mut shared_memory = SharedMemory::new_lined(shared_memory_size, 1u32);
+    shared_memory[UNIT_POS] = Line::empty(1u32).fill(N::from_int(0));
+
+    // Each unit sums the partials at a fixed stride, so the order never changes.
+    let mut k = UNIT_POS;
+    while k < partials.len() {
+        shared_memory[UNIT_POS] += Line::new(partials[k]);
+        k += CUBE_DIM;
+    }
+
+    let line = sum_shared_memory(&mut shared_memory);
+
+    if UNIT_POS == 0 {
+        output[0] += line[0];
+    }
+}
+
+/// Sum the lines assigned to the current cube into a single value.
+#[cube]
+fn cube_sum<N: Numeric>(
+    input: &Tensor<Line<N>>,
+    #[comptime] shared_memory_size: u32,
+    #[comptime] line_size: u32,
+    #[comptime] num_lines_per_unit: u32,
+) -> N {
     let mut shared_memory = SharedMemory::new_lined(shared_memory_size, line_size);
     shared_memory[UNIT_POS] = Line::empty(line_size).fill(N::from_int(0));
 
@@ -140,10 +281,7 @@ fn shared_sum_kernel<N: Numeric>(
         sum.store(update);
     }
 
-    // Add the sum for the current cube to the output.
-    if UNIT_POS == 0 {
-        Atomic::add(&output[0], sum.consume());
-    }
+    sum.consume()
 }
 
 // This is a simplified version of [tree_reduce].
@@ -0,0 +1,359 @@
+use cubecl::prelude::*;
+
+use crate::{
+    LineMode, ReduceDtypes, ReduceError,
+    components::{
+        global::cube::reduce_tree,
+        instructions::{LogSumExp, LogSumExpAccumulator, LogSumExpState, SharedAccumulator},
+    },
+    launch::{RoutineStrategy, prepare_routine},
+    routines::{GlobalReduceBlueprint, ReduceLineSettings, ReduceProblem, scan::vector_offset},
+    validate_axis,
+};
+
+/// The longest row kept in the registers of a unit.
+const UNIT_CACHE_MAX: u32 = 32;
+/// The maximum number of elements of the rows kept in the shared memory of a cube.
+const SHARED_CACHE_MAX: u32 = 4096;
+
+/// Compute the softmax of the given `axis` of the `input` tensor and write it into `output`.
+///
+/// The `output` tensor must have the same shape as `input`. See [log_softmax] for the details.
+pub fn softmax<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    axis: usize,
+    strategy: RoutineStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    launch_softmax(client, input, output, axis, strategy, false, dtypes)
+}
+
+/// Compute the logarithm of the softmax of the given `axis` of the `input` tensor and write it
+/// into `output`.
+///
+/// The `output` tensor must have the same shape as `input`. Each row is reduced by a unit,
+/// a plane or a cube depending on the [RoutineStrategy], in a single kernel. A first pass
+/// computes the log-sum-exp of the row with a running maximum, as in
+/// [LogSumExp](crate::components::instructions::LogSumExp), and a second pass writes the
+/// normalized values. When the row is short enough, it is kept in registers for the unit
+/// routine or in shared memory for the plane and cube routines, so the input is read only once.
+/// Otherwise, the second pass reads the input again.
+///
+/// The exponentials and logarithms are computed in `f32`. For `f16` or `bf16` inputs,
+/// an `f32` accumulation type is recommended.
+///
+/// Returns an error if the plane routine is requested but planes are unavailable or of variable size.
+pub fn log_softmax<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    axis: usize,
+    strategy: RoutineStrategy,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    launch_softmax(client, input, output, axis, strategy, true, dtypes)
+}
+
+/// The comptime settings of the softmax kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SoftmaxSettings {
+    /// Write the logarithm of the softmax.
+    log: bool,
+    /// Keep the rows in registers or shared memory.
+    cached: bool,
+    /// The number of elements of the cache, the maximum of the routine when the rows are cached
+    /// and 1 otherwise, so the kernel doesn't depend on the length of the rows.
+    cache_capacity: u32,
+    /// The number of elements of the shared memory of a cube.
+    shared_memory_size: u32,
+}
+
+fn launch_softmax<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    output: TensorHandleRef<R>,
+    axis: usize,
+    strategy: RoutineStrategy,
+    log: bool,
+    dtypes: ReduceDtypes,
+) -> Result<(), ReduceError> {
+    validate_axis(input.shape.len(), axis)?;
+    if output.shape != input.shape {
+        return Err(ReduceError::MismatchShape {
+            expected_shape: input.shape.to_vec(),
+            output_shape: output.shape.to_vec(),
+        });
+    }
+
+    let hardware = &client.properties().hardware;
+    if matches!(strategy, RoutineStrategy::Plane(_))
+        && hardware.plane_size_min != hardware.plane_size_max
+    {
+        return Err(ReduceError::ImprecisePlaneDim);
+    }
+
+    // The routines only need the shape of the reduction, not its output.
+    let row_len = input.shape[axis] as u32;
+    let num_rows = (input.shape.iter().product::<usize>() / input.shape[axis]) as u32;
+    let problem = ReduceProblem {
+        vector_size: row_len,
+        vector_count: num_rows,
+        axis: axis as u32,
+        dtypes,
+    };
+    let line_mode = match input.strides[axis] {
+        1 => LineMode::Parallel,
+        _ => LineMode::Perpendicular,
+    };
+    let line_settings = ReduceLineSettings {
+        line_mode,
+        line_size_input: 1,
+        line_size_output: 1,
+    };
+    let (blueprint, launch) = prepare_routine(client, problem, line_settings, strategy)?;
+
+    let cube_dim = launch.cube_dim;
+    let (cache_len, cache_max) = match blueprint.global {
+        GlobalReduceBlueprint::Unit(_) => (row_len, UNIT_CACHE_MAX),
+        GlobalReduceBlueprint::Plane(_) => (row_len * cube_dim.y, SHARED_CACHE_MAX),
+        GlobalReduceBlueprint::Cube(_) => (row_len, SHARED_CACHE_MAX),
+    };
+    let cached = cache_len <= cache_max;
+    let settings = SoftmaxSettings {
+        log,
+        cached,
+        cache_capacity: if cached { cache_max } else { 1 },
+        shared_memory_size: cube_dim.num_elems(),
+    };
+
+    unsafe {
+        softmax_kernel::launch_unchecked::<R>(
+            client,
+            launch.cube_count,
+            cube_dim,
+            input.as_tensor_arg(1),
+            output.as_tensor_arg(1),
+            ScalarArg::new(axis as u32),
+            ScalarArg::new(num_rows),
+            blueprint.global,
+            settings,
+            dtypes.input,
+            dtypes.output,
+            dtypes.accumulation,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn softmax_kernel<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    output: &mut Tensor<Line<Out>>,
+    axis: u32,
+    num_rows: u32,
+    #[comptime] blueprint: GlobalReduceBlueprint,
+    #[comptime] settings: SoftmaxSettings,
+    #[define(In)] _input_dtype: StorageType,
+    #[define(Out)] _output_dtype: StorageType,
+    #[define(Acc)] _acc_dtype: StorageType,
+) {
+    match comptime!(blueprint) {
+        GlobalReduceBlueprint::Unit(_) => {
+            softmax_unit::<In, Out, Acc>(input, output, axis, num_rows, settings)
+        }
+        GlobalReduceBlueprint::Plane(_) => {
+            softmax_plane::<In, Out, Acc>(input, output, axis, num_rows, settings)
+        }
+        GlobalReduceBlueprint::Cube(_) => {
+            softmax_cube::<In, Out, Acc>(input, output, axis, num_rows, settings)
+        }
+    };
+}
+
+/// Each unit normalizes a full row, keeping it in registers when it's short enough.
+#[cube]
+fn softmax_unit<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    output: &mut Tensor<Line<Out>>,
+    axis: u32,
+    num_rows: u32,
+    #[comptime] settings: SoftmaxSettings,
+) {
+    let row = ABSOLUTE_POS;
+    if row >= num_rows {
+        terminate!();
+    }
+
+    let row = SoftmaxRow::new(input, output, row, axis, input.shape(axis));
+    let mut cache = Array::<Acc>::new(settings.cache_capacity);
+
+    let mut state = LogSumExpState::<Acc>::null(1u32);
+    for k in 0..row.length {
+        let value = row.read::<In, Acc>(input, k);
+        if comptime!(settings.cached) {
+            cache[k] = value[0];
+        }
+        state = state.merge(&LogSumExpState::<Acc>::from_item(value));
+    }
+
+    let lse = Line::<f32>::cast_from(state.value());
+    for k in 0..row.length {
+        let value = if comptime!(settings.cached) {
+            Line::new(cache[k])
+        } else {
+            row.read::<In, Acc>(input, k)
+        };
+        row.write::<Acc, Out>(output, k, value, lse, settings.log);
+    }
+}
+
+/// Each plane normalizes a full row, keeping it in shared memory when it's short enough.
+#[cube]
+fn softmax_plane<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    output: &mut Tensor<Line<Out>>,
+    axis: u32,
+    num_rows: u32,
+    #[comptime] settings: SoftmaxSettings,
+) {
+    // Idle planes still take part in the plane instructions, but without any element.
+    let row = CUBE_POS * CUBE_DIM_Y + UNIT_POS_Y;
+    let length = select(row < num_rows, input.shape(axis), 0u32);
+
+    let row = SoftmaxRow::new(input, output, row, axis, length);
+    let mut cache = SharedMemory::<Acc>::new(settings.cache_capacity);
+    let cache_offset = UNIT_POS_Y * input.shape(axis);
+
+    let mut state = LogSumExpState::<Acc>::null(1u32);
+    let mut k = UNIT_POS_X;
+    while k < row.length {
+        let value = row.read::<In, Acc>(input, k);
+        if comptime!(settings.cached) {
+            cache[cache_offset + k] = value[0];
+        }
+        state = state.merge(&LogSumExpState::<Acc>::from_item(value));
+        k += CUBE_DIM_X;
+    }
+
+    // Each unit only reads back its own elements, so no synchronization is needed.
+    let lse = Line::<f32>::cast_from(state.merge_plane().value());
+    let mut k = UNIT_POS_X;
+    while k < row.length {
+        let value = if comptime!(settings.cached) {
+            Line::new(cache[cache_offset + k])
+        } else {
+            row.read::<In, Acc>(input, k)
+        };
+        row.write::<Acc, Out>(output, k, value, lse, settings.log);
+        k += CUBE_DIM_X;
+    }
+}
+
+/// Each cube normalizes a full row, keeping it in shared memory when it's short enough.
+#[cube]
+fn softmax_cube<In: Numeric, Out: Numeric, Acc: Numeric>(
+    input: &Tensor<Line<In>>,
+    output: &mut Tensor<Line<Out>>,
+    axis: u32,
+    num_rows: u32,
+    #[comptime] settings: SoftmaxSettings,
+) {
+    // Idle cubes still take part in the synchronizations, but without any element.
+    let row = CUBE_POS;
+    let length = select(row < num_rows, input.shape(axis), 0u32);
+
+    let row = SoftmaxRow::new(input, output, row, axis, length);
+    let mut cache = SharedMemory::<Acc>::new(settings.cache_capacity);
+
+    let mut state = LogSumExpState::<Acc>::null(1u32);
+    let mut k = UNIT_POS;
+    while k < row.length {
+        let value = row.read::<In, Acc>(input, k);
+        if comptime!(settings.cached) {
+            cache[k] = value[0];
+        }
+        state = state.merge(&LogSumExpState::<Acc>::from_item(value));
+        k += CUBE_DIM;
+    }
+
+    let mut shared =
+        LogSumExpAccumulator::<Acc>::allocate(settings.shared_memory_size, 1u32, false);
+    LogSumExpAccumulator::<Acc>::write(&mut shared, UNIT_POS, state);
+    sync_cube();
+
+    // The tree leaves the fused state in every unit.
+    let mut fused = LogSumExpState::<Acc>::null(1u32);
+    reduce_tree::<(Acc, Acc), LogSumExp>(
+        &LogSumExp {},
+        &mut shared,
+        &mut fused,
+        UNIT_POS,
+        settings.shared_memory_size,
+    );
+
+    let lse = Line::<f32>::cast_from(fused.value());
+    let mut k = UNIT_POS;
+    while k < row.length {
+        let value = if comptime!(settings.cached) {
+            Line::new(cache[k])
+        } else {
+            row.read::<In, Acc>(input, k)
+        };
+        row.write::<Acc, Out>(output, k, value, lse, settings.log);
+        k += CUBE_DIM;
+    }
+}
+
+/// The location of a row in the input and output tensors.
+#[derive(CubeType)]
+struct SoftmaxRow {
+    input_offset: u32,
+    input_stride: u32,
+    output_offset: u32,
+    output_stride: u32,
+    length: u32,
+}
+
+#[cube]
+impl SoftmaxRow {
+    fn new<In: Numeric, Out: Numeric>(
+        input: &Tensor<Line<In>>,
+        output: &Tensor<Line<Out>>,
+        row: u32,
+        axis: u32,
+        length: u32,
+    ) -> SoftmaxRow {
+        SoftmaxRow {
+            input_offset: vector_offset::<In>(input, row, axis),
+            input_stride: input.stride(axis),
+            output_offset: vector_offset::<Out>(output, row, axis),
+            output_stride: output.stride(axis),
+            length,
+        }
+    }
+
+    fn read<In: Numeric, Acc: Numeric>(&self, input: &Tensor<Line<In>>, k: u32) -> Line<Acc> {
+        Line::cast_from(input[self.input_offset + k * self.input_stride])
+    }
+
+    /// Write `value - lse` or its exponential.
+    fn write<Acc: Numeric, Out: Numeric>(
+        &self,
+        output: &mut Tensor<Line<Out>>,
+        k: u32,
+        value: Line<Acc>,
+        lse: Line<f32>,
+        #[comptime] log: bool,
+    ) {
+        let shifted = Line::<f32>::cast_from(value) - lse;
+        let result = if comptime!(log) {
+            shifted
+        } else {
+            shifted.exp()
+        };
+        output[self.output_offset + k * self.output_stride] = Line::cast_from(result);
+    }
+}
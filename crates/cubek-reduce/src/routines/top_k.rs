@@ -0,0 +1,397 @@
+use cubecl::prelude::*;
+
+use crate::{
+    ReduceError, components::instructions::SharedAccumulator, routines::cube_count_safe,
+    validate_axis,
+};
+
+/// The largest `k` supported by [top_k].
+///
+/// Each unit keeps its list in registers, so a larger `k` would spill.
+pub const MAX_TOP_K: u32 = 64;
+
+/// Which elements are selected by [top_k].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TopKConfig {
+    /// The number of elements to select along the axis.
+    pub k: u32,
+    /// Select the largest elements if `true`, the smallest ones otherwise.
+    pub largest: bool,
+}
+
+/// How the work of [top_k] is distributed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TopKStrategy {
+    /// Each unit selects the elements of a full vector.
+    ///
+    /// This is best when there are many short vectors.
+    Unit,
+    /// All the units of a cube select the elements of a full vector together,
+    /// then merge their lists through shared memory.
+    ///
+    /// This is best when there are few long vectors.
+    Cube,
+}
+
+/// The number of units collaborating on a vector with [TopKStrategy::Cube].
+///
+/// NOTE: This must stay a power of 2 for the merge tree.
+const CUBE_SIZE: u32 = 32;
+const UNIT_CUBE_SIZE: u32 = 64;
+
+/// Select the `k` largest or smallest elements along the given `axis` of the `input` tensor.
+///
+/// The selected elements are written into `values` sorted from the best to the worst, with their
+/// position along the axis written at the same place into `indices`. Equal elements are ordered by
+/// increasing position, so the results are stable. NaN values are selected after all the others.
+///
+/// The `values` tensor has the element type `dtype` of the input and `indices` holds `u32`.
+/// Both must have the same shape as `input` except with a value of `k` for the given `axis`.
+///
+/// Returns an error if `k` is 0, larger than [MAX_TOP_K] or larger than the size of the axis.
+#[allow(clippy::too_many_arguments)]
+pub fn top_k<R: Runtime>(
+    client: &ComputeClient<R>,
+    input: TensorHandleRef<R>,
+    values: TensorHandleRef<R>,
+    indices: TensorHandleRef<R>,
+    axis: usize,
+    config: TopKConfig,
+    strategy: TopKStrategy,
+    dtype: StorageType,
+) -> Result<(), ReduceError> {
+    validate_axis(input.shape.len(), axis)?;
+    if config.k == 0 || config.k > MAX_TOP_K {
+        return Err(ReduceError::Validation {
+            details: "The number of selected elements must be between 1 and `MAX_TOP_K`.",
+        });
+    }
+    if config.k as usize > input.shape[axis] {
+        return Err(ReduceError::Validation {
+            details: "Can't select more elements than the size of the axis.",
+        });
+    }
+
+    let mut expected_shape = input.shape.to_vec();
+    expected_shape[axis] = config.k as usize;
+    for output_shape in [values.shape, indices.shape] {
+        if output_shape != expected_shape {
+            return Err(ReduceError::MismatchShape {
+                expected_shape,
+                output_shape: output_shape.to_vec(),
+            });
+        }
+    }
+
+    let num_vectors = (input.shape.iter().product::<usize>() / input.shape[axis]) as u32;
+    let (cube_dim, working_cubes) = match strategy {
+        TopKStrategy::Unit => (
+            CubeDim::new_1d(UNIT_CUBE_SIZE),
+            num_vectors.div_ceil(UNIT_CUBE_SIZE),
+        ),
+        TopKStrategy::Cube => (CubeDim::new_1d(CUBE_SIZE), num_vectors),
+    };
+    let (cube_count, _) = cube_count_safe(client, working_cubes);
+
+    unsafe {
+        top_k_kernel::launch_unchecked::<R>(
+            client,
+            cube_count,
+            cube_dim,
+            input.as_tensor_arg(1),
+            values.as_tensor_arg(1),
+            indices.as_tensor_arg(1),
+            ScalarArg::new(axis as u32),
+            ScalarArg::new(num_vectors),
+            config,
+            strategy,
+            dtype,
+        )
+    }
+    .map_err(ReduceError::Launch)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cube(launch_unchecked)]
+fn top_k_kernel<N: Numeric>(
+    input: &Tensor<Line<N>>,
+    values: &mut Tensor<Line<N>>,
+    indices: &mut Tensor<Line<u32>>,
+    axis: u32,
+    num_vectors: u32,
+    #[comptime] config: TopKConfig,
+    #[comptime] strategy: TopKStrategy,
+    #[define(N)] _dtype: StorageType,
+) {
+    let (vector, first, step) = match comptime!(strategy) {
+        TopKStrategy::Unit => (ABSOLUTE_POS, 0u32, 1u32),
+        TopKStrategy::Cube => (CUBE_POS, UNIT_POS, CUBE_DIM),
+    };
+
+    if vector >= num_vectors {
+        terminate!();
+    }
+
+    // Find the offsets of the vector from its coordinates along the other axes.
+    let mut remainder = vector;
+    let mut input_offset = 0u32;
+    let mut values_offset = 0u32;
+    let mut indices_offset = 0u32;
+    for i in 0..input.rank() {
+        let dim = input.rank() - 1 - i;
+        if dim != axis {
+            let shape = input.shape(dim);
+            let coordinate = remainder % shape;
+            remainder /= shape;
+            input_offset += coordinate * input.stride(dim);
+            values_offset += coordinate * values.stride(dim);
+            indices_offset += coordinate * indices.stride(dim);
+        }
+    }
+
+    let input_stride = input.stride(axis);
+    let mut list = TopKList::<N>::new(config);
+    let mut position = first;
+    while position < input.shape(axis) {
+        list.insert(
+            input[input_offset + position * input_stride][0],
+            position,
+            config,
+        );
+        position += step;
+    }
+
+    if comptime!(strategy == TopKStrategy::Cube) {
+        let merged = merge_cube::<N>(list, config);
+        if UNIT_POS == 0 {
+            write_list::<N>(
+                values,
+                indices,
+                values_offset,
+                indices_offset,
+                axis,
+                &merged,
+                config,
+            );
+        }
+    } else {
+        write_list::<N>(
+            values,
+            indices,
+            values_offset,
+            indices_offset,
+            axis,
+            &list,
+            config,
+        );
+    }
+}
+
+#[cube]
+fn write_list<N: Numeric>(
+    values: &mut Tensor<Line<N>>,
+    indices: &mut Tensor<Line<u32>>,
+    values_offset: u32,
+    indices_offset: u32,
+    axis: u32,
+    list: &TopKList<N>,
+    #[comptime] config: TopKConfig,
+) {
+    for n in 0..config.k {
+        values[values_offset + n * values.stride(axis)] = Line::new(list.values[n]);
+        indices[indices_offset + n * indices.stride(axis)] = Line::new(list.indices[n]);
+    }
+}
+
+/// Merge the lists of all units within the cube, the result is only valid for the first unit.
+#[cube]
+fn merge_cube<N: Numeric>(list: TopKList<N>, #[comptime] config: TopKConfig) -> TopKList<N> {
+    let mut shared = TopKAccumulator::<N>::allocate(CUBE_SIZE, config.k, false);
+    TopKAccumulator::<N>::write(&mut shared, UNIT_POS, list);
+    sync_cube();
+
+    let mut stride = CUBE_DIM / 2;
+    while stride > 0 {
+        if UNIT_POS < stride {
+            let lhs = TopKAccumulator::<N>::read(&shared, UNIT_POS);
+            let rhs = TopKAccumulator::<N>::read(&shared, UNIT_POS + stride);
+            TopKAccumulator::<N>::write(&mut shared, UNIT_POS, lhs.merge(&rhs, config));
+        }
+        sync_cube();
+        stride /= 2;
+    }
+
+    TopKAccumulator::<N>::read(&shared, 0)
+}
+
+/// A list of the best `k` elements seen so far, sorted from the best to the worst.
+///
+/// Empty slots hold the index `u32::MAX`, so any element, even NaN or an infinity,
+/// is better than them.
+#[derive(CubeType)]
+pub struct TopKList<N: Numeric> {
+    pub values: Array<N>,
+    pub indices: Array<u32>,
+}
+
+#[cube]
+impl<N: Numeric> TopKList<N> {
+    /// A list without any element.
+    pub fn new(#[comptime] config: TopKConfig) -> TopKList<N> {
+        let mut values = Array::<N>::new(config.k);
+        let mut indices = Array::<u32>::new(config.k);
+        let empty = if comptime!(config.largest) {
+            N::min_value()
+        } else {
+            N::max_value()
+        };
+
+        for n in 0..config.k {
+            values[n] = empty;
+            indices[n] = u32::MAX;
+        }
+
+        TopKList::<N> { values, indices }
+    }
+
+    /// Insert the element in its sorted position if it is better than the last one,
+    /// dropping the last element.
+    pub fn insert(&mut self, value: N, index: u32, #[comptime] config: TopKConfig) {
+        let last = comptime!(config.k - 1);
+        if is_better::<N>(
+            value,
+            index,
+            self.values[last],
+            self.indices[last],
+            config.largest,
+        ) {
+            self.values[last] = value;
+            self.indices[last] = index;
+
+            // Move the element up until the previous one is better.
+            for offset in 1..config.k {
+                let position = config.k - offset;
+                let previous = position - 1;
+                if !is_better::<N>(
+                    value,
+                    index,
+                    self.values[previous],
+                    self.indices[previous],
+                    config.largest,
+                ) {
+                    break;
+                }
+                self.values[position] = self.values[previous];
+                self.indices[position] = self.indices[previous];
+                self.values[previous] = value;
+                self.indices[previous] = index;
+            }
+        }
+    }
+
+    /// Merge two sorted lists, keeping the best `k` elements.
+    pub fn merge(&self, other: &TopKList<N>, #[comptime] config: TopKConfig) -> TopKList<N> {
+        let mut values = Array::<N>::new(config.k);
+        let mut indices = Array::<u32>::new(config.k);
+
+        // There are `n` elements taken before position `n`, so `i` and `j` stay below `k`.
+        let mut i = 0u32;
+        let mut j = 0u32;
+        for n in 0..config.k {
+            if is_better::<N>(
+                self.values[i],
+                self.indices[i],
+                other.values[j],
+                other.indices[j],
+                config.largest,
+            ) {
+                values[n] = self.values[i];
+                indices[n] = self.indices[i];
+                i += 1;
+            } else {
+                values[n] = other.values[j];
+                indices[n] = other.indices[j];
+                j += 1;
+            }
+        }
+
+        TopKList::<N> { values, indices }
+    }
+}
+
+/// Whether the first element comes before the second one, ties are broken by the lowest index.
+///
+/// Numbers come first, then NaN values, then the empty slots of a [TopKList].
+#[cube]
+fn is_better<N: Numeric>(
+    value: N,
+    index: u32,
+    other: N,
+    other_index: u32,
+    #[comptime] largest: bool,
+) -> bool {
+    let rank = select(index == u32::MAX, 2u32, u32::cast_from(value != value));
+    let other_rank = select(
+        other_index == u32::MAX,
+        2u32,
+        u32::cast_from(other != other),
+    );
+
+    let wins = if comptime!(largest) {
+        value > other
+    } else {
+        value < other
+    };
+    // NaN values only tie with each other, by their index.
+    let ties = (value == other || rank == 1) && index < other_index;
+
+    rank < other_rank || (rank == other_rank && (wins || ties))
+}
+
+/// The shared memory used to merge [TopKList].
+///
+/// For this accumulator, the line size is the number of elements `k` in each list.
+#[derive(CubeType)]
+pub struct TopKAccumulator<N: Numeric> {
+    pub values: SharedMemory<N>,
+    pub indices: SharedMemory<u32>,
+    #[cube(comptime)]
+    pub k: u32,
+}
+
+#[cube]
+impl<N: Numeric> SharedAccumulator for TopKAccumulator<N> {
+    type Item = TopKList<N>;
+
+    fn allocate(
+        #[comptime] length: u32,
+        #[comptime] line_size: u32,
+        #[comptime] _coordinate: bool,
+    ) -> Self {
+        TopKAccumulator::<N> {
+            values: SharedMemory::new(comptime!(length * line_size)),
+            indices: SharedMemory::new(comptime!(length * line_size)),
+            k: line_size,
+        }
+    }
+
+    fn read(accumulator: &Self, index: u32) -> Self::Item {
+        let k = comptime!(accumulator.k);
+        let mut values = Array::<N>::new(k);
+        let mut indices = Array::<u32>::new(k);
+        for n in 0..k {
+            values[n] = accumulator.values[index * k + n];
+            indices[n] = accumulator.indices[index * k + n];
+        }
+
+        TopKList::<N> { values, indices }
+    }
+
+    fn write(accumulator: &mut Self, index: u32, item: Self::Item) {
+        let k = comptime!(accumulator.k);
+        for n in 0..k {
+            accumulator.values[index * k + n] = item.values[n];
+            accumulator.indices[index * k + n] = item.indices[n];
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::suite::test_case::assert_approx_equal;
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubek_reduce::{
+    ReduceError, arg_extremum,
+    components::instructions::{ArgExtremumConfig, Extremum, TieBreak},
+    launch::RoutineStrategy,
+    routines::{BlueprintStrategy, cube::CubeStrategy, plane::PlaneStrategy, unit::UnitStrategy},
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+// Only a few distinct values so that most vectors contain ties for their extremum.
+static PRECISION: i32 = 2;
+
+#[test]
+pub fn test_arg_max_first_index() {
+    test_case().test_arg_extremum(Extremum::Max, TieBreak::FirstIndex, false);
+}
+
+#[test]
+pub fn test_arg_max_last_index() {
+    test_case().test_arg_extremum(Extremum::Max, TieBreak::LastIndex, false);
+}
+
+#[test]
+pub fn test_arg_min_first_index() {
+    test_case().test_arg_extremum(Extremum::Min, TieBreak::FirstIndex, false);
+}
+
+#[test]
+pub fn test_arg_min_last_index() {
+    test_case().test_arg_extremum(Extremum::Min, TieBreak::LastIndex, false);
+}
+
+#[test]
+pub fn test_arg_max_nan() {
+    test_case().test_arg_extremum(Extremum::Max, TieBreak::FirstIndex, true);
+    test_case().test_arg_extremum(Extremum::Max, TieBreak::LastIndex, true);
+}
+
+#[test]
+pub fn test_arg_min_nan() {
+    test_case().test_arg_extremum(Extremum::Min, TieBreak::FirstIndex, true);
+    test_case().test_arg_extremum(Extremum::Min, TieBreak::LastIndex, true);
+}
+
+#[test]
+pub fn test_arg_max_neg_inf() {
+    test_case().test_arg_extremum_infinite(Extremum::Max, TieBreak::FirstIndex);
+    test_case().test_arg_extremum_infinite(Extremum::Max, TieBreak::LastIndex);
+}
+
+#[test]
+pub fn test_arg_min_inf() {
+    test_case().test_arg_extremum_infinite(Extremum::Min, TieBreak::FirstIndex);
+    test_case().test_arg_extremum_infinite(Extremum::Min, TieBreak::LastIndex);
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+        axis: test_axis(),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+    pub axis: usize,
+}
+
+impl TestCase {
+    pub fn test_arg_extremum(&self, extremum: Extremum, tie_break: TieBreak, nan: bool) {
+        let input_values: Vec<TestDType> = match nan {
+            true => self.nan_input_values(),
+            false => self.random_input_values(),
+        };
+        self.run_arg_extremum(input_values, extremum, tie_break);
+    }
+
+    /// Every item is the infinity that loses against all the others, so the masked items of a
+    /// vector whose length isn't a multiple of the line size must never be selected.
+    pub fn test_arg_extremum_infinite(&self, extremum: Extremum, tie_break: TieBreak) {
+        let infinity = match extremum {
+            Extremum::Max => f32::NEG_INFINITY,
+            Extremum::Min => f32::INFINITY,
+        };
+        let input_values = vec![TestDType::new(infinity); self.input_size()];
+        self.run_arg_extremum(input_values, extremum, tie_break);
+    }
+
+    fn run_arg_extremum(
+        &self,
+        input_values: Vec<TestDType>,
+        extremum: Extremum,
+        tie_break: TieBreak,
+    ) {
+        let config = ArgExtremumConfig {
+            extremum,
+            tie_break,
+        };
+        let (expected_values, expected_indices) = self.cpu_arg_extremum(&input_values, config);
+
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let output_shape = self.output_shape();
+        let output_stride = contiguous_strides(&output_shape);
+
+        let strategies = [
+            RoutineStrategy::Unit(BlueprintStrategy::Inferred(UnitStrategy)),
+            RoutineStrategy::Plane(BlueprintStrategy::Inferred(PlaneStrategy {
+                independent: true,
+            })),
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: false,
+            })),
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: true,
+            })),
+        ];
+
+        for strategy in strategies {
+            let values_handle = client.empty(expected_values.len() * size_of::<TestDType>());
+            let indices_handle = client.empty(expected_indices.len() * size_of::<u32>());
+
+            let (input, values, indices) = unsafe {
+                (
+                    TensorHandleRef::from_raw_parts(
+                        &input_handle,
+                        &self.stride,
+                        &self.shape,
+                        size_of::<TestDType>(),
+                    ),
+                    TensorHandleRef::from_raw_parts(
+                        &values_handle,
+                        &output_stride,
+                        &output_shape,
+                        size_of::<TestDType>(),
+                    ),
+                    TensorHandleRef::from_raw_parts(
+                        &indices_handle,
+                        &output_stride,
+                        &output_shape,
+                        size_of::<u32>(),
+                    ),
+                )
+            };
+
+            let result = arg_extremum::<TestRuntime>(
+                &client,
+                input,
+                values,
+                indices,
+                self.axis,
+                config,
+                strategy.clone(),
+                TestDType::as_type_native_unchecked(),
+            );
+
+            match result {
+                Ok(_) => {}
+                Err(ReduceError::PlanesUnavailable | ReduceError::ImprecisePlaneDim) => {
+                    continue;
+                }
+                Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+                Err(err) => panic!("Invalid test case {self:?}: {err:?}"),
+            }
+
+            let actual_values = client.read_one(values_handle);
+            assert_approx_equal(
+                TestDType::from_bytes(&actual_values),
+                &expected_values,
+                false,
+            );
+            let actual_indices = client.read_one(indices_handle);
+            assert_eq!(
+                u32::from_bytes(&actual_indices),
+                expected_indices.as_slice(),
+                "Indices differ with {strategy:?}"
+            );
+        }
+    }
+
+    /// Scan every vector in order, so the last index among ties is the latest one selected.
+    fn cpu_arg_extremum(
+        &self,
+        values: &[TestDType],
+        config: ArgExtremumConfig,
+    ) -> (Vec<TestDType>, Vec<u32>) {
+        let output_shape = self.output_shape();
+        let output_stride = contiguous_strides(&output_shape);
+        let output_size = output_shape.iter().product::<usize>();
+        let mut extremum_values = vec![TestDType::from_int(0); output_size];
+        let mut extremum_indices = vec![0; output_size];
+
+        let num_vectors = self.shape.iter().product::<usize>() / self.shape[self.axis];
+        for vector in 0..num_vectors {
+            let mut remainder = vector;
+            let mut input_offset = 0;
+            let mut output_offset = 0;
+            for dim in (0..self.shape.len()).rev() {
+                if dim != self.axis {
+                    let coordinate = remainder % self.shape[dim];
+                    remainder /= self.shape[dim];
+                    input_offset += coordinate * self.stride[dim];
+                    output_offset += coordinate * output_stride[dim];
+                }
+            }
+
+            let mut selected = 0;
+            let mut best = values[input_offset];
+            for i in 1..self.shape[self.axis] {
+                let value = values[input_offset + i * self.stride[self.axis]];
+                let (value_f32, best_f32) = (value.to_f32().unwrap(), best.to_f32().unwrap());
+                let tie = (value_f32.is_nan() && best_f32.is_nan()) || value_f32 == best_f32;
+                let better = match config.extremum {
+                    Extremum::Max => value_f32 > best_f32,
+                    Extremum::Min => value_f32 < best_f32,
+                };
+                let replace = match tie {
+                    true => config.tie_break == TieBreak::LastIndex,
+                    false => !best_f32.is_nan() && (value_f32.is_nan() || better),
+                };
+                if replace {
+                    selected = i;
+                    best = value;
+                }
+            }
+
+            extremum_values[output_offset] = best;
+            extremum_indices[output_offset] = selected as u32;
+        }
+
+        (extremum_values, extremum_indices)
+    }
+
+    fn output_shape(&self) -> Vec<usize> {
+        let mut shape = self.shape.clone();
+        shape[self.axis] = 1;
+        shape
+    }
+
+    fn random_input_values<F: Float>(&self) -> Vec<F> {
+        let size = self.input_size();
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| F::new(r as f32 * factor))
+            .collect()
+    }
+
+    // Random values where most vectors contain a NaN and some contain several.
+    fn nan_input_values<F: Float>(&self) -> Vec<F> {
+        let mut values = self.random_input_values::<F>();
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed() + 1);
+        let distribution = Uniform::new(0, self.shape[self.axis]).unwrap();
+        for (value, r) in values.iter_mut().zip(distribution.sample_iter(rng)) {
+            if r < 2 {
+                *value = F::new(f32::NAN);
+            }
+        }
+        values
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
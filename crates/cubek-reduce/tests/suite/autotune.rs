@@ -0,0 +1,205 @@
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubecl::std::tensor::TensorHandle;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError,
+    components::instructions::ReduceOperationConfig,
+    launch::tune_key::{ReduceAccumulatorClass, ReduceAutotuneKey},
+    reduce_autotune,
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+// The values are multiples of 1 / PRECISION, so that the sums are exact in any order.
+static PRECISION: i32 = 4;
+
+#[test]
+pub fn test_autotune_sum() {
+    test_case().test_autotune(ReduceOperationConfig::Sum, |vector| vector.iter().sum());
+}
+
+#[test]
+pub fn test_autotune_max() {
+    test_case().test_autotune(ReduceOperationConfig::Max, |vector| {
+        vector.iter().copied().fold(f32::NEG_INFINITY, f32::max)
+    });
+}
+
+#[test]
+pub fn test_autotune_invalid_axis() {
+    let case = test_case();
+    let client = TestRuntime::client(&Default::default());
+    let rank = case.shape.len();
+
+    let result = reduce_autotune::<TestRuntime>(
+        &client,
+        &Default::default(),
+        case.tensor(&client, &case.input_values()).as_ref(),
+        case.tensor(&client, &case.input_values()).as_ref(),
+        rank,
+        ReduceOperationConfig::Sum,
+        dtypes(),
+    );
+    assert!(matches!(result, Err(ReduceError::InvalidAxis { .. })));
+}
+
+#[test]
+pub fn test_autotune_key_accumulator() {
+    let case = test_case();
+    let key = |operation| {
+        let elem = f32::as_type_native_unchecked().elem_type();
+        ReduceAutotuneKey::generate(elem, elem, elem, operation, &case.shape, true, case.axis)
+    };
+
+    assert_eq!(
+        key(ReduceOperationConfig::Sum),
+        key(ReduceOperationConfig::Max)
+    );
+    assert_ne!(
+        key(ReduceOperationConfig::Sum),
+        key(ReduceOperationConfig::ArgMax)
+    );
+    assert_ne!(
+        key(ReduceOperationConfig::Sum),
+        key(ReduceOperationConfig::Var { correction: 1 })
+    );
+    assert_eq!(
+        key(ReduceOperationConfig::Var { correction: 1 }).accumulator,
+        ReduceAccumulatorClass::Auxiliary { aux: 2 }
+    );
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+        axis: test_axis(),
+    }
+}
+
+fn dtypes() -> ReduceDtypes {
+    ReduceDtypes {
+        input: f32::as_type_native_unchecked(),
+        output: f32::as_type_native_unchecked(),
+        accumulation: f32::as_type_native_unchecked(),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+    pub axis: usize,
+}
+
+impl TestCase {
+    /// Launch twice, so that the second launch uses the cached strategy.
+    pub fn test_autotune(
+        &self,
+        operation: ReduceOperationConfig,
+        reference: impl Fn(&[f32]) -> f32,
+    ) {
+        let input_values = self.input_values();
+        let expected = self.cpu_reduce(&input_values, reference);
+        let client = TestRuntime::client(&Default::default());
+        let output_shape = self.output_shape();
+        let output_stride = contiguous_strides(&output_shape);
+
+        for _ in 0..2 {
+            let input = self.tensor(&client, &input_values);
+            let output = TensorHandle::new(
+                client.empty(expected.len() * size_of::<f32>()),
+                output_shape.clone(),
+                output_stride.clone(),
+                f32::as_type_native_unchecked(),
+            );
+
+            let result = reduce_autotune::<TestRuntime>(
+                &client,
+                &Default::default(),
+                input.as_ref(),
+                output.as_ref(),
+                self.axis,
+                operation,
+                dtypes(),
+            );
+            if let Err(err) = result {
+                panic!("Invalid test case {self:?}: {err:?}");
+            }
+
+            let actual = client.read_one(output.handle);
+            assert_eq!(f32::from_bytes(&actual), &expected);
+        }
+    }
+
+    fn tensor(
+        &self,
+        client: &ComputeClient<TestRuntime>,
+        values: &[f32],
+    ) -> TensorHandle<TestRuntime> {
+        TensorHandle::new(
+            client.create_from_slice(f32::as_bytes(values)),
+            self.shape.clone(),
+            self.stride.clone(),
+            f32::as_type_native_unchecked(),
+        )
+    }
+
+    /// Gather the vectors along the axis in the order of the output, and reduce them with
+    /// `reference`.
+    fn cpu_reduce(&self, values: &[f32], reference: impl Fn(&[f32]) -> f32) -> Vec<f32> {
+        let num_vectors = self.shape.iter().product::<usize>() / self.shape[self.axis];
+        let mut vectors = vec![Vec::new(); num_vectors];
+        for element in 0..self.shape.iter().product::<usize>() {
+            let mut remainder = element;
+            let mut offset = 0;
+            let mut vector = 0;
+            let mut vector_stride = 1;
+            for dim in (0..self.shape.len()).rev() {
+                let coordinate = remainder % self.shape[dim];
+                remainder /= self.shape[dim];
+                offset += coordinate * self.stride[dim];
+                if dim != self.axis {
+                    vector += coordinate * vector_stride;
+                    vector_stride *= self.shape[dim];
+                }
+            }
+            vectors[vector].push(values[offset]);
+        }
+        vectors.iter().map(|vector| reference(vector)).collect()
+    }
+
+    fn input_values(&self) -> Vec<f32> {
+        let size = self.shape.iter().product::<usize>();
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| r as f32 * factor)
+            .collect()
+    }
+
+    fn output_shape(&self) -> Vec<usize> {
+        let mut shape = self.shape.clone();
+        shape[self.axis] = 1;
+        shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
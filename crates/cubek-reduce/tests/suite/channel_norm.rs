@@ -0,0 +1,455 @@
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubecl::server::Handle;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError, batch_norm, group_norm,
+    routines::channel_norm::{BatchNormMode, BatchNormTensors, GroupNormTensors},
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+static PRECISION: i32 = 4;
+static EPSILON: f32 = 1e-5;
+static MOMENTUM: f32 = 0.1;
+
+#[test]
+pub fn test_group_norm() {
+    test_case().test_group_norm(false);
+}
+
+#[test]
+pub fn test_group_norm_affine_statistics() {
+    test_case().test_group_norm(true);
+}
+
+#[test]
+pub fn test_group_norm_nan() {
+    test_case().test_group_norm_nan();
+}
+
+#[test]
+pub fn test_batch_norm_training() {
+    test_case().test_batch_norm(BatchNormMode::Training { momentum: MOMENTUM });
+}
+
+#[test]
+pub fn test_batch_norm_inference() {
+    test_case().test_batch_norm(BatchNormMode::Inference);
+}
+
+/// A single row has no unbiased variance, so the running variance is kept.
+#[test]
+pub fn test_batch_norm_training_single_row() {
+    let num_channels = *test_shape().last().unwrap();
+    TestCase {
+        shape: vec![1, num_channels],
+        stride: vec![num_channels, 1],
+        num_groups: test_num_groups(),
+    }
+    .test_batch_norm(BatchNormMode::Training { momentum: MOMENTUM });
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+        num_groups: test_num_groups(),
+    }
+}
+
+/// The mean and biased variance of every group, with the number of elements of a group.
+struct GroupStatistics {
+    mean: Vec<f32>,
+    var: Vec<f32>,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+    pub num_groups: usize,
+}
+
+impl TestCase {
+    pub fn test_group_norm(&self, affine: bool) {
+        self.run_group_norm_test(self.random_values(self.input_size(), 0), affine);
+    }
+
+    /// A NaN turns the statistics and all the outputs of its group into NaN,
+    /// while the other groups are unaffected.
+    pub fn test_group_norm_nan(&self) {
+        let mut input_values: Vec<TestDType> = self.random_values(self.input_size(), 0);
+        input_values[5] = TestDType::new(f32::NAN);
+        self.run_group_norm_test(input_values, true);
+    }
+
+    fn run_group_norm_test(&self, input_values: Vec<TestDType>, affine: bool) {
+        let num_channels = *self.shape.last().unwrap();
+        let gamma_values: Vec<TestDType> = self.random_values(num_channels, 1);
+        let beta_values: Vec<TestDType> = self.random_values(num_channels, 2);
+
+        let statistics = self.cpu_statistics(&input_values, true, self.num_groups);
+        let rstd = statistics
+            .var
+            .iter()
+            .map(|var| 1.0 / (var + EPSILON).sqrt())
+            .collect::<Vec<_>>();
+        let expected = self.cpu_apply(
+            &input_values,
+            affine.then_some((gamma_values.as_slice(), beta_values.as_slice())),
+            (statistics.mean.as_slice(), rstd.as_slice()),
+            true,
+            self.num_groups,
+        );
+
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let gamma_handle = client.create_from_slice(TestDType::as_bytes(&gamma_values));
+        let beta_handle = client.create_from_slice(TestDType::as_bytes(&beta_values));
+        let output_handle = client.empty(expected.len() * size_of::<TestDType>());
+        let mean_handle = client.empty(statistics.mean.len() * size_of::<f32>());
+        let rstd_handle = client.empty(statistics.mean.len() * size_of::<f32>());
+
+        let output_stride = contiguous_strides(&self.shape);
+        let channel_shape = [num_channels];
+        let statistics_shape = [self.shape[0], self.num_groups];
+        let statistics_stride = [self.num_groups, 1];
+        let tensors = GroupNormTensors {
+            gamma: affine
+                .then(|| tensor(&gamma_handle, &[1], &channel_shape, size_of::<TestDType>())),
+            beta: affine
+                .then(|| tensor(&beta_handle, &[1], &channel_shape, size_of::<TestDType>())),
+            mean: affine.then(|| {
+                tensor(
+                    &mean_handle,
+                    &statistics_stride,
+                    &statistics_shape,
+                    size_of::<f32>(),
+                )
+            }),
+            rstd: affine.then(|| {
+                tensor(
+                    &rstd_handle,
+                    &statistics_stride,
+                    &statistics_shape,
+                    size_of::<f32>(),
+                )
+            }),
+        };
+
+        let result = group_norm::<TestRuntime>(
+            &client,
+            tensor(
+                &input_handle,
+                &self.stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            ),
+            tensor(
+                &output_handle,
+                &output_stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            ),
+            self.num_groups,
+            tensors,
+            EPSILON,
+            dtypes(),
+        );
+        self.check_result(result);
+
+        assert_close(&read_values(&client, output_handle), &expected);
+        if affine {
+            assert_close(
+                f32::from_bytes(&client.read_one(mean_handle)),
+                &statistics.mean,
+            );
+            assert_close(f32::from_bytes(&client.read_one(rstd_handle)), &rstd);
+        }
+    }
+
+    pub fn test_batch_norm(&self, mode: BatchNormMode) {
+        let num_channels = *self.shape.last().unwrap();
+        let input_values: Vec<TestDType> = self.random_values(self.input_size(), 0);
+        let gamma_values: Vec<TestDType> = self.random_values(num_channels, 1);
+        let beta_values: Vec<TestDType> = self.random_values(num_channels, 2);
+        let running_mean: Vec<f32> = self.random_values(num_channels, 3);
+        let running_var = self
+            .random_values::<f32>(num_channels, 4)
+            .iter()
+            .map(|v| 1.0 + v.abs())
+            .collect::<Vec<_>>();
+
+        let statistics = self.cpu_statistics(&input_values, false, num_channels);
+        let (mean, rstd, expected_running_mean, expected_running_var) = match mode {
+            BatchNormMode::Training { momentum } => {
+                let rstd = statistics
+                    .var
+                    .iter()
+                    .map(|var| 1.0 / (var + EPSILON).sqrt())
+                    .collect::<Vec<_>>();
+                // The running variance is kept without the degrees of freedom of an unbiased one.
+                let count = statistics.count as f32;
+                let has_variance = statistics.count > 1;
+                let running_mean = running_mean
+                    .iter()
+                    .zip(statistics.mean.iter())
+                    .map(|(running, mean)| (1.0 - momentum) * running + momentum * mean)
+                    .collect::<Vec<_>>();
+                let running_var = running_var
+                    .iter()
+                    .zip(statistics.var.iter())
+                    .map(|(running, var)| match has_variance {
+                        true => (1.0 - momentum) * running + momentum * var * count / (count - 1.0),
+                        false => *running,
+                    })
+                    .collect::<Vec<_>>();
+                (statistics.mean, rstd, running_mean, running_var)
+            }
+            BatchNormMode::Inference => {
+                let rstd = running_var
+                    .iter()
+                    .map(|var| 1.0 / (var + EPSILON).sqrt())
+                    .collect::<Vec<_>>();
+                (
+                    running_mean.clone(),
+                    rstd,
+                    running_mean.clone(),
+                    running_var.clone(),
+                )
+            }
+        };
+        let expected = self.cpu_apply(
+            &input_values,
+            Some((gamma_values.as_slice(), beta_values.as_slice())),
+            (mean.as_slice(), rstd.as_slice()),
+            false,
+            num_channels,
+        );
+
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let gamma_handle = client.create_from_slice(TestDType::as_bytes(&gamma_values));
+        let beta_handle = client.create_from_slice(TestDType::as_bytes(&beta_values));
+        let running_mean_handle = client.create_from_slice(f32::as_bytes(&running_mean));
+        let running_var_handle = client.create_from_slice(f32::as_bytes(&running_var));
+        let output_handle = client.empty(expected.len() * size_of::<TestDType>());
+        let mean_handle = client.empty(num_channels * size_of::<f32>());
+        let rstd_handle = client.empty(num_channels * size_of::<f32>());
+
+        let training = matches!(mode, BatchNormMode::Training { .. });
+        let output_stride = contiguous_strides(&self.shape);
+        let channel_shape = [num_channels];
+        let channel_tensor = |handle, elem_size| tensor(handle, &[1], &channel_shape, elem_size);
+        let tensors = BatchNormTensors {
+            gamma: Some(channel_tensor(&gamma_handle, size_of::<TestDType>())),
+            beta: Some(channel_tensor(&beta_handle, size_of::<TestDType>())),
+            running_mean: Some(channel_tensor(&running_mean_handle, size_of::<f32>())),
+            running_var: Some(channel_tensor(&running_var_handle, size_of::<f32>())),
+            mean: training.then(|| channel_tensor(&mean_handle, size_of::<f32>())),
+            rstd: training.then(|| channel_tensor(&rstd_handle, size_of::<f32>())),
+        };
+
+        let result = batch_norm::<TestRuntime>(
+            &client,
+            tensor(
+                &input_handle,
+                &self.stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            ),
+            tensor(
+                &output_handle,
+                &output_stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            ),
+            tensors,
+            mode,
+            EPSILON,
+            dtypes(),
+        );
+        self.check_result(result);
+
+        assert_close(&read_values(&client, output_handle), &expected);
+        assert_close(
+            f32::from_bytes(&client.read_one(running_mean_handle)),
+            &expected_running_mean,
+        );
+        assert_close(
+            f32::from_bytes(&client.read_one(running_var_handle)),
+            &expected_running_var,
+        );
+        if training {
+            assert_close(f32::from_bytes(&client.read_one(mean_handle)), &mean);
+            assert_close(f32::from_bytes(&client.read_one(rstd_handle)), &rstd);
+        }
+    }
+
+    fn check_result(&self, result: Result<(), ReduceError>) {
+        match result {
+            Ok(_) => {}
+            Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+            Err(err) => panic!("Invalid test case {self:?}: {err:?}"),
+        }
+    }
+
+    /// Compute the statistics of every group in `f32`, where the batch elements share their
+    /// statistics unless `per_batch` is true.
+    fn cpu_statistics(
+        &self,
+        input: &[TestDType],
+        per_batch: bool,
+        num_groups: usize,
+    ) -> GroupStatistics {
+        let mut groups = vec![Vec::new(); self.num_batches(per_batch) * num_groups];
+        for index in 0..self.shape.iter().product::<usize>() {
+            let group = self.group(index, per_batch, num_groups);
+            groups[group].push(input[self.input_offset(index)].to_f32().unwrap());
+        }
+
+        let mean = groups
+            .iter()
+            .map(|values| values.iter().sum::<f32>() / values.len() as f32)
+            .collect::<Vec<_>>();
+        let var = groups
+            .iter()
+            .zip(mean.iter())
+            .map(|(values, mean)| {
+                values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32
+            })
+            .collect::<Vec<_>>();
+
+        GroupStatistics {
+            mean,
+            var,
+            count: groups[0].len(),
+        }
+    }
+
+    /// Normalize every element with the statistics of its group, with a contiguous output.
+    fn cpu_apply(
+        &self,
+        input: &[TestDType],
+        affine: Option<(&[TestDType], &[TestDType])>,
+        (mean, rstd): (&[f32], &[f32]),
+        per_batch: bool,
+        num_groups: usize,
+    ) -> Vec<f32> {
+        let num_channels = *self.shape.last().unwrap();
+        (0..self.shape.iter().product::<usize>())
+            .map(|index| {
+                let group = self.group(index, per_batch, num_groups);
+                let value = input[self.input_offset(index)].to_f32().unwrap();
+                let result = (value - mean[group]) * rstd[group];
+                match affine {
+                    Some((gamma, beta)) => {
+                        let channel = index % num_channels;
+                        result * gamma[channel].to_f32().unwrap() + beta[channel].to_f32().unwrap()
+                    }
+                    None => result,
+                }
+            })
+            .collect()
+    }
+
+    /// The group of the element at the given row-major `index`.
+    fn group(&self, index: usize, per_batch: bool, num_groups: usize) -> usize {
+        let num_channels = *self.shape.last().unwrap();
+        let batch_size = self.shape.iter().product::<usize>() / self.num_batches(per_batch);
+        let channel = index % num_channels;
+        (index / batch_size) * num_groups + channel / (num_channels / num_groups)
+    }
+
+    fn num_batches(&self, per_batch: bool) -> usize {
+        if per_batch { self.shape[0] } else { 1 }
+    }
+
+    fn input_offset(&self, index: usize) -> usize {
+        let mut remainder = index;
+        let mut offset = 0;
+        for dim in (0..self.shape.len()).rev() {
+            offset += (remainder % self.shape[dim]) * self.stride[dim];
+            remainder /= self.shape[dim];
+        }
+        offset
+    }
+
+    fn random_values<F: Float>(&self, size: usize, seed_offset: u64) -> Vec<F> {
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed() + seed_offset);
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| F::new(r as f32 * factor))
+            .collect()
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+fn dtypes() -> ReduceDtypes {
+    ReduceDtypes {
+        input: TestDType::as_type_native_unchecked(),
+        output: TestDType::as_type_native_unchecked(),
+        accumulation: f32::as_type_native_unchecked(),
+    }
+}
+
+fn read_values(client: &ComputeClient<TestRuntime>, handle: Handle) -> Vec<f32> {
+    TestDType::from_bytes(&client.read_one(handle))
+        .iter()
+        .map(|v| v.to_f32().unwrap())
+        .collect()
+}
+
+fn tensor<'a>(
+    handle: &'a Handle,
+    strides: &'a [usize],
+    shape: &'a [usize],
+    elem_size: usize,
+) -> TensorHandleRef<'a, TestRuntime> {
+    unsafe { TensorHandleRef::from_raw_parts(handle, strides, shape, elem_size) }
+}
+
+/// The normalized values are often close to 0, so the tolerance is partly absolute.
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+        if e.is_nan() {
+            assert!(a.is_nan(), "Values are not NaN: index={i} actual={a}");
+            continue;
+        }
+        let tolerance = 1e-2 * (1.0 + e.abs());
+        assert!(
+            (a - e).abs() < tolerance,
+            "Values are not approx equal: index={i} actual={a}, expected={e}"
+        );
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
@@ -0,0 +1,198 @@
+use crate::suite::test_case::assert_approx_equal;
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError, Summation,
+    components::instructions::ReduceOperationConfig,
+    launch::{LineSizeStrategy, ReduceStrategy, RoutineStrategy},
+    reduce,
+    routines::{BlueprintStrategy, cube::CubeStrategy, plane::PlaneStrategy, unit::UnitStrategy},
+};
+
+// Adding `SMALL` to `LARGE` in f32 doesn't change it, so a running sum starting at `LARGE`
+// drops every element that follows until it is cancelled.
+static LARGE: f32 = 65536.0;
+static SMALL: f32 = 1.0 / 512.0;
+
+// The square of `HUGE` overflows f32.
+static HUGE: f32 = 1.0e30;
+
+#[test]
+pub fn test_compensated_sum_cancellation() {
+    test_case().test_cancellation(ReduceOperationConfig::Sum);
+}
+
+#[test]
+pub fn test_compensated_mean_cancellation() {
+    test_case().test_cancellation(ReduceOperationConfig::Mean);
+}
+
+#[test]
+pub fn test_compensated_l2_norm_huge() {
+    test_case().test_l2_norm_huge();
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+        axis: test_axis(),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+    pub axis: usize,
+}
+
+impl TestCase {
+    /// Every vector starts with `LARGE` and `-LARGE`, followed by `SMALL` elements.
+    pub fn test_cancellation(&self, operation: ReduceOperationConfig) {
+        let input_values = self.input_values();
+        let length = self.shape[self.axis];
+        let sum = (length - 2) as f64 * SMALL as f64;
+        let expected = match operation {
+            ReduceOperationConfig::Mean => sum / length as f64,
+            _ => sum,
+        };
+        let expected_values = vec![expected as f32; self.num_vectors()];
+        self.run(input_values, expected_values, operation);
+    }
+
+    /// Every element is `HUGE`, so the squares overflow unless they are scaled.
+    pub fn test_l2_norm_huge(&self) {
+        let input_values = vec![HUGE; self.shape.iter().product::<usize>()];
+        let length = self.shape[self.axis];
+        let expected = HUGE as f64 * (length as f64).sqrt();
+        let expected_values = vec![expected as f32; self.num_vectors()];
+        self.run(input_values, expected_values, ReduceOperationConfig::L2Norm);
+    }
+
+    fn run(
+        &self,
+        input_values: Vec<f32>,
+        expected_values: Vec<f32>,
+        operation: ReduceOperationConfig,
+    ) {
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(f32::as_bytes(&input_values));
+        let output_shape = self.output_shape();
+        let output_stride = contiguous_strides(&output_shape);
+
+        let routines = [
+            RoutineStrategy::Unit(BlueprintStrategy::Inferred(UnitStrategy)),
+            RoutineStrategy::Plane(BlueprintStrategy::Inferred(PlaneStrategy {
+                independent: true,
+            })),
+            RoutineStrategy::Plane(BlueprintStrategy::Inferred(PlaneStrategy {
+                independent: false,
+            })),
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: false,
+            })),
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: true,
+            })),
+        ];
+
+        for routine in routines {
+            let output_handle = client.empty(expected_values.len() * size_of::<f32>());
+            let (input, output) = unsafe {
+                (
+                    TensorHandleRef::from_raw_parts(
+                        &input_handle,
+                        &self.stride,
+                        &self.shape,
+                        size_of::<f32>(),
+                    ),
+                    TensorHandleRef::from_raw_parts(
+                        &output_handle,
+                        &output_stride,
+                        &output_shape,
+                        size_of::<f32>(),
+                    ),
+                )
+            };
+
+            let result = reduce::<TestRuntime>(
+                &client,
+                input,
+                output,
+                self.axis,
+                ReduceStrategy {
+                    routine: routine.clone(),
+                    line_size: LineSizeStrategy {
+                        parallel_output_vectorization: false,
+                    },
+                },
+                operation.with_summation(Summation::Compensated),
+                ReduceDtypes {
+                    input: f32::as_type_native_unchecked(),
+                    output: f32::as_type_native_unchecked(),
+                    accumulation: f32::as_type_native_unchecked(),
+                },
+            );
+
+            match result {
+                Ok(_) => {}
+                Err(ReduceError::PlanesUnavailable | ReduceError::ImprecisePlaneDim) => {
+                    continue;
+                }
+                Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+                Err(err) => panic!("Invalid test case {self:?} with {routine:?}: {err:?}"),
+            }
+
+            let actual = client.read_one(output_handle);
+            assert_approx_equal(f32::from_bytes(&actual), &expected_values, false);
+        }
+    }
+
+    fn input_values(&self) -> Vec<f32> {
+        let size = self.shape.iter().product::<usize>();
+        let mut values = vec![0.0; size];
+        for element in 0..size {
+            let coordinate = self.coordinate(element);
+            let offset = coordinate
+                .iter()
+                .zip(self.stride.iter())
+                .map(|(coordinate, stride)| coordinate * stride)
+                .sum::<usize>();
+            values[offset] = match coordinate[self.axis] {
+                0 => LARGE,
+                1 => -LARGE,
+                _ => SMALL,
+            };
+        }
+        values
+    }
+
+    fn coordinate(&self, element: usize) -> Vec<usize> {
+        let mut remainder = element;
+        let mut coordinate = vec![0; self.shape.len()];
+        for dim in (0..self.shape.len()).rev() {
+            coordinate[dim] = remainder % self.shape[dim];
+            remainder /= self.shape[dim];
+        }
+        coordinate
+    }
+
+    fn num_vectors(&self) -> usize {
+        self.shape.iter().product::<usize>() / self.shape[self.axis]
+    }
+
+    fn output_shape(&self) -> Vec<usize> {
+        let mut shape = self.shape.clone();
+        shape[self.axis] = 1;
+        shape
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
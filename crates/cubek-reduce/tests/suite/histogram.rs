@@ -0,0 +1,315 @@
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubek_reduce::{Determinism, HistogramBins, ReduceError, bincount, histogram};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+// The weights and the values are multiples of 1 / PRECISION, so that every count is exact.
+static PRECISION: i32 = 4;
+
+// The range of the histograms, the values are sampled from a slightly larger range.
+static RANGE: (f32, f32) = (-1.5, 1.5);
+
+#[test]
+pub fn test_bincount() {
+    test_case().test_bincount(false);
+}
+
+#[test]
+pub fn test_bincount_weighted() {
+    test_case().test_bincount(true);
+}
+
+#[test]
+pub fn test_histogram_range() {
+    test_case().test_histogram(false, false);
+}
+
+#[test]
+pub fn test_histogram_range_weighted() {
+    test_case().test_histogram(false, true);
+}
+
+#[test]
+pub fn test_histogram_edges() {
+    test_case().test_histogram(true, false);
+}
+
+#[test]
+pub fn test_histogram_edges_weighted() {
+    test_case().test_histogram(true, true);
+}
+
+#[test]
+pub fn test_histogram_invalid_range() {
+    let client = TestRuntime::client(&Default::default());
+    let handle = client.create_from_slice(TestDType::as_bytes(&[TestDType::from_int(0)]));
+    let tensor = || unsafe {
+        TensorHandleRef::<TestRuntime>::from_raw_parts(&handle, &[1], &[1], size_of::<TestDType>())
+    };
+
+    let result = histogram::<TestRuntime>(
+        &client,
+        tensor(),
+        None,
+        tensor(),
+        HistogramBins::Range { min: 1.0, max: 1.0 },
+        Determinism::Relaxed,
+        TestDType::as_type_native_unchecked().elem_type(),
+        TestDType::as_type_native_unchecked().elem_type(),
+    );
+    assert!(matches!(result, Err(ReduceError::Validation { .. })));
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+        num_bins: test_num_bins(),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+    pub num_bins: usize,
+}
+
+impl TestCase {
+    /// Count integers spanning a few values outside of the bins on both sides.
+    pub fn test_bincount(&self, weighted: bool) {
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new(-3, self.num_bins as i32 + 3).unwrap();
+        let input_values: Vec<i32> = distribution
+            .sample_iter(rng)
+            .take(self.input_size())
+            .collect();
+        let weights = weighted.then(|| self.random_weights());
+
+        let expected = self.cpu_histogram(weights.as_deref(), |i| {
+            let value = input_values[i];
+            (value >= 0 && (value as usize) < self.num_bins).then_some(value as usize)
+        });
+
+        self.run_histogram_test(
+            i32::as_bytes(&input_values),
+            size_of::<i32>(),
+            weights,
+            expected,
+            |client, input, weights, output, determinism| {
+                bincount::<TestRuntime>(
+                    client,
+                    input,
+                    weights,
+                    output,
+                    determinism,
+                    i32::as_type_native_unchecked().elem_type(),
+                    TestDType::as_type_native_unchecked().elem_type(),
+                )
+            },
+        );
+    }
+
+    /// Bin values spanning a bit more than the range, with a NaN every few elements.
+    pub fn test_histogram(&self, use_edges: bool, weighted: bool) {
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        let input_values: Vec<TestDType> = distribution
+            .sample_iter(rng)
+            .take(self.input_size())
+            .enumerate()
+            .map(|(i, r)| match i % 17 {
+                0 => TestDType::new(f32::NAN),
+                _ => TestDType::new(r as f32 * factor),
+            })
+            .collect();
+        let weights = weighted.then(|| self.random_weights());
+
+        // Edges growing quadratically so that the bins have different widths.
+        let edges: Vec<TestDType> = (0..=self.num_bins)
+            .map(|i| {
+                let t = i as f32 / self.num_bins as f32;
+                TestDType::new(RANGE.0 + (RANGE.1 - RANGE.0) * t * t)
+            })
+            .collect();
+        let edges_f32: Vec<f32> = edges.iter().map(|e| e.to_f32().unwrap()).collect();
+        let scale = self.num_bins as f32 / (RANGE.1 - RANGE.0);
+
+        let expected = self.cpu_histogram(weights.as_deref(), |i| {
+            let value = input_values[i].to_f32().unwrap();
+            match use_edges {
+                true => (value >= edges_f32[0] && value <= edges_f32[self.num_bins]).then(|| {
+                    edges_f32[..self.num_bins]
+                        .iter()
+                        .rposition(|edge| *edge <= value)
+                        .unwrap()
+                }),
+                false => (value >= RANGE.0 && value <= RANGE.1)
+                    .then(|| (((value - RANGE.0) * scale) as usize).min(self.num_bins - 1)),
+            }
+        });
+
+        self.run_histogram_test(
+            TestDType::as_bytes(&input_values),
+            size_of::<TestDType>(),
+            weights,
+            expected,
+            |client, input, weights, output, determinism| {
+                let edges_handle = client.create_from_slice(TestDType::as_bytes(&edges));
+                let edges_shape = [edges.len()];
+                let bins = match use_edges {
+                    true => HistogramBins::Edges(unsafe {
+                        TensorHandleRef::from_raw_parts(
+                            &edges_handle,
+                            &[1],
+                            &edges_shape,
+                            size_of::<TestDType>(),
+                        )
+                    }),
+                    false => HistogramBins::Range {
+                        min: RANGE.0,
+                        max: RANGE.1,
+                    },
+                };
+                histogram::<TestRuntime>(
+                    client,
+                    input,
+                    weights,
+                    output,
+                    bins,
+                    determinism,
+                    TestDType::as_type_native_unchecked().elem_type(),
+                    TestDType::as_type_native_unchecked().elem_type(),
+                )
+            },
+        );
+    }
+
+    /// Launch with both determinism modes on an output starting at one, since the counts
+    /// are added to it.
+    fn run_histogram_test(
+        &self,
+        input_bytes: &[u8],
+        input_elem_size: usize,
+        weights: Option<Vec<TestDType>>,
+        expected: Vec<TestDType>,
+        launch: impl Fn(
+            &ComputeClient<TestRuntime>,
+            TensorHandleRef<TestRuntime>,
+            Option<TensorHandleRef<TestRuntime>>,
+            TensorHandleRef<TestRuntime>,
+            Determinism,
+        ) -> Result<(), ReduceError>,
+    ) {
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(input_bytes);
+        let weights_handle = weights
+            .as_ref()
+            .map(|weights| client.create_from_slice(TestDType::as_bytes(weights)));
+        let output_shape = [self.num_bins];
+
+        for determinism in [Determinism::Relaxed, Determinism::Strict] {
+            let output_handle = client.create_from_slice(TestDType::as_bytes(&vec![
+                TestDType::from_int(1);
+                self.num_bins
+            ]));
+
+            let (input, weights, output) = unsafe {
+                (
+                    TensorHandleRef::from_raw_parts(
+                        &input_handle,
+                        &self.stride,
+                        &self.shape,
+                        input_elem_size,
+                    ),
+                    weights_handle.as_ref().map(|handle| {
+                        TensorHandleRef::from_raw_parts(
+                            handle,
+                            &self.stride,
+                            &self.shape,
+                            size_of::<TestDType>(),
+                        )
+                    }),
+                    TensorHandleRef::from_raw_parts(
+                        &output_handle,
+                        &[1],
+                        &output_shape,
+                        size_of::<TestDType>(),
+                    ),
+                )
+            };
+
+            match launch(&client, input, weights, output, determinism) {
+                Ok(_) => {}
+                Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+                Err(err) => panic!("Invalid test case {self:?}: {err:?}"),
+            }
+
+            let bytes = client.read_one(output_handle);
+            let actual: Vec<f32> = TestDType::from_bytes(&bytes)
+                .iter()
+                .map(|count| count.to_f32().unwrap())
+                .collect();
+            let expected: Vec<f32> = expected
+                .iter()
+                .map(|count| count.to_f32().unwrap() + 1.0)
+                .collect();
+            assert_eq!(actual, expected, "Counts differ with {determinism:?}");
+        }
+    }
+
+    /// Visit the elements in order, `bin` mapping the position of an element in the buffer
+    /// to its bin.
+    fn cpu_histogram(
+        &self,
+        weights: Option<&[TestDType]>,
+        bin: impl Fn(usize) -> Option<usize>,
+    ) -> Vec<TestDType> {
+        let mut counts = vec![0.0f32; self.num_bins];
+        let num_elements = self.shape.iter().product::<usize>();
+        for element in 0..num_elements {
+            let mut remainder = element;
+            let mut offset = 0;
+            for dim in (0..self.shape.len()).rev() {
+                offset += (remainder % self.shape[dim]) * self.stride[dim];
+                remainder /= self.shape[dim];
+            }
+
+            if let Some(bin) = bin(offset) {
+                counts[bin] += weights.map_or(1.0, |weights| weights[offset].to_f32().unwrap());
+            }
+        }
+        counts.into_iter().map(TestDType::new).collect()
+    }
+
+    fn random_weights(&self) -> Vec<TestDType> {
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed() + 1);
+        let distribution = Uniform::new_inclusive(0, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(self.input_size())
+            .map(|r| TestDType::new(r as f32 * factor))
+            .collect()
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
@@ -0,0 +1,398 @@
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubecl::server::Handle;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError,
+    launch::RoutineStrategy,
+    layer_norm, rms_norm,
+    routines::{
+        BlueprintStrategy, cube::CubeStrategy, layer_norm::NormTensors, plane::PlaneStrategy,
+        unit::UnitStrategy,
+    },
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+static PRECISION: i32 = 4;
+static EPSILON: f32 = 1e-5;
+
+#[test]
+pub fn test_layer_norm() {
+    test_case().test_norm(NormOptions {
+        rms: false,
+        affine: false,
+        residual: false,
+        statistics: false,
+    });
+}
+
+#[test]
+pub fn test_layer_norm_affine_statistics() {
+    test_case().test_norm(NormOptions {
+        rms: false,
+        affine: true,
+        residual: false,
+        statistics: true,
+    });
+}
+
+#[test]
+pub fn test_layer_norm_residual() {
+    test_case().test_norm(NormOptions {
+        rms: false,
+        affine: true,
+        residual: true,
+        statistics: true,
+    });
+}
+
+#[test]
+pub fn test_layer_norm_nan() {
+    test_case().test_norm_nan(NormOptions {
+        rms: false,
+        affine: false,
+        residual: false,
+        statistics: true,
+    });
+}
+
+#[test]
+pub fn test_rms_norm() {
+    test_case().test_norm(NormOptions {
+        rms: true,
+        affine: false,
+        residual: false,
+        statistics: false,
+    });
+}
+
+#[test]
+pub fn test_rms_norm_affine_residual() {
+    test_case().test_norm(NormOptions {
+        rms: true,
+        affine: true,
+        residual: true,
+        statistics: true,
+    });
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NormOptions {
+    pub rms: bool,
+    pub affine: bool,
+    pub residual: bool,
+    /// Write the mean for LayerNorm and the rstd for both norms.
+    pub statistics: bool,
+}
+
+/// The expected values of all outputs.
+struct NormReference {
+    output: Vec<f32>,
+    residual_output: Vec<f32>,
+    mean: Vec<f32>,
+    rstd: Vec<f32>,
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+}
+
+impl TestCase {
+    pub fn test_norm(&self, options: NormOptions) {
+        self.run_norm_test(self.random_values(self.input_size(), 0), options);
+    }
+
+    /// A NaN in a row turns its statistics and all its outputs into NaN.
+    pub fn test_norm_nan(&self, options: NormOptions) {
+        let mut input_values: Vec<TestDType> = self.random_values(self.input_size(), 0);
+        for (i, value) in input_values.iter_mut().enumerate() {
+            if i % 37 == 5 {
+                *value = TestDType::new(f32::NAN);
+            }
+        }
+        self.run_norm_test(input_values, options);
+    }
+
+    fn run_norm_test(&self, input_values: Vec<TestDType>, options: NormOptions) {
+        let row_len = *self.shape.last().unwrap();
+        let residual_values: Vec<TestDType> = self.random_values(self.input_size(), 1);
+        let gamma_values: Vec<TestDType> = self.random_values(row_len, 2);
+        let beta_values: Vec<TestDType> = self.random_values(row_len, 3);
+        let expected = self.cpu_norm(
+            &input_values,
+            &residual_values,
+            &gamma_values,
+            &beta_values,
+            options,
+        );
+
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let residual_handle = client.create_from_slice(TestDType::as_bytes(&residual_values));
+        let gamma_handle = client.create_from_slice(TestDType::as_bytes(&gamma_values));
+        let beta_handle = client.create_from_slice(TestDType::as_bytes(&beta_values));
+
+        let output_size = self.shape.iter().product::<usize>();
+        let output_stride = contiguous_strides(&self.shape);
+        let mut statistics_shape = self.shape.clone();
+        *statistics_shape.last_mut().unwrap() = 1;
+        let statistics_stride = contiguous_strides(&statistics_shape);
+        let num_rows = output_size / row_len;
+
+        let strategies = [
+            RoutineStrategy::Unit(BlueprintStrategy::Inferred(UnitStrategy)),
+            RoutineStrategy::Plane(BlueprintStrategy::Inferred(PlaneStrategy {
+                independent: true,
+            })),
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: false,
+            })),
+        ];
+
+        for strategy in strategies {
+            let output_handle = client.empty(output_size * size_of::<TestDType>());
+            let residual_output_handle = client.empty(output_size * size_of::<TestDType>());
+            let mean_handle = client.empty(num_rows * size_of::<f32>());
+            let rstd_handle = client.empty(num_rows * size_of::<f32>());
+
+            let input = tensor(
+                &input_handle,
+                &self.stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            );
+            let output = tensor(
+                &output_handle,
+                &output_stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            );
+            let row_shape = [row_len];
+            let tensors = NormTensors {
+                residual: options.residual.then(|| {
+                    tensor(
+                        &residual_handle,
+                        &self.stride,
+                        &self.shape,
+                        size_of::<TestDType>(),
+                    )
+                }),
+                residual_output: options.residual.then(|| {
+                    tensor(
+                        &residual_output_handle,
+                        &output_stride,
+                        &self.shape,
+                        size_of::<TestDType>(),
+                    )
+                }),
+                gamma: options
+                    .affine
+                    .then(|| tensor(&gamma_handle, &[1], &row_shape, size_of::<TestDType>())),
+                beta: options
+                    .affine
+                    .then(|| tensor(&beta_handle, &[1], &row_shape, size_of::<TestDType>())),
+                mean: (options.statistics && !options.rms).then(|| {
+                    tensor(
+                        &mean_handle,
+                        &statistics_stride,
+                        &statistics_shape,
+                        size_of::<f32>(),
+                    )
+                }),
+                rstd: options.statistics.then(|| {
+                    tensor(
+                        &rstd_handle,
+                        &statistics_stride,
+                        &statistics_shape,
+                        size_of::<f32>(),
+                    )
+                }),
+            };
+            let dtypes = ReduceDtypes {
+                input: TestDType::as_type_native_unchecked(),
+                output: TestDType::as_type_native_unchecked(),
+                accumulation: f32::as_type_native_unchecked(),
+            };
+
+            let result = match options.rms {
+                true => rms_norm::<TestRuntime>(
+                    &client, input, output, tensors, EPSILON, strategy, dtypes,
+                ),
+                false => layer_norm::<TestRuntime>(
+                    &client, input, output, tensors, EPSILON, strategy, dtypes,
+                ),
+            };
+
+            match result {
+                Ok(_) => {}
+                Err(ReduceError::PlanesUnavailable | ReduceError::ImprecisePlaneDim) => {
+                    continue;
+                }
+                Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+                Err(err) => panic!("Invalid test case {self:?}: {err:?}"),
+            }
+
+            let read_values = |handle| {
+                TestDType::from_bytes(&client.read_one(handle))
+                    .iter()
+                    .map(|v| v.to_f32().unwrap())
+                    .collect::<Vec<_>>()
+            };
+            assert_close(&read_values(output_handle), &expected.output);
+            if options.residual {
+                assert_close(
+                    &read_values(residual_output_handle),
+                    &expected.residual_output,
+                );
+            }
+            if options.statistics {
+                assert_close(
+                    f32::from_bytes(&client.read_one(rstd_handle)),
+                    &expected.rstd,
+                );
+                if !options.rms {
+                    assert_close(
+                        f32::from_bytes(&client.read_one(mean_handle)),
+                        &expected.mean,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Normalize every row in `f32`, with contiguous outputs.
+    fn cpu_norm(
+        &self,
+        input: &[TestDType],
+        residual: &[TestDType],
+        gamma: &[TestDType],
+        beta: &[TestDType],
+        options: NormOptions,
+    ) -> NormReference {
+        let row_len = *self.shape.last().unwrap();
+        let row_stride = *self.stride.last().unwrap();
+        let num_rows = self.shape.iter().product::<usize>() / row_len;
+        let mut reference = NormReference {
+            output: Vec::new(),
+            residual_output: Vec::new(),
+            mean: Vec::new(),
+            rstd: Vec::new(),
+        };
+
+        for row in 0..num_rows {
+            let mut remainder = row;
+            let mut offset = 0;
+            for dim in (0..self.shape.len() - 1).rev() {
+                offset += (remainder % self.shape[dim]) * self.stride[dim];
+                remainder /= self.shape[dim];
+            }
+
+            let values = (0..row_len)
+                .map(|i| {
+                    let index = offset + i * row_stride;
+                    let value = input[index].to_f32().unwrap();
+                    // The sum is normalized as stored in the residual output.
+                    match options.residual {
+                        true => TestDType::new(value + residual[index].to_f32().unwrap())
+                            .to_f32()
+                            .unwrap(),
+                        false => value,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mean = values.iter().sum::<f32>() / row_len as f32;
+            let center = if options.rms { 0.0 } else { mean };
+            let second_moment = values
+                .iter()
+                .map(|v| (v - center) * (v - center))
+                .sum::<f32>()
+                / row_len as f32;
+            let rstd = 1.0 / (second_moment + EPSILON).sqrt();
+
+            for (i, value) in values.iter().enumerate() {
+                let mut result = (value - center) * rstd;
+                if options.affine {
+                    result = result * gamma[i].to_f32().unwrap() + beta[i].to_f32().unwrap();
+                }
+                reference.output.push(result);
+                reference.residual_output.push(*value);
+            }
+            reference.mean.push(mean);
+            reference.rstd.push(rstd);
+        }
+
+        reference
+    }
+
+    fn random_values<F: Float>(&self, size: usize, seed_offset: u64) -> Vec<F> {
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed() + seed_offset);
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| F::new(r as f32 * factor))
+            .collect()
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+fn tensor<'a>(
+    handle: &'a Handle,
+    strides: &'a [usize],
+    shape: &'a [usize],
+    elem_size: usize,
+) -> TensorHandleRef<'a, TestRuntime> {
+    unsafe { TensorHandleRef::from_raw_parts(handle, strides, shape, elem_size) }
+}
+
+/// The normalized values are often close to 0, so the tolerance is partly absolute.
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+        if e.is_nan() {
+            assert!(a.is_nan(), "Values are not NaN: index={i} actual={a}");
+            continue;
+        }
+        let tolerance = 1e-2 * (1.0 + e.abs());
+        assert!(
+            (a - e).abs() < tolerance,
+            "Values are not approx equal: index={i} actual={a}, expected={e}"
+        );
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
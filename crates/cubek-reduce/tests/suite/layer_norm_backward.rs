@@ -0,0 +1,324 @@
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubecl::server::Handle;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError,
+    launch::RoutineStrategy,
+    layer_norm_backward, rms_norm_backward,
+    routines::{
+        BlueprintStrategy, cube::CubeStrategy, layer_norm_backward::NormBackwardTensors,
+        plane::PlaneStrategy, unit::UnitStrategy,
+    },
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+static PRECISION: i32 = 4;
+static EPSILON: f32 = 1e-5;
+
+#[test]
+pub fn test_layer_norm_backward() {
+    test_case().test_norm_backward(NormBackwardOptions {
+        rms: false,
+        affine: true,
+    });
+}
+
+#[test]
+pub fn test_layer_norm_backward_input_only() {
+    test_case().test_norm_backward(NormBackwardOptions {
+        rms: false,
+        affine: false,
+    });
+}
+
+#[test]
+pub fn test_rms_norm_backward() {
+    test_case().test_norm_backward(NormBackwardOptions {
+        rms: true,
+        affine: true,
+    });
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NormBackwardOptions {
+    pub rms: bool,
+    /// Scale by gamma and compute the gradients of gamma and beta.
+    pub affine: bool,
+}
+
+/// The statistics of the forward pass and the expected gradients.
+struct NormBackwardReference {
+    mean: Vec<f32>,
+    rstd: Vec<f32>,
+    grad_input: Vec<f32>,
+    grad_gamma: Vec<f32>,
+    grad_beta: Vec<f32>,
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+}
+
+impl TestCase {
+    pub fn test_norm_backward(&self, options: NormBackwardOptions) {
+        let row_len = *self.shape.last().unwrap();
+        let grad_output_values: Vec<TestDType> = self.random_values(self.input_size(), 0);
+        let input_values: Vec<TestDType> = self.random_values(self.input_size(), 1);
+        let gamma_values: Vec<TestDType> = self.random_values(row_len, 2);
+        let expected =
+            self.cpu_norm_backward(&grad_output_values, &input_values, &gamma_values, options);
+
+        let client = TestRuntime::client(&Default::default());
+        let grad_output_handle = client.create_from_slice(TestDType::as_bytes(&grad_output_values));
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let gamma_handle = client.create_from_slice(TestDType::as_bytes(&gamma_values));
+        let mean_handle = client.create_from_slice(f32::as_bytes(&expected.mean));
+        let rstd_handle = client.create_from_slice(f32::as_bytes(&expected.rstd));
+
+        let output_size = self.shape.iter().product::<usize>();
+        let output_stride = contiguous_strides(&self.shape);
+        let mut statistics_shape = self.shape.clone();
+        *statistics_shape.last_mut().unwrap() = 1;
+        let statistics_stride = contiguous_strides(&statistics_shape);
+
+        let strategies = [
+            RoutineStrategy::Unit(BlueprintStrategy::Inferred(UnitStrategy)),
+            RoutineStrategy::Plane(BlueprintStrategy::Inferred(PlaneStrategy {
+                independent: true,
+            })),
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: false,
+            })),
+        ];
+
+        for strategy in strategies {
+            let grad_input_handle = client.empty(output_size * size_of::<TestDType>());
+            let grad_gamma_handle = client.empty(row_len * size_of::<TestDType>());
+            let grad_beta_handle = client.empty(row_len * size_of::<TestDType>());
+
+            let grad_output = tensor(
+                &grad_output_handle,
+                &self.stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            );
+            let input = tensor(
+                &input_handle,
+                &self.stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            );
+            let mean = tensor(
+                &mean_handle,
+                &statistics_stride,
+                &statistics_shape,
+                size_of::<f32>(),
+            );
+            let rstd = tensor(
+                &rstd_handle,
+                &statistics_stride,
+                &statistics_shape,
+                size_of::<f32>(),
+            );
+            let grad_input = tensor(
+                &grad_input_handle,
+                &output_stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            );
+            let row_shape = [row_len];
+            let row_tensor = |handle| tensor(handle, &[1], &row_shape, size_of::<TestDType>());
+            let tensors = NormBackwardTensors {
+                gamma: options.affine.then(|| row_tensor(&gamma_handle)),
+                grad_gamma: options.affine.then(|| row_tensor(&grad_gamma_handle)),
+                grad_beta: options.affine.then(|| row_tensor(&grad_beta_handle)),
+            };
+            let dtypes = ReduceDtypes {
+                input: TestDType::as_type_native_unchecked(),
+                output: TestDType::as_type_native_unchecked(),
+                accumulation: f32::as_type_native_unchecked(),
+            };
+
+            let result = match options.rms {
+                true => rms_norm_backward::<TestRuntime>(
+                    &client,
+                    grad_output,
+                    input,
+                    rstd,
+                    grad_input,
+                    tensors,
+                    strategy,
+                    dtypes,
+                ),
+                false => layer_norm_backward::<TestRuntime>(
+                    &client,
+                    grad_output,
+                    input,
+                    mean,
+                    rstd,
+                    grad_input,
+                    tensors,
+                    strategy,
+                    dtypes,
+                ),
+            };
+
+            match result {
+                Ok(_) => {}
+                Err(ReduceError::PlanesUnavailable | ReduceError::ImprecisePlaneDim) => {
+                    continue;
+                }
+                Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+                Err(err) => panic!("Invalid test case {self:?}: {err:?}"),
+            }
+
+            let read_values = |handle| {
+                TestDType::from_bytes(&client.read_one(handle))
+                    .iter()
+                    .map(|v| v.to_f32().unwrap())
+                    .collect::<Vec<_>>()
+            };
+            assert_close(&read_values(grad_input_handle), &expected.grad_input);
+            if options.affine {
+                assert_close(&read_values(grad_gamma_handle), &expected.grad_gamma);
+                assert_close(&read_values(grad_beta_handle), &expected.grad_beta);
+            }
+        }
+    }
+
+    /// Compute the statistics and the gradients of every row in `f32`, with contiguous outputs.
+    fn cpu_norm_backward(
+        &self,
+        grad_output: &[TestDType],
+        input: &[TestDType],
+        gamma: &[TestDType],
+        options: NormBackwardOptions,
+    ) -> NormBackwardReference {
+        let row_len = *self.shape.last().unwrap();
+        let row_stride = *self.stride.last().unwrap();
+        let num_rows = self.shape.iter().product::<usize>() / row_len;
+        let mut reference = NormBackwardReference {
+            mean: Vec::new(),
+            rstd: Vec::new(),
+            grad_input: Vec::new(),
+            grad_gamma: vec![0.0; row_len],
+            grad_beta: vec![0.0; row_len],
+        };
+
+        for row in 0..num_rows {
+            let mut remainder = row;
+            let mut offset = 0;
+            for dim in (0..self.shape.len() - 1).rev() {
+                offset += (remainder % self.shape[dim]) * self.stride[dim];
+                remainder /= self.shape[dim];
+            }
+
+            let read = |values: &[TestDType]| {
+                (0..row_len)
+                    .map(|i| values[offset + i * row_stride].to_f32().unwrap())
+                    .collect::<Vec<_>>()
+            };
+            let dy = read(grad_output);
+            let x = read(input);
+
+            let mean = x.iter().sum::<f32>() / row_len as f32;
+            let center = if options.rms { 0.0 } else { mean };
+            let second_moment =
+                x.iter().map(|v| (v - center) * (v - center)).sum::<f32>() / row_len as f32;
+            let rstd = 1.0 / (second_moment + EPSILON).sqrt();
+
+            let xhat = x.iter().map(|v| (v - center) * rstd).collect::<Vec<_>>();
+            let g = (0..row_len)
+                .map(|i| match options.affine {
+                    true => dy[i] * gamma[i].to_f32().unwrap(),
+                    false => dy[i],
+                })
+                .collect::<Vec<_>>();
+            let mean_g = g.iter().sum::<f32>() / row_len as f32;
+            let mean_gx =
+                g.iter().zip(xhat.iter()).map(|(g, x)| g * x).sum::<f32>() / row_len as f32;
+
+            for (i, xhat) in xhat.iter().enumerate() {
+                let mut result = g[i] - xhat * mean_gx;
+                if !options.rms {
+                    result -= mean_g;
+                }
+                reference.grad_input.push(result * rstd);
+                reference.grad_gamma[i] += dy[i] * xhat;
+                reference.grad_beta[i] += dy[i];
+            }
+            reference.mean.push(mean);
+            reference.rstd.push(rstd);
+        }
+
+        reference
+    }
+
+    fn random_values<F: Float>(&self, size: usize, seed_offset: u64) -> Vec<F> {
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed() + seed_offset);
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| F::new(r as f32 * factor))
+            .collect()
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+fn tensor<'a>(
+    handle: &'a Handle,
+    strides: &'a [usize],
+    shape: &'a [usize],
+    elem_size: usize,
+) -> TensorHandleRef<'a, TestRuntime> {
+    unsafe { TensorHandleRef::from_raw_parts(handle, strides, shape, elem_size) }
+}
+
+/// The gradients are often close to 0, so the tolerance is partly absolute.
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+        let tolerance = 1e-2 * (1.0 + e.abs());
+        assert!(
+            (a - e).abs() < tolerance,
+            "Values are not approx equal: index={i} actual={a}, expected={e}"
+        );
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
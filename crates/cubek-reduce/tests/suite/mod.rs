@@ -46,6 +46,40 @@ macro_rules! testgen_reduce {
 
             include!("reduce_shared.rs");
         }
+
+        mod reduce_all {
+            type TestDType = $dtype;
+            fn test_shape() -> Vec<usize> {
+                $shape
+            }
+            fn test_strides() -> Vec<usize> {
+                $strides
+            }
+
+            include!("reduce_all.rs");
+        }
+    };
+
+    (
+        dtype: $dtype:ty,
+        shape: $shape:expr,
+        strides: $strides:expr,
+        axes: $axes:expr,
+    ) => {
+        mod reduce_axes {
+            type TestDType = $dtype;
+            fn test_shape() -> Vec<usize> {
+                $shape
+            }
+            fn test_strides() -> Vec<usize> {
+                $strides
+            }
+            fn test_axes() -> Vec<usize> {
+                $axes
+            }
+
+            include!("reduce_axes.rs");
+        }
     };
 
     (
@@ -295,6 +329,368 @@ macro_rules! testgen_reduce {
             );
         }
     };
+    (
+        shape: $shape:expr,
+        strides: $strides:expr,
+        axes: $axes:expr,
+    ) => {
+        mod f32 {
+            testgen_reduce!(
+                dtype: f32,
+                shape: $shape,
+                strides: $strides,
+                axes: $axes,
+            );
+        }
+    };
+    (
+        shape: $shape:expr,
+        strides: $strides:expr,
+        axis: $axis:expr,
+        k: $k:expr,
+    ) => {
+        mod f32 {
+            mod top_k {
+                type TestDType = f32;
+                fn test_shape() -> Vec<usize> {
+                    $shape
+                }
+                fn test_strides() -> Vec<usize> {
+                    $strides
+                }
+                fn test_axis() -> usize {
+                    $axis
+                }
+                fn test_k() -> u32 {
+                    $k
+                }
+
+                include!("top_k.rs");
+            }
+        }
+    };
+    (
+        shape: $shape:expr,
+        strides: $strides:expr,
+        scan_axis: $axis:expr,
+    ) => {
+        mod f32 {
+            mod scan {
+                type TestDType = f32;
+                fn test_shape() -> Vec<usize> {
+                    $shape
+                }
+                fn test_strides() -> Vec<usize> {
+                    $strides
+                }
+                fn test_axis() -> usize {
+                    $axis
+                }
+
+                include!("scan.rs");
+            }
+        }
+    };
+    (
+        dtype: $dtype:ty,
+        shape: $shape:expr,
+        strides: $strides:expr,
+        softmax_axis: $axis:expr,
+    ) => {
+        mod softmax {
+            type TestDType = $dtype;
+            fn test_shape() -> Vec<usize> {
+                $shape
+            }
+            fn test_strides() -> Vec<usize> {
+                $strides
+            }
+            fn test_axis() -> usize {
+                $axis
+            }
+
+            include!("softmax.rs");
+        }
+    };
+    (
+        shape: $shape:expr,
+        strides: $strides:expr,
+        softmax_axis: $axis:expr,
+    ) => {
+        mod f32 {
+            testgen_reduce!(
+                dtype: f32,
+                shape: $shape,
+                strides: $strides,
+                softmax_axis: $axis,
+            );
+        }
+        mod f16 {
+            testgen_reduce!(
+                dtype: half::f16,
+                shape: $shape,
+                strides: $strides,
+                softmax_axis: $axis,
+            );
+        }
+    };
+    (
+        dtype: $dtype:ty,
+        norm_shape: $shape:expr,
+        strides: $strides:expr,
+    ) => {
+        mod layer_norm {
+            type TestDType = $dtype;
+            fn test_shape() -> Vec<usize> {
+                $shape
+            }
+            fn test_strides() -> Vec<usize> {
+                $strides
+            }
+
+            include!("layer_norm.rs");
+        }
+    };
+    (
+        norm_shape: $shape:expr,
+        strides: $strides:expr,
+    ) => {
+        mod f32 {
+            testgen_reduce!(
+                dtype: f32,
+                norm_shape: $shape,
+                strides: $strides,
+            );
+        }
+        mod f16 {
+            testgen_reduce!(
+                dtype: half::f16,
+                norm_shape: $shape,
+                strides: $strides,
+            );
+        }
+    };
+    (
+        dtype: $dtype:ty,
+        norm_backward_shape: $shape:expr,
+        strides: $strides:expr,
+    ) => {
+        mod layer_norm_backward {
+            type TestDType = $dtype;
+            fn test_shape() -> Vec<usize> {
+                $shape
+            }
+            fn test_strides() -> Vec<usize> {
+                $strides
+            }
+
+            include!("layer_norm_backward.rs");
+        }
+    };
+    (
+        norm_backward_shape: $shape:expr,
+        strides: $strides:expr,
+    ) => {
+        mod f32 {
+            testgen_reduce!(
+                dtype: f32,
+                norm_backward_shape: $shape,
+                strides: $strides,
+            );
+        }
+        mod f16 {
+            testgen_reduce!(
+                dtype: half::f16,
+                norm_backward_shape: $shape,
+                strides: $strides,
+            );
+        }
+    };
+    (
+        dtype: $dtype:ty,
+        channel_norm_shape: $shape:expr,
+        strides: $strides:expr,
+        num_groups: $num_groups:expr,
+    ) => {
+        mod channel_norm {
+            type TestDType = $dtype;
+            fn test_shape() -> Vec<usize> {
+                $shape
+            }
+            fn test_strides() -> Vec<usize> {
+                $strides
+            }
+            fn test_num_groups() -> usize {
+                $num_groups
+            }
+
+            include!("channel_norm.rs");
+        }
+    };
+    (
+        channel_norm_shape: $shape:expr,
+        strides: $strides:expr,
+        num_groups: $num_groups:expr,
+    ) => {
+        mod f32 {
+            testgen_reduce!(
+                dtype: f32,
+                channel_norm_shape: $shape,
+                strides: $strides,
+                num_groups: $num_groups,
+            );
+        }
+        mod f16 {
+            testgen_reduce!(
+                dtype: half::f16,
+                channel_norm_shape: $shape,
+                strides: $strides,
+                num_groups: $num_groups,
+            );
+        }
+    };
+    (
+        dtype: $dtype:ty,
+        shape: $shape:expr,
+        strides: $strides:expr,
+        arg_axis: $axis:expr,
+    ) => {
+        mod arg_extremum {
+            type TestDType = $dtype;
+            fn test_shape() -> Vec<usize> {
+                $shape
+            }
+            fn test_strides() -> Vec<usize> {
+                $strides
+            }
+            fn test_axis() -> usize {
+                $axis
+            }
+
+            include!("arg_extremum.rs");
+        }
+    };
+    (
+        shape: $shape:expr,
+        strides: $strides:expr,
+        arg_axis: $axis:expr,
+    ) => {
+        mod f32 {
+            testgen_reduce!(
+                dtype: f32,
+                shape: $shape,
+                strides: $strides,
+                arg_axis: $axis,
+            );
+        }
+        mod f16 {
+            testgen_reduce!(
+                dtype: half::f16,
+                shape: $shape,
+                strides: $strides,
+                arg_axis: $axis,
+            );
+        }
+    };
+    (
+        segment_lengths: $lengths:expr,
+    ) => {
+        mod f32 {
+            mod segmented {
+                type TestDType = f32;
+                fn test_segment_lengths() -> Vec<u32> {
+                    $lengths
+                }
+
+                include!("segmented.rs");
+            }
+        }
+    };
+    (
+        compensated_shape: $shape:expr,
+        strides: $strides:expr,
+        axis: $axis:expr,
+    ) => {
+        mod f32 {
+            mod compensated {
+                fn test_shape() -> Vec<usize> {
+                    $shape
+                }
+                fn test_strides() -> Vec<usize> {
+                    $strides
+                }
+                fn test_axis() -> usize {
+                    $axis
+                }
+
+                include!("compensated.rs");
+            }
+        }
+    };
+    (
+        autotune_shape: $shape:expr,
+        strides: $strides:expr,
+        axis: $axis:expr,
+    ) => {
+        mod f32 {
+            mod autotune {
+                fn test_shape() -> Vec<usize> {
+                    $shape
+                }
+                fn test_strides() -> Vec<usize> {
+                    $strides
+                }
+                fn test_axis() -> usize {
+                    $axis
+                }
+
+                include!("autotune.rs");
+            }
+        }
+    };
+    (
+        dtype: $dtype:ty,
+        histogram_shape: $shape:expr,
+        strides: $strides:expr,
+        num_bins: $num_bins:expr,
+    ) => {
+        mod histogram {
+            type TestDType = $dtype;
+            fn test_shape() -> Vec<usize> {
+                $shape
+            }
+            fn test_strides() -> Vec<usize> {
+                $strides
+            }
+            fn test_num_bins() -> usize {
+                $num_bins
+            }
+
+            include!("histogram.rs");
+        }
+    };
+    (
+        histogram_shape: $shape:expr,
+        strides: $strides:expr,
+        num_bins: $num_bins:expr,
+    ) => {
+        mod f32 {
+            testgen_reduce!(
+                dtype: f32,
+                histogram_shape: $shape,
+                strides: $strides,
+                num_bins: $num_bins,
+            );
+        }
+        mod f16 {
+            testgen_reduce!(
+                dtype: half::f16,
+                histogram_shape: $shape,
+                strides: $strides,
+                num_bins: $num_bins,
+            );
+        }
+    };
     (
         shape: $shape:expr,
         strides: $strides:expr,
@@ -544,3 +940,418 @@ mod reduce {
         );
     }
 }
+
+mod reduce_axes {
+    mod contiguous_inner_axes {
+        testgen_reduce!(
+            shape: vec![4, 6, 8],
+            strides: vec![48, 8, 1],
+            axes: vec![1, 2],
+        );
+    }
+
+    mod contiguous_outer_axes {
+        testgen_reduce!(
+            shape: vec![4, 6, 8],
+            strides: vec![48, 8, 1],
+            axes: vec![0, 1],
+        );
+    }
+
+    mod non_adjacent_axes {
+        testgen_reduce!(
+            shape: vec![4, 6, 8, 5],
+            strides: vec![240, 40, 5, 1],
+            axes: vec![0, 2],
+        );
+    }
+
+    mod non_adjacent_unordered_axes {
+        testgen_reduce!(
+            shape: vec![3, 4, 5, 6],
+            strides: vec![120, 30, 6, 1],
+            axes: vec![3, 2, 0],
+        );
+    }
+
+    mod transposed_axes {
+        testgen_reduce!(
+            shape: vec![8, 16, 4],
+            strides: vec![1, 32, 8],
+            axes: vec![0, 2],
+        );
+    }
+
+    mod all_axes {
+        testgen_reduce!(
+            shape: vec![4, 8, 16],
+            strides: vec![128, 16, 1],
+            axes: vec![0, 1, 2],
+        );
+    }
+}
+
+mod top_k {
+    mod parallel_matrix_small {
+        testgen_reduce!(
+            shape: vec![4, 40],
+            strides: vec![40, 1],
+            axis: 1,
+            k: 5,
+        );
+    }
+
+    mod parallel_matrix_large_k {
+        testgen_reduce!(
+            shape: vec![3, 300],
+            strides: vec![300, 1],
+            axis: 1,
+            k: 64,
+        );
+    }
+
+    mod perpendicular_matrix {
+        testgen_reduce!(
+            shape: vec![50, 6],
+            strides: vec![6, 1],
+            axis: 0,
+            k: 8,
+        );
+    }
+
+    mod k_equals_axis_size {
+        testgen_reduce!(
+            shape: vec![2, 16],
+            strides: vec![16, 1],
+            axis: 1,
+            k: 16,
+        );
+    }
+
+    mod rank_three_tensor_many_vectors {
+        testgen_reduce!(
+            shape: vec![16, 12, 10],
+            strides: vec![120, 10, 1],
+            axis: 1,
+            k: 3,
+        );
+    }
+}
+
+mod scan {
+    mod vector_long {
+        testgen_reduce!(
+            shape: vec![1000],
+            strides: vec![1],
+            scan_axis: 0,
+        );
+    }
+
+    mod parallel_matrix {
+        testgen_reduce!(
+            shape: vec![8, 70],
+            strides: vec![70, 1],
+            scan_axis: 1,
+        );
+    }
+
+    mod perpendicular_matrix {
+        testgen_reduce!(
+            shape: vec![70, 8],
+            strides: vec![8, 1],
+            scan_axis: 0,
+        );
+    }
+
+    mod rank_three_tensor_transposed {
+        testgen_reduce!(
+            shape: vec![4, 33, 5],
+            strides: vec![165, 1, 33],
+            scan_axis: 1,
+        );
+    }
+}
+
+mod segmented {
+    mod short_segments {
+        testgen_reduce!(
+            segment_lengths: vec![1, 3, 7, 32, 2, 17],
+        );
+    }
+
+    mod mixed_segments {
+        testgen_reduce!(
+            segment_lengths: vec![5, 33, 1024, 1025, 3000, 1, 200],
+        );
+    }
+
+    mod long_segments {
+        testgen_reduce!(
+            segment_lengths: vec![4096, 5000],
+        );
+    }
+}
+
+mod softmax {
+    mod vector {
+        testgen_reduce!(
+            shape: vec![1000],
+            strides: vec![1],
+            softmax_axis: 0,
+        );
+    }
+
+    mod parallel_matrix {
+        testgen_reduce!(
+            shape: vec![8, 20],
+            strides: vec![20, 1],
+            softmax_axis: 1,
+        );
+    }
+
+    mod perpendicular_matrix {
+        testgen_reduce!(
+            shape: vec![20, 8],
+            strides: vec![8, 1],
+            softmax_axis: 0,
+        );
+    }
+
+    mod parallel_matrix_long_rows {
+        testgen_reduce!(
+            shape: vec![3, 5000],
+            strides: vec![5000, 1],
+            softmax_axis: 1,
+        );
+    }
+
+    mod rank_three_tensor_transposed {
+        testgen_reduce!(
+            shape: vec![4, 33, 5],
+            strides: vec![165, 1, 33],
+            softmax_axis: 1,
+        );
+    }
+}
+
+mod layer_norm {
+    mod small_rows {
+        testgen_reduce!(
+            norm_shape: vec![8, 20],
+            strides: vec![20, 1],
+        );
+    }
+
+    mod long_rows {
+        testgen_reduce!(
+            norm_shape: vec![4, 1000],
+            strides: vec![1000, 1],
+        );
+    }
+
+    mod rank_three_tensor {
+        testgen_reduce!(
+            norm_shape: vec![3, 5, 64],
+            strides: vec![320, 64, 1],
+        );
+    }
+
+    mod transposed_matrix {
+        testgen_reduce!(
+            norm_shape: vec![6, 32],
+            strides: vec![1, 6],
+        );
+    }
+}
+
+mod layer_norm_backward {
+    mod small_rows {
+        testgen_reduce!(
+            norm_backward_shape: vec![8, 20],
+            strides: vec![20, 1],
+        );
+    }
+
+    mod long_rows {
+        testgen_reduce!(
+            norm_backward_shape: vec![4, 1000],
+            strides: vec![1000, 1],
+        );
+    }
+
+    mod many_rows {
+        testgen_reduce!(
+            norm_backward_shape: vec![70, 80],
+            strides: vec![80, 1],
+        );
+    }
+
+    mod transposed_matrix {
+        testgen_reduce!(
+            norm_backward_shape: vec![40, 32],
+            strides: vec![1, 40],
+        );
+    }
+}
+
+mod channel_norm {
+    mod small_nhwc {
+        testgen_reduce!(
+            channel_norm_shape: vec![2, 4, 4, 8],
+            strides: vec![128, 32, 8, 1],
+            num_groups: 2,
+        );
+    }
+
+    mod large_nhwc {
+        testgen_reduce!(
+            channel_norm_shape: vec![4, 48, 48, 8],
+            strides: vec![18432, 384, 8, 1],
+            num_groups: 1,
+        );
+    }
+
+    mod nchw_memory_layout {
+        testgen_reduce!(
+            channel_norm_shape: vec![2, 5, 6, 12],
+            strides: vec![360, 6, 1, 30],
+            num_groups: 3,
+        );
+    }
+
+    mod rank_three_tensor {
+        testgen_reduce!(
+            channel_norm_shape: vec![3, 10, 6],
+            strides: vec![60, 6, 1],
+            num_groups: 6,
+        );
+    }
+}
+
+mod arg_extremum {
+    mod vector {
+        testgen_reduce!(
+            shape: vec![1000],
+            strides: vec![1],
+            arg_axis: 0,
+        );
+    }
+
+    mod parallel_matrix {
+        testgen_reduce!(
+            shape: vec![8, 70],
+            strides: vec![70, 1],
+            arg_axis: 1,
+        );
+    }
+
+    mod perpendicular_matrix {
+        testgen_reduce!(
+            shape: vec![70, 8],
+            strides: vec![8, 1],
+            arg_axis: 0,
+        );
+    }
+
+    mod parallel_matrix_unaligned {
+        testgen_reduce!(
+            shape: vec![8, 37],
+            strides: vec![37, 1],
+            arg_axis: 1,
+        );
+    }
+
+    mod rank_three_tensor_transposed {
+        testgen_reduce!(
+            shape: vec![4, 33, 5],
+            strides: vec![165, 1, 33],
+            arg_axis: 1,
+        );
+    }
+}
+
+mod histogram {
+    mod vector_few_bins {
+        testgen_reduce!(
+            histogram_shape: vec![1000],
+            strides: vec![1],
+            num_bins: 10,
+        );
+    }
+
+    mod matrix_many_cubes {
+        testgen_reduce!(
+            histogram_shape: vec![20, 1000],
+            strides: vec![1000, 1],
+            num_bins: 100,
+        );
+    }
+
+    mod transposed_matrix {
+        testgen_reduce!(
+            histogram_shape: vec![33, 70],
+            strides: vec![1, 33],
+            num_bins: 7,
+        );
+    }
+
+    mod more_bins_than_shared_memory {
+        testgen_reduce!(
+            histogram_shape: vec![6000],
+            strides: vec![1],
+            num_bins: 5000,
+        );
+    }
+}
+
+mod compensated {
+    mod vector {
+        testgen_reduce!(
+            compensated_shape: vec![65536],
+            strides: vec![1],
+            axis: 0,
+        );
+    }
+
+    mod parallel_matrix {
+        testgen_reduce!(
+            compensated_shape: vec![4, 16384],
+            strides: vec![16384, 1],
+            axis: 1,
+        );
+    }
+
+    mod perpendicular_matrix {
+        testgen_reduce!(
+            compensated_shape: vec![16384, 4],
+            strides: vec![4, 1],
+            axis: 0,
+        );
+    }
+}
+
+mod autotune {
+    mod parallel_matrix {
+        testgen_reduce!(
+            autotune_shape: vec![37, 1024],
+            strides: vec![1024, 1],
+            axis: 1,
+        );
+    }
+
+    mod perpendicular_matrix {
+        testgen_reduce!(
+            autotune_shape: vec![1024, 37],
+            strides: vec![37, 1],
+            axis: 0,
+        );
+    }
+
+    mod rank_three_tensor {
+        testgen_reduce!(
+            autotune_shape: vec![16, 12, 64],
+            strides: vec![768, 64, 1],
+            axis: 1,
+        );
+    }
+}
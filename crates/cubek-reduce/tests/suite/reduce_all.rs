@@ -0,0 +1,279 @@
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubecl::server::Handle;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError, components::instructions::ReduceOperationConfig, reduce_all,
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+static PRECISION: i32 = 4;
+
+#[test]
+pub fn test_sum() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let expected = input_values
+        .iter()
+        .fold(TestDType::from_int(0), |acc, v| acc + *v);
+    test_case().run_reduce_all_test(input_values, expected, ReduceOperationConfig::Sum);
+}
+
+#[test]
+pub fn test_mean() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let sum = input_values
+        .iter()
+        .fold(TestDType::from_int(0), |acc, v| acc + *v);
+    let expected = sum / TestDType::from_int(input_values.len() as i64);
+    test_case().run_reduce_all_test(input_values, expected, ReduceOperationConfig::Mean);
+}
+
+#[test]
+pub fn test_max() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let expected =
+        input_values.iter().fold(
+            TestDType::min_value(),
+            |acc, v| if *v > acc { *v } else { acc },
+        );
+    test_case().run_reduce_all_test(input_values, expected, ReduceOperationConfig::Max);
+}
+
+#[test]
+pub fn test_min() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let expected =
+        input_values.iter().fold(
+            TestDType::max_value(),
+            |acc, v| if *v < acc { *v } else { acc },
+        );
+    test_case().run_reduce_all_test(input_values, expected, ReduceOperationConfig::Min);
+}
+
+#[test]
+pub fn test_max_abs() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let expected = input_values.iter().fold(TestDType::from_int(0), |acc, v| {
+        if v.abs() > acc { v.abs() } else { acc }
+    });
+    test_case().run_reduce_all_test(input_values, expected, ReduceOperationConfig::MaxAbs);
+}
+
+#[test]
+pub fn test_argmax() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let (_, expected) = input_values.iter().enumerate().fold(
+        (TestDType::min_value(), 0),
+        |(best, index), (i, v)| {
+            if *v > best { (*v, i) } else { (best, index) }
+        },
+    );
+    test_case().run_reduce_all_test(input_values, expected as u32, ReduceOperationConfig::ArgMax);
+}
+
+#[test]
+pub fn test_argmin() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let (_, expected) = input_values.iter().enumerate().fold(
+        (TestDType::max_value(), 0),
+        |(best, index), (i, v)| {
+            if *v < best { (*v, i) } else { (best, index) }
+        },
+    );
+    test_case().run_reduce_all_test(input_values, expected as u32, ReduceOperationConfig::ArgMin);
+}
+
+#[test]
+pub fn test_var() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let values = input_values.iter().map(|v| v.to_f32().unwrap());
+    let mean = values.clone().sum::<f32>() / input_values.len() as f32;
+    let m2 = values.map(|v| (v - mean) * (v - mean)).sum::<f32>();
+    let expected = TestDType::new(m2 / (input_values.len() - 1) as f32);
+    test_case().run_reduce_all_test(
+        input_values,
+        expected,
+        ReduceOperationConfig::Var { correction: 1 },
+    );
+}
+
+#[test]
+pub fn test_logsumexp() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let values = input_values.iter().map(|v| v.to_f32().unwrap());
+    let max = values.clone().fold(f32::NEG_INFINITY, f32::max);
+    let sum = values.map(|v| (v - max).exp()).sum::<f32>();
+    let expected = TestDType::new(max + sum.ln());
+    test_case().run_reduce_all_test(input_values, expected, ReduceOperationConfig::LogSumExp);
+}
+
+#[test]
+pub fn test_frobenius_norm() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let squares = input_values
+        .iter()
+        .map(|v| v.to_f32().unwrap() * v.to_f32().unwrap());
+    let expected = TestDType::new(squares.sum::<f32>().sqrt());
+    test_case().run_reduce_all_test(input_values, expected, ReduceOperationConfig::L2Norm);
+}
+
+#[test]
+pub fn test_count_nonzero() {
+    let input_values: Vec<TestDType> = test_case().random_input_values();
+    let expected = input_values
+        .iter()
+        .filter(|v| **v != TestDType::from_int(0))
+        .count() as u32;
+    test_case().run_reduce_all_test(input_values, expected, ReduceOperationConfig::CountNonZero);
+}
+
+#[test]
+pub fn test_zero_cubes() {
+    test_case().run_zero_cubes_test();
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+}
+
+impl TestCase {
+    pub fn run_reduce_all_test<O: Numeric + CubeElement>(
+        &self,
+        input_values: Vec<TestDType>,
+        expected: O,
+        config: ReduceOperationConfig,
+    ) {
+        let client = TestRuntime::client(&Default::default());
+        let output_handle = client.empty(size_of::<O>());
+
+        let result = self.launch::<O>(&client, input_values, &output_handle, 3, config);
+
+        match result {
+            Ok(_) => {}
+            // Only contiguous inputs are supported.
+            Err(ReduceError::Validation { .. }) => return,
+            Err(err) => panic!("The test didn't run: {err:?}"),
+        }
+
+        let bytes = client.read_one(output_handle);
+        let actual = O::from_bytes(&bytes);
+        assert_approx_equal(actual, &[expected]);
+    }
+
+    pub fn run_zero_cubes_test(&self) {
+        let client = TestRuntime::client(&Default::default());
+        let output_handle = client.empty(size_of::<TestDType>());
+        let input_values = self.random_input_values();
+
+        let result = self.launch::<TestDType>(
+            &client,
+            input_values,
+            &output_handle,
+            0,
+            ReduceOperationConfig::Sum,
+        );
+
+        assert!(matches!(result, Err(ReduceError::Validation { .. })));
+    }
+
+    fn launch<O: Numeric + CubeElement>(
+        &self,
+        client: &ComputeClient<TestRuntime>,
+        input_values: Vec<TestDType>,
+        output_handle: &Handle,
+        cube_count: u32,
+        config: ReduceOperationConfig,
+    ) -> Result<(), ReduceError> {
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let output_shape = vec![1; self.shape.len()];
+
+        let input = unsafe {
+            TensorHandleRef::from_raw_parts(
+                &input_handle,
+                &self.stride,
+                &self.shape,
+                size_of::<TestDType>(),
+            )
+        };
+        let output = unsafe {
+            TensorHandleRef::from_raw_parts(
+                output_handle,
+                &output_shape,
+                &output_shape,
+                size_of::<O>(),
+            )
+        };
+
+        reduce_all(
+            client,
+            input,
+            output,
+            cube_count,
+            config,
+            ReduceDtypes {
+                input: TestDType::as_type_native_unchecked(),
+                output: O::as_type_native_unchecked(),
+                accumulation: TestDType::as_type_native_unchecked(),
+            },
+        )
+    }
+
+    fn random_input_values<F: Float>(&self) -> Vec<F> {
+        let size = self.input_size();
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| F::new(r as f32 * factor))
+            .collect()
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+pub fn assert_approx_equal<N: Numeric>(actual: &[N], expected: &[N]) {
+    for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+        let a = a.to_f32().unwrap();
+        let e = e.to_f32().unwrap();
+        let diff = (a - e).abs();
+        if e == 0.0 {
+            assert!(
+                diff < 1e-5,
+                "Values are not approx equal: index={i} actual={a}, expected={e}, difference={diff}",
+            );
+        } else {
+            let rel_diff = diff / e.abs();
+            assert!(
+                rel_diff < 0.0625,
+                "Values are not approx equal: index={i} actual={a}, expected={e}"
+            );
+        }
+    }
+}
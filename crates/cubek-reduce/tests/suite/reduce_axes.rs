@@ -0,0 +1,288 @@
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError, ReduceStrategy,
+    components::instructions::ReduceOperationConfig,
+    launch::{LineSizeStrategy, RoutineStrategy},
+    reduce_axes,
+    routines::{BlueprintStrategy, plane::PlaneStrategy, unit::UnitStrategy},
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+static PRECISION: i32 = 4;
+
+#[test]
+pub fn test_sum() {
+    test_case().test_sum();
+}
+
+#[test]
+pub fn test_mean() {
+    test_case().test_mean();
+}
+
+#[test]
+pub fn test_max() {
+    test_case().test_max();
+}
+
+#[test]
+pub fn test_argmax() {
+    test_case().test_argmax();
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+        axes: test_axes(),
+    }
+}
+
+fn strategies() -> Vec<ReduceStrategy> {
+    let line_size = LineSizeStrategy {
+        parallel_output_vectorization: false,
+    };
+
+    vec![
+        ReduceStrategy {
+            routine: RoutineStrategy::Unit(BlueprintStrategy::Inferred(UnitStrategy)),
+            line_size,
+        },
+        ReduceStrategy {
+            routine: RoutineStrategy::Plane(BlueprintStrategy::Inferred(PlaneStrategy {
+                independent: true,
+            })),
+            line_size,
+        },
+    ]
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+    pub axes: Vec<usize>,
+}
+
+impl TestCase {
+    pub fn test_sum(&self) {
+        let input_values: Vec<TestDType> = self.random_input_values();
+        let expected = self.cpu_fold(&input_values, TestDType::from_int(0), |acc, v, _| acc + v);
+        self.run_reduce_axes_test(input_values, expected, ReduceOperationConfig::Sum);
+    }
+
+    pub fn test_mean(&self) {
+        let input_values: Vec<TestDType> = self.random_input_values();
+        let count = TestDType::from_int(self.reduce_size() as i64);
+        let expected = self
+            .cpu_fold(&input_values, TestDType::from_int(0), |acc, v, _| acc + v)
+            .into_iter()
+            .map(|sum| sum / count)
+            .collect();
+        self.run_reduce_axes_test(input_values, expected, ReduceOperationConfig::Mean);
+    }
+
+    pub fn test_max(&self) {
+        let input_values: Vec<TestDType> = self.random_input_values();
+        let expected = self.cpu_fold(&input_values, TestDType::min_value(), |acc, v, _| {
+            if v > acc { v } else { acc }
+        });
+        self.run_reduce_axes_test(input_values, expected, ReduceOperationConfig::Max);
+    }
+
+    pub fn test_argmax(&self) {
+        let input_values: Vec<TestDType> = self.random_input_values();
+        let expected = self
+            .cpu_fold(
+                &input_values,
+                (TestDType::min_value(), 0),
+                |(best, index), v, i| {
+                    if v > best { (v, i) } else { (best, index) }
+                },
+            )
+            .into_iter()
+            .map(|(_, index)| index as u32)
+            .collect();
+        self.run_reduce_axes_test(input_values, expected, ReduceOperationConfig::ArgMax);
+    }
+
+    /// Fold every reduced vector, visiting the reduced axes in row-major order.
+    ///
+    /// The folding function receives the accumulator, the value and the flattened index of the
+    /// value along the reduced axes.
+    fn cpu_fold<A: Clone>(
+        &self,
+        values: &[TestDType],
+        init: A,
+        fold: impl Fn(A, TestDType, usize) -> A,
+    ) -> Vec<A> {
+        let mut acc = vec![init; self.output_shape().iter().product::<usize>()];
+
+        for index in 0..self.shape.iter().product::<usize>() {
+            let coordinate = self.coordinate(index);
+            let value = values[self.input_offset(&coordinate)];
+
+            let mut output_index = 0;
+            let mut reduce_index = 0;
+            for (axis, (c, s)) in coordinate.iter().zip(self.shape.iter()).enumerate() {
+                if self.axes.contains(&axis) {
+                    reduce_index = reduce_index * s + c;
+                } else {
+                    output_index = output_index * s + c;
+                }
+            }
+
+            acc[output_index] = fold(acc[output_index].clone(), value, reduce_index);
+        }
+
+        acc
+    }
+
+    pub fn run_reduce_axes_test<O>(
+        &self,
+        input_values: Vec<TestDType>,
+        expected_values: Vec<O>,
+        config: ReduceOperationConfig,
+    ) where
+        O: Numeric + CubeElement,
+    {
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let output_shape = self.output_shape();
+        let output_stride = contiguous_strides(&output_shape);
+
+        for strategy in strategies() {
+            let output_handle =
+                client.create_from_slice(O::as_bytes(&vec![O::from_int(0); expected_values.len()]));
+
+            let input = unsafe {
+                TensorHandleRef::from_raw_parts(
+                    &input_handle,
+                    &self.stride,
+                    &self.shape,
+                    size_of::<TestDType>(),
+                )
+            };
+            let output = unsafe {
+                TensorHandleRef::from_raw_parts(
+                    &output_handle,
+                    &output_stride,
+                    &output_shape,
+                    size_of::<O>(),
+                )
+            };
+
+            let result = reduce_axes::<TestRuntime>(
+                &client,
+                input,
+                output,
+                &self.axes,
+                strategy,
+                config,
+                ReduceDtypes {
+                    input: TestDType::as_type_native_unchecked(),
+                    output: O::as_type_native_unchecked(),
+                    accumulation: f32::as_type_native_unchecked(),
+                },
+            );
+
+            match result {
+                Ok(_) => {}
+                Err(ReduceError::PlanesUnavailable | ReduceError::ImprecisePlaneDim) => continue,
+                Err(err) => panic!("The test didn't run: {err:?}"),
+            }
+
+            let bytes = client.read_one(output_handle);
+            let actual = O::from_bytes(&bytes);
+            assert_approx_equal(actual, &expected_values);
+        }
+    }
+
+    fn output_shape(&self) -> Vec<usize> {
+        let mut shape = self.shape.clone();
+        for axis in self.axes.iter() {
+            shape[*axis] = 1;
+        }
+        shape
+    }
+
+    fn reduce_size(&self) -> usize {
+        self.axes.iter().map(|axis| self.shape[*axis]).product()
+    }
+
+    fn coordinate(&self, index: usize) -> Vec<usize> {
+        contiguous_strides(&self.shape)
+            .iter()
+            .zip(self.shape.iter())
+            .map(|(stride, shape)| (index / stride) % shape)
+            .collect()
+    }
+
+    fn input_offset(&self, coordinate: &[usize]) -> usize {
+        coordinate
+            .iter()
+            .zip(self.stride.iter())
+            .map(|(c, s)| c * s)
+            .sum()
+    }
+
+    fn random_input_values<F: Float>(&self) -> Vec<F> {
+        let size = self.input_size();
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| F::new(r as f32 * factor))
+            .collect()
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
+
+pub fn assert_approx_equal<N: Numeric>(actual: &[N], expected: &[N]) {
+    for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+        let a = a.to_f32().unwrap();
+        let e = e.to_f32().unwrap();
+        let diff = (a - e).abs();
+        if e == 0.0 {
+            assert!(
+                diff < 1e-5,
+                "Values are not approx equal: index={i} actual={a}, expected={e}, difference={diff}",
+            );
+        } else {
+            let rel_diff = diff / e.abs();
+            assert!(
+                rel_diff < 0.0625,
+                "Values are not approx equal: index={i} actual={a}, expected={e}"
+            );
+        }
+    }
+}
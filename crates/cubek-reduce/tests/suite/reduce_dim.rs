@@ -1,4 +1,5 @@
 use crate::suite::test_case::TestCase;
+use cubek_reduce::Summation;
 
 #[test]
 pub fn test_argmax() {
@@ -12,12 +13,22 @@ pub fn test_argmin() {
 
 #[test]
 pub fn test_mean() {
-    test_case().test_mean();
+    test_case().test_mean(Summation::Naive);
+}
+
+#[test]
+pub fn test_compensated_mean() {
+    test_case().test_mean(Summation::Compensated);
 }
 
 #[test]
 pub fn test_sum() {
-    test_case().test_sum();
+    test_case().test_sum(Summation::Naive);
+}
+
+#[test]
+pub fn test_compensated_sum() {
+    test_case().test_sum(Summation::Compensated);
 }
 
 #[test]
@@ -25,6 +36,191 @@ pub fn test_prod() {
     test_case().test_prod();
 }
 
+#[test]
+pub fn test_var() {
+    test_case().test_var(1);
+}
+
+#[test]
+pub fn test_std() {
+    test_case().test_std(0);
+}
+
+#[test]
+pub fn test_logsumexp() {
+    test_case().test_logsumexp();
+}
+
+#[test]
+pub fn test_logsumexp_inf() {
+    test_case().test_logsumexp_inf();
+}
+
+#[test]
+pub fn test_l1_norm() {
+    test_case().test_l1_norm(Summation::Naive);
+}
+
+#[test]
+pub fn test_compensated_l1_norm() {
+    test_case().test_l1_norm(Summation::Compensated);
+}
+
+#[test]
+pub fn test_l2_norm() {
+    test_case().test_l2_norm(Summation::Naive);
+}
+
+#[test]
+pub fn test_compensated_l2_norm() {
+    test_case().test_l2_norm(Summation::Compensated);
+}
+
+#[test]
+pub fn test_lp_norm() {
+    test_case().test_lp_norm(3.0);
+}
+
+#[test]
+pub fn test_runtime_lp_norm() {
+    test_case().test_runtime_lp_norm(3.0);
+}
+
+#[test]
+pub fn test_runtime_lp_norm_inf() {
+    test_case().test_runtime_lp_norm_inf(3.0);
+}
+
+#[test]
+pub fn test_runtime_lp_norm_integer_accumulation() {
+    test_case().test_runtime_lp_norm_integer_accumulation();
+}
+
+#[test]
+pub fn test_any() {
+    test_case().test_any();
+}
+
+#[test]
+pub fn test_all() {
+    test_case().test_all();
+}
+
+#[test]
+pub fn test_count_nonzero() {
+    test_case().test_count_nonzero();
+}
+
+#[test]
+pub fn test_sum_of_powers() {
+    test_case().test_sum_of_powers(2);
+}
+
+#[test]
+pub fn test_sum_of_squares() {
+    test_case().test_sum_of_squares();
+}
+
+#[test]
+pub fn test_sum_of_large_squares() {
+    test_case().test_sum_of_large_squares();
+}
+
+#[test]
+pub fn test_sum_of_abs() {
+    test_case().test_sum_of_abs();
+}
+
+#[test]
+pub fn test_sum_of_exp() {
+    test_case().test_sum_of_exp();
+}
+
+#[test]
+pub fn test_scaled_sum() {
+    test_case().test_scaled_sum();
+}
+
+#[test]
+pub fn test_dot_product() {
+    test_case().test_dot_product();
+}
+
+#[test]
+pub fn test_large_dot_product() {
+    test_case().test_large_dot_product();
+}
+
+#[test]
+pub fn test_sum_nan() {
+    test_case().test_sum_nan();
+}
+
+#[test]
+pub fn test_var_nan() {
+    test_case().test_var_nan();
+}
+
+#[test]
+pub fn test_max_nan() {
+    test_case().test_max_nan();
+}
+
+#[test]
+pub fn test_min_nan() {
+    test_case().test_min_nan();
+}
+
+#[test]
+pub fn test_max_abs_nan() {
+    test_case().test_max_abs_nan();
+}
+
+#[test]
+pub fn test_max_nan_neg_inf() {
+    test_case().test_max_nan_neg_inf();
+}
+
+#[test]
+pub fn test_min_nan_inf() {
+    test_case().test_min_nan_inf();
+}
+
+#[test]
+pub fn test_nan_sum() {
+    test_case().test_nan_sum();
+}
+
+#[test]
+pub fn test_nan_mean() {
+    test_case().test_nan_mean();
+}
+
+#[test]
+pub fn test_nan_max() {
+    test_case().test_nan_max();
+}
+
+#[test]
+pub fn test_nan_min() {
+    test_case().test_nan_min();
+}
+
+#[test]
+pub fn test_nan_max_neg_inf() {
+    test_case().test_nan_max_neg_inf();
+}
+
+#[test]
+pub fn test_nan_min_inf() {
+    test_case().test_nan_min_inf();
+}
+
+#[test]
+pub fn test_mean_var() {
+    test_case().test_mean_var(1);
+}
+
 fn test_case() -> TestCase<TestDType> {
     TestCase::<TestDType> {
         shape: test_shape(),
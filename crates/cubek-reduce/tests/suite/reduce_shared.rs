@@ -1,6 +1,6 @@
 use cubecl::TestRuntime;
 use cubecl::prelude::*;
-use cubek_reduce::shared_sum;
+use cubek_reduce::{Determinism, shared_sum_with};
 use rand::{
     SeedableRng,
     distr::{Distribution, Uniform},
@@ -11,7 +11,12 @@ static PRECISION: i32 = 4;
 
 #[test]
 pub fn test_shared_sum() {
-    test_case().test_shared_sum()
+    test_case().test_shared_sum(Determinism::Relaxed)
+}
+
+#[test]
+pub fn test_shared_sum_deterministic() {
+    test_case().test_shared_sum(Determinism::Strict)
 }
 
 fn test_case() -> TestCase {
@@ -28,16 +33,21 @@ pub struct TestCase {
 }
 
 impl TestCase {
-    pub fn test_shared_sum(&self) {
+    pub fn test_shared_sum(&self, determinism: Determinism) {
         let input_values: Vec<TestDType> = self.random_input_values();
         let mut expected = TestDType::from_int(0);
         for v in input_values.iter() {
             expected += *v;
         }
-        self.run_shared_sum_test(input_values, expected);
+        self.run_shared_sum_test(input_values, expected, determinism);
     }
 
-    pub fn run_shared_sum_test(&self, input_values: Vec<TestDType>, expected: TestDType) {
+    pub fn run_shared_sum_test(
+        &self,
+        input_values: Vec<TestDType>,
+        expected: TestDType,
+        determinism: Determinism,
+    ) {
         let client = TestRuntime::client(&Default::default());
 
         let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
@@ -57,12 +67,13 @@ impl TestCase {
         };
 
         let cube_count = 3;
-        let result = shared_sum(
+        let result = shared_sum_with(
             &client,
             input,
             output,
             cube_count,
             TestDType::as_type_native_unchecked().elem_type(),
+            determinism,
         );
 
         if result.is_err() {
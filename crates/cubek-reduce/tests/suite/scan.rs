@@ -0,0 +1,261 @@
+use crate::suite::test_case::assert_approx_equal;
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError,
+    routines::scan::{ScanConfig, ScanOperation, ScanStrategy},
+    scan,
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+static PRECISION: i32 = 4;
+
+#[test]
+pub fn test_cumsum() {
+    test_case().test_scan(ScanOperation::Sum);
+}
+
+#[test]
+pub fn test_cumprod() {
+    test_case().test_scan(ScanOperation::Prod);
+}
+
+#[test]
+pub fn test_cummax() {
+    test_case().test_scan(ScanOperation::Max);
+}
+
+#[test]
+pub fn test_cummin() {
+    test_case().test_scan(ScanOperation::Min);
+}
+
+#[test]
+pub fn test_cummax_infinite() {
+    test_case().test_scan_infinite(ScanOperation::Max);
+}
+
+#[test]
+pub fn test_cummin_infinite() {
+    test_case().test_scan_infinite(ScanOperation::Min);
+}
+
+#[test]
+pub fn test_logcumsumexp() {
+    test_case().test_scan(ScanOperation::LogSumExp);
+}
+
+#[test]
+pub fn test_logcumsumexp_infinite() {
+    test_case().test_scan_infinite(ScanOperation::LogSumExp);
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+        axis: test_axis(),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+    pub axis: usize,
+}
+
+impl TestCase {
+    pub fn test_scan(&self, operation: ScanOperation) {
+        self.run_scan_test(operation, self.random_input_values(operation));
+    }
+
+    /// Every vector starts with the infinity that the operation discards,
+    /// and the other infinity shows up along the axis.
+    pub fn test_scan_infinite(&self, operation: ScanOperation) {
+        let (first, other) = match operation {
+            ScanOperation::Max | ScanOperation::LogSumExp => (f32::NEG_INFINITY, f32::INFINITY),
+            _ => (f32::INFINITY, f32::NEG_INFINITY),
+        };
+        let mut input_values: Vec<TestDType> = self.random_input_values(operation);
+        for (i, value) in input_values.iter_mut().enumerate() {
+            let position = (i / self.stride[self.axis]) % self.shape[self.axis];
+            if position < 3 {
+                *value = TestDType::new(first);
+            } else if position % 7 == 6 {
+                *value = TestDType::new(other);
+            }
+        }
+        self.run_scan_test(operation, input_values);
+    }
+
+    fn run_scan_test(&self, operation: ScanOperation, input_values: Vec<TestDType>) {
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let output_stride = contiguous_strides(&self.shape);
+        let output_size = self.shape.iter().product::<usize>();
+
+        let strategies = [
+            ScanStrategy::Unit,
+            ScanStrategy::Cube { use_planes: false },
+            ScanStrategy::Cube { use_planes: true },
+            ScanStrategy::MultiPass {
+                use_planes: false,
+                num_blocks: 3,
+            },
+            ScanStrategy::MultiPass {
+                use_planes: true,
+                num_blocks: 3,
+            },
+        ];
+
+        for exclusive in [false, true] {
+            let config = ScanConfig {
+                operation,
+                exclusive,
+            };
+            let expected_values = self.cpu_scan(&input_values, config);
+
+            for strategy in strategies {
+                let output_handle = client.empty(output_size * size_of::<TestDType>());
+                let (input, output) = unsafe {
+                    (
+                        TensorHandleRef::from_raw_parts(
+                            &input_handle,
+                            &self.stride,
+                            &self.shape,
+                            size_of::<TestDType>(),
+                        ),
+                        TensorHandleRef::from_raw_parts(
+                            &output_handle,
+                            &output_stride,
+                            &self.shape,
+                            size_of::<TestDType>(),
+                        ),
+                    )
+                };
+
+                let result = scan::<TestRuntime>(
+                    &client,
+                    input,
+                    output,
+                    self.axis,
+                    config,
+                    strategy,
+                    ReduceDtypes {
+                        input: TestDType::as_type_native_unchecked(),
+                        output: TestDType::as_type_native_unchecked(),
+                        accumulation: f32::as_type_native_unchecked(),
+                    },
+                );
+
+                match result {
+                    Ok(_) => {}
+                    Err(ReduceError::PlanesUnavailable | ReduceError::ImprecisePlaneDim) => {
+                        continue;
+                    }
+                    Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+                    Err(err) => panic!("Invalid test case {self:?}: {err:?}"),
+                }
+
+                let actual_values = client.read_one(output_handle);
+                assert_approx_equal(
+                    TestDType::from_bytes(&actual_values),
+                    &expected_values,
+                    false,
+                );
+            }
+        }
+    }
+
+    /// Scan every vector sequentially in `f32`.
+    fn cpu_scan(&self, values: &[TestDType], config: ScanConfig) -> Vec<TestDType> {
+        let output_stride = contiguous_strides(&self.shape);
+        let mut output = vec![TestDType::from_int(0); self.shape.iter().product::<usize>()];
+
+        let (identity, combine): (f32, fn(f32, f32) -> f32) = match config.operation {
+            ScanOperation::Sum => (0.0, |lhs, rhs| lhs + rhs),
+            ScanOperation::Prod => (1.0, |lhs, rhs| lhs * rhs),
+            ScanOperation::Max => (f32::NEG_INFINITY, f32::max),
+            ScanOperation::Min => (f32::INFINITY, f32::min),
+            // Equal infinities would give a NaN difference.
+            ScanOperation::LogSumExp => (f32::NEG_INFINITY, |lhs, rhs| {
+                let (max, min) = (lhs.max(rhs), lhs.min(rhs));
+                if lhs == rhs {
+                    max + core::f32::consts::LN_2
+                } else {
+                    max + (1.0 + (min - max).exp()).ln()
+                }
+            }),
+        };
+
+        let num_vectors = output.len() / self.shape[self.axis];
+        for vector in 0..num_vectors {
+            let mut remainder = vector;
+            let mut input_offset = 0;
+            let mut output_offset = 0;
+            for dim in (0..self.shape.len()).rev() {
+                if dim != self.axis {
+                    let coordinate = remainder % self.shape[dim];
+                    remainder /= self.shape[dim];
+                    input_offset += coordinate * self.stride[dim];
+                    output_offset += coordinate * output_stride[dim];
+                }
+            }
+
+            let mut prefix = identity;
+            for i in 0..self.shape[self.axis] {
+                let value = values[input_offset + i * self.stride[self.axis]];
+                let inclusive = combine(prefix, value.to_f32().unwrap());
+                let result = if config.exclusive { prefix } else { inclusive };
+                output[output_offset + i * output_stride[self.axis]] = TestDType::new(result);
+                prefix = inclusive;
+            }
+        }
+
+        output
+    }
+
+    // The products use values close to 1, so they don't overflow on long axes.
+    fn random_input_values<F: Float>(&self, operation: ScanOperation) -> Vec<F> {
+        let size = self.input_size();
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| match operation {
+                ScanOperation::Prod => F::new(1.0 + r as f32 * factor / 32.0),
+                _ => F::new(r as f32 * factor),
+            })
+            .collect()
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
@@ -0,0 +1,203 @@
+use crate::suite::test_case::assert_approx_equal;
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError, components::instructions::ReduceOperationConfig, segmented_reduce,
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+static PRECISION: i32 = 4;
+
+#[test]
+pub fn test_sum() {
+    test_case().test_segmented(ReduceOperationConfig::Sum, |values| values.iter().sum());
+}
+
+#[test]
+pub fn test_mean() {
+    test_case().test_segmented(ReduceOperationConfig::Mean, |values| {
+        values.iter().sum::<f32>() / values.len() as f32
+    });
+}
+
+#[test]
+pub fn test_max() {
+    test_case().test_segmented(ReduceOperationConfig::Max, |values| {
+        values.iter().copied().fold(f32::MIN, f32::max)
+    });
+}
+
+#[test]
+pub fn test_min() {
+    test_case().test_segmented(ReduceOperationConfig::Min, |values| {
+        values.iter().copied().fold(f32::MAX, f32::min)
+    });
+}
+
+#[test]
+pub fn test_argmax() {
+    test_case().test_segmented_arg(ReduceOperationConfig::ArgMax, |lhs, rhs| lhs > rhs);
+}
+
+#[test]
+pub fn test_argmin() {
+    test_case().test_segmented_arg(ReduceOperationConfig::ArgMin, |lhs, rhs| lhs < rhs);
+}
+
+#[test]
+pub fn test_var() {
+    test_case().test_segmented(ReduceOperationConfig::Var { correction: 0 }, |values| {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let m2 = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>();
+        m2 / values.len() as f32
+    });
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        lengths: test_segment_lengths(),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub lengths: Vec<u32>,
+}
+
+impl TestCase {
+    pub fn test_segmented(&self, config: ReduceOperationConfig, reference: impl Fn(&[f32]) -> f32) {
+        let input_values: Vec<TestDType> = self.random_input_values();
+        let expected = self
+            .segments(&input_values)
+            .into_iter()
+            .map(|segment| TestDType::new(reference(&segment)))
+            .collect::<Vec<_>>();
+
+        let actual = self.run_segmented::<TestDType>(&input_values, config);
+        assert_approx_equal(&actual, &expected, false);
+    }
+
+    /// The reference is the index of the first element better than all the previous ones.
+    pub fn test_segmented_arg(&self, config: ReduceOperationConfig, better: fn(f32, f32) -> bool) {
+        let input_values: Vec<TestDType> = self.random_input_values();
+        let expected = self
+            .segments(&input_values)
+            .into_iter()
+            .map(|segment| {
+                let mut best = 0;
+                for (i, value) in segment.iter().enumerate() {
+                    if better(*value, segment[best]) {
+                        best = i;
+                    }
+                }
+                best as u32
+            })
+            .collect::<Vec<_>>();
+
+        let actual = self.run_segmented::<u32>(&input_values, config);
+        assert_eq!(actual, expected);
+    }
+
+    fn run_segmented<O: Numeric + CubeElement>(
+        &self,
+        input_values: &[TestDType],
+        config: ReduceOperationConfig,
+    ) -> Vec<O> {
+        let client = TestRuntime::client(&Default::default());
+
+        let offsets = self.offsets();
+        let num_segments = self.lengths.len();
+        let input_handle = client.create_from_slice(TestDType::as_bytes(input_values));
+        let offsets_handle = client.create_from_slice(u32::as_bytes(&offsets));
+        let output_handle = client.empty(num_segments * size_of::<O>());
+
+        let input_shape = [input_values.len()];
+        let offsets_shape = [offsets.len()];
+        let output_shape = [num_segments];
+        let (input, offsets, output) = unsafe {
+            (
+                TensorHandleRef::from_raw_parts(
+                    &input_handle,
+                    &[1],
+                    &input_shape,
+                    size_of::<TestDType>(),
+                ),
+                TensorHandleRef::from_raw_parts(
+                    &offsets_handle,
+                    &[1],
+                    &offsets_shape,
+                    size_of::<u32>(),
+                ),
+                TensorHandleRef::from_raw_parts(
+                    &output_handle,
+                    &[1],
+                    &output_shape,
+                    size_of::<O>(),
+                ),
+            )
+        };
+
+        let result = segmented_reduce::<TestRuntime>(
+            &client,
+            input,
+            offsets,
+            output,
+            config,
+            ReduceDtypes {
+                input: TestDType::as_type_native_unchecked(),
+                output: O::as_type_native_unchecked(),
+                accumulation: f32::as_type_native_unchecked(),
+            },
+        );
+
+        match result {
+            Ok(_) => {}
+            Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+            Err(err) => panic!("Invalid test case {self:?}: {err:?}"),
+        }
+
+        let bytes = client.read_one(output_handle);
+        O::from_bytes(&bytes).to_vec()
+    }
+
+    fn segments(&self, values: &[TestDType]) -> Vec<Vec<f32>> {
+        self.offsets()
+            .windows(2)
+            .map(|range| {
+                values[range[0] as usize..range[1] as usize]
+                    .iter()
+                    .map(|v| v.to_f32().unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn offsets(&self) -> Vec<u32> {
+        let mut offsets = vec![0];
+        for length in self.lengths.iter() {
+            offsets.push(offsets.last().unwrap() + length);
+        }
+        offsets
+    }
+
+    fn random_input_values<F: Float>(&self) -> Vec<F> {
+        let size = self.lengths.iter().sum::<u32>() as usize;
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| F::new(r as f32 * factor))
+            .collect()
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
@@ -0,0 +1,193 @@
+use crate::suite::test_case::assert_approx_equal;
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubek_reduce::{
+    ReduceDtypes, ReduceError,
+    launch::RoutineStrategy,
+    log_softmax,
+    routines::{BlueprintStrategy, cube::CubeStrategy, plane::PlaneStrategy, unit::UnitStrategy},
+    softmax,
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+static PRECISION: i32 = 4;
+
+#[test]
+pub fn test_softmax() {
+    test_case().test_softmax(false);
+}
+
+#[test]
+pub fn test_log_softmax() {
+    test_case().test_softmax(true);
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+        axis: test_axis(),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+    pub axis: usize,
+}
+
+impl TestCase {
+    pub fn test_softmax(&self, log: bool) {
+        let input_values: Vec<TestDType> = self.random_input_values();
+        let expected_values = self.cpu_softmax(&input_values, log);
+
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let output_stride = contiguous_strides(&self.shape);
+        let output_size = self.shape.iter().product::<usize>();
+
+        let strategies = [
+            RoutineStrategy::Unit(BlueprintStrategy::Inferred(UnitStrategy)),
+            RoutineStrategy::Plane(BlueprintStrategy::Inferred(PlaneStrategy {
+                independent: true,
+            })),
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: false,
+            })),
+            RoutineStrategy::Cube(BlueprintStrategy::Inferred(CubeStrategy {
+                use_planes: true,
+            })),
+        ];
+
+        for strategy in strategies {
+            let output_handle = client.empty(output_size * size_of::<TestDType>());
+            let (input, output) = unsafe {
+                (
+                    TensorHandleRef::from_raw_parts(
+                        &input_handle,
+                        &self.stride,
+                        &self.shape,
+                        size_of::<TestDType>(),
+                    ),
+                    TensorHandleRef::from_raw_parts(
+                        &output_handle,
+                        &output_stride,
+                        &self.shape,
+                        size_of::<TestDType>(),
+                    ),
+                )
+            };
+            let dtypes = ReduceDtypes {
+                input: TestDType::as_type_native_unchecked(),
+                output: TestDType::as_type_native_unchecked(),
+                accumulation: f32::as_type_native_unchecked(),
+            };
+
+            let result = match log {
+                true => {
+                    log_softmax::<TestRuntime>(&client, input, output, self.axis, strategy, dtypes)
+                }
+                false => {
+                    softmax::<TestRuntime>(&client, input, output, self.axis, strategy, dtypes)
+                }
+            };
+
+            match result {
+                Ok(_) => {}
+                Err(ReduceError::PlanesUnavailable | ReduceError::ImprecisePlaneDim) => {
+                    continue;
+                }
+                Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+                Err(err) => panic!("Invalid test case {self:?}: {err:?}"),
+            }
+
+            let actual_values = client.read_one(output_handle);
+            assert_approx_equal(
+                TestDType::from_bytes(&actual_values),
+                &expected_values,
+                false,
+            );
+        }
+    }
+
+    /// Normalize every row in `f32`.
+    fn cpu_softmax(&self, values: &[TestDType], log: bool) -> Vec<TestDType> {
+        let output_stride = contiguous_strides(&self.shape);
+        let mut output = vec![TestDType::from_int(0); self.shape.iter().product::<usize>()];
+
+        let num_rows = output.len() / self.shape[self.axis];
+        for row in 0..num_rows {
+            let mut remainder = row;
+            let mut input_offset = 0;
+            let mut output_offset = 0;
+            for dim in (0..self.shape.len()).rev() {
+                if dim != self.axis {
+                    let coordinate = remainder % self.shape[dim];
+                    remainder /= self.shape[dim];
+                    input_offset += coordinate * self.stride[dim];
+                    output_offset += coordinate * output_stride[dim];
+                }
+            }
+
+            let row_values = (0..self.shape[self.axis])
+                .map(|i| {
+                    values[input_offset + i * self.stride[self.axis]]
+                        .to_f32()
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            let max = row_values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let lse = max + row_values.iter().map(|v| (v - max).exp()).sum::<f32>().ln();
+
+            for (i, value) in row_values.iter().enumerate() {
+                let result = match log {
+                    true => value - lse,
+                    false => (value - lse).exp(),
+                };
+                output[output_offset + i * output_stride[self.axis]] = TestDType::new(result);
+            }
+        }
+
+        output
+    }
+
+    fn random_input_values<F: Float>(&self) -> Vec<F> {
+        let size = self.input_size();
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| F::new(r as f32 * factor))
+            .collect()
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
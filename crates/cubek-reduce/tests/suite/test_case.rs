@@ -2,9 +2,19 @@ use std::marker::PhantomData;
 
 use cubecl::TestRuntime;
 use cubecl::prelude::*;
-use cubek_reduce::components::instructions::ReduceOperationConfig;
+use cubek_reduce::components::instructions::{NormExponent, ReduceOperationConfig};
 use cubek_reduce::launch::RoutineStrategy;
-use cubek_reduce::{ReduceDtypes, ReduceError, ReducePrecision, launch::ReduceStrategy, reduce};
+use cubek_reduce::{
+    ReduceDtypes, ReduceError, ReduceFamily, ReduceInstruction, ReducePrecision, Summation,
+    components::{
+        instructions::{ReduceCoordinate, ReduceRequirements},
+        readers::ReduceInputTransform,
+    },
+    launch::ReduceStrategy,
+    mean_var, reduce, reduce_lp_norm, reduce_transformed, reduce_with,
+};
+use cubek_test_utils::reference::{ReduceOp, reduce_cpu_reference};
+use cubek_test_utils::{HostData, HostDataVec};
 use rand::{
     SeedableRng,
     distr::{Distribution, Uniform},
@@ -41,62 +51,31 @@ where
         let input_values: Vec<P::EI> = self.random_input_values::<P::EI>();
         let expected_values = match self.axis {
             Some(axis) if self.stride[axis] == 0 => vec![0; input_values.len()],
-            _ => self.cpu_argmax(&input_values),
+            _ => self.cpu_indices(&input_values, ReduceOp::ArgMax),
         };
         self.run_reduce_test::<u32>(input_values, expected_values, ReduceOperationConfig::ArgMax)
     }
 
-    fn cpu_argmax<F: Float>(&self, values: &[F]) -> Vec<u32> {
-        let mut expected = vec![(F::min_value(), 0_u32); self.num_output_values()];
-        for (input_index, &value) in values.iter().enumerate() {
-            if let Some(output_index) = self.to_output_index(input_index) {
-                let (best, _) = expected[output_index];
-                if value > best {
-                    let coordinate = self.to_input_coordinate(input_index).unwrap();
-                    expected[output_index] = (value, coordinate[self.axis.unwrap()] as u32);
-                }
-            }
-        }
-        expected.into_iter().map(|(_, i)| i).collect()
-    }
-
     pub fn test_argmin(&self) {
         let input_values: Vec<<P as ReducePrecision>::EI> = self.random_input_values();
         let expected_values = match self.axis {
             Some(axis) if self.stride[axis] == 0 => vec![0; input_values.len()],
-            _ => self.cpu_argmin(&input_values),
+            _ => self.cpu_indices(&input_values, ReduceOp::ArgMin),
         };
         self.run_reduce_test::<u32>(input_values, expected_values, ReduceOperationConfig::ArgMin)
     }
 
-    fn cpu_argmin<F: Float>(&self, values: &[F]) -> Vec<u32> {
-        let mut expected = vec![(F::max_value(), 0_u32); self.num_output_values()];
-        for (input_index, &value) in values.iter().enumerate() {
-            if let Some(output_index) = self.to_output_index(input_index) {
-                let (best, _) = expected[output_index];
-                if value < best {
-                    let coordinate = self.to_input_coordinate(input_index).unwrap();
-                    expected[output_index] = (value, coordinate[self.axis.unwrap()] as u32);
-                }
-            }
-        }
-        expected.into_iter().map(|(_, i)| i).collect()
-    }
-
-    pub fn test_mean(&self) {
+    pub fn test_mean(&self, summation: Summation) {
         let input_values: Vec<P::EI> = self.random_input_values();
         let expected_values = match self.axis {
             Some(axis) if self.stride[axis] == 0 => input_values.clone(),
-            _ => self.cpu_mean(&input_values),
+            _ => self.cpu_reference(&input_values, ReduceOp::Mean),
         };
-        self.run_reduce_test::<P::EI>(input_values, expected_values, ReduceOperationConfig::Mean)
-    }
-
-    fn cpu_mean<F: Float>(&self, values: &[F]) -> Vec<F> {
-        self.cpu_sum(values)
-            .into_iter()
-            .map(|sum| sum / F::new(self.shape[self.axis.unwrap()] as f32))
-            .collect()
+        self.run_reduce_test::<P::EI>(
+            input_values,
+            expected_values,
+            ReduceOperationConfig::Mean.with_summation(summation),
+        )
     }
 
     pub fn test_prod(&self) {
@@ -106,7 +85,7 @@ where
                 .iter()
                 .map(|v| Self::powf(*v, self.shape[axis]))
                 .collect(),
-            _ => self.cpu_prod(&input_values),
+            _ => self.cpu_reference(&input_values, ReduceOp::Prod),
         };
         self.run_reduce_test::<P::EI>(input_values, expected_values, ReduceOperationConfig::Prod)
     }
@@ -119,41 +98,621 @@ where
         result
     }
 
-    fn cpu_prod<F: Float>(&self, values: &[F]) -> Vec<F> {
-        let mut expected = vec![F::new(1.0); self.num_output_values()];
+    pub fn test_sum(&self, summation: Summation) {
+        println!("Printing test: {self:?}");
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let expected_values = match self.axis {
+            Some(axis) if self.stride[axis] == 0 => input_values
+                .iter()
+                .map(|v| *v * P::EI::from_int(self.shape[axis] as i64))
+                .collect(),
+            _ => self.cpu_reference(&input_values, ReduceOp::Sum),
+        };
+        self.run_reduce_test::<P::EI>(
+            input_values,
+            expected_values,
+            ReduceOperationConfig::Sum.with_summation(summation),
+        )
+    }
 
-        for (input_index, value) in values.iter().enumerate() {
-            if let Some(output_index) = self.to_output_index(input_index) {
-                expected[output_index] *= *value;
-            }
-        }
-        expected
+    pub fn test_var(&self, correction: u32) {
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let expected_values = match self.axis {
+            Some(axis) if self.stride[axis] == 0 => vec![P::EI::new(0.0); input_values.len()],
+            _ => self.cpu_reference(&input_values, ReduceOp::Var { correction }),
+        };
+        self.run_reduce_test::<P::EI>(
+            input_values,
+            expected_values,
+            ReduceOperationConfig::Var { correction },
+        )
     }
 
-    pub fn test_sum(&self) {
-        println!("Printing test: {self:?}");
+    pub fn test_std(&self, correction: u32) {
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let expected_values = match self.axis {
+            Some(axis) if self.stride[axis] == 0 => vec![P::EI::new(0.0); input_values.len()],
+            _ => self
+                .cpu_reference::<P::EI>(&input_values, ReduceOp::Var { correction })
+                .into_iter()
+                .map(|var| P::EI::new(var.to_f32().unwrap().sqrt()))
+                .collect(),
+        };
+        self.run_reduce_test::<P::EI>(
+            input_values,
+            expected_values,
+            ReduceOperationConfig::Std { correction },
+        )
+    }
+
+    pub fn test_logsumexp(&self) {
         let input_values: Vec<P::EI> = self.random_input_values();
         let expected_values = match self.axis {
             Some(axis) if self.stride[axis] == 0 => input_values
                 .iter()
-                .map(|v| *v * P::EI::from_int(self.shape[axis] as i64))
+                .map(|v| P::EI::new(v.to_f32().unwrap() + (self.shape[axis] as f32).ln()))
                 .collect(),
-            _ => self.cpu_sum(&input_values),
+            _ => self.cpu_reference(&input_values, ReduceOp::LogSumExp),
         };
-        self.run_reduce_test::<P::EI>(input_values, expected_values, ReduceOperationConfig::Sum)
+        self.run_reduce_test::<P::EI>(
+            input_values,
+            expected_values,
+            ReduceOperationConfig::LogSumExp,
+        )
+    }
+
+    pub fn test_logsumexp_inf(&self) {
+        let input_values: Vec<P::EI> = self.special_input_values(f32::INFINITY);
+        let expected_values = self.cpu_reference(&input_values, ReduceOp::LogSumExp);
+        self.run_reduce_test::<P::EI>(
+            input_values,
+            expected_values,
+            ReduceOperationConfig::LogSumExp,
+        )
+    }
+
+    pub fn test_l1_norm(&self, summation: Summation) {
+        self.test_norm(1.0, ReduceOperationConfig::L1Norm.with_summation(summation))
+    }
+
+    pub fn test_l2_norm(&self, summation: Summation) {
+        self.test_norm(2.0, ReduceOperationConfig::L2Norm.with_summation(summation))
+    }
+
+    pub fn test_lp_norm(&self, p: f32) {
+        self.test_norm(
+            p,
+            ReduceOperationConfig::LpNorm {
+                p: NormExponent::new(p),
+            },
+        )
+    }
+
+    pub fn test_runtime_lp_norm(&self, p: f32) {
+        self.run_runtime_lp_norm(self.random_input_values(), p)
+    }
+
+    pub fn test_runtime_lp_norm_inf(&self, p: f32) {
+        self.run_runtime_lp_norm(self.special_input_values(f32::NEG_INFINITY), p)
+    }
+
+    pub fn test_runtime_lp_norm_integer_accumulation(&self) {
+        let client = TestRuntime::client(&Default::default());
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let input_handle =
+            client.create_from_slice(<P::EI as CubeElement>::as_bytes(&input_values));
+        let output_handle = client.empty(self.num_output_values() * size_of::<P::EI>());
+        let mut output_shape = self.shape.clone();
+        output_shape[self.axis.unwrap()] = 1;
+        let output_stride = self.output_stride();
+
+        let result = reduce_lp_norm::<TestRuntime>(
+            &client,
+            unsafe {
+                TensorHandleRef::from_raw_parts(
+                    &input_handle,
+                    &self.stride,
+                    &self.shape,
+                    size_of::<P::EI>(),
+                )
+            },
+            unsafe {
+                TensorHandleRef::from_raw_parts(
+                    &output_handle,
+                    &output_stride,
+                    &output_shape,
+                    size_of::<P::EI>(),
+                )
+            },
+            self.axis.unwrap(),
+            3.0,
+            self.strategy.clone(),
+            ReduceDtypes {
+                input: P::EI::as_type_native_unchecked(),
+                output: P::EI::as_type_native_unchecked(),
+                accumulation: u32::as_type_native_unchecked(),
+            },
+        );
+        assert!(matches!(result, Err(ReduceError::Validation { .. })));
     }
 
-    fn cpu_sum<F: Float>(&self, values: &[F]) -> Vec<F> {
-        let mut expected = vec![F::new(0.0); self.num_output_values()];
+    fn run_runtime_lp_norm(&self, input_values: Vec<P::EI>, p: f32) {
+        let expected_values = self.cpu_norm(&input_values, p);
+        self.run_launch_test(
+            input_values,
+            expected_values,
+            false,
+            |client, input, output, dtypes| {
+                reduce_lp_norm::<TestRuntime>(
+                    client,
+                    input,
+                    output,
+                    self.axis.unwrap(),
+                    p,
+                    self.strategy.clone(),
+                    dtypes,
+                )
+            },
+        )
+    }
+
+    fn test_norm(&self, p: f32, config: ReduceOperationConfig) {
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let expected_values = self.cpu_norm(&input_values, p);
+        self.run_reduce_test::<P::EI>(input_values, expected_values, config)
+    }
+
+    fn cpu_norm(&self, input_values: &[P::EI], p: f32) -> Vec<P::EI> {
+        match self.axis {
+            Some(axis) if self.stride[axis] == 0 => input_values
+                .iter()
+                .map(|v| {
+                    let scale = (self.shape[axis] as f32).powf(1.0 / p);
+                    P::EI::new(v.to_f32().unwrap().abs() * scale)
+                })
+                .collect(),
+            _ => self.cpu_reference(input_values, ReduceOp::Norm { p }),
+        }
+    }
+
+    pub fn test_any(&self) {
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let expected_values = self
+            .cpu_indices(&input_values, ReduceOp::CountNonZero)
+            .into_iter()
+            .map(|count| (count > 0) as u32)
+            .collect();
+        self.run_reduce_test::<u32>(input_values, expected_values, ReduceOperationConfig::Any)
+    }
+
+    pub fn test_all(&self) {
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let size = self.shape[self.axis.unwrap()] as u32;
+        let expected_values = self
+            .cpu_indices(&input_values, ReduceOp::CountNonZero)
+            .into_iter()
+            .map(|count| (count == size) as u32)
+            .collect();
+        self.run_reduce_test::<u32>(input_values, expected_values, ReduceOperationConfig::All)
+    }
+
+    pub fn test_count_nonzero(&self) {
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let expected_values = self.cpu_indices(&input_values, ReduceOp::CountNonZero);
+        self.run_reduce_test::<u32>(
+            input_values,
+            expected_values,
+            ReduceOperationConfig::CountNonZero,
+        )
+    }
+
+    pub fn test_sum_of_powers(&self, power: u32) {
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let expected_values = self
+            .cpu_vectors(&input_values)
+            .iter()
+            .map(|vector| P::EI::new(vector.iter().map(|v| v.powi(power as i32)).sum()))
+            .collect();
+        self.run_launch_test(
+            input_values,
+            expected_values,
+            false,
+            |client, input, output, dtypes| {
+                reduce_with::<TestRuntime, SumOfPowers>(
+                    client,
+                    input,
+                    output,
+                    self.axis.unwrap(),
+                    self.strategy.clone(),
+                    SumOfPowersConfig { power },
+                    dtypes,
+                )
+            },
+        )
+    }
+
+    pub fn test_sum_of_squares(&self) {
+        self.test_transform(ReduceInputTransform::Square, |v| v * v)
+    }
+
+    pub fn test_sum_of_abs(&self) {
+        self.test_transform(ReduceInputTransform::Abs, |v| v.abs())
+    }
+
+    pub fn test_sum_of_exp(&self) {
+        self.test_transform(ReduceInputTransform::exp(1.5), |v| (v - 1.5).exp())
+    }
+
+    pub fn test_scaled_sum(&self) {
+        self.test_transform(ReduceInputTransform::scale(-0.5), |v| v * -0.5)
+    }
+
+    fn test_transform(&self, transform: ReduceInputTransform, reference: impl Fn(f32) -> f32) {
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let expected_values = self
+            .cpu_vectors(&input_values)
+            .iter()
+            .map(|vector| P::EI::new(vector.iter().map(|v| reference(*v)).sum()))
+            .collect();
+        self.run_launch_test(
+            input_values,
+            expected_values,
+            false,
+            |client, input, output, dtypes| {
+                reduce_transformed::<TestRuntime>(
+                    client,
+                    input,
+                    None,
+                    output,
+                    self.axis.unwrap(),
+                    self.strategy.clone(),
+                    ReduceOperationConfig::Sum,
+                    transform,
+                    dtypes,
+                )
+            },
+        )
+    }
+
+    /// The squares overflow a half-precision input, but not the accumulation type.
+    pub fn test_sum_of_large_squares(&self) {
+        let input_values: Vec<P::EI> = self.large_input_values();
+        let expected_values = self
+            .cpu_vectors(&input_values)
+            .iter()
+            .map(|vector| vector.iter().map(|v| v * v).sum::<f32>())
+            .collect();
+        self.run_launch_test::<f32>(
+            input_values,
+            expected_values,
+            false,
+            |client, input, output, dtypes| {
+                reduce_transformed::<TestRuntime>(
+                    client,
+                    input,
+                    None,
+                    output,
+                    self.axis.unwrap(),
+                    self.strategy.clone(),
+                    ReduceOperationConfig::Sum,
+                    ReduceInputTransform::Square,
+                    dtypes,
+                )
+            },
+        )
+    }
+
+    pub fn test_dot_product(&self) {
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let expected_values = self
+            .cpu_dot_products(&input_values)
+            .into_iter()
+            .map(P::EI::new)
+            .collect();
+        self.run_dot_product_test(input_values, expected_values)
+    }
+
+    /// The products overflow a half-precision input, but not the accumulation type.
+    pub fn test_large_dot_product(&self) {
+        let input_values: Vec<P::EI> = self.large_input_values();
+        let expected_values = self.cpu_dot_products(&input_values);
+        self.run_dot_product_test(input_values, expected_values)
+    }
+
+    /// The dot products of the input with its reverse along every vector.
+    fn cpu_dot_products(&self, input_values: &[P::EI]) -> Vec<f32> {
+        let rhs_values: Vec<P::EI> = input_values.iter().rev().copied().collect();
+        let products = input_values
+            .iter()
+            .zip(rhs_values.iter())
+            .map(|(lhs, rhs)| lhs.to_f32().unwrap() * rhs.to_f32().unwrap())
+            .collect::<Vec<f32>>();
+        self.cpu_vectors(&products)
+            .iter()
+            .map(|vector| vector.iter().sum())
+            .collect()
+    }
+
+    fn run_dot_product_test<O>(&self, input_values: Vec<P::EI>, expected_values: Vec<O>)
+    where
+        O: Numeric + CubeElement + std::fmt::Display,
+    {
+        let rhs_values: Vec<P::EI> = input_values.iter().rev().copied().collect();
+        self.run_launch_test(
+            input_values,
+            expected_values,
+            false,
+            |client, input, output, dtypes| {
+                let rhs_handle =
+                    client.create_from_slice(<P::EI as CubeElement>::as_bytes(&rhs_values));
+                let rhs = unsafe {
+                    TensorHandleRef::from_raw_parts(
+                        &rhs_handle,
+                        &self.stride,
+                        &self.shape,
+                        size_of::<P::EI>(),
+                    )
+                };
+                reduce_transformed::<TestRuntime>(
+                    client,
+                    input,
+                    Some(rhs),
+                    output,
+                    self.axis.unwrap(),
+                    self.strategy.clone(),
+                    ReduceOperationConfig::Sum,
+                    ReduceInputTransform::Identity,
+                    dtypes,
+                )
+            },
+        )
+    }
+
+    pub fn test_sum_nan(&self) {
+        self.test_nan(ReduceOperationConfig::Sum, |vector| vector.iter().sum())
+    }
+
+    pub fn test_max_nan(&self) {
+        self.test_nan(ReduceOperationConfig::Max, cpu_max_propagate_nan)
+    }
+
+    pub fn test_min_nan(&self) {
+        self.test_nan(ReduceOperationConfig::Min, cpu_min_propagate_nan)
+    }
+
+    pub fn test_max_abs_nan(&self) {
+        self.test_nan(ReduceOperationConfig::MaxAbs, |vector| {
+            let abs = vector.iter().map(|v| v.abs()).collect::<Vec<_>>();
+            cpu_max_propagate_nan(&abs)
+        })
+    }
+
+    pub fn test_max_nan_neg_inf(&self) {
+        self.test_nan_infinite(
+            ReduceOperationConfig::Max,
+            f32::NEG_INFINITY,
+            cpu_max_propagate_nan,
+        )
+    }
 
-        for (input_index, value) in values.iter().enumerate() {
+    pub fn test_min_nan_inf(&self) {
+        self.test_nan_infinite(
+            ReduceOperationConfig::Min,
+            f32::INFINITY,
+            cpu_min_propagate_nan,
+        )
+    }
+
+    pub fn test_var_nan(&self) {
+        self.test_nan(ReduceOperationConfig::Var { correction: 0 }, |vector| {
+            let mean = vector.iter().sum::<f32>() / vector.len() as f32;
+            let m2 = vector.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>();
+            m2 / vector.len() as f32
+        })
+    }
+
+    pub fn test_nan_sum(&self) {
+        self.test_nan(ReduceOperationConfig::NanSum, |vector| {
+            vector.iter().filter(|v| !v.is_nan()).sum()
+        })
+    }
+
+    pub fn test_nan_mean(&self) {
+        self.test_nan(ReduceOperationConfig::NanMean, |vector| {
+            let valid = vector.iter().filter(|v| !v.is_nan());
+            valid.clone().sum::<f32>() / valid.count() as f32
+        })
+    }
+
+    // `f32::max` and `f32::min` already ignore NaN, and return NaN when both are NaN.
+    pub fn test_nan_max(&self) {
+        self.test_nan(ReduceOperationConfig::NanMax, |vector| {
+            vector.iter().fold(f32::NAN, |max, v| max.max(*v))
+        })
+    }
+
+    pub fn test_nan_min(&self) {
+        self.test_nan(ReduceOperationConfig::NanMin, |vector| {
+            vector.iter().fold(f32::NAN, |min, v| min.min(*v))
+        })
+    }
+
+    pub fn test_nan_max_neg_inf(&self) {
+        self.test_nan_infinite(ReduceOperationConfig::NanMax, f32::NEG_INFINITY, |vector| {
+            vector.iter().fold(f32::NAN, |max, v| max.max(*v))
+        })
+    }
+
+    pub fn test_nan_min_inf(&self) {
+        self.test_nan_infinite(ReduceOperationConfig::NanMin, f32::INFINITY, |vector| {
+            vector.iter().fold(f32::NAN, |min, v| min.min(*v))
+        })
+    }
+
+    fn test_nan(&self, config: ReduceOperationConfig, reference: impl Fn(&[f32]) -> f32) {
+        self.run_nan_test(self.nan_input_values(), config, reference)
+    }
+
+    // Like `test_nan`, but every value that isn't NaN is replaced by the given infinity.
+    fn test_nan_infinite(
+        &self,
+        config: ReduceOperationConfig,
+        infinity: f32,
+        reference: impl Fn(&[f32]) -> f32,
+    ) {
+        let input_values = self
+            .nan_input_values::<P::EI>()
+            .into_iter()
+            .map(|v| match v.to_f32().unwrap().is_nan() {
+                true => v,
+                false => P::EI::new(infinity),
+            })
+            .collect();
+        self.run_nan_test(input_values, config, reference)
+    }
+
+    fn run_nan_test(
+        &self,
+        input_values: Vec<P::EI>,
+        config: ReduceOperationConfig,
+        reference: impl Fn(&[f32]) -> f32,
+    ) {
+        let expected_values = self
+            .cpu_vectors(&input_values)
+            .iter()
+            .map(|vector| P::EI::new(reference(vector)))
+            .collect();
+        self.run_reduce_test::<P::EI>(input_values, expected_values, config)
+    }
+
+    // The shared CPU reference, laid out like the output.
+    fn cpu_reference<F: Float>(&self, values: &[F], op: ReduceOp) -> Vec<F> {
+        self.cpu_reference_f32(values, op)
+            .into_iter()
+            .map(F::new)
+            .collect()
+    }
+
+    fn cpu_indices<F: Float>(&self, values: &[F], op: ReduceOp) -> Vec<u32> {
+        self.cpu_reference_f32(values, op)
+            .into_iter()
+            .map(|v| v as u32)
+            .collect()
+    }
+
+    fn cpu_reference_f32<F: Float>(&self, values: &[F], op: ReduceOp) -> Vec<f32> {
+        let input = HostData {
+            data: HostDataVec::F32(values.iter().map(|v| v.to_f32().unwrap()).collect()),
+            shape: self.shape.clone(),
+            strides: self.stride.clone(),
+        };
+
+        // The reference is row-major, while the output makes the first kept axis the fastest.
+        let axis = self.axis.unwrap();
+        let reference = reduce_cpu_reference(&input, axis, op);
+        let mut expected = vec![0.0; self.num_output_values()];
+        for input_index in 0..values.len() {
             if let Some(output_index) = self.to_output_index(input_index) {
-                expected[output_index] += *value;
+                let mut coordinate = self.to_input_coordinate(input_index).unwrap();
+                coordinate[axis] = 0;
+                expected[output_index] = reference.get_f32(&coordinate);
             }
         }
         expected
     }
 
+    // The elements of each reduced vector, in order along the axis.
+    fn cpu_vectors<F: Float>(&self, values: &[F]) -> Vec<Vec<f32>> {
+        match self.axis {
+            Some(axis) if self.stride[axis] == 0 => values
+                .iter()
+                .map(|v| vec![v.to_f32().unwrap(); self.shape[axis]])
+                .collect(),
+            _ => {
+                let mut vectors = vec![Vec::new(); self.num_output_values()];
+                for (input_index, value) in values.iter().enumerate() {
+                    if let Some(output_index) = self.to_output_index(input_index) {
+                        vectors[output_index].push(value.to_f32().unwrap());
+                    }
+                }
+                vectors
+            }
+        }
+    }
+
+    pub fn test_mean_var(&self, correction: u32) {
+        // The mean and variance are always computed by the unit routine.
+        if !matches!(self.strategy.routine, RoutineStrategy::Unit(_)) {
+            return;
+        }
+
+        let input_values: Vec<P::EI> = self.random_input_values();
+        let (expected_mean, expected_var) = match self.axis {
+            Some(axis) if self.stride[axis] == 0 => (
+                input_values.clone(),
+                vec![P::EI::new(0.0); input_values.len()],
+            ),
+            _ => (
+                self.cpu_reference(&input_values, ReduceOp::Mean),
+                self.cpu_reference(&input_values, ReduceOp::Var { correction }),
+            ),
+        };
+
+        let client = TestRuntime::client(&Default::default());
+        let input_handle =
+            client.create_from_slice(<P::EI as CubeElement>::as_bytes(&input_values));
+        let zeros = vec![P::EI::from_int(0); expected_mean.len()];
+        let mean_handle = client.create_from_slice(<P::EI as CubeElement>::as_bytes(&zeros));
+        let var_handle = client.create_from_slice(<P::EI as CubeElement>::as_bytes(&zeros));
+
+        let mut output_shape = self.shape.clone();
+        output_shape[self.axis.unwrap()] = 1;
+        let output_stride = self.output_stride();
+
+        let (input, mean, var) = unsafe {
+            (
+                TensorHandleRef::from_raw_parts(
+                    &input_handle,
+                    &self.stride,
+                    &self.shape,
+                    size_of::<P::EI>(),
+                ),
+                TensorHandleRef::from_raw_parts(
+                    &mean_handle,
+                    &output_stride,
+                    &output_shape,
+                    size_of::<P::EI>(),
+                ),
+                TensorHandleRef::from_raw_parts(
+                    &var_handle,
+                    &output_stride,
+                    &output_shape,
+                    size_of::<P::EI>(),
+                ),
+            )
+        };
+
+        mean_var::<TestRuntime>(
+            &client,
+            input,
+            mean,
+            var,
+            self.axis.unwrap(),
+            correction,
+            ReduceDtypes {
+                input: P::EI::as_type_native_unchecked(),
+                output: P::EI::as_type_native_unchecked(),
+                accumulation: P::EA::as_type_native_unchecked(),
+            },
+        )
+        .unwrap();
+
+        let actual_mean = client.read_one(mean_handle);
+        assert_approx_equal(P::EI::from_bytes(&actual_mean), &expected_mean, false);
+        let actual_var = client.read_one(var_handle);
+        assert_approx_equal(P::EI::from_bytes(&actual_var), &expected_var, false);
+    }
+
     pub fn run_reduce_test<O>(
         &self,
         input_values: Vec<P::EI>,
@@ -161,6 +720,39 @@ where
         config: ReduceOperationConfig,
     ) where
         O: Numeric + CubeElement + std::fmt::Display,
+    {
+        self.run_launch_test(
+            input_values,
+            expected_values,
+            // For prod we only test with relative difference.
+            matches!(config, ReduceOperationConfig::Prod),
+            |client, input, output, dtypes| {
+                reduce::<TestRuntime>(
+                    client,
+                    input,
+                    output,
+                    self.axis.unwrap(),
+                    self.strategy.clone(),
+                    config,
+                    dtypes,
+                )
+            },
+        )
+    }
+
+    fn run_launch_test<O>(
+        &self,
+        input_values: Vec<P::EI>,
+        expected_values: Vec<O>,
+        only_relative: bool,
+        launch: impl FnOnce(
+            &ComputeClient<TestRuntime>,
+            TensorHandleRef<TestRuntime>,
+            TensorHandleRef<TestRuntime>,
+            ReduceDtypes,
+        ) -> Result<(), ReduceError>,
+    ) where
+        O: Numeric + CubeElement + std::fmt::Display,
     {
         let client = TestRuntime::client(&Default::default());
         if let RoutineStrategy::Cube(_blueprint) = &self.strategy.routine
@@ -208,13 +800,10 @@ where
             )
         };
 
-        let result = reduce::<TestRuntime>(
+        let result = launch(
             &client,
             input,
             output,
-            self.axis.unwrap(),
-            self.strategy.clone(),
-            config,
             ReduceDtypes {
                 input: <P as ReducePrecision>::EI::as_type_native_unchecked(),
                 output: O::as_type_native_unchecked(),
@@ -245,12 +834,7 @@ where
 
         let bytes = client.read_one(output_handle);
         let output_values = O::from_bytes(&bytes);
-        assert_approx_equal(
-            output_values,
-            &expected_values,
-            // For prod we only test with relative difference.
-            matches!(config, ReduceOperationConfig::Prod),
-        );
+        assert_approx_equal(output_values, &expected_values, only_relative);
     }
 
     fn num_output_values(&self) -> usize {
@@ -314,6 +898,14 @@ where
             .collect()
     }
 
+    // Random values whose squares are larger than the maximum of half-precision floats.
+    fn large_input_values<F: Float>(&self) -> Vec<F> {
+        self.random_input_values::<F>()
+            .into_iter()
+            .map(|v| F::new(v.to_f32().unwrap() * 512.0))
+            .collect()
+    }
+
     fn random_input_values<F: Float>(&self) -> Vec<F> {
         let size = self.input_size();
         let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
@@ -327,6 +919,24 @@ where
         // (0..size).map(|x| F::from_int(x as i64)).collect() TODO DELETE
     }
 
+    // Random values where about 40% of the vectors contain a NaN.
+    fn nan_input_values<F: Float>(&self) -> Vec<F> {
+        self.special_input_values(f32::NAN)
+    }
+
+    // Random values where about one value per vector is replaced by `special`.
+    fn special_input_values<F: Float>(&self, special: f32) -> Vec<F> {
+        let mut values = self.random_input_values::<F>();
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed() + 1);
+        let distribution = Uniform::new(0, 2 * self.shape[self.axis.unwrap()]).unwrap();
+        for (value, r) in values.iter_mut().zip(distribution.sample_iter(rng)) {
+            if r == 0 {
+                *value = F::new(special);
+            }
+        }
+        values
+    }
+
     fn input_size(&self) -> usize {
         let (stride, shape) = self
             .stride
@@ -348,7 +958,14 @@ pub fn assert_approx_equal<N: Numeric>(actual: &[N], expected: &[N], only_relati
         let a = a.to_f32().unwrap();
         let e = e.to_f32().unwrap();
         let diff = (a - e).abs();
-        if e == 0.0 {
+        if e.is_nan() {
+            assert!(
+                a.is_nan(),
+                "Values are not approx equal: index={i} actual={a}, expected=NaN"
+            );
+        } else if e.is_infinite() {
+            assert_eq!(a, e, "Values are not equal: index={i}");
+        } else if e == 0.0 {
             assert!(
                 diff < 1e-10,
                 "Values are not approx equal: index={i} actual={a}, expected={e}, difference={diff}",
@@ -362,3 +979,142 @@ pub fn assert_approx_equal<N: Numeric>(actual: &[N], expected: &[N], only_relati
         }
     }
 }
+
+/// A custom instruction defined outside of the crate, summing the items raised to a power.
+#[derive(Debug, CubeType, Clone)]
+pub struct SumOfPowers {
+    #[cube(comptime)]
+    config: SumOfPowersConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SumOfPowersConfig {
+    power: u32,
+}
+
+impl ReduceFamily for SumOfPowers {
+    type Instruction<P: ReducePrecision> = Self;
+    type Config = SumOfPowersConfig;
+}
+
+#[cube]
+impl SumOfPowers {
+    fn pow<N: Numeric>(&self, item: Line<N>) -> Line<N> {
+        let mut result = Line::empty(item.size()).fill(N::from_int(1));
+        #[unroll]
+        for _ in 0..self.config.power {
+            result = result * item;
+        }
+        result
+    }
+}
+
+#[cube]
+impl<P: ReducePrecision> ReduceInstruction<P> for SumOfPowers {
+    type AccumulatorItem = Line<P::EA>;
+    type SharedAccumulator = SharedMemory<Line<P::EA>>;
+    type Config = SumOfPowersConfig;
+
+    fn requirements(_this: &Self) -> ReduceRequirements {
+        ReduceRequirements { coordinates: false }
+    }
+
+    fn from_config(#[comptime] config: Self::Config) -> Self {
+        SumOfPowers { config }
+    }
+
+    fn null_input(_this: &Self, #[comptime] line_size: u32) -> Line<P::EI> {
+        Line::empty(line_size).fill(P::EI::from_int(0))
+    }
+
+    fn null_accumulator(_this: &Self, #[comptime] line_size: u32) -> Self::AccumulatorItem {
+        Line::empty(line_size).fill(P::EA::from_int(0))
+    }
+
+    fn assign_accumulator(
+        _this: &Self,
+        destination: &mut Self::AccumulatorItem,
+        source: &Self::AccumulatorItem,
+    ) {
+        *destination = *source;
+    }
+
+    fn read_accumulator(
+        _this: &Self,
+        accumulator: &Self::AccumulatorItem,
+    ) -> (Line<P::EI>, ReduceCoordinate) {
+        (
+            Line::cast_from(*accumulator),
+            ReduceCoordinate::new_NotRequired(),
+        )
+    }
+
+    fn reduce(
+        this: &Self,
+        accumulator: &Self::AccumulatorItem,
+        item: Line<P::EI>,
+        _coordinate: ReduceCoordinate,
+        #[comptime] use_planes: bool,
+    ) -> Self::AccumulatorItem {
+        let item = this.pow(Line::<P::EA>::cast_from(item));
+        if comptime!(use_planes) {
+            *accumulator + plane_sum(item)
+        } else {
+            *accumulator + item
+        }
+    }
+
+    fn fuse_accumulators(
+        _this: &Self,
+        lhs: Self::AccumulatorItem,
+        rhs: Self::AccumulatorItem,
+    ) -> Self::AccumulatorItem {
+        lhs + rhs
+    }
+
+    // The accumulator already holds powers, so it is summed without reducing it again.
+    fn fuse_plane(_this: &Self, accumulator: Self::AccumulatorItem) -> Self::AccumulatorItem {
+        plane_sum(accumulator)
+    }
+
+    fn merge_line<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Out {
+        let mut sum = P::EA::from_int(0);
+        #[unroll]
+        for k in 0..accumulator.size() {
+            sum += accumulator[k];
+        }
+        Out::cast_from(sum)
+    }
+
+    fn to_output_perpendicular<Out: Numeric>(
+        _this: &Self,
+        accumulator: Self::AccumulatorItem,
+        _shape_axis_reduce: u32,
+    ) -> Line<Out> {
+        Line::cast_from(accumulator)
+    }
+}
+
+fn cpu_max_propagate_nan(vector: &[f32]) -> f32 {
+    vector.iter().fold(f32::NEG_INFINITY, |max, v| {
+        if max.is_nan() || v.is_nan() {
+            f32::NAN
+        } else {
+            max.max(*v)
+        }
+    })
+}
+
+fn cpu_min_propagate_nan(vector: &[f32]) -> f32 {
+    vector.iter().fold(f32::INFINITY, |min, v| {
+        if min.is_nan() || v.is_nan() {
+            f32::NAN
+        } else {
+            min.min(*v)
+        }
+    })
+}
@@ -0,0 +1,237 @@
+use cubecl::TestRuntime;
+use cubecl::prelude::*;
+use cubek_reduce::{
+    ReduceError,
+    routines::top_k::{TopKConfig, TopKStrategy},
+    top_k,
+};
+use rand::{
+    SeedableRng,
+    distr::{Distribution, Uniform},
+    rngs::StdRng,
+};
+
+static PRECISION: i32 = 4;
+
+#[test]
+pub fn test_top_k_largest() {
+    test_case().test_top_k(true);
+}
+
+#[test]
+pub fn test_top_k_smallest() {
+    test_case().test_top_k(false);
+}
+
+#[test]
+pub fn test_top_k_largest_non_finite() {
+    test_case().test_top_k_non_finite(true);
+}
+
+#[test]
+pub fn test_top_k_smallest_non_finite() {
+    test_case().test_top_k_non_finite(false);
+}
+
+fn test_case() -> TestCase {
+    TestCase {
+        shape: test_shape(),
+        stride: test_strides(),
+        axis: test_axis(),
+        k: test_k(),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestCase {
+    pub shape: Vec<usize>,
+    pub stride: Vec<usize>,
+    pub axis: usize,
+    pub k: u32,
+}
+
+impl TestCase {
+    pub fn test_top_k(&self, largest: bool) {
+        self.run_top_k_test(self.random_input_values(), largest);
+    }
+
+    /// Fewer finite values than `k` in every vector, the others being NaN or the worst infinity.
+    pub fn test_top_k_non_finite(&self, largest: bool) {
+        let worst = if largest {
+            f32::NEG_INFINITY
+        } else {
+            f32::INFINITY
+        };
+        let mut input_values: Vec<TestDType> = self.random_input_values();
+        for (i, value) in input_values.iter_mut().enumerate() {
+            let position = (i / self.stride[self.axis]) % self.shape[self.axis];
+            if position + 1 >= self.k as usize {
+                let non_finite = if position % 2 == 0 { f32::NAN } else { worst };
+                *value = TestDType::new(non_finite);
+            }
+        }
+        self.run_top_k_test(input_values, largest);
+    }
+
+    fn run_top_k_test(&self, input_values: Vec<TestDType>, largest: bool) {
+        let (expected_values, expected_indices) = self.cpu_top_k(&input_values, largest);
+
+        let client = TestRuntime::client(&Default::default());
+        let input_handle = client.create_from_slice(TestDType::as_bytes(&input_values));
+        let output_shape = self.output_shape();
+        let output_stride = contiguous_strides(&output_shape);
+
+        for strategy in [TopKStrategy::Unit, TopKStrategy::Cube] {
+            let values_handle = client.empty(expected_values.len() * size_of::<TestDType>());
+            let indices_handle = client.empty(expected_indices.len() * size_of::<u32>());
+
+            let (input, values, indices) = unsafe {
+                (
+                    TensorHandleRef::from_raw_parts(
+                        &input_handle,
+                        &self.stride,
+                        &self.shape,
+                        size_of::<TestDType>(),
+                    ),
+                    TensorHandleRef::from_raw_parts(
+                        &values_handle,
+                        &output_stride,
+                        &output_shape,
+                        size_of::<TestDType>(),
+                    ),
+                    TensorHandleRef::from_raw_parts(
+                        &indices_handle,
+                        &output_stride,
+                        &output_shape,
+                        size_of::<u32>(),
+                    ),
+                )
+            };
+
+            let result = top_k::<TestRuntime>(
+                &client,
+                input,
+                values,
+                indices,
+                self.axis,
+                TopKConfig { k: self.k, largest },
+                strategy,
+                TestDType::as_type_native_unchecked(),
+            );
+
+            match result {
+                Ok(_) => {}
+                Err(ReduceError::Launch(err)) => panic!("The test didn't run: {err:?}"),
+                Err(err) => panic!("Invalid test case {self:?}: {err:?}"),
+            }
+
+            let actual_values = client.read_one(values_handle);
+            assert_eq!(
+                comparable(TestDType::from_bytes(&actual_values)),
+                comparable(&expected_values),
+                "Values differ with {strategy:?}"
+            );
+            let actual_indices = client.read_one(indices_handle);
+            assert_eq!(
+                u32::from_bytes(&actual_indices),
+                expected_indices.as_slice(),
+                "Indices differ with {strategy:?}"
+            );
+        }
+    }
+
+    /// Select the elements of every vector with a stable sort, so equal values keep their order.
+    /// NaN values come after all the others.
+    fn cpu_top_k(&self, values: &[TestDType], largest: bool) -> (Vec<TestDType>, Vec<u32>) {
+        let output_shape = self.output_shape();
+        let output_stride = contiguous_strides(&output_shape);
+        let output_size = output_shape.iter().product::<usize>();
+        let mut top_values = vec![TestDType::from_int(0); output_size];
+        let mut top_indices = vec![0; output_size];
+
+        let num_vectors = self.shape.iter().product::<usize>() / self.shape[self.axis];
+        for vector in 0..num_vectors {
+            let mut remainder = vector;
+            let mut input_offset = 0;
+            let mut output_offset = 0;
+            for dim in (0..self.shape.len()).rev() {
+                if dim != self.axis {
+                    let coordinate = remainder % self.shape[dim];
+                    remainder /= self.shape[dim];
+                    input_offset += coordinate * self.stride[dim];
+                    output_offset += coordinate * output_stride[dim];
+                }
+            }
+
+            let mut elements = (0..self.shape[self.axis])
+                .map(|i| (values[input_offset + i * self.stride[self.axis]], i as u32))
+                .collect::<Vec<_>>();
+            elements.sort_by(|(lhs, _), (rhs, _)| match (is_nan(*lhs), is_nan(*rhs)) {
+                (false, false) if largest => rhs.partial_cmp(lhs).unwrap(),
+                (false, false) => lhs.partial_cmp(rhs).unwrap(),
+                (lhs_nan, rhs_nan) => lhs_nan.cmp(&rhs_nan),
+            });
+
+            for (n, (value, index)) in elements.into_iter().take(self.k as usize).enumerate() {
+                let offset = output_offset + n * output_stride[self.axis];
+                top_values[offset] = value;
+                top_indices[offset] = index;
+            }
+        }
+
+        (top_values, top_indices)
+    }
+
+    fn output_shape(&self) -> Vec<usize> {
+        let mut shape = self.shape.clone();
+        shape[self.axis] = self.k as usize;
+        shape
+    }
+
+    fn random_input_values<F: Float>(&self) -> Vec<F> {
+        let size = self.input_size();
+        let rng = StdRng::seed_from_u64(self.pseudo_random_seed());
+        let distribution = Uniform::new_inclusive(-2 * PRECISION, 2 * PRECISION).unwrap();
+        let factor = 1.0 / (PRECISION as f32);
+        distribution
+            .sample_iter(rng)
+            .take(size)
+            .map(|r| F::new(r as f32 * factor))
+            .collect()
+    }
+
+    fn input_size(&self) -> usize {
+        let (stride, shape) = self
+            .stride
+            .iter()
+            .zip(self.shape.iter())
+            .max_by_key(|(stride, _)| *stride)
+            .unwrap();
+        stride * shape
+    }
+
+    // We don't need a fancy crypto-secure seed as this is only for testing.
+    fn pseudo_random_seed(&self) -> u64 {
+        123456789
+    }
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
+
+fn is_nan(value: TestDType) -> bool {
+    value.to_f32().unwrap().is_nan()
+}
+
+/// NaN never equals itself, so values are compared with NaN as `None`.
+fn comparable(values: &[TestDType]) -> Vec<Option<f32>> {
+    values
+        .iter()
+        .map(|v| (!is_nan(*v)).then(|| v.to_f32().unwrap()))
+        .collect()
+}
@@ -2,6 +2,9 @@ mod correctness;
 mod test_mode;
 mod test_tensor;
 
+#[cfg(feature = "reference")]
+pub mod reference;
+
 pub use correctness::assert_equals_approx;
 pub use test_mode::*;
 pub use test_tensor::*;
@@ -0,0 +1,75 @@
+use crate::HostData;
+use crate::reference::base::host_data_row_major;
+
+/// Computes `softmax(Q @ K^T / sqrt(head_dim)) @ V` using the flash attention v2
+/// online softmax.
+///
+/// Inputs are `[batch, num_heads, seq, dim]` and the optional boolean mask is
+/// `[batch, num_heads, seq_q, seq_kv]`, where `true` masks out a position.
+/// Fully masked rows output zeros.
+pub fn attention_cpu_reference(
+    query: &HostData,
+    key: &HostData,
+    value: &HostData,
+    mask: Option<&HostData>,
+    causal: bool,
+) -> HostData {
+    let [batch, num_heads, seq_q, head_dim] = query.shape[..] else {
+        panic!("Query must be of rank 4");
+    };
+    let seq_kv = key.shape[2];
+    let val_dim = value.shape[3];
+
+    let out_shape = vec![batch, num_heads, seq_q, val_dim];
+    let mut out = vec![0.; batch * num_heads * seq_q * val_dim];
+
+    let scale = (head_dim as f32).sqrt().recip();
+
+    for b in 0..batch {
+        for h in 0..num_heads {
+            for i in 0..seq_q {
+                // Running row max, sum and accumulator
+                let mut m = f32::NEG_INFINITY;
+                let mut l = 0.;
+                let mut acc_row = vec![0.; val_dim];
+
+                for j in 0..seq_kv {
+                    let mut dot = 0.;
+                    for d in 0..head_dim {
+                        dot += query.get_f32(&[b, h, i, d]) * key.get_f32(&[b, h, j, d]);
+                    }
+                    dot *= scale;
+
+                    let masked =
+                        (causal && j > i) || mask.is_some_and(|mask| mask.get_bool(&[b, h, i, j]));
+                    let s_val = if masked { f32::NEG_INFINITY } else { dot };
+
+                    // Skip update if row is fully masked so far (prevent NaNs)
+                    if s_val == f32::NEG_INFINITY && m == f32::NEG_INFINITY {
+                        continue;
+                    }
+
+                    let m_new = m.max(s_val);
+                    let p_tilde = f32::exp(s_val - m_new);
+                    let scale_old = f32::exp(m - m_new);
+
+                    for (d, acc) in acc_row.iter_mut().enumerate() {
+                        *acc = *acc * scale_old + p_tilde * value.get_f32(&[b, h, j, d]);
+                    }
+
+                    l = scale_old * l + p_tilde;
+                    m = m_new;
+                }
+
+                let eps = 1e-20f32; // numerical safety
+                let denom = if l > eps { l } else { eps };
+                let row_offset = ((b * num_heads + h) * seq_q + i) * val_dim;
+                for (d, acc) in acc_row.iter().enumerate() {
+                    out[row_offset + d] = acc / denom;
+                }
+            }
+        }
+    }
+
+    host_data_row_major(out, out_shape)
+}
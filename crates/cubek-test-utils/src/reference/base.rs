@@ -0,0 +1,23 @@
+use crate::{HostData, HostDataVec};
+
+/// Wraps a contiguous row-major f32 buffer into [HostData].
+pub(crate) fn host_data_row_major(data: Vec<f32>, shape: Vec<usize>) -> HostData {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+
+    HostData {
+        data: HostDataVec::F32(data),
+        shape,
+        strides,
+    }
+}
+
+/// Decodes a flat row-major index into `index`, following `shape`.
+pub(crate) fn unravel_index(mut flat: usize, shape: &[usize], index: &mut [usize]) {
+    for d in (0..shape.len()).rev() {
+        index[d] = flat % shape[d];
+        flat /= shape[d];
+    }
+}
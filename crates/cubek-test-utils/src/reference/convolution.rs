@@ -0,0 +1,83 @@
+use crate::HostData;
+use crate::reference::base::host_data_row_major;
+
+/// Parameters of a 2D convolution.
+#[derive(Clone, Debug)]
+pub struct Conv2dOptions {
+    pub stride: [usize; 2],
+    pub padding: [i32; 2],
+    pub dilation: [usize; 2],
+}
+
+impl Default for Conv2dOptions {
+    fn default() -> Self {
+        Self {
+            stride: [1, 1],
+            padding: [0, 0],
+            dilation: [1, 1],
+        }
+    }
+}
+
+/// Computes a 2D convolution in channels-last layout.
+///
+/// The input is `[batch, height, width, channels]`, the weight is
+/// `[out_channels, kernel_h, kernel_w, channels]` and the output is
+/// `[batch, out_h, out_w, out_channels]`.
+pub fn conv2d_cpu_reference(
+    input: &HostData,
+    weight: &HostData,
+    options: &Conv2dOptions,
+) -> HostData {
+    let [n, h, w, c] = input.shape[..] else {
+        panic!("Input must be of rank 4");
+    };
+    let [out_channels, kh, kw, _] = weight.shape[..] else {
+        panic!("Weight must be of rank 4");
+    };
+
+    let out_size = |size: usize, kernel: usize, dim: usize| {
+        let padded = size as i32 + 2 * options.padding[dim];
+        let dilated_kernel = (options.dilation[dim] * (kernel - 1) + 1) as i32;
+        ((padded - dilated_kernel) / options.stride[dim] as i32 + 1) as usize
+    };
+    let out_h = out_size(h, kh, 0);
+    let out_w = out_size(w, kw, 1);
+
+    let out_shape = vec![n, out_h, out_w, out_channels];
+    let mut out = vec![0.0; n * out_h * out_w * out_channels];
+
+    for b in 0..n {
+        for out_y in 0..out_h {
+            for out_x in 0..out_w {
+                for out_c in 0..out_channels {
+                    let mut acc = 0.0;
+
+                    for ky in 0..kh {
+                        let in_y = (out_y * options.stride[0] + ky * options.dilation[0]) as i32
+                            - options.padding[0];
+
+                        for kx in 0..kw {
+                            let in_x = (out_x * options.stride[1] + kx * options.dilation[1])
+                                as i32
+                                - options.padding[1];
+
+                            if in_y < 0 || in_y >= h as i32 || in_x < 0 || in_x >= w as i32 {
+                                continue;
+                            }
+
+                            for in_c in 0..c {
+                                let value = input.get_f32(&[b, in_y as usize, in_x as usize, in_c]);
+                                acc += value * weight.get_f32(&[out_c, ky, kx, in_c]);
+                            }
+                        }
+                    }
+
+                    out[((b * out_h + out_y) * out_w + out_x) * out_channels + out_c] = acc;
+                }
+            }
+        }
+    }
+
+    host_data_row_major(out, out_shape)
+}
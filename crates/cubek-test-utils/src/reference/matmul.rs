@@ -0,0 +1,65 @@
+use crate::HostData;
+use crate::reference::base::{host_data_row_major, unravel_index};
+
+/// Solves `lhs @ rhs`, where the last two dimensions of each input are the matrix dimensions
+/// and all others are batch dimensions, broadcasted when of size 1.
+///
+/// The output is contiguous and row-major.
+pub fn matmul_cpu_reference(lhs: &HostData, rhs: &HostData) -> HostData {
+    let rank = lhs.shape.len();
+    assert_eq!(rank, rhs.shape.len(), "Lhs and rhs must have the same rank");
+    assert_eq!(
+        lhs.shape[rank - 1],
+        rhs.shape[rank - 2],
+        "Reduction dimensions don't match"
+    );
+
+    let m = lhs.shape[rank - 2];
+    let k = lhs.shape[rank - 1];
+    let n = rhs.shape[rank - 1];
+
+    let batch_shape: Vec<usize> = lhs.shape[..rank - 2]
+        .iter()
+        .zip(&rhs.shape[..rank - 2])
+        .map(|(l, r)| usize::max(*l, *r))
+        .collect();
+    let num_batches: usize = batch_shape.iter().product();
+
+    let mut out_shape = batch_shape.clone();
+    out_shape.extend([m, n]);
+
+    let mut out = vec![0.0; num_batches * m * n];
+
+    let mut batch_index = vec![0usize; rank - 2];
+    let mut lhs_index = vec![0usize; rank];
+    let mut rhs_index = vec![0usize; rank];
+
+    for batch_flat in 0..num_batches {
+        unravel_index(batch_flat, &batch_shape, &mut batch_index);
+
+        for d in 0..rank - 2 {
+            lhs_index[d] = if lhs.shape[d] == 1 { 0 } else { batch_index[d] };
+            rhs_index[d] = if rhs.shape[d] == 1 { 0 } else { batch_index[d] };
+        }
+
+        for i in 0..m {
+            lhs_index[rank - 2] = i;
+
+            for j in 0..n {
+                rhs_index[rank - 1] = j;
+
+                let mut sum = 0.0;
+                for kk in 0..k {
+                    lhs_index[rank - 1] = kk;
+                    rhs_index[rank - 2] = kk;
+
+                    sum += lhs.get_f32(&lhs_index) * rhs.get_f32(&rhs_index);
+                }
+
+                out[batch_flat * (m * n) + i * n + j] = sum;
+            }
+        }
+    }
+
+    host_data_row_major(out, out_shape)
+}
@@ -0,0 +1,15 @@
+//! Naive CPU implementations of the kernels, working on [HostData](crate::HostData).
+//!
+//! They are very slow on large payloads and are meant to validate kernels
+//! (including fused variants) against a common ground truth.
+
+mod attention;
+mod base;
+mod convolution;
+mod matmul;
+mod reduce;
+
+pub use attention::*;
+pub use convolution::*;
+pub use matmul::*;
+pub use reduce::*;
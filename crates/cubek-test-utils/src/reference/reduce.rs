@@ -0,0 +1,110 @@
+use crate::HostData;
+use crate::reference::base::{host_data_row_major, unravel_index};
+
+/// Reduction applied by [reduce_cpu_reference].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReduceOp {
+    Sum,
+    Prod,
+    Mean,
+    /// Maximum, NaN if any value is NaN.
+    Max,
+    /// Minimum, NaN if any value is NaN.
+    Min,
+    /// Two-pass variance, divided by `count - correction`, NaN when `correction >= count`.
+    Var {
+        correction: u32,
+    },
+    LogSumExp,
+    /// `(sum |x|^p)^(1/p)`.
+    Norm {
+        p: f32,
+    },
+    CountNonZero,
+    /// Index of the maximum, the lowest index wins ties.
+    ArgMax,
+    /// Index of the minimum, the lowest index wins ties.
+    ArgMin,
+}
+
+/// Reduces `input` along `axis`.
+///
+/// The reduced axis is kept with a size of 1, and indices are returned as f32.
+pub fn reduce_cpu_reference(input: &HostData, axis: usize, op: ReduceOp) -> HostData {
+    let mut out_shape = input.shape.clone();
+    out_shape[axis] = 1;
+
+    let num_outputs: usize = out_shape.iter().product();
+    let reduce_size = input.shape[axis];
+
+    let mut index = vec![0usize; input.shape.len()];
+    let out = (0..num_outputs)
+        .map(|out_flat| {
+            unravel_index(out_flat, &out_shape, &mut index);
+
+            let values = (0..reduce_size).map(|i| {
+                index[axis] = i;
+                input.get_f32(&index)
+            });
+
+            reduce_values(values, op)
+        })
+        .collect();
+
+    host_data_row_major(out, out_shape)
+}
+
+fn reduce_values(values: impl Iterator<Item = f32>, op: ReduceOp) -> f32 {
+    match op {
+        ReduceOp::Sum => values.sum(),
+        ReduceOp::Prod => values.product(),
+        ReduceOp::Mean => {
+            let (sum, count) = values.fold((0.0, 0), |(sum, count), x| (sum + x, count + 1));
+            sum / count as f32
+        }
+        ReduceOp::Max => values.fold(f32::NEG_INFINITY, |max, x| propagate_nan(max, x, f32::max)),
+        ReduceOp::Min => values.fold(f32::INFINITY, |min, x| propagate_nan(min, x, f32::min)),
+        ReduceOp::Var { correction } => {
+            let values = values.collect::<Vec<_>>();
+            let mean = values.iter().sum::<f32>() / values.len() as f32;
+            let m2 = values.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>();
+            match values.len().checked_sub(correction as usize) {
+                Some(dof) if dof > 0 => m2 / dof as f32,
+                _ => f32::NAN,
+            }
+        }
+        ReduceOp::LogSumExp => {
+            let values = values.collect::<Vec<_>>();
+            let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            // Equal values are rescaled to 1, so a `+inf` maximum doesn't compute `inf - inf`.
+            let rescale = |x: f32| if x == max { 1.0 } else { (x - max).exp() };
+            max + values.iter().copied().map(rescale).sum::<f32>().ln()
+        }
+        ReduceOp::Norm { p } => values.map(|x| x.abs().powf(p)).sum::<f32>().powf(1.0 / p),
+        ReduceOp::CountNonZero => values.filter(|x| *x != 0.0).count() as f32,
+        ReduceOp::ArgMax => arg_best(values, |candidate, best| candidate > best),
+        ReduceOp::ArgMin => arg_best(values, |candidate, best| candidate < best),
+    }
+}
+
+/// Combine two values with `f`, returning NaN if either is NaN.
+fn propagate_nan(lhs: f32, rhs: f32, f: impl Fn(f32, f32) -> f32) -> f32 {
+    if lhs.is_nan() || rhs.is_nan() {
+        f32::NAN
+    } else {
+        f(lhs, rhs)
+    }
+}
+
+fn arg_best(values: impl Iterator<Item = f32>, is_better: impl Fn(f32, f32) -> bool) -> f32 {
+    let mut best = None;
+
+    for (i, x) in values.enumerate() {
+        match best {
+            Some((_, best_value)) if !is_better(x, best_value) => {}
+            _ => best = Some((i, x)),
+        }
+    }
+
+    best.map(|(i, _)| i as f32).unwrap_or(0.0)
+}
@@ -140,3 +140,73 @@ fn custom_handle_row_major_col_major() {
 
     assert_equals_approx(&col_major, &row_major, 0.001).unwrap();
 }
+
+#[cfg(feature = "reference")]
+fn host_data(shape: Vec<usize>, data: Vec<f32>) -> HostData {
+    let strides = StrideSpec::RowMajor.compute_strides(&shape);
+    HostData {
+        data: cubek_test_utils::HostDataVec::F32(data),
+        shape,
+        strides,
+    }
+}
+
+#[test]
+#[cfg(feature = "reference")]
+fn reference_matmul_broadcasts_batches() {
+    use cubek_test_utils::reference::matmul_cpu_reference;
+
+    let lhs = host_data(vec![2, 1, 2], [1., 2., 3., 4.].to_vec());
+    let rhs = host_data(vec![1, 2, 2], [1., 0., 0., 1.].to_vec());
+
+    let actual = matmul_cpu_reference(&lhs, &rhs);
+    let expected = host_data(vec![2, 1, 2], [1., 2., 3., 4.].to_vec());
+
+    assert_equals_approx(&actual, &expected, 0.001).unwrap();
+}
+
+#[test]
+#[cfg(feature = "reference")]
+fn reference_reduce_argmax_picks_lowest_index_on_ties() {
+    use cubek_test_utils::reference::{ReduceOp, reduce_cpu_reference};
+
+    let input = host_data(vec![2, 3], [1., 5., 5., 2., 0., 2.].to_vec());
+
+    let actual = reduce_cpu_reference(&input, 1, ReduceOp::ArgMax);
+    let expected = host_data(vec![2, 1], [1., 0.].to_vec());
+
+    assert_equals_approx(&actual, &expected, 0.001).unwrap();
+}
+
+#[test]
+#[cfg(feature = "reference")]
+fn reference_reduce_max_min_propagate_nan() {
+    use cubek_test_utils::reference::{ReduceOp, reduce_cpu_reference};
+
+    let input = host_data(vec![2, 3], [1., f32::NAN, 2., 3., 0., 2.].to_vec());
+
+    for op in [ReduceOp::Max, ReduceOp::Min] {
+        let actual = reduce_cpu_reference(&input, 1, op);
+        assert!(
+            actual.get_f32(&[0, 0]).is_nan(),
+            "{op:?} should propagate NaN"
+        );
+        assert!(
+            !actual.get_f32(&[1, 0]).is_nan(),
+            "{op:?} shouldn't produce NaN"
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "reference")]
+fn reference_reduce_var_without_degrees_of_freedom_is_nan() {
+    use cubek_test_utils::reference::{ReduceOp, reduce_cpu_reference};
+
+    let input = host_data(vec![1, 2], [1., 2.].to_vec());
+
+    for correction in [2, 3] {
+        let actual = reduce_cpu_reference(&input, 1, ReduceOp::Var { correction });
+        assert!(actual.get_f32(&[0, 0]).is_nan());
+    }
+}